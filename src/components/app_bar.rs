@@ -0,0 +1,130 @@
+use yew::prelude::*;
+
+use crate::components::modal::Modal;
+use crate::hooks::use_locale::use_locale;
+use crate::services::{a11y, pwa};
+use crate::t;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// The open room, as it should read in the bar.
+    pub title: String,
+    pub user_count: usize,
+    /// The connection to the server is still up.
+    pub connected: bool,
+    /// Opens the sidebar drawer on narrow screens.
+    pub on_menu: Callback<()>,
+    pub on_mentions: Callback<()>,
+    pub on_channels: Callback<()>,
+    pub on_export: Callback<()>,
+    pub on_stats: Callback<()>,
+    pub on_settings: Callback<()>,
+    pub on_logout: Callback<()>,
+    /// Shown in the menu while the browser is ready to install the app.
+    pub on_install: Callback<()>,
+    /// Controls for the open room, shown inline on wide screens only.
+    #[prop_or_default]
+    pub children: Children,
+}
+
+/// The bar above the message list. On wide screens it is the usual header row; on narrow
+/// ones it sticks to the top with a hamburger for the sidebar, and a tab strip along the
+/// bottom of the screen takes over the sidebar's main destinations.
+#[function_component(AppBar)]
+pub fn app_bar(props: &Props) -> Html {
+    use_locale();
+    let menu_open = use_state(|| false);
+    let help_open = use_state(|| false);
+
+    // Menu entries close the menu on their way to the parent.
+    let item = |label: String, action: Callback<()>| {
+        let menu_open = menu_open.clone();
+        let onclick = Callback::from(move |_| {
+            menu_open.set(false);
+            action.emit(());
+        });
+        html! { <div {onclick} role="menuitem" tabindex="0" onkeydown={a11y::press_on_key} class="px-3 py-2 cursor-pointer hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring">{ label }</div> }
+    };
+    let show_help = {
+        let help_open = help_open.clone();
+        Callback::from(move |_| help_open.set(true))
+    };
+    let hide_help = {
+        let help_open = help_open.clone();
+        Callback::from(move |_| help_open.set(false))
+    };
+    let toggle_menu = {
+        let menu_open = menu_open.clone();
+        Callback::from(move |_| menu_open.set(!*menu_open))
+    };
+    let tab = |icon: &'static str, label: String, action: &Callback<()>| {
+        html! {
+            <button onclick={action.reform(|_| ())} class="flex-1 flex flex-col items-center justify-center text-xs text-gray-600 dark:text-gray-300 focus-ring">
+                <span class="text-lg leading-none">{ icon }</span>
+                { label }
+            </button>
+        }
+    };
+
+    html! {
+        <>
+            <div class="sticky top-0 z-10 h-14 border-b p-3 flex items-center bg-white dark:bg-gray-800">
+                <button onclick={props.on_menu.reform(|_| ())} title={t!("app_bar.menu")} class="md:hidden mr-1 w-8 h-8 rounded-full hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring">
+                    {"☰"}
+                </button>
+                <span class="hidden md:inline text-xl font-semibold">{"💬 Chat!"}</span>
+                <span class="md:ml-3 text-gray-500 truncate">{ &props.title }</span>
+                <span
+                    title={if props.connected { t!("app_bar.connected") } else { t!("app_bar.disconnected") }}
+                    class={classes!("ml-2", "w-2", "h-2", "flex-none", "rounded-full", if props.connected { "bg-green-500" } else { "bg-red-500" })}
+                ></span>
+                <span class="ml-2 text-xs text-gray-400 whitespace-nowrap">{ t!("app_bar.online", count = props.user_count) }</span>
+                <div class="hidden md:flex flex-1 items-center">
+                    { for props.children.iter() }
+                </div>
+                <button onclick={props.on_settings.reform(|_| ())} title={t!("app_bar.settings")} class="ml-auto md:ml-1 w-8 h-8 rounded-full hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring">
+                    {"⚙"}
+                </button>
+                <div class="relative ml-1">
+                    <button onclick={toggle_menu} title={t!("app_bar.more")} aria-haspopup="menu" aria-expanded={menu_open.to_string()} class="w-8 h-8 rounded-full hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring">{"⋯"}</button>
+                    if *menu_open {
+                        <div role="menu" class="absolute z-10 right-0 top-10 w-44 bg-white dark:bg-gray-800 border rounded-lg shadow text-sm">
+                            { item(t!("app_bar.settings"), props.on_settings.clone()) }
+                            { item(t!("app_bar.direct_messages"), props.on_menu.clone()) }
+                            { item(t!("app_bar.browse_channels"), props.on_channels.clone()) }
+                            { item(t!("app_bar.export"), props.on_export.clone()) }
+                            { item(t!("app_bar.stats"), props.on_stats.clone()) }
+                            if pwa::install_available() {
+                                { item(t!("app_bar.install"), props.on_install.clone()) }
+                            }
+                            <div onclick={show_help} role="menuitem" tabindex="0" onkeydown={a11y::press_on_key} class="px-3 py-2 cursor-pointer hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring">{ t!("app_bar.help") }</div>
+                            { item(t!("app_bar.log_out"), props.on_logout.clone()) }
+                        </div>
+                    }
+                </div>
+            </div>
+            <nav class="md:hidden fixed bottom-0 inset-x-0 z-10 h-14 flex border-t bg-white dark:bg-gray-800">
+                { tab("💬", t!("app_bar.tab.chats"), &props.on_menu) }
+                { tab("@", t!("app_bar.tab.mentions"), &props.on_mentions) }
+                { tab("#", t!("app_bar.tab.channels"), &props.on_channels) }
+                { tab("⚙", t!("app_bar.settings"), &props.on_settings) }
+            </nav>
+            if *help_open {
+                <Modal onclick={hide_help} label={t!("app_bar.help")} class="fixed inset-0 z-20 flex items-center justify-center bg-black bg-opacity-50">
+                    <div class="w-80 p-4 bg-white dark:bg-gray-800 rounded-xl text-sm space-y-1">
+                        <div class="mb-2 text-lg font-semibold">{ t!("app_bar.help") }</div>
+                        <div><span class="font-mono">{"Ctrl+K"}</span>{ t!("help.search_local") }</div>
+                        <div><span class="font-mono">{"/search text"}</span>{ t!("help.search_server") }</div>
+                        <div><span class="font-mono">{"/welcome text"}</span>{ t!("help.welcome") }</div>
+                        <div><span class="font-mono">{"/slowmode seconds"}</span>{ t!("help.slowmode") }</div>
+                        <div><span class="font-mono">{"/ephemeral seconds text"}</span>{ t!("help.ephemeral") }</div>
+                        <div class="pt-2 text-gray-500">{ t!("help.shortcuts") }</div>
+                        <div class="mt-3 text-right">
+                            <button class="px-3 py-1 rounded-full bg-blue-600 text-white focus-ring">{ t!("common.ok") }</button>
+                        </div>
+                    </div>
+                </Modal>
+            }
+        </>
+    }
+}
@@ -0,0 +1,87 @@
+use web_sys::{HtmlInputElement, HtmlTextAreaElement};
+use yew::prelude::*;
+
+use crate::components::chat::Broadcast;
+use crate::hooks::use_locale::use_locale;
+use crate::t;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// Rooms the announcement can be aimed at, in sidebar order.
+    pub rooms: Vec<String>,
+    pub on_send: Callback<Broadcast>,
+}
+
+/// The admin panel's announcement form. With no room ticked it goes to every room.
+#[function_component(BroadcastForm)]
+pub fn broadcast_form(props: &Props) -> Html {
+    use_locale();
+    let text = use_state(String::new);
+    let targets = use_state(Vec::<String>::new);
+
+    let on_text = {
+        let text = text.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlTextAreaElement = e.target_unchecked_into();
+            text.set(input.value());
+        })
+    };
+    let room = |room: &String| {
+        let checked = targets.contains(room);
+        let onchange = {
+            let targets = targets.clone();
+            let room = room.clone();
+            Callback::from(move |e: Event| {
+                let input: HtmlInputElement = e.target_unchecked_into();
+                let mut next = (*targets).clone();
+                next.retain(|r| r != &room);
+                if input.checked() {
+                    next.push(room.clone());
+                }
+                targets.set(next);
+            })
+        };
+        html! {
+            <label class="flex items-center gap-1 text-xs">
+                <input type="checkbox" {checked} {onchange}/>
+                { room }
+            </label>
+        }
+    };
+    let onclick = {
+        let text = text.clone();
+        let targets = targets.clone();
+        let on_send = props.on_send.clone();
+        Callback::from(move |_| {
+            on_send.emit(Broadcast {
+                text: text.trim().to_string(),
+                target_rooms: (!targets.is_empty()).then(|| (*targets).clone()),
+            });
+            text.set(String::new());
+            targets.set(Vec::new());
+        })
+    };
+
+    html! {
+        <div class="flex flex-col gap-2">
+            <textarea
+                value={(*text).clone()}
+                oninput={on_text}
+                rows="2"
+                placeholder={t!("broadcast.placeholder")}
+                class="w-full px-2 py-1 text-sm border rounded bg-white dark:bg-gray-900"
+            />
+            <fieldset class="flex flex-wrap gap-x-3 gap-y-1">
+                <legend class="mb-1 text-xs text-gray-500">{ t!("broadcast.rooms") }</legend>
+                { props.rooms.iter().map(room).collect::<Html>() }
+            </fieldset>
+            <button
+                {onclick}
+                disabled={text.trim().is_empty()}
+                class="self-end px-3 py-1 text-sm rounded bg-blue-600 text-white hover:bg-blue-700 disabled:opacity-50 focus-ring"
+            >
+                { if targets.is_empty() { t!("broadcast.send_all") } else { t!("broadcast.send_rooms", count = targets.len()) } }
+            </button>
+        </div>
+    }
+}
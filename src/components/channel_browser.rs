@@ -0,0 +1,257 @@
+use serde::{Deserialize, Serialize};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::hooks::use_focus_trap::use_focus_trap;
+use crate::hooks::use_locale::use_locale;
+use crate::fuzzy::fuzzy_filter;
+use crate::services::room_name::validate_room_name;
+use crate::t;
+
+#[derive(Deserialize, PartialEq, Clone)]
+pub struct ChannelInfo {
+    pub name: String,
+    pub members: u32,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RoomType {
+    /// Listed, and anyone may join.
+    Public,
+    /// Listed, but joining takes an invitation.
+    Private,
+    /// Unlisted; only invited members know it exists.
+    Secret,
+}
+
+impl RoomType {
+    const ALL: [RoomType; 3] = [RoomType::Public, RoomType::Private, RoomType::Secret];
+
+    fn label(self) -> String {
+        match self {
+            RoomType::Public => t!("channel_browser.public"),
+            RoomType::Private => t!("channel_browser.private"),
+            RoomType::Secret => t!("channel_browser.secret"),
+        }
+    }
+}
+
+/// Payload of a `roomcreate` frame.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct RoomCreate {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub room_type: RoomType,
+    /// No limit when left out.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_members: Option<u32>,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// `None` while the list is still on its way.
+    pub channels: Option<Vec<ChannelInfo>>,
+    pub joined: Vec<String>,
+    /// A room this user asked to create, while the server has yet to answer.
+    #[prop_or_default]
+    pub creating: Option<String>,
+    pub on_join: Callback<String>,
+    pub on_create: Callback<RoomCreate>,
+    pub on_close: Callback<()>,
+}
+
+#[derive(Properties, PartialEq)]
+struct FormProps {
+    pending: bool,
+    on_create: Callback<RoomCreate>,
+    on_cancel: Callback<()>,
+}
+
+/// What a new room should be called and who may join it. The name is checked as it is
+/// typed; the server has the last word on whether it is free.
+#[function_component(CreateRoomForm)]
+fn create_room_form(props: &FormProps) -> Html {
+    use_locale();
+    let name = use_state(String::new);
+    let description = use_state(String::new);
+    let room_type = use_state(|| RoomType::Public);
+    let max_members = use_state(String::new);
+
+    let field = |state: &UseStateHandle<String>| {
+        let state = state.clone();
+        Callback::from(move |e: InputEvent| state.set(e.target_unchecked_into::<HtmlInputElement>().value()))
+    };
+    let valid_name = validate_room_name(&name);
+    let max = max_members.trim();
+    let valid_max = if max.is_empty() {
+        Ok(None)
+    } else {
+        match max.parse::<u32>() {
+            Ok(n) if n >= 2 => Ok(Some(n)),
+            _ => Err(t!("channel_browser.max_invalid")),
+        }
+    };
+    let request = match (&valid_name, &valid_max) {
+        (Ok(name), Ok(max_members)) => Some(RoomCreate {
+            name: name.clone(),
+            description: Some(description.trim().to_string()).filter(|d| !d.is_empty()),
+            room_type: *room_type,
+            max_members: *max_members,
+        }),
+        _ => None,
+    };
+    let onsubmit = {
+        let on_create = props.on_create.clone();
+        let request = request.clone();
+        Callback::from(move |e: FocusEvent| {
+            e.prevent_default();
+            if let Some(request) = &request {
+                on_create.emit(request.clone());
+            }
+        })
+    };
+
+    html! {
+        <form {onsubmit} class="p-3 border-b space-y-2 text-sm">
+            <input
+                oninput={field(&name)}
+                value={(*name).clone()}
+                placeholder={t!("channel_browser.name_placeholder")}
+                class="w-full rounded-full bg-gray-100 dark:bg-gray-700 px-4 py-2 focus:outline-none focus-ring"
+            />
+            if let (false, Err(e)) = (name.is_empty(), &valid_name) {
+                <div class="px-2 text-xs text-red-600">{ e.to_string() }</div>
+            }
+            <input
+                oninput={field(&description)}
+                value={(*description).clone()}
+                placeholder={t!("channel_browser.description")}
+                class="w-full rounded-full bg-gray-100 dark:bg-gray-700 px-4 py-2 focus:outline-none focus-ring"
+            />
+            <div class="flex rounded-lg border overflow-hidden">
+                { for RoomType::ALL.iter().map(|&option| {
+                    let room_type = room_type.clone();
+                    html! {
+                        <button
+                            type="button"
+                            onclick={Callback::from(move |_| room_type.set(option))}
+                            class={classes!(
+                                "flex-1", "p-1", "focus-ring",
+                                if option == *room_type { "bg-blue-600 text-white" } else { "hover:bg-gray-100 dark:hover:bg-gray-700" },
+                            )}
+                        >
+                            { option.label() }
+                        </button>
+                    }
+                }) }
+            </div>
+            <input
+                oninput={field(&max_members)}
+                value={(*max_members).clone()}
+                type="number"
+                min="2"
+                placeholder={t!("channel_browser.max_members")}
+                class="w-full rounded-full bg-gray-100 dark:bg-gray-700 px-4 py-2 focus:outline-none focus-ring"
+            />
+            if let Err(e) = valid_max {
+                <div class="px-2 text-xs text-red-600">{ e }</div>
+            }
+            <div class="flex justify-end gap-2">
+                <button type="button" onclick={props.on_cancel.reform(|_| ())} class="px-3 py-1 rounded-full hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring">
+                    { t!("common.cancel") }
+                </button>
+                <button
+                    type="submit"
+                    disabled={request.is_none() || props.pending}
+                    class="px-3 py-1 rounded-full bg-blue-600 text-white disabled:opacity-40 focus-ring"
+                >
+                    { if props.pending { t!("channel_browser.creating") } else { t!("channel_browser.create") } }
+                </button>
+            </div>
+        </form>
+    }
+}
+
+#[function_component(ChannelBrowser)]
+pub fn channel_browser(props: &Props) -> Html {
+    use_locale();
+    let trap = use_focus_trap(true);
+    let query = use_state(String::new);
+    let form_open = use_state(|| false);
+
+    let oninput = {
+        let query = query.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            query.set(input.value());
+        })
+    };
+    let close = props.on_close.reform(|_| ());
+
+    let body = match &props.channels {
+        None => html! { <div class="p-4 text-sm text-gray-400">{ t!("channel_browser.loading") }</div> },
+        Some(channels) if channels.is_empty() => {
+            html! { <div class="p-4 text-sm text-gray-400">{ t!("channel_browser.empty") }</div> }
+        }
+        Some(channels) => {
+            let matches = fuzzy_filter(&query, channels, |c| &c.name);
+            if matches.is_empty() {
+                html! { <div class="p-4 text-sm text-gray-400">{ t!("channel_browser.no_match") }</div> }
+            } else {
+                matches.into_iter().map(|c| {
+                    let joined = props.joined.contains(&c.name);
+                    let name = c.name.clone();
+                    let onclick = props.on_join.reform(move |_| name.clone());
+                    html! {
+                        <div class="flex items-center px-4 py-2 border-b">
+                            <div class="flex-1">
+                                <div class="font-medium">{ format!("# {}", c.name) }</div>
+                                <div class="text-xs text-gray-400">{ t!("channel_browser.members", count = c.members) }</div>
+                            </div>
+                            if joined {
+                                <button disabled=true class="px-3 py-1 rounded-full text-sm bg-gray-200 text-gray-500 focus-ring">{ t!("channel_browser.joined") }</button>
+                            } else {
+                                <button {onclick} class="px-3 py-1 rounded-full text-sm bg-blue-600 text-white focus-ring">{ t!("channel_browser.join") }</button>
+                            }
+                        </div>
+                    }
+                }).collect::<Html>()
+            }
+        }
+    };
+
+    html! {
+        <div ref={trap} role="dialog" aria-modal="true" aria-label={t!("channel_browser.title")} class="fixed inset-0 z-10 flex items-center justify-center bg-black bg-opacity-50">
+            <div class="w-96 max-h-screen flex flex-col bg-white dark:bg-gray-800 rounded-xl overflow-hidden">
+                <div class="flex items-center p-3 border-b">
+                    <div class="flex-1 text-lg font-semibold">{ t!("channel_browser.title") }</div>
+                    <button onclick={close} aria-label={t!("common.close")} class="text-gray-400 hover:text-gray-800 focus-ring">{"×"}</button>
+                </div>
+                if *form_open {
+                    <CreateRoomForm
+                        pending={props.creating.is_some()}
+                        on_create={props.on_create.clone()}
+                        on_cancel={{
+                            let form_open = form_open.clone();
+                            Callback::from(move |_| form_open.set(false))
+                        }}
+                    />
+                } else {
+                    <button
+                        onclick={{
+                            let form_open = form_open.clone();
+                            Callback::from(move |_| form_open.set(true))
+                        }}
+                        class="mx-3 mt-3 px-3 py-2 rounded-full border text-sm hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring"
+                    >
+                        { t!("channel_browser.create_room") }
+                    </button>
+                }
+                <input {oninput} value={(*query).clone()} placeholder={t!("channel_browser.search")} class="m-3 rounded-full bg-gray-100 dark:bg-gray-700 px-4 py-2 focus:outline-none focus-ring"/>
+                <div class="overflow-auto">{ body }</div>
+            </div>
+        </div>
+    }
+}
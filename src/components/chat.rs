@@ -1,22 +1,447 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use gloo_events::EventListener;
+use gloo_timers::callback::{Interval, Timeout};
 use serde::{Deserialize, Serialize};
-use web_sys::HtmlInputElement;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement, HtmlInputElement, NotificationPermission};
+use yew::context::ContextHandle;
 use yew::prelude::*;
-use yew_agent::{Bridge, Bridged};
+use yew::virtual_dom::AttrValue;
+use yew_agent::{Bridge, Bridged, Dispatched, Dispatcher};
+use yew_router::prelude::{History, Location, RouterScopeExt};
 
-use crate::{User, services::websocket::WebsocketService};
+use crate::{t, Route, UserCtx, UserState, services::websocket::WebsocketService};
+use crate::components::app_bar::AppBar;
+use crate::components::broadcast_form::BroadcastForm;
+use crate::components::channel_browser::{ChannelBrowser, ChannelInfo, RoomCreate};
+use crate::components::empty_state::{EmptyState, EmptyStateVariant};
+use crate::components::clear_data_dialog::ClearDataDialog;
+use crate::components::cooldown_bar::CooldownBar;
+use crate::components::export_dialog::ExportDialog;
+use crate::components::pin_dialog::PinDialog;
+use crate::components::image_lightbox::ImageLightbox;
+use crate::components::mention_pill::MentionPill;
+use crate::components::modal::Modal;
+use crate::components::message_search::MessageSearchPanel;
+use crate::components::lazy_image::LazyImage;
+use crate::components::mod_log::ModLog;
+use crate::components::reaction_bar::ReactionBar;
+use crate::components::request_access::RequestAccessForm;
+use crate::components::search_results::SearchResultsPanel;
+use crate::components::thread_panel::ThreadPanel;
+use crate::components::settings_panel::SettingsPanel;
+use crate::components::shortcut_help::ShortcutHelp;
+use crate::components::shutdown_banner::ShutdownBanner;
+use crate::components::stats_dialog::StatsDialog;
+use crate::components::status_bar::{Status, StatusBar};
+use crate::components::user_profile_card::{ProfileSummary, UserProfileCard};
+use crate::components::video_player::VideoPlayer;
+use crate::fuzzy::fuzzy_filter;
+use crate::services::a11y;
+use crate::services::browser_notifications::{self, NotificationTarget, PermissionPrompt};
+use crate::services::channel_store::{
+    conversation_key, Channel, ChannelStore, HistoryState, MentionSource, ReactionChange, WindowFill, WindowSource,
+    DEFAULT_CHANNEL,
+};
+use crate::services::connection::{ConnectionAction, ConnectionEvent, ConnectionState};
+use crate::services::clipboard;
+use crate::services::message_link::MessageLink;
+use crate::services::chat_export::{combine, export_filename, to_json, to_text, ExportFormat};
+use crate::services::display::{self, Density};
+use crate::services::dnd::{Now, Snooze};
+use crate::services::download::download_text;
+use crate::services::drafts::Drafts;
 use crate::services::event_bus::EventBus;
+use crate::services::favicon::FaviconBadge;
+use crate::services::feature_flags::FeatureFlags;
+use crate::services::file_chunks::{split_into_chunks, ChunkAssembler, FileChunk, CHUNK_SIZE};
+use crate::services::geolocation::{current_position, map_link, parse_map_link, static_map_url};
+use crate::services::last_username;
+use crate::services::message_archive::MessageArchive;
+use crate::services::message_cache::MessageCache;
+use crate::services::search::{day_key, merge_results, parse_day, search as search_messages, SearchQuery, SearchScope, DAY_MS, MAX_RESULTS};
+use crate::services::presence_service::PresenceService;
+use crate::services::pwa;
+use crate::services::notifications::{
+    is_mention, mention_spans, should_cue, should_notify, surfaces_archived, vibrate, Decision, NotifyLevel,
+    RoomPreference, UserMute, Visibility,
+};
+use crate::services::recorder::{ClipRecorder, ClipSource};
+use crate::services::scroll_memory::{ReadingPosition, ScrollMemory};
+use crate::services::scroll_sentinel::{preserved_scroll_top, ScrollSentinel};
+use crate::services::storage::Store;
+use crate::services::room_name::validate_room_name;
+use crate::services::session_stats::SessionStats;
+use crate::services::shortcuts::{Shortcut, Shortcuts};
+use crate::services::settings::UserSettings;
+use crate::services::audio_service::{AudioService, SoundEffect};
+use crate::services::avatar::{self, avatar_for};
+use crate::services::theme;
+use crate::services::thumbnail::thumbnail;
+use crate::services::viewport::{within_slop, KeyboardWatch, LONG_PRESS_MS, TOUCH_TARGET};
+use crate::services::title::TitleBadge;
+use crate::services::transcript::Transcript;
+use crate::services::toasts::{Toast, ToastBus, ToastLevel, ToastMsg};
+use crate::services::username::validate_username;
+use crate::services::i18n::{self, Locale};
+use crate::services::typing::{typing_label, TypingEvent, TypingList, TypingTracker};
+use crate::services::welcome::{SeenWelcomes, SetWelcome, WelcomeMessage};
+
+const CHANNELS: [&str; 2] = [DEFAULT_CHANNEL, "random"];
+
+/// How long a message jumped to stays highlighted.
+const HIGHLIGHT_MS: u32 = 2_000;
+
+/// Most hits asked for per `/search`.
+const SEARCH_LIMIT: u32 = 50;
+/// How many earlier messages to fetch when opening a channel for the first time.
+const HISTORY_LIMIT: u32 = 50;
+/// How long a page of history may take before the request counts as lost.
+const HISTORY_TIMEOUT_MS: u32 = 10_000;
+/// Most messages fetched for a day picked from the calendar.
+const DAY_HISTORY_LIMIT: u32 = 500;
+/// How long a picked day waits on the server and the archive before showing what it has.
+const DAY_TIMEOUT_MS: u32 = 10_000;
+/// Most older pages fetched looking for a linked message the archive doesn't have.
+const LINK_PAGES: u32 = 10;
+/// How long the message cache waits for more changes before writing them out.
+const CACHE_SAVE_DEBOUNCE_MS: u32 = 2_000;
+/// A pause in typing this long saves the draft.
+const DRAFT_SYNC_MS: u32 = 3_000;
+/// How often the reading position is saved while the message list scrolls.
+const SCROLL_SAVE_DEBOUNCE_MS: u32 = 1_000;
+/// Messages per channel read back from the archive on startup.
+const ARCHIVE_LOAD_LIMIT: usize = 500;
+/// Archived messages older than this are deleted.
+const ARCHIVE_RETENTION_MS: f64 = 90.0 * 24.0 * 60.0 * 60.0 * 1000.0;
+/// How long to wait for search results before giving up.
+const SEARCH_TIMEOUT_MS: u32 = 10_000;
+
+/// Clips are cut off after this long.
+const MAX_CLIP_MS: u32 = 30_000;
+/// Clips bigger than this need an explicit confirmation before they are sent.
+const CLIP_WARN_BYTES: f64 = 5.0 * 1024.0 * 1024.0;
+
+/// Minimum time between two nudges to the same user.
+const NUDGE_COOLDOWN_MS: f64 = 60_000.0;
+/// How long "Bob nudged you." stays up.
+const NUDGE_NOTICE_MS: u32 = 4_000;
+
+/// How long a room asked for from the channel browser waits on the server's answer.
+const ROOM_CREATE_TIMEOUT_MS: u32 = 10_000;
+/// How long to wait for a channel list before assuming the server can't send one.
+const CHANNEL_LIST_TIMEOUT_MS: u32 = 5_000;
+
+/// A checkpoint goes out after this many new messages...
+const CHECKPOINT_EVERY: usize = 30;
+/// ...or on this timer, whichever comes first, and whenever the tab's visibility changes.
+const CHECKPOINT_INTERVAL_MS: u32 = 15_000;
 
 pub enum Msg {
     HandleMsg(String),
+    FlagsChanged(FeatureFlags),
+    UserChanged(UserCtx),
     SubmitMessage,
-    React(usize, String),
+    Typing,
+    PruneTyping,
+    /// The slow-mode wait in the open channel is over.
+    CooldownOver,
+    /// The announced shutdown time has come.
+    ShutdownDue,
+    OpenRequestAccess,
+    CloseRequestAccess,
+    SendAccessRequest(String),
+    /// By message id: positions in the list shift as messages arrive and old ones are dropped.
+    React(String, String),
+    ToggleLike(String),
+    DeleteFile(String),
+    /// Shows a message collapsed as possible spam.
+    RevealSpam(String),
+    /// Tells the server a message it scored as spam isn't.
+    MarkSafe(String),
+    SetLocked(String, bool),
+    /// Asks which kind of pin to put on this message.
+    OpenPinDialog(String),
+    /// An ephemeral message's countdown is due to move on, or to run out.
+    EphemeralTick(String),
+    ClosePinDialog,
+    PinMessage(PinType),
+    UnpinMessage(String),
+    SwitchChannel(String),
+    OpenDm(String),
+    CloseDm(String),
+    OpenChannelBrowser,
+    CloseChannelBrowser,
+    ChannelListTimedOut,
+    RoomCreateTimedOut,
+    HistoryTimedOut(String),
+    JoinChannel(String),
+    CreateRoom(RoomCreate),
+    FilterUsers(String),
+    CycleRoomPreference,
+    CycleNotifyMode,
+    SetUserMute(String, Option<UserMute>),
+    EnableNotifications,
+    SnoozeNotificationPrompt,
+    PermissionAnswered(NotificationPermission),
+    ToggleSound,
+    DownloadTranscript,
+    OpenSettings,
+    CloseSettings,
+    Shortcut(Shortcut),
+    CloseShortcutHelp,
+    /// A message got keyboard focus, which makes it the list's tab stop.
+    FocusMessage(String),
+    /// Load one of the user's own messages into the composer to edit it.
+    StartEdit(String),
+    CancelEdit,
+    /// Today's transcript as saved before this page load, if there was one.
+    TranscriptLoaded(Option<Transcript>),
+    SendCheckpoint,
+    VisibilityChanged,
+    MessageListScrolled,
+    SaveScrollPosition,
+    SaveDraft,
+    SetArchived(String, bool),
+    ToggleArchivedSection,
+    ShowMentions,
+    JumpToMessage { channel: String, id: String },
+    OpenNotification(NotificationTarget),
+    ClearHighlight,
+    ToggleUserMenu(String),
+    Nudge(String),
+    ClearNudge,
+    ToggleRecorder,
+    SetClipSource(ClipSource),
+    StartRecording,
+    RecordingStarted(ClipRecorder),
+    RecordingFailed,
+    StopRecording,
+    ClipRecorded(web_sys::Blob),
+    /// A clip read for sending, with the id its upload was given.
+    ClipEncoded(u32, String),
+    AttachFile(web_sys::File),
+    /// Go ahead with the upload the confirm dialog is holding back.
+    ConfirmUpload,
+    DiscardUpload,
+    AttachmentEncoded(u32, Attachment, String),
+    UploadFailed(u32),
+    /// Drop the newest file being read instead of sending it.
+    CancelUpload,
+    SendLocation,
+    LocationFound(f64, f64),
+    LocationFailed,
+    OpenLightbox(Rc<str>),
+    CloseLightbox,
+    UpdateSettings(Box<UserSettings>),
+    SettingsRejected(String),
+    RefreshDnd,
+    CloseSearch,
+    SetAdminTab(AdminTab),
+    SendBroadcast(Broadcast),
+    DismissBroadcast,
+    JumpToDate(String),
+    DayArchived(String, f64, Vec<MessageData>),
+    /// When the archive says a linked message in this channel was sent, if it has it.
+    LinkArchived(String, Option<f64>),
+    DayTimedOut(String, f64),
+    BackToLatest,
+    OpenLocalSearch,
+    CloseLocalSearch,
+    LocalSearch(String),
+    SetSearchScope(SearchScope),
+    LocalSearchArchived(SearchQuery, Vec<MessageData>),
+    ShowPolicyNotice(bool),
+    SetDrawer(bool),
+    /// Collapses or expands the user list.
+    ToggleSidebar,
+    /// From the socket with this number; events from sockets since replaced are dropped.
+    SocketEvent(u32, ConnectionEvent),
+    /// From the browser or the user.
+    Connection(ConnectionEvent),
+    OpenConnection,
+    OpenExport,
+    CloseExport,
+    OpenStats,
+    CloseStats,
+    Logout,
+    /// Asks the browser to install the app.
+    InstallApp,
+    /// Shows the full profile card of the user named.
+    OpenProfile(String),
+    CloseProfile,
+    /// Right-click on the message with this id, at these client coordinates.
+    OpenMessageMenu(String, i32, i32),
+    CloseMessageMenu,
+    /// A finger went down on the message with this id; held long enough, it opens the menu.
+    PressMessage(String, i32, i32),
+    /// The finger lifted before the long press fired.
+    ReleaseMessage,
+    /// A finger resting on a message moved to here.
+    MovePress(i32, i32),
+    /// The on-screen keyboard now covers this many pixels at the bottom of the page.
+    KeyboardInset(f64),
+    CopyMessageLink(String),
+    LinkCopied(bool),
+    /// The new text of the note about the user named; empty deletes it.
+    SaveUserNote(String, String),
+    OpenClearData,
+    CloseClearData,
+    /// Wipes what this browser keeps of the chat; `true` resets settings as well.
+    ClearLocalData(bool),
+    ExportChat(ExportFormat),
+    ExportReady(ExportFormat, String, Vec<MessageData>),
+    LoadOlder,
+    SearchTimedOut,
+    SaveCache,
+    ArchiveOpened(Option<MessageArchive>),
+    ArchiveLoaded(String, Vec<MessageData>),
+    /// Opens the thread hanging off the message with this id.
+    OpenThread(String),
+    CloseThread,
+    /// The open thread's composer saw input.
+    ThreadTyping,
+    SendThreadReply(String),
 }
 
-#[derive(Deserialize, PartialEq, Clone)]
+impl Msg {
+    /// Whether the user did something, as opposed to a timer, the server, an observer or a
+    /// browser API getting back to us. Only these count as activity for presence, so anything
+    /// new stays out until it is added here.
+    fn is_user_initiated(&self) -> bool {
+        matches!(
+            self,
+            Msg::SubmitMessage | Msg::Typing | Msg::OpenRequestAccess | Msg::CloseRequestAccess
+            | Msg::SendAccessRequest(_) | Msg::React(..) | Msg::ToggleLike(_) | Msg::DeleteFile(_)
+            | Msg::RevealSpam(_) | Msg::MarkSafe(_) | Msg::SetLocked(..) | Msg::OpenPinDialog(_)
+            | Msg::ClosePinDialog | Msg::PinMessage(_) | Msg::UnpinMessage(_) | Msg::SwitchChannel(_)
+            | Msg::OpenDm(_) | Msg::CloseDm(_) | Msg::OpenChannelBrowser | Msg::CloseChannelBrowser
+            | Msg::JoinChannel(_) | Msg::CreateRoom(_) | Msg::FilterUsers(_) | Msg::CycleRoomPreference
+            | Msg::CycleNotifyMode | Msg::SetUserMute(..) | Msg::EnableNotifications
+            | Msg::SnoozeNotificationPrompt | Msg::ToggleSound | Msg::DownloadTranscript | Msg::OpenSettings
+            | Msg::CloseSettings | Msg::Shortcut(_) | Msg::CloseShortcutHelp | Msg::FocusMessage(_)
+            | Msg::StartEdit(_) | Msg::CancelEdit | Msg::MessageListScrolled | Msg::SetArchived(..) | Msg::ToggleArchivedSection
+            | Msg::ShowMentions | Msg::JumpToMessage { .. } | Msg::OpenNotification(_) | Msg::ToggleUserMenu(_)
+            | Msg::Nudge(_) | Msg::ToggleRecorder | Msg::SetClipSource(_) | Msg::StartRecording
+            | Msg::StopRecording | Msg::AttachFile(_) | Msg::ConfirmUpload | Msg::DiscardUpload
+            | Msg::CancelUpload | Msg::SendLocation
+            | Msg::OpenLightbox(_) | Msg::CloseLightbox | Msg::UpdateSettings(_) | Msg::SettingsRejected(_)
+            | Msg::CloseSearch | Msg::SetAdminTab(_) | Msg::SendBroadcast(_) | Msg::DismissBroadcast
+            | Msg::JumpToDate(_) | Msg::BackToLatest | Msg::OpenLocalSearch | Msg::CloseLocalSearch
+            | Msg::LocalSearch(_) | Msg::SetSearchScope(_) | Msg::ShowPolicyNotice(_) | Msg::SetDrawer(_)
+            | Msg::ToggleSidebar | Msg::Connection(ConnectionEvent::Retry) | Msg::OpenExport | Msg::CloseExport
+            | Msg::OpenStats | Msg::CloseStats | Msg::Logout | Msg::InstallApp | Msg::OpenProfile(_)
+            | Msg::CloseProfile | Msg::OpenMessageMenu(..) | Msg::CloseMessageMenu | Msg::PressMessage(..)
+            | Msg::ReleaseMessage | Msg::MovePress(..) | Msg::CopyMessageLink(_) | Msg::SaveUserNote(..)
+            | Msg::OpenClearData | Msg::CloseClearData | Msg::ClearLocalData(_) | Msg::ExportChat(_)
+            | Msg::OpenThread(_) | Msg::CloseThread | Msg::ThreadTyping | Msg::SendThreadReply(_)
+        )
+    }
+
+    /// Whether the composer gets focus back once this is handled. Clicking send moves focus
+    /// to the button; typing should carry straight on.
+    fn refocuses_composer(&self) -> bool {
+        matches!(self, Msg::SubmitMessage)
+    }
+
+    /// Whether the drawer is open once this is handled, given whether it is now. Going
+    /// somewhere from inside it closes it, so what was picked isn't left behind the drawer.
+    fn drawer_after(&self, open: bool) -> bool {
+        match self {
+            Msg::SetDrawer(open) => *open,
+            Msg::SwitchChannel(_) | Msg::ShowMentions | Msg::OpenChannelBrowser | Msg::OpenSettings => false,
+            _ => open,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
 pub struct MessageData {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub channel: Option<String>,
     pub from: String,
     pub message: String,
     pub reactions: Option<Vec<(String, Vec<String>)>>,
+    /// Who tapped the heart, apart from any emoji reactions.
+    #[serde(default)]
+    pub likes: Vec<String>,
+    /// File id of an attached video clip, assembled from `filechunk` frames.
+    #[serde(default)]
+    pub video_clip: Option<String>,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    /// Milliseconds since the epoch, when the server sent one.
+    #[serde(default)]
+    pub timestamp: Option<f64>,
+    /// Restored from the local cache and not yet confirmed by the server.
+    #[serde(skip)]
+    pub cached: bool,
+    #[serde(default)]
+    pub edited: bool,
+    /// Who made the last edit, when it wasn't the author: an admin acting on policy.
+    #[serde(default)]
+    pub edited_by: Option<String>,
+    /// Why an admin edited it. Only admins are told.
+    #[serde(default)]
+    pub edit_reason: Option<String>,
+    /// From −1.0 (very negative) to 1.0 (very positive), on servers that score messages.
+    #[serde(default)]
+    pub sentiment: Option<f32>,
+    /// How likely the server thinks this is spam, from 0.0 to 1.0.
+    #[serde(default)]
+    pub spam_score: Option<f32>,
+    /// Frozen by an admin: no more reactions, and only admins may edit or delete it.
+    #[serde(default)]
+    pub locked: bool,
+    /// Sent with `/ephemeral`: the server deletes it this many seconds after sending.
+    #[serde(default)]
+    pub ttl_seconds: Option<u32>,
+    /// When an ephemeral message disappears, in ms since the epoch; worked out on arrival.
+    #[serde(default)]
+    pub expires_at: Option<f64>,
+    /// A reply in a thread: the id of the message the thread hangs off.
+    #[serde(default)]
+    pub thread_id: Option<String>,
+}
+
+impl MessageData {
+    /// Whole seconds until an ephemeral message disappears, if it is one.
+    pub fn seconds_left(&self, now: f64) -> Option<u32> {
+        self.expires_at.map(|at| ((at - now) / 1000.0).ceil().max(0.0) as u32)
+    }
+}
+
+/// A file sent alongside a message; its contents arrive as `filechunk` frames.
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct Attachment {
+    pub file_id: String,
+    pub name: String,
+    pub mime: String,
+    /// A 10×10 preview of an image, shown blurred while the full file arrives.
+    #[serde(default)]
+    pub thumbnail_data_uri: Option<String>,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+    /// Deleted by its sender or an admin; the file is gone from the server.
+    #[serde(default)]
+    pub removed: bool,
+}
+
+impl Attachment {
+    pub fn is_image(&self) -> bool {
+        self.mime.starts_with("image/")
+    }
+
+    pub fn is_video(&self) -> bool {
+        self.mime.starts_with("video/")
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -25,6 +450,53 @@ pub enum MsgTypes {
     Users,
     Register,
     Message,
+    ServerStats,
+    Members,
+    ChannelList,
+    FileChunk,
+    VideoClip,
+    Attachment,
+    Nudge,
+    Typing,
+    Checkpoint,
+    SearchRequest,
+    SearchResult,
+    UserMerge,
+    WelcomeMessage,
+    SetWelcome,
+    SlowMode,
+    AvatarUpdate,
+    Reaction,
+    ReactionRemoved,
+    History,
+    GuestMode,
+    AccessRequest,
+    Presence,
+    Moderation,
+    Edit,
+    UserTypingList,
+    LikeMessage,
+    FileDelete,
+    ServerShutdown,
+    MessagePin,
+    Unpin,
+    Batch,
+    SpamScore,
+    MessageFlag,
+    DraftSync,
+    EmojiReactionCap,
+    Unregister,
+    MessageLock,
+    UserNote,
+    GetUserNote,
+    Quota,
+    RoomCreate,
+    SlashResult,
+    EphemeralMessage,
+    Delete,
+    Broadcast,
+    PinList,
+    ThreadReply,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -33,6 +505,482 @@ pub struct WebSocketMessage {
     pub message_type: MsgTypes,
     pub data_array: Option<Vec<String>>,
     pub data: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+}
+
+/// Aggregate numbers the server answers `/admin stats` with (admins only).
+#[derive(Deserialize, PartialEq, Clone)]
+pub struct ServerStats {
+    pub total_rooms: u32,
+    pub total_users: u32,
+    pub total_messages_today: u64,
+    pub uptime_seconds: u64,
+}
+
+/// Tells the server the newest message this session has seen, so a reconnect within the
+/// session TTL only needs what came after it.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+    pub last_seen_id: String,
+}
+
+#[derive(Serialize)]
+pub struct SearchRequest {
+    pub query: String,
+    pub room: String,
+    pub limit: u32,
+}
+
+#[derive(Deserialize)]
+pub struct SearchResult {
+    pub query: String,
+    pub hits: Vec<MessageData>,
+}
+
+/// Asks for the latest `limit` messages of a channel.
+#[derive(Serialize)]
+pub struct HistoryRequest {
+    pub channel: String,
+    pub limit: u32,
+    /// Page back from this message instead of fetching the latest.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<String>,
+    /// Instead, fetch the oldest `limit` messages sent from `from` up to `to`, in ms since the epoch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<f64>,
+}
+
+/// The server's answer to a [`HistoryRequest`], oldest first. Each message carries its
+/// reactions and likes as they stand, so a page is complete in one round trip.
+#[derive(Deserialize)]
+pub struct HistoryBatch {
+    pub channel: String,
+    pub messages: Vec<MessageData>,
+    /// Whether anything older is left. Without it, a full page is taken to mean there may be.
+    #[serde(default)]
+    pub has_more: Option<bool>,
+    /// Echoes the request's `from` when answering for a picked day.
+    #[serde(default)]
+    pub from: Option<f64>,
+}
+
+/// A guest asking the admins to be allowed to post.
+#[derive(Serialize)]
+pub struct AccessRequest {
+    pub message: String,
+}
+
+/// Payload of `reaction` and `reactionremoved` frames. `from` is filled in by the server.
+#[derive(Serialize, Deserialize)]
+pub struct ReactionEvent {
+    #[serde(default)]
+    pub from: String,
+    pub channel: String,
+    pub message_id: String,
+    pub emoji: String,
+}
+
+/// Payload of `likemessage` frames, both ways. `liked: false` takes the like back.
+#[derive(Serialize, Deserialize)]
+pub struct LikeMessage {
+    pub message_id: String,
+    pub username: String,
+    pub liked: bool,
+}
+
+/// The unsent text of a room, both ways: sent after a pause in typing, and sent back by the
+/// server with the copy it kept, e.g. one typed on another device.
+#[derive(Serialize, Deserialize)]
+pub struct DraftSync {
+    pub room: String,
+    pub text: String,
+}
+
+/// What the chat route's query can ask for.
+#[derive(Deserialize)]
+struct ChatQuery {
+    channel: Option<String>,
+    /// A message to jump to once the channel's history is in, from a message link.
+    message: Option<String>,
+}
+
+/// A private note about another user. The server keeps it for its author alone, and sends
+/// it back in answer to `GetUserNote`; an empty note deletes it.
+#[derive(Serialize, Deserialize)]
+pub struct UserNote {
+    pub about_username: String,
+    pub note: String,
+}
+
+#[derive(Serialize)]
+pub struct GetUserNote {
+    pub about_username: String,
+}
+
+/// How many different emoji a message in a room may collect; 0 lifts the limit.
+#[derive(Deserialize)]
+pub struct EmojiReactionCap {
+    pub max_reactions: u32,
+}
+
+/// The server's verdict on a command such as `room_create`, with its reason on failure.
+#[derive(Deserialize)]
+pub struct SlashResult {
+    pub command: String,
+    pub success: bool,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// How much of their allowance the user has used, on deployments that set one. A quota or
+/// limit of 0 means there is none.
+#[derive(Deserialize, PartialEq, Clone)]
+pub struct QuotaInfo {
+    pub files_used_bytes: u64,
+    pub files_quota_bytes: u64,
+    pub messages_today: u32,
+    pub messages_daily_limit: u32,
+}
+
+impl QuotaInfo {
+    /// Past 90% of the file storage quota.
+    pub fn storage_nearly_full(&self) -> bool {
+        self.files_quota_bytes > 0 && self.files_used_bytes as f64 > self.files_quota_bytes as f64 * 0.9
+    }
+
+    pub fn daily_limit_reached(&self) -> bool {
+        self.messages_daily_limit > 0 && self.messages_today >= self.messages_daily_limit
+    }
+}
+
+/// A spam score the server worked out after the message went out.
+#[derive(Deserialize)]
+pub struct SpamScore {
+    pub message_id: String,
+    pub score: f32,
+}
+
+/// Sent by an admin to overrule the spam filter; `reason` is `"safe"`.
+#[derive(Serialize)]
+pub struct MessageFlag {
+    pub message_id: String,
+    pub reason: String,
+}
+
+/// Payload of an outgoing `ephemeralmessage`. It comes back like any message, with
+/// `ttl_seconds` set, and a `delete` for it follows once they are up.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EphemeralMessage {
+    pub text: String,
+    pub ttl_seconds: u32,
+}
+
+/// Payload of `delete` frames: the message is gone for everyone.
+#[derive(Serialize, Deserialize)]
+pub struct MessageDelete {
+    pub message_id: String,
+}
+
+/// Whether a new message in the open channel takes the view down to it: only when the
+/// reader was at the bottom already or sent it, and nothing else is waiting to be scrolled
+/// to, such as a reading position restored after a reload.
+fn follows_new_message(pending: Option<&ScrollTarget>, at_bottom: bool, own: bool) -> bool {
+    pending.is_none() && (at_bottom || own)
+}
+
+/// Where to put the view back to for `anchor`: `position` with its offset when it is about
+/// that same message, else just the message.
+fn anchor_target(anchor: String, position: Option<ReadingPosition>) -> ScrollTarget {
+    match position {
+        Some(position) if position.message_id == anchor => ScrollTarget::Position(position),
+        _ => ScrollTarget::Message(anchor),
+    }
+}
+
+/// Where the view goes on opening a channel: back to the anchor the store `restored` for
+/// it, else the bottom.
+fn arrival_target(restored: Option<String>, position: Option<ReadingPosition>) -> ScrollTarget {
+    match restored {
+        Some(anchor) => anchor_target(anchor, position),
+        None => ScrollTarget::Bottom,
+    }
+}
+
+/// Puts back the reading positions a reload interrupted, in every channel whose cached
+/// messages still hold the anchor, and says where the open channel's view goes: back to
+/// where it was, or the bottom when the cache no longer has that message.
+fn resume_reading<S: Store>(channels: &mut ChannelStore, memory: &ScrollMemory<S>) -> ScrollTarget {
+    for (name, position) in memory.positions() {
+        channels.restore_anchor(name, &position.message_id);
+    }
+    match memory.get(channels.active_name()) {
+        Some(position) if channels.active().contains(&position.message_id) => ScrollTarget::Position(position.clone()),
+        _ => ScrollTarget::Bottom,
+    }
+}
+
+/// Drops the loaded messages and everything stored about them: the cache a reload starts
+/// from, reading positions and drafts. Settings and the remembered username are left alone.
+fn forget_conversations<S: Store>(
+    channels: &mut ChannelStore,
+    cache: &MessageCache<S>,
+    memory: &mut ScrollMemory<S>,
+    drafts: &mut Drafts<S>,
+) {
+    channels.clear();
+    cache.clear();
+    memory.clear();
+    drafts.clear();
+}
+
+/// How many of `messages` each sender wrote, by lowercased name.
+fn count_by_sender<'a>(messages: impl Iterator<Item = &'a MessageData>) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for m in messages {
+        *counts.entry(m.from.to_lowercase()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Which of the `shown` message ids the list puts in the tab order: `current` while it is
+/// still shown, else the newest.
+fn tab_stop<'a>(shown: &[&'a str], current: Option<&str>) -> Option<&'a str> {
+    shown
+        .iter()
+        .find(|&&id| Some(id) == current)
+        .or_else(|| shown.last())
+        .copied()
+}
+
+/// The id `step` places away from `id` in `shown`, if both are there.
+fn neighbour<'a>(shown: &[&'a str], id: &str, step: isize) -> Option<&'a str> {
+    let at = shown.iter().position(|&shown| shown == id)?;
+    shown.get(at.checked_add_signed(step)?).copied()
+}
+
+/// The frame telling the server who is on a socket.
+fn register_message(username: String, is_guest: bool) -> WebSocketMessage {
+    WebSocketMessage {
+        message_type: MsgTypes::Register,
+        data: Some(username),
+        data_array: is_guest.then(|| vec!["guest".to_string()]),
+        channel: None,
+    }
+}
+
+/// The goodbye sent when the user logs out.
+fn unregister_message(username: String) -> WebSocketMessage {
+    WebSocketMessage {
+        message_type: MsgTypes::Unregister,
+        data: Some(username),
+        data_array: None,
+        channel: None,
+    }
+}
+
+/// The name the user context now holds, if it is a rename of `current`. Logging out
+/// empties the name on the way to the login page, which is nothing to follow.
+fn renamed_to<'a>(current: &str, user: &'a UserState) -> Option<&'a str> {
+    Some(user.username.as_str()).filter(|name| !name.is_empty() && *name != current)
+}
+
+/// `/ephemeral 60 some text`, as the message to send; `None` for anything else or when
+/// the seconds or the text are missing.
+fn parse_ephemeral(command: &str) -> Option<EphemeralMessage> {
+    let (seconds, text) = command.strip_prefix("/ephemeral ")?.trim().split_once(' ')?;
+    let ttl_seconds = seconds.parse::<u32>().ok().filter(|&s| s > 0)?;
+    let text = text.trim();
+    (!text.is_empty()).then(|| EphemeralMessage { text: text.to_string(), ttl_seconds })
+}
+
+/// Freezes a message or thaws it again, both ways. Only admins may send it.
+#[derive(Serialize, Deserialize)]
+pub struct MessageLock {
+    pub message_id: String,
+    pub locked: bool,
+}
+
+/// Payload of `filedelete` frames, both ways. Only the sender of the file or an admin may
+/// send one; the server checks, deletes the stored file and tells everyone.
+#[derive(Serialize, Deserialize)]
+pub struct FileDelete {
+    pub file_id: String,
+}
+
+/// How long a pin stays up.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PinType {
+    /// Until someone takes it down.
+    #[default]
+    Permanent,
+    /// Until midnight UTC, when the server sends the `unpin` itself.
+    DailyHighlight,
+}
+
+/// Payload of `messagepin` frames, both ways. Only admins may pin; `by` is filled in by
+/// the server.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct MessagePin {
+    pub channel: String,
+    pub message_id: String,
+    #[serde(default)]
+    pub pin_type: PinType,
+    #[serde(default)]
+    pub by: String,
+}
+
+/// Payload of `unpin` frames, both ways.
+#[derive(Serialize, Deserialize)]
+pub struct Unpin {
+    pub channel: String,
+    pub message_id: String,
+}
+
+/// A planned shutdown announced by the operator. `shutdown_at` is in seconds since the
+/// epoch; 0 calls off the one announced before.
+#[derive(Deserialize, Clone)]
+pub struct ServerShutdown {
+    pub reason: String,
+    pub shutdown_at: u64,
+}
+
+/// An admin's announcement, shown as a banner over the message list. `target_rooms` limits
+/// it to people currently in one of those rooms; without it every room gets it.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Broadcast {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_rooms: Option<Vec<String>>,
+}
+
+impl Broadcast {
+    pub fn reaches(&self, room: &str) -> bool {
+        match &self.target_rooms {
+            Some(rooms) => rooms.iter().any(|r| r == room),
+            None => true,
+        }
+    }
+}
+
+/// Several frames the server sent as one. Each event is an ordinary message; a batch
+/// inside a batch is opened too, but nothing deeper.
+#[derive(Deserialize)]
+pub struct Batch {
+    pub events: Vec<WebSocketMessage>,
+}
+
+impl Batch {
+    /// The events in order, with nested batches opened one level and any below that dropped.
+    fn flatten(self) -> Vec<WebSocketMessage> {
+        let mut events = Vec::with_capacity(self.events.len());
+        for event in self.events {
+            if !matches!(event.message_type, MsgTypes::Batch) {
+                events.push(event);
+                continue;
+            }
+            match event.data.and_then(|data| serde_json::from_str::<Batch>(&data).ok()) {
+                Some(inner) => events.extend(
+                    inner.events.into_iter().filter(|e| !matches!(e.message_type, MsgTypes::Batch)),
+                ),
+                None => log::error!("Malformed nested batch"),
+            }
+        }
+        events
+    }
+}
+
+/// Broadcast when someone changes their avatar.
+#[derive(Deserialize)]
+pub struct AvatarUpdate {
+    pub username: String,
+    pub avatar_url: String,
+}
+
+/// Sent to everyone once the server has confirmed a rename.
+#[derive(Deserialize)]
+pub struct UserMerge {
+    pub old_name: String,
+    pub new_name: String,
+}
+
+/// An `edit` frame going out: the user's new text for one of their own messages.
+#[derive(Serialize)]
+pub struct MessageEditRequest {
+    pub message_id: String,
+    pub message: String,
+}
+
+/// A `threadreply` frame going out. The server sends it back as a message with `thread_id` set.
+#[derive(Serialize)]
+pub struct ThreadReply {
+    pub thread_id: String,
+    pub message: String,
+}
+
+/// Payload of an `edit` frame: a message's new text. `by` is filled in by the server.
+#[derive(Deserialize)]
+pub struct MessageEdit {
+    pub message_id: String,
+    pub message: String,
+    pub by: String,
+    /// Given with admin edits, and only passed on to admins.
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// A kick, ban, mute or resolved flag. The server only sends these to admins.
+#[derive(Deserialize, PartialEq, Clone)]
+pub struct ModerationEntry {
+    pub action: String,
+    pub target: String,
+    pub by: String,
+    /// Milliseconds since the epoch.
+    pub timestamp: u64,
+}
+
+/// A room's slow-mode cooldown; 0 turns it off.
+#[derive(Serialize, Deserialize)]
+pub struct SlowMode {
+    pub seconds: u32,
+    /// Set by the server on the copy it sends to admins.
+    #[serde(default)]
+    pub exempt: bool,
+}
+
+/// Which side of the admin panel is showing.
+#[derive(Clone, Copy, PartialEq)]
+pub enum AdminTab {
+    Stats,
+    ModLog,
+    Broadcast,
+}
+
+/// The search panel opened with Ctrl+K, which looks through the messages on this device.
+struct LocalSearch {
+    /// As typed, to search again when the scope changes.
+    input: String,
+    query: SearchQuery,
+    /// Why the latest input couldn't be searched; the previous results stay up meanwhile.
+    error: Option<String>,
+    /// Newest first, up to one past `MAX_RESULTS`.
+    results: Vec<MessageData>,
+}
+
+/// The `/search` currently shown over the message list.
+struct Search {
+    query: String,
+    /// The room searched, which hits without a channel of their own are from.
+    room: String,
+    /// `None` until the server answers.
+    hits: Option<Vec<MessageData>>,
+    failed: bool,
+    /// Dropped along with the search, or once results arrive.
+    timeout: Option<Timeout>,
 }
 
 #[derive(Clone)]
@@ -41,192 +989,4899 @@ pub struct UserProfile {
     pub avatar: String,
 }
 
+/// Where a linked message that wasn't in the first page of history is being looked for.
+#[derive(Clone, Copy, PartialEq)]
+enum LinkSearch {
+    Latest,
+    /// In the archive, which knows when it was sent and so which day to open.
+    Archive,
+    /// In older pages from the server, this many fetched so far.
+    Older(u32),
+    /// In the day opened for it.
+    Day,
+}
+
+/// Where the message list should be scrolled once the next render lands.
+enum ScrollTarget {
+    Bottom,
+    Message(String),
+    /// Keep the viewport where it was before content was added above it.
+    KeepPosition { scroll_height: i32, scroll_top: i32 },
+    /// The separator of this `YYYY-MM-DD`, or of the first day after it that has messages.
+    Day(String),
+    /// Where the user was reading before the page was reloaded.
+    Position(ReadingPosition),
+}
+
 pub struct Chat {
     users: Vec<UserProfile>,
     chat_input: NodeRef,
+    message_list: NodeRef,
+    /// Empty marker at the top of the message list; older history loads when it comes into view.
+    history_sentinel: NodeRef,
+    sentinel: Option<ScrollSentinel>,
     wss: WebsocketService,
-    messages: Vec<MessageData>,
+    channels: ChannelStore,
+    pending_scroll: Option<ScrollTarget>,
+    /// Reading positions kept across reloads.
+    scroll_memory: ScrollMemory,
+    /// Pending save of the reading position; at most one a second while scrolling.
+    scroll_save: Option<Timeout>,
+    settings: UserSettings,
+    server_stats: Option<ServerStats>,
+    /// The user's storage and message allowance, once the server has said.
+    quota: Option<QuotaInfo>,
+    mod_log: Vec<ModerationEntry>,
+    admin_tab: AdminTab,
+    /// "This message was edited for policy reasons." is showing.
+    policy_notice_open: bool,
+    /// An upload waiting on the user's go-ahead, and the warnings it was held back for.
+    upload_confirm: Option<(HeldUpload, Vec<String>)>,
+    /// The newest message someone else sent to the open channel, for screen readers to read
+    /// out. Only live messages land here, so loading pages of history stays quiet.
+    announcement: String,
+    /// The sidebar is pulled out over the chat on a narrow screen.
+    drawer_open: bool,
+    /// The user list is expanded on wide screens. Below `md` it always shows, inside the drawer.
+    sidebar_visible: bool,
+    connection: ConnectionState,
+    /// Numbers the sockets opened so far; the current one is `socket`.
+    socket: u32,
+    reconnect_timer: Option<Timeout>,
+    _network_listeners: [EventListener; 2],
+    export_open: bool,
+    /// Worked out when the stats modal opens, so it doesn't move while being read.
+    stats: Option<SessionStats>,
+    /// Whose profile card is open.
+    profile_open: Option<String>,
+    /// The message a right-click menu is open for, and where on the screen.
+    message_menu: Option<(String, i32, i32)>,
+    /// The message whose thread is open over the list.
+    open_thread: Option<String>,
+    /// Message a link opened the chat on, jumped to once it has been found, and how far
+    /// that has got.
+    pending_link: Option<(String, LinkSearch)>,
+    /// The current user's private notes, by the name they are about.
+    user_notes: HashMap<String, String>,
+    /// Loaded messages per lowercased sender, counted at most once a render since every
+    /// mention on screen asks for it.
+    message_counts: RefCell<Option<HashMap<String, usize>>>,
+    clear_data_open: bool,
+    settings_open: bool,
+    /// Id of the message the pin dialog is open for.
+    pin_dialog: Option<String>,
+    channel_browser_open: bool,
+    channel_list: Option<Vec<ChannelInfo>>,
+    /// Name of the room asked for from the channel browser, until the server answers, and
+    /// the timer that stops waiting for it.
+    creating_room: Option<String>,
+    creating_room_timeout: Option<Timeout>,
+    /// Cleared when the server never answered a channel list request.
+    channel_list_supported: bool,
+    channel_list_timeout: Option<Timeout>,
+    /// Gives up on a picked day's sources after a while.
+    day_timeout: Option<Timeout>,
+    /// Gives up on each channel's page of history in flight, by channel.
+    history_timeouts: HashMap<String, Timeout>,
+    user_filter: String,
+    recorder_open: bool,
+    /// The server registered us as a guest: we can read everything but send nothing.
+    read_only: bool,
+    /// Joined with the guest option, so the name isn't offered on the login screen next time.
+    joined_as_guest: bool,
+    /// The server confirmed our registration, and the name is stored for the login screen.
+    name_remembered: bool,
+    request_access_open: bool,
+    clip_source: ClipSource,
+    recorder: Option<ClipRecorder>,
+    recording_timeout: Option<Timeout>,
+    files: ChunkAssembler,
+    lightbox_image: Option<Rc<str>>,
+    locating: bool,
+    permission_prompt: PermissionPrompt,
+    /// The permission prompt waits until the user has taken part in the chat.
+    has_sent_message: bool,
+    seen_welcomes: SeenWelcomes,
+    drafts: Drafts,
+    /// Room whose draft the message input holds; the input is swapped over when it changes.
+    draft_room: String,
+    /// Saves the draft once typing pauses.
+    draft_sync: Option<Timeout>,
+    showing_mentions: bool,
+    /// Message picked out after a jump, until `highlight_timeout` fires.
+    highlighted: Option<String>,
+    highlight_timeout: Option<Timeout>,
+    /// The one message the list puts in the tab order, so the list is a single tab stop
+    /// and the arrow keys move within it. The newest one while this isn't shown.
+    tab_stop: Option<String>,
+    /// Messages flagged as possible spam that the user chose to see anyway.
+    revealed_spam: HashSet<String>,
+    search: Option<Search>,
+    local_search: Option<LocalSearch>,
+    _shortcuts: Shortcuts,
+    shortcut_help_open: bool,
+    /// Id of the message the composer is editing instead of writing a new one.
+    editing: Option<String>,
+    /// Files and clips being read for sending, oldest first, by id and name. Removing one
+    /// drops it once it has been read.
+    uploads: Vec<(u32, String)>,
+    next_upload: u32,
+    archived_open: bool,
+    user_menu: Option<String>,
+    nudges_sent: HashMap<String, f64>,
+    nudged_by: Option<String>,
+    nudge_timeout: Option<Timeout>,
+    typing: TypingTracker,
+    /// An announced shutdown still to come, and the timer that goes off when it is due.
+    shutdown: Option<ServerShutdown>,
+    shutdown_timer: Option<Timeout>,
+    /// The latest admin announcement for each set of target rooms, oldest first, until
+    /// dismissed. Each room shows the newest that reaches it.
+    broadcasts: Vec<Broadcast>,
+    message_cache: MessageCache,
+    /// Long-term history in IndexedDB; `None` until it has opened, or where it can't.
+    archive: Option<MessageArchive>,
+    /// Today's transcript, while the user keeps one. Saved to the archive along with the
+    /// message cache.
+    transcript: Option<Transcript>,
+    /// Pending write of the message cache; saves are batched so bursts cost one write.
+    cache_save: Option<Timeout>,
+    presence: PresenceService,
+    /// Muted whenever sound is off in the settings.
+    audio: AudioService,
+    /// Follows the system colour scheme while the theme is set to System.
+    _theme_watch: Option<EventListener>,
+    /// One pending tick per ephemeral message on screen, keyed by message id; each moves its
+    /// countdown on a second, and `rendered` sets up the next.
+    ephemeral_timers: HashMap<String, Timeout>,
+    /// Counts down to the long-press menu while a finger rests on a message, with where it
+    /// went down.
+    long_press: Option<(Timeout, (i32, i32))>,
+    /// Pixels of the page hidden behind the on-screen keyboard; the composer sits above them.
+    keyboard_inset: f64,
+    _keyboard_watch: Option<KeyboardWatch>,
+    /// The system asks for less motion, so transitions and attention animations are left out.
+    prefers_reduced_motion: bool,
+    /// Newest server-assigned message id seen.
+    checkpoint_id: String,
+    /// What the server was last told `checkpoint_id` is.
+    sent_checkpoint_id: String,
+    since_checkpoint: usize,
+    _checkpoint_timer: Interval,
+    _visibility_listener: EventListener,
+    /// Messages that arrived while the tab was hidden, shown in the tab title.
+    title: TitleBadge,
+    favicon: Option<FaviconBadge>,
+    /// Cached result of the DND check, refreshed on a timer so schedules kick in by themselves.
+    dnd_active: bool,
+    _dnd_timer: Interval,
+    _typing_prune: Interval,
     _producer: Box<dyn Bridge<EventBus>>,
+    toasts: Dispatcher<ToastBus>,
+    /// Features this deployment has switched on; updates once `/api/config` answers.
+    flags: FeatureFlags,
+    _flags_handle: Option<ContextHandle<FeatureFlags>>,
+    _user_handle: ContextHandle<UserCtx>,
     current_user: String,
 }
 
-impl Component for Chat {
-    type Message = Msg;
-    type Properties = ();
+/// Splits online users into those in `members` and everyone else, keeping their order.
+pub fn split_by_membership<'a>(
+    users: &'a [UserProfile],
+    members: &[String],
+) -> (Vec<&'a UserProfile>, Vec<&'a UserProfile>) {
+    users.iter().partition(|u| members.contains(&u.name))
+}
 
-    fn create(ctx: &Context<Self>) -> Self {
-        let (user, _) = ctx
-            .link()
-            .context::<User>(Callback::noop())
-            .expect("context to be set");
+/// What a screen reader reads out for a message: "alice, 14:32: hello".
+fn message_label(m: &MessageData) -> String {
+    match m.timestamp {
+        Some(ms) => t!("message.label", from = &m.from, time = i18n::format_time(ms), text = &m.message),
+        None => t!("message.label_untimed", from = &m.from, text = &m.message),
+    }
+}
+
+/// What a pointer event of type `event` on a message sends. Only touches count, as a mouse
+/// has the context menu: holding a finger on a message that is `linkable` starts the long
+/// press that opens its menu, and moving or lifting the finger may end it.
+fn touch_msg(event: &str, pointer_type: &str, id: &str, linkable: bool, (x, y): (i32, i32)) -> Option<Msg> {
+    if pointer_type != "touch" {
+        return None;
+    }
+    match event {
+        "pointerdown" => linkable.then(|| Msg::PressMessage(id.to_string(), x, y)),
+        "pointermove" => Some(Msg::MovePress(x, y)),
+        "pointerup" | "pointercancel" => Some(Msg::ReleaseMessage),
+        _ => None,
+    }
+}
 
-        let wss = WebsocketService::new();
-        let username = user.username.borrow().clone();
+/// What the live region reads out for a message that just came in: only what someone else
+/// posted in the open channel, and nothing the user will never be shown.
+fn announcement(m: &MessageData, own: bool, hidden: bool, in_open_channel: bool) -> Option<String> {
+    (!own && !hidden && in_open_channel).then(|| message_label(m))
+}
 
-        let register_msg = WebSocketMessage {
-            message_type: MsgTypes::Register,
-            data: Some(username.clone()),
-            data_array: None,
-        };
+/// The left border a message with a sentiment score gets: green when clearly positive, red
+/// when clearly negative, grey in between.
+fn sentiment_border(sentiment: Option<f32>) -> Option<&'static str> {
+    match sentiment? {
+        s if s > 0.5 => Some("border-l-4 border-green-300"),
+        s if s < -0.5 => Some("border-l-4 border-red-300"),
+        _ => Some("border-l-4 border-gray-300"),
+    }
+}
 
-        if let Ok(_) = wss.tx.clone().try_send(serde_json::to_string(&register_msg).unwrap()) {
-            log::debug!("Registered user {}", username);
+/// Above this a message is collapsed behind a warning.
+const SPAM_SUSPECT: f32 = 0.8;
+/// Above this only admins see the message at all.
+const SPAM_HIDDEN: f32 = 0.95;
+
+/// A file or clip held back until the user confirms sending it.
+enum HeldUpload {
+    File(web_sys::File),
+    Clip(web_sys::Blob),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SpamLevel {
+    Clean,
+    Suspect,
+    Hidden,
+}
+
+fn spam_level(score: Option<f32>) -> SpamLevel {
+    match score {
+        Some(s) if s > SPAM_HIDDEN => SpamLevel::Hidden,
+        Some(s) if s > SPAM_SUSPECT => SpamLevel::Suspect,
+        _ => SpamLevel::Clean,
+    }
+}
+
+/// `12.3 MB`.
+pub fn format_megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / 1024.0 / 1024.0)
+}
+
+fn format_uptime(seconds: u64) -> String {
+    let (days, hours, minutes) = (seconds / 86_400, seconds % 86_400 / 3_600, seconds % 3_600 / 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+impl Chat {
+    /// Plays `sound` unless Do Not Disturb is on; muting is the audio service's business.
+    fn play(&self, sound: SoundEffect) {
+        if !self.settings.dnd.is_active(Now::local()) {
+            self.audio.play(sound);
         }
+    }
 
-        Chat {
-            users: vec![],
-            chat_input: NodeRef::default(),
-            wss,
-            messages: vec![],
-            _producer: EventBus::bridge(ctx.link().callback(Msg::HandleMsg)),
-            current_user: username,
+    fn daily_limit_reached(&self) -> bool {
+        self.quota.as_ref().map_or(false, QuotaInfo::daily_limit_reached)
+    }
+
+    /// Why an upload should be confirmed before it goes out: a clip of `clip_size` bytes
+    /// over the limit, or storage that is nearly full. Empty if it can just go.
+    fn upload_warnings(&self, clip_size: Option<f64>) -> Vec<String> {
+        let mut warnings = vec![];
+        if let Some(size) = clip_size.filter(|&size| size > CLIP_WARN_BYTES) {
+            warnings.push(t!("upload.clip_too_big", size = format!("{:.1}", size / 1024.0 / 1024.0)));
         }
+        if let Some(quota) = self.quota.as_ref().filter(|quota| quota.storage_nearly_full()) {
+            warnings.push(t!(
+                "quota.upload_confirm",
+                used = format_megabytes(quota.files_used_bytes),
+                quota = format_megabytes(quota.files_quota_bytes),
+            ));
+        }
+        warnings
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
-        match msg {
-            Msg::HandleMsg(data) => {
-                let msg: WebSocketMessage = serde_json::from_str(&data).unwrap();
-                match msg.message_type {
-                    MsgTypes::Users => {
-                        let usernames = msg.data_array.unwrap_or_default();
-                        self.users = usernames
-                            .into_iter()
-                            .map(|name| UserProfile {
-                                avatar: format!("https://avatars.dicebear.com/api/adventurer-neutral/{}.svg", name),
-                                name,
-                            })
-                            .collect();
-                        true
-                    }
-                    MsgTypes::Message => {
-                        let message_data: MessageData = serde_json::from_str(&msg.data.unwrap()).unwrap();
-                        self.messages.push(message_data);
-                        true
-                    }
-                    _ => false,
-                }
-            }
-            Msg::SubmitMessage => {
-                if let Some(input) = self.chat_input.cast::<HtmlInputElement>() {
-                    let text = input.value();
-                    let message = WebSocketMessage {
-                        message_type: MsgTypes::Message,
-                        data: Some(text.clone()),
-                        data_array: None,
-                    };
-                    if !text.trim().is_empty() {
-                        let _ = self.wss.tx.clone().try_send(serde_json::to_string(&message).unwrap());
+    /// Sends `upload`, or holds it for the confirm dialog if there is anything to warn about.
+    fn upload_or_confirm(&mut self, ctx: &Context<Self>, upload: HeldUpload) {
+        let clip_size = match &upload {
+            HeldUpload::Clip(blob) => Some(blob.size()),
+            HeldUpload::File(_) => None,
+        };
+        let warnings = self.upload_warnings(clip_size);
+        if warnings.is_empty() {
+            self.start_reading(ctx, upload);
+        } else {
+            self.upload_confirm = Some((upload, warnings));
+        }
+    }
+
+    /// Reads `upload` in the background, to be sent once it has been encoded.
+    fn start_reading(&mut self, ctx: &Context<Self>, upload: HeldUpload) {
+        match upload {
+            HeldUpload::Clip(blob) => {
+                let id = self.start_upload(t!("status.video_clip"));
+                ctx.link().send_future(async move {
+                    match gloo_file::futures::read_as_data_url(&blob.into()).await {
+                        Ok(url) => Msg::ClipEncoded(id, url),
+                        Err(e) => {
+                            log::error!("failed to encode clip: {:?}", e);
+                            Msg::UploadFailed(id)
+                        }
                     }
-                    input.set_value("");
-                }
-                false
+                });
             }
-            Msg::React(index, emoji) => {
-                if let Some(msg) = self.messages.get_mut(index) {
-                    let user = self.current_user.clone();
-                    if let Some(reactions) = &mut msg.reactions {
-                        if let Some((_, users)) = reactions.iter_mut().find(|(e, _)| e == &emoji) {
-                            if users.contains(&user) {
-                                users.retain(|u| u != &user);
-                            } else {
-                                users.push(user);
+            HeldUpload::File(file) => {
+                let id = self.start_upload(file.name());
+                let mut attachment = Attachment {
+                    file_id: String::new(),
+                    name: file.name(),
+                    mime: file.type_(),
+                    thumbnail_data_uri: None,
+                    width: None,
+                    height: None,
+                    removed: false,
+                };
+                ctx.link().send_future(async move {
+                    match gloo_file::futures::read_as_data_url(&file.into()).await {
+                        Ok(url) => {
+                            if attachment.is_image() {
+                                if let Some(thumbnail) = thumbnail(&url).await {
+                                    attachment.thumbnail_data_uri = Some(thumbnail.data_uri);
+                                    attachment.width = Some(thumbnail.width);
+                                    attachment.height = Some(thumbnail.height);
+                                }
                             }
-                        } else {
-                            reactions.push((emoji, vec![user]));
+                            Msg::AttachmentEncoded(id, attachment, url)
+                        }
+                        Err(e) => {
+                            log::error!("failed to read attachment: {:?}", e);
+                            Msg::UploadFailed(id)
                         }
-                    } else {
-                        msg.reactions = Some(vec![(emoji, vec![user])]);
                     }
-                    true
-                } else {
-                    false
-                }
+                });
             }
         }
     }
 
-    fn view(&self, ctx: &Context<Self>) -> Html {
-        let submit = ctx.link().callback(|_| Msg::SubmitMessage);
-        let react = ctx.link().callback(|(idx, emoji): (usize, String)| Msg::React(idx, emoji));
-        let emojis = vec!["👍", "❤️", "😂", "😮", "😢", "👏"];
+    /// Shows `name` as being read for sending and returns the id its result will carry.
+    fn start_upload(&mut self, name: String) -> u32 {
+        let id = self.next_upload;
+        self.next_upload = self.next_upload.wrapping_add(1);
+        self.uploads.push((id, name));
+        id
+    }
 
-        html! {
-            <div class="flex w-screen">
-                <div class="w-56 h-screen bg-gray-100 overflow-auto">
-                    <div class="text-xl p-3 font-semibold">{"Users"}</div>
-                    {
-                        self.users.iter().map(|u| html! {
-                            <div class="flex m-3 bg-white rounded-lg p-2">
-                                <img class="w-12 h-12 rounded-full" src={u.avatar.clone()} alt="avatar"/>
-                                <div class="p-3 text-sm">
-                                    <div class="font-medium">{ &u.name }</div>
-                                    <div class="text-xs text-gray-400">{"Hi there!"}</div>
+    /// Stops showing upload `id`. False if it was cancelled meanwhile, so its result is dropped.
+    fn finish_upload(&mut self, id: u32) -> bool {
+        let before = self.uploads.len();
+        self.uploads.retain(|(upload, _)| *upload != id);
+        self.uploads.len() != before
+    }
+
+    fn toast(&mut self, level: ToastLevel, text: &str) {
+        self.toasts.send(ToastMsg::Show(Toast::new(level, text)));
+    }
+
+    /// Reads back what today's transcript held before this page load, so it carries on
+    /// rather than starting over.
+    fn load_transcript(&self, ctx: &Context<Self>) {
+        if let (Some(archive), Some(transcript)) = (self.archive.clone(), &self.transcript) {
+            let day = transcript.day.clone();
+            ctx.link().send_future(async move {
+                match archive.transcript(&day).await {
+                    Ok(saved) => Msg::TranscriptLoaded(saved),
+                    Err(e) => {
+                        log::error!("failed to read the transcript: {:?}", e);
+                        Msg::TranscriptLoaded(None)
+                    }
+                }
+            });
+        }
+    }
+
+    /// Adds a message that just arrived to the transcript, if one is kept, saving the day
+    /// before when it has passed midnight.
+    fn record_transcript(&mut self, message: &MessageData) {
+        if let Some(transcript) = &mut self.transcript {
+            let offset = -(js_sys::Date::new_0().get_timezone_offset() as i32);
+            if let Some(finished) = transcript.append(message, js_sys::Date::now(), offset) {
+                if let Some(archive) = &self.archive {
+                    archive.save_transcript(&finished);
+                }
+            }
+        }
+    }
+
+    /// Keeps `text` as the draft of `room` here and on the server.
+    fn store_draft(&mut self, room: &str, text: &str) {
+        if !self.drafts.set(room, text) {
+            return;
+        }
+        let draft = DraftSync {
+            room: room.to_string(),
+            text: text.to_string(),
+        };
+        self.send(WebSocketMessage {
+            message_type: MsgTypes::DraftSync,
+            data: Some(serde_json::to_string(&draft).unwrap()),
+            data_array: None,
+            channel: None,
+        });
+    }
+
+    /// The layer Escape should close, topmost first: dialogs, then menus, then the drawer.
+    fn close_top_layer(&self) -> Option<Msg> {
+        let msg = if self.shortcut_help_open {
+            Msg::CloseShortcutHelp
+        } else if self.upload_confirm.is_some() {
+            Msg::DiscardUpload
+        } else if self.request_access_open {
+            Msg::CloseRequestAccess
+        } else if self.policy_notice_open {
+            Msg::ShowPolicyNotice(false)
+        } else if self.clear_data_open {
+            Msg::CloseClearData
+        } else if self.pin_dialog.is_some() {
+            Msg::ClosePinDialog
+        } else if self.stats.is_some() {
+            Msg::CloseStats
+        } else if self.profile_open.is_some() {
+            Msg::CloseProfile
+        } else if self.settings_open {
+            Msg::CloseSettings
+        } else if self.message_menu.is_some() {
+            Msg::CloseMessageMenu
+        } else if self.export_open {
+            Msg::CloseExport
+        } else if self.channel_browser_open {
+            Msg::CloseChannelBrowser
+        } else if self.lightbox_image.is_some() {
+            Msg::CloseLightbox
+        } else if self.local_search.is_some() {
+            Msg::CloseLocalSearch
+        } else if let Some(name) = &self.user_menu {
+            Msg::ToggleUserMenu(name.clone())
+        } else if self.drawer_open {
+            Msg::SetDrawer(false)
+        } else {
+            return None;
+        };
+        Some(msg)
+    }
+
+    /// Channels and DMs in the order the rail lists them.
+    fn rail_order(&self) -> Vec<String> {
+        let channels = self.channels.channels();
+        let rooms = channels
+            .iter()
+            .filter(|c| !c.is_dm() && (c.surfaced || !self.settings.notifications.is_archived(&c.name)));
+        let dms = channels.iter().filter(|c| c.is_dm() && !c.hidden);
+        rooms.chain(dms).map(|c| c.name.clone()).collect()
+    }
+
+    /// Sets a timer for every ephemeral message on screen that doesn't have one, due when its
+    /// countdown next drops a second.
+    fn schedule_ephemeral_ticks(&mut self, ctx: &Context<Self>) {
+        let now = js_sys::Date::now();
+        for m in self.channels.active().visible() {
+            let expires_at = match m.expires_at {
+                Some(at) if !self.ephemeral_timers.contains_key(&m.id) => at,
+                _ => continue,
+            };
+            let remaining = (expires_at - now).max(0.0);
+            let wait = match remaining % 1000.0 {
+                part if part > 0.0 => part,
+                _ if remaining > 0.0 => 1000.0,
+                _ => 0.0,
+            };
+            let link = ctx.link().clone();
+            let id = m.id.clone();
+            let timer = Timeout::new(wait.ceil() as u32, move || link.send_message(Msg::EphemeralTick(id)));
+            self.ephemeral_timers.insert(m.id.clone(), timer);
+        }
+    }
+
+    fn focus_composer(&self) {
+        if let Some(input) = self.chat_input.cast::<HtmlElement>() {
+            if let Err(e) = input.focus() {
+                log::error!("failed to focus the composer: {:?}", e);
+            }
+        }
+    }
+
+    /// Leaves edit mode, putting the room's draft back in the composer.
+    fn cancel_edit(&mut self) {
+        if self.editing.take().is_none() {
+            return;
+        }
+        if let Some(input) = self.chat_input.cast::<HtmlInputElement>() {
+            input.set_value(self.drafts.get(self.channels.active_name()).unwrap_or_default());
+        }
+    }
+
+    /// After a switch of room, stores what was typed for the room left and fills the input
+    /// with the new room's draft.
+    fn swap_draft(&mut self) {
+        if self.draft_room == self.channels.active_name() {
+            return;
+        }
+        let input = match self.chat_input.cast::<HtmlInputElement>() {
+            Some(input) => input,
+            None => return,
+        };
+        let left = std::mem::replace(&mut self.draft_room, self.channels.active_name().to_string());
+        if !left.is_empty() {
+            self.draft_sync = None;
+            self.store_draft(&left, &input.value());
+        }
+        input.set_value(self.drafts.get(&self.draft_room).unwrap_or_default());
+    }
+
+    /// Opens connection number `socket` to the server and registers `user` on it.
+    fn connect(ctx: &Context<Self>, user: &UserState, socket: u32) -> WebsocketService {
+        let wss = WebsocketService::new(
+            ctx.link().callback(move |_| Msg::SocketEvent(socket, ConnectionEvent::Opened)),
+            ctx.link().callback(move |_| Msg::SocketEvent(socket, ConnectionEvent::Closed)),
+        );
+        Self::register(&wss, user);
+        wss
+    }
+
+    /// Tells the server who is on `wss`.
+    fn register(wss: &WebsocketService, user: &UserState) {
+        // The login form checks names, but a remembered session or a stale build may not have.
+        let username = match validate_username(&user.username) {
+            Ok(username) => username,
+            Err(e) => {
+                log::error!("not registering invalid username {:?}: {}", user.username, e);
+                return;
+            }
+        };
+
+        let register_msg = register_message(username.clone(), user.is_guest);
+        if let Ok(_) = wss.tx.clone().try_send(serde_json::to_string(&register_msg).unwrap()) {
+            log::debug!("Registered user {}", username);
+        }
+    }
+
+    /// Moves the connection state machine on and does what the new state needs.
+    fn transition(&mut self, ctx: &Context<Self>, event: ConnectionEvent) -> bool {
+        let online = web_sys::window().unwrap().navigator().on_line();
+        let (state, action) = self.connection.next(event, online);
+        if state == self.connection && action == ConnectionAction::None {
+            return false;
+        }
+        self.connection = state;
+        match action {
+            ConnectionAction::None => {}
+            ConnectionAction::Reconnect { delay_ms } => {
+                let link = ctx.link().clone();
+                self.reconnect_timer = Some(Timeout::new(delay_ms, move || link.send_message(Msg::OpenConnection)));
+            }
+            ConnectionAction::Resume => {
+                self.presence.start(self.wss.tx.clone());
+                // The first socket's history requests are already answered on it.
+                if self.socket > 0 {
+                    // An answer to a room asked for on the old socket isn't coming.
+                    self.creating_room = None;
+                    self.creating_room_timeout = None;
+                    self.channels.refetch_history();
+                    self.request_history(ctx);
+                    self.sent_checkpoint_id.clear();
+                    ctx.link().send_message(Msg::SendCheckpoint);
+                }
+            }
+            ConnectionAction::HangUp => {
+                self.reconnect_timer = None;
+                self.presence.stop();
+                self.wss.close();
+            }
+        }
+        true
+    }
+
+    fn send(&self, message: WebSocketMessage) {
+        let _ = self.wss.tx.clone().try_send(serde_json::to_string(&message).unwrap());
+    }
+
+    /// Sends a data URL out as `filechunk` frames and returns the file id to reference it by.
+    fn upload(&mut self, url: String) -> String {
+        let file_id = format!("{}-{}", self.current_user, js_sys::Date::now());
+        let chunks = split_into_chunks(&url, CHUNK_SIZE);
+        let total = chunks.len();
+        for (index, data) in chunks.into_iter().enumerate() {
+            let chunk = FileChunk {
+                file_id: file_id.clone(),
+                index,
+                total,
+                data,
+            };
+            self.send(WebSocketMessage {
+                message_type: MsgTypes::FileChunk,
+                data: Some(serde_json::to_string(&chunk).unwrap()),
+                data_array: None,
+                channel: None,
+            });
+        }
+        self.files.insert(file_id.clone(), url);
+        file_id
+    }
+
+    /// Loaded image attachments of the active channel, oldest first.
+    fn channel_images(&self) -> Vec<Rc<str>> {
+        self.channels
+            .active()
+            .messages
+            .iter()
+            .flat_map(|m| m.attachments.iter())
+            .filter(|a| a.is_image())
+            .filter_map(|a| self.files.get(&a.file_id).cloned())
+            .collect()
+    }
+
+    /// The active channel's pins, each a banner that jumps to its message: orange for
+    /// permanent pins, a softer yellow for the day's highlights.
+    fn view_pins(&self, ctx: &Context<Self>) -> Html {
+        let channel = self.channels.active();
+        channel
+            .pins
+            .iter()
+            .map(|pin| {
+                let class = match pin.pin_type {
+                    PinType::Permanent => "bg-orange-100 border-orange-300 text-orange-900",
+                    PinType::DailyHighlight => "bg-yellow-50 border-yellow-200 text-yellow-900",
+                };
+                let label = match pin.pin_type {
+                    PinType::Permanent => t!("pins.pinned"),
+                    PinType::DailyHighlight => t!("pins.highlight"),
+                };
+                let text = channel
+                    .find(&pin.message_id)
+                    .map(|m| format!("{}: {}", m.from, m.message))
+                    .unwrap_or_else(|| t!("pins.not_loaded"));
+                let jump = {
+                    let channel = pin.channel.clone();
+                    let id = pin.message_id.clone();
+                    ctx.link().callback(move |_| Msg::JumpToMessage {
+                        channel: channel.clone(),
+                        id: id.clone(),
+                    })
+                };
+                let unpin = {
+                    let id = pin.message_id.clone();
+                    ctx.link().callback(move |e: MouseEvent| {
+                        e.stop_propagation();
+                        Msg::UnpinMessage(id.clone())
+                    })
+                };
+                html! {
+                    <div onclick={jump} class={classes!("flex", "items-center", "px-4", "py-2", "border-b", "text-sm", "cursor-pointer", class)}>
+                        <button type="button" class="flex flex-1 min-w-0 text-left focus-ring">
+                            <span class="mr-2 font-semibold whitespace-nowrap">{ label }</span>
+                            <span class="flex-1 truncate">{ text }</span>
+                        </button>
+                        if self.is_admin() {
+                            <button onclick={unpin} title={t!("pins.unpin")} aria-label={t!("pins.unpin")} class="ml-2 opacity-60 hover:opacity-100 focus-ring">{"×"}</button>
+                        }
+                    </div>
+                }
+            })
+            .collect()
+    }
+
+    /// Admins get a pin beside each message that isn't pinned yet.
+    fn view_pin_button(&self, ctx: &Context<Self>, m: &MessageData) -> Html {
+        if !self.is_admin() || m.expires_at.is_some() || self.channels.active().pins.iter().any(|p| p.message_id == m.id) {
+            return html! {};
+        }
+        let id = m.id.clone();
+        html! {
+            <button
+                onclick={ctx.link().callback(move |_| Msg::OpenPinDialog(id.clone()))}
+                title={t!("pins.pin")}
+                class={classes!("focus-ring", "mt-1", "ml-2", "text-xs", "text-gray-400", "hover:text-orange-500", TOUCH_TARGET)}
+            >
+                {"📌"}
+            </button>
+        }
+    }
+
+    /// Sets the timer for the announced shutdown. Browsers fire timeouts longer than an i32
+    /// of milliseconds straight away, so a far-off one is set again when that runs out.
+    fn arm_shutdown_timer(&mut self, ctx: &Context<Self>) {
+        let shutdown_at = match &self.shutdown {
+            Some(shutdown) => shutdown.shutdown_at as f64 * 1000.0,
+            None => return,
+        };
+        let wait = (shutdown_at - js_sys::Date::now()).clamp(0.0, i32::MAX as f64);
+        let link = ctx.link().clone();
+        self.shutdown_timer = Some(Timeout::new(wait as u32, move || link.send_message(Msg::ShutdownDue)));
+    }
+
+    /// Where in `broadcasts` the announcement for the room being looked at is, if any.
+    fn shown_broadcast(&self) -> Option<usize> {
+        self.broadcasts.iter().rposition(|broadcast| broadcast.reaches(self.channels.active_name()))
+    }
+
+    /// The latest announcement meant for the room being looked at.
+    fn view_broadcast_banner(&self, ctx: &Context<Self>) -> Html {
+        let broadcast = match self.shown_broadcast() {
+            Some(index) => &self.broadcasts[index],
+            None => return html! {},
+        };
+        html! {
+            <div role="status" class="flex items-center px-4 py-2 bg-amber-100 text-amber-900 text-sm">
+                <span class="mr-2">{"📢"}</span>
+                <span class="flex-1">{ &broadcast.text }</span>
+                <button
+                    onclick={ctx.link().callback(|_| Msg::DismissBroadcast)}
+                    aria-label={t!("common.close")}
+                    class="px-2 rounded hover:bg-amber-200 focus-ring"
+                >
+                    {"✕"}
+                </button>
+            </div>
+        }
+    }
+
+    /// Admins are only recognisable by what the server sends them; it checks for itself anyway.
+    fn is_admin(&self) -> bool {
+        self.server_stats.is_some() || !self.mod_log.is_empty()
+    }
+
+    fn view_attachment(&self, ctx: &Context<Self>, m: &MessageData, attachment: &Attachment) -> Html {
+        if attachment.removed {
+            return html! { <div class="mt-2 text-xs italic text-gray-400">{ t!("file.removed") }</div> };
+        }
+        let delete = if !self.read_only && (self.is_admin() || (m.from == self.current_user && !m.locked)) {
+            let file_id = attachment.file_id.clone();
+            html! {
+                <button
+                    onclick={ctx.link().callback(move |_| Msg::DeleteFile(file_id.clone()))}
+                    title={t!("file.delete")}
+                    class="ml-1 text-xs text-gray-400 hover:text-red-600 focus-ring"
+                >
+                    {"🗑"}
+                </button>
+            }
+        } else {
+            html! {}
+        };
+        html! {
+            <div class="flex items-end">
+                { self.view_attachment_body(ctx, attachment) }
+                { delete }
+            </div>
+        }
+    }
+
+    fn view_attachment_body(&self, ctx: &Context<Self>, attachment: &Attachment) -> Html {
+        let url = self.files.get(&attachment.file_id).cloned();
+        if attachment.is_image() {
+            let onclick = {
+                let url = url.clone();
+                ctx.link().batch_callback(move |_| url.clone().map(Msg::OpenLightbox))
+            };
+            return html! {
+                <LazyImage
+                    src={url}
+                    thumbnail={attachment.thumbnail_data_uri.clone()}
+                    width={attachment.width}
+                    height={attachment.height}
+                    alt={attachment.name.clone()}
+                    {onclick}
+                />
+            };
+        }
+        if attachment.is_video() {
+            return html! {
+                <VideoPlayer src={url} thumbnail={attachment.thumbnail_data_uri.clone()} name={attachment.name.clone()}/>
+            };
+        }
+        let url = match url {
+            Some(url) => url,
+            None => return html! { <div class="mt-2 text-xs text-gray-400">{ t!("file.receiving", name = attachment.name) }</div> },
+        };
+        {
+            html! { <a class="mt-2 block text-sm text-blue-600 underline focus-ring" href={AttrValue::from(url)} download={attachment.name.clone()}>{ &attachment.name }</a> }
+        }
+    }
+
+    /// Unviewed mentions for the search panel to show while its box is empty.
+    fn recent_mentions(&self) -> Vec<MessageData> {
+        self.channels
+            .mentions()
+            .take(MAX_RESULTS)
+            .map(|(mention, message)| {
+                let mut message = message.clone();
+                message.channel.get_or_insert_with(|| mention.channel.clone());
+                message
+            })
+            .collect()
+    }
+
+    /// A line naming the day above the first message of each day in view.
+    fn view_day_separator(&self, index: usize, m: &MessageData, utc_offset: i32) -> Html {
+        let ts = match m.timestamp {
+            Some(ts) => ts,
+            None => return html! {},
+        };
+        let day = day_key(ts, utc_offset);
+        let previous = index
+            .checked_sub(1)
+            .and_then(|i| self.channels.active().visible().get(i))
+            .and_then(|p| p.timestamp)
+            .map(|p| day_key(p, utc_offset));
+        if previous.as_ref() == Some(&day) {
+            return html! {};
+        }
+        let label = i18n::format_date(ts);
+        html! {
+            <div data-day={day} class="day-separator flex items-center text-xs text-gray-400">
+                <div class="flex-1 border-t"></div>
+                <span class="px-2">{ label }</span>
+                <div class="flex-1 border-t"></div>
+            </div>
+        }
+    }
+
+    /// The heart under a message: filled once I've liked it, with everyone's count beside it.
+    /// How many replies the thread under `m` has, as a link that opens it.
+    fn view_thread_link(&self, ctx: &Context<Self>, m: &MessageData, replies: usize) -> Html {
+        if replies == 0 {
+            return html! {};
+        }
+        let id = m.id.clone();
+        html! {
+            <button
+                onclick={ctx.link().callback(move |_| Msg::OpenThread(id.clone()))}
+                class={classes!("focus-ring", "mt-1", "ml-2", "text-xs", "text-blue-600", "hover:underline", TOUCH_TARGET)}
+            >
+                { format!("💬 {}", t!("thread.reply_count", count = replies)) }
+            </button>
+        }
+    }
+
+    fn view_like(&self, ctx: &Context<Self>, m: &MessageData) -> Html {
+        let liked = m.likes.contains(&self.current_user);
+        let id = m.id.clone();
+        html! {
+            <button
+                onclick={ctx.link().callback(move |_| Msg::ToggleLike(id.clone()))}
+                disabled={self.read_only || m.locked}
+                title={if liked { t!("like.unlike") } else { t!("like.like") }}
+                class={classes!("focus-ring", "mt-1", "inline-flex", "items-center", "justify-center", "text-xs", "text-gray-500", TOUCH_TARGET)}
+            >
+                <span class={if liked { "text-red-500" } else { "text-gray-400 hover:text-red-400" }}>
+                    { if liked { "♥" } else { "♡" } }
+                </span>
+                if !m.likes.is_empty() {
+                    <span class="ml-1">{ m.likes.len() }</span>
+                }
+            </button>
+        }
+    }
+
+    /// The "Possible spam" warning, with the raw score and a way to clear it for admins.
+    fn view_spam_badge(&self, ctx: &Context<Self>, m: &MessageData) -> Html {
+        if spam_level(m.spam_score) == SpamLevel::Clean {
+            return html! {};
+        }
+        let id = m.id.clone();
+        html! {
+            <div class="flex items-center mt-1 text-xs">
+                <span class="px-2 rounded-full bg-yellow-100 text-yellow-800">{ t!("spam.badge") }</span>
+                if self.is_admin() {
+                    <span class="ml-2 text-gray-500">{ t!("spam.score", score = format!("{:.2}", m.spam_score.unwrap_or_default())) }</span>
+                    <button
+                        onclick={ctx.link().callback(move |_| Msg::MarkSafe(id.clone()))}
+                        class="ml-2 text-blue-600 hover:underline focus-ring"
+                    >
+                        { t!("spam.mark_safe") }
+                    </button>
+                }
+            </div>
+        }
+    }
+
+    fn can_nudge(&self, name: &str) -> bool {
+        self.nudges_sent
+            .get(name)
+            .map_or(true, |at| js_sys::Date::now() - at >= NUDGE_COOLDOWN_MS)
+    }
+
+    fn visibility_of(&self, channel: &str) -> Visibility {
+        let hidden = web_sys::window()
+            .and_then(|w| w.document())
+            .map(|d| d.hidden())
+            .unwrap_or(false);
+        if hidden {
+            Visibility::Hidden
+        } else if channel == self.channels.active_name() {
+            Visibility::Viewing
+        } else {
+            Visibility::Background
+        }
+    }
+
+    /// Asks the browser for notification permission. Must run within the click itself, as
+    /// browsers require.
+    fn request_permission(&self, ctx: &Context<Self>) {
+        let answer = browser_notifications::request_permission();
+        ctx.link().send_future(async move { Msg::PermissionAnswered(answer.await) });
+    }
+
+    /// Asks for permission when the user has just picked `pref` and the browser hasn't been
+    /// answered yet, so opting into alerts doesn't silently do nothing.
+    fn request_permission_on(&self, ctx: &Context<Self>, pref: RoomPreference) {
+        if self.permission_prompt.should_request_on(browser_notifications::permission(), pref) {
+            self.request_permission(ctx);
+        }
+    }
+
+    fn request_members(&mut self) {
+        let name = self.channels.active_name().to_string();
+        if self.channels.claim_members_request(&name, js_sys::Date::now()) {
+            self.send(WebSocketMessage {
+                message_type: MsgTypes::Members,
+                data: None,
+                data_array: None,
+                channel: Some(name),
+            });
+        }
+    }
+
+    fn request_history(&mut self, ctx: &Context<Self>) {
+        let name = self.channels.active_name().to_string();
+        if self.channels.claim_history_request(&name) {
+            self.send_history_request(ctx, name.clone(), None);
+            self.request_pins(name);
+        }
+    }
+
+    /// Asks for `channel`'s pins. The server answers with a `pinlist` frame holding all of them.
+    fn request_pins(&self, channel: String) {
+        self.send(WebSocketMessage {
+            message_type: MsgTypes::PinList,
+            data: None,
+            data_array: None,
+            channel: Some(channel),
+        });
+    }
+
+    fn send_history_request(&mut self, ctx: &Context<Self>, channel: String, before: Option<String>) {
+        let link = ctx.link().clone();
+        let timed_out = channel.clone();
+        self.history_timeouts.insert(
+            channel.clone(),
+            Timeout::new(HISTORY_TIMEOUT_MS, move || link.send_message(Msg::HistoryTimedOut(timed_out))),
+        );
+        let request = HistoryRequest {
+            channel,
+            limit: HISTORY_LIMIT,
+            before,
+            from: None,
+            to: None,
+        };
+        self.send(WebSocketMessage {
+            message_type: MsgTypes::History,
+            data: Some(serde_json::to_string(&request).unwrap()),
+            data_array: None,
+            channel: None,
+        });
+    }
+
+    /// The first message whose top edge is inside the visible part of the list.
+    fn topmost_visible(&self) -> Option<ReadingPosition> {
+        let list = self.message_list.cast::<Element>()?;
+        let top = list.scroll_top();
+        let children = list.children();
+        (0..children.length())
+            .filter_map(|i| children.item(i))
+            .filter_map(|child| child.dyn_into::<HtmlElement>().ok())
+            .filter(|child| child.has_attribute("data-message-id"))
+            .find(|child| child.offset_top() >= top)
+            .and_then(|child| {
+                Some(ReadingPosition {
+                    message_id: child.get_attribute("data-message-id")?,
+                    offset: child.offset_top() - top,
+                })
+            })
+    }
+
+    /// Id of the first message whose top edge is inside the visible part of the list.
+    fn topmost_visible_message(&self) -> Option<String> {
+        self.topmost_visible().map(|p| p.message_id)
+    }
+
+    /// Scrolls the message list to the separator of `day`, or of the first later day shown.
+    fn scroll_to_day(&self, day: &str) {
+        let list = match self.message_list.cast::<Element>() {
+            Some(list) => list,
+            None => return,
+        };
+        let separators = list.get_elements_by_class_name("day-separator");
+        let target = (0..separators.length())
+            .filter_map(|i| separators.item(i))
+            .filter(|el| matches!(el.get_attribute("data-day"), Some(d) if d.as_str() >= day))
+            .find_map(|el| el.dyn_into::<HtmlElement>().ok());
+        if let Some(el) = target {
+            list.set_scroll_top(el.offset_top());
+        }
+    }
+
+    /// Opens the day starting at `from` in `channel`, asking the server and the archive for it.
+    fn open_day(&mut self, ctx: &Context<Self>, channel: String, from: f64) {
+        let offset = -(js_sys::Date::new_0().get_timezone_offset() as i32);
+        let to = from + DAY_MS;
+        let sources = if self.archive.is_some() { 2 } else { 1 };
+        self.channels.open_window(&channel, day_key(from, offset), from, to, sources);
+        self.day_timeout = Some({
+            let link = ctx.link().clone();
+            let channel = channel.clone();
+            Timeout::new(DAY_TIMEOUT_MS, move || link.send_message(Msg::DayTimedOut(channel, from)))
+        });
+        let request = HistoryRequest {
+            channel: channel.clone(),
+            limit: DAY_HISTORY_LIMIT,
+            before: None,
+            from: Some(from),
+            to: Some(to),
+        };
+        self.send(WebSocketMessage {
+            message_type: MsgTypes::History,
+            data: Some(serde_json::to_string(&request).unwrap()),
+            data_array: None,
+            channel: None,
+        });
+        if let Some(archive) = self.archive.clone() {
+            ctx.link().send_future(async move {
+                let messages = archive.range(&channel, from, to).await.unwrap_or_else(|e| {
+                    log::error!("failed to read the message archive: {:?}", e);
+                    vec![]
+                });
+                Msg::DayArchived(channel, from, messages)
+            });
+        }
+    }
+
+    /// Files one source's messages for the day picked in `channel`.
+    fn fill_day(&mut self, ctx: &Context<Self>, channel: &str, from: f64, source: WindowSource, messages: Vec<MessageData>) -> bool {
+        let fill = self.channels.fill_window(channel, from, source, messages);
+        self.show_day(ctx, channel, fill)
+    }
+
+    /// Scrolls to the day a window was filled for, when it's in the channel being looked at,
+    /// or to the linked message it was opened for once every source is in.
+    fn show_day(&mut self, ctx: &Context<Self>, channel: &str, fill: WindowFill) -> bool {
+        let day = match fill {
+            WindowFill::Stale => return false,
+            WindowFill::Detached(day) | WindowFill::Joined(day) => day,
+        };
+        if channel == self.channels.active_name() {
+            self.pending_scroll = Some(ScrollTarget::Day(day));
+        }
+        let waiting = matches!(self.channels.get(channel).and_then(|c| c.window.as_ref()), Some(w) if w.pending > 0);
+        if !waiting && matches!(self.pending_link, Some((_, LinkSearch::Day))) {
+            let (id, search) = self.pending_link.take().unwrap();
+            self.follow_link(ctx, channel.to_string(), id, search);
+        }
+        true
+    }
+
+    /// Jumps to a linked message once it's loaded in `channel`. Until then it is looked for
+    /// further each time: in the archive, then in older pages from the server. Only when
+    /// neither has it is the user told it's gone.
+    fn follow_link(&mut self, ctx: &Context<Self>, channel: String, id: String, search: LinkSearch) {
+        if matches!(self.channels.get(&channel), Some(c) if c.find(&id).is_some()) {
+            ctx.link().send_message(Msg::JumpToMessage { channel, id });
+            return;
+        }
+        let pages = match search {
+            LinkSearch::Latest => match self.archive.clone() {
+                Some(archive) => {
+                    self.pending_link = Some((id.clone(), LinkSearch::Archive));
+                    ctx.link().send_future(async move {
+                        let wanted = (channel.clone(), id);
+                        let found = archive
+                            .search(move |c, m| c == wanted.0 && m.id == wanted.1, 1)
+                            .await
+                            .unwrap_or_else(|e| {
+                                log::error!("failed to read the message archive: {:?}", e);
+                                vec![]
+                            });
+                        Msg::LinkArchived(channel, found.first().and_then(|m| m.timestamp))
+                    });
+                    return;
+                }
+                None => 0,
+            },
+            LinkSearch::Archive => 0,
+            LinkSearch::Older(pages) => pages,
+            LinkSearch::Day => LINK_PAGES,
+        };
+        if pages < LINK_PAGES {
+            if let Some(before) = self.channels.claim_older_history(&channel) {
+                self.pending_link = Some((id, LinkSearch::Older(pages + 1)));
+                self.send_history_request(ctx, channel, Some(before));
+                return;
+            }
+        }
+        self.toast(ToastLevel::Info, &t!("messages.unavailable"));
+    }
+
+    /// Ids of the active channel's messages the list shows a row for, oldest first.
+    fn shown_message_ids(&self) -> Vec<&str> {
+        self.channels
+            .active()
+            .visible()
+            .iter()
+            .filter(|m| m.thread_id.is_none())
+            .filter(|m| self.is_admin() || spam_level(m.spam_score) != SpamLevel::Hidden)
+            .map(|m| m.id.as_str())
+            .collect()
+    }
+
+    /// Moves keyboard focus from message `id` to the one `step` rows away, if there is one.
+    /// Its focus handler then makes it the tab stop.
+    fn focus_neighbour(&self, id: &str, step: isize) {
+        let target = match neighbour(&self.shown_message_ids(), id, step) {
+            Some(target) => target.to_string(),
+            None => return,
+        };
+        if let Some(el) = self
+            .message_list
+            .cast::<Element>()
+            .and_then(|list| list.query_selector(&format!("[data-message-id=\"{}\"]", target)).ok().flatten())
+            .and_then(|el| el.dyn_into::<HtmlElement>().ok())
+        {
+            let _ = el.focus();
+        }
+    }
+
+    /// Scrolls the message list so `id` sits at the top. Returns false if it isn't rendered.
+    fn scroll_to_message(&self, id: &str) -> bool {
+        let list = match self.message_list.cast::<Element>() {
+            Some(list) => list,
+            None => return false,
+        };
+        match list
+            .query_selector(&format!("[data-message-id=\"{}\"]", id))
+            .ok()
+            .flatten()
+            .and_then(|el| el.dyn_into::<HtmlElement>().ok())
+        {
+            Some(el) => {
+                list.set_scroll_top(el.offset_top());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Puts the message of `position` back where it was. Returns false if it isn't rendered.
+    fn scroll_to_position(&self, position: &ReadingPosition) -> bool {
+        let list = match self.message_list.cast::<Element>() {
+            Some(list) => list,
+            None => return false,
+        };
+        match list
+            .query_selector(&format!("[data-message-id=\"{}\"]", position.message_id))
+            .ok()
+            .flatten()
+            .and_then(|el| el.dyn_into::<HtmlElement>().ok())
+        {
+            Some(el) => {
+                list.set_scroll_top(el.offset_top() - position.offset);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Anchor to remember for the active channel when leaving it.
+    fn current_anchor(&self) -> Option<String> {
+        if self.showing_mentions {
+            self.channels.active().scroll_anchor.clone()
+        } else {
+            self.topmost_visible_message()
+        }
+    }
+
+    /// Keeps the tab title and favicon counters in step.
+    fn set_unread_badges(&mut self, n: usize) {
+        self.title.set_unread(n);
+        if let Some(favicon) = &mut self.favicon {
+            favicon.set_unread(n);
+        }
+    }
+
+    fn is_at_bottom(&self) -> bool {
+        self.message_list.cast::<Element>().map_or(true, |list| {
+            list.scroll_height() - list.scroll_top() - list.client_height() <= 1
+        })
+    }
+
+    fn scroll_to_bottom(&self) {
+        if let Some(list) = self.message_list.cast::<Element>() {
+            list.set_scroll_top(list.scroll_height());
+        }
+    }
+
+    fn view_users(&self, ctx: &Context<Self>) -> Html {
+        let users: Vec<UserProfile> = fuzzy_filter(&self.user_filter, &self.users, |u| &u.name)
+            .into_iter()
+            .cloned()
+            .collect();
+        match &self.channels.active().members {
+            Some(members) if !self.channels.active().is_dm() => {
+                let (here, elsewhere) = split_by_membership(&users, members);
+                html! {
+                    <>
+                        <div class="text-sm px-3 pt-2 font-semibold text-gray-500">{ t!("sidebar.in_channel") }</div>
+                        { here.into_iter().map(|u| self.view_user(ctx, u)).collect::<Html>() }
+                        <div class="text-sm px-3 pt-2 font-semibold text-gray-500">{ t!("sidebar.elsewhere") }</div>
+                        { elsewhere.into_iter().map(|u| self.view_user(ctx, u)).collect::<Html>() }
+                    </>
+                }
+            }
+            _ => users.iter().map(|u| self.view_user(ctx, u)).collect::<Html>(),
+        }
+    }
+
+    fn view_user(&self, ctx: &Context<Self>, u: &UserProfile) -> Html {
+        let name = u.name.clone();
+        let onclick = ctx.link().callback(move |_| Msg::OpenDm(name.clone()));
+        let has_unread_dm = self
+            .channels
+            .get(&format!("@{}", u.name))
+            .map(|c| c.unread > 0)
+            .unwrap_or(false);
+        let name = u.name.clone();
+        let toggle_menu = ctx.link().callback(move |e: MouseEvent| {
+            e.stop_propagation();
+            Msg::ToggleUserMenu(name.clone())
+        });
+        let nudged = self.nudged_by.as_ref() == Some(&u.name);
+        let class = if nudged && !self.prefers_reduced_motion {
+            "relative flex m-3 bg-white dark:bg-gray-800 rounded-lg p-2 cursor-pointer focus-ring shake"
+        } else {
+            "relative flex m-3 bg-white dark:bg-gray-800 rounded-lg p-2 cursor-pointer focus-ring"
+        };
+
+        html! {
+            // The row is a click target for the mouse; keyboards reach the same click through
+            // the name button, so no button ends up nested inside another.
+            <div {onclick} {class}>
+                <button
+                    onclick={toggle_menu}
+                    aria-label={t!("sidebar.user_actions", name = u.name)}
+                    aria-haspopup="menu"
+                    aria-expanded={(self.user_menu.as_ref() == Some(&u.name)).to_string()}
+                    class="flex-none rounded-full focus-ring"
+                >
+                    <img class="w-12 h-12 rounded-full" src={u.avatar.clone()} alt=""/>
+                </button>
+                <button type="button" class="flex-1 p-3 text-sm text-left rounded-lg focus-ring">
+                    <span class="block font-medium">{ &u.name }</span>
+                    if nudged {
+                        <span class="block text-xs text-blue-600">{ t!("sidebar.nudged", name = u.name) }</span>
+                    } else {
+                        <span class="block text-xs text-gray-400">{ t!("sidebar.greeting") }</span>
+                    }
+                </button>
+                if has_unread_dm {
+                    <span class="absolute top-2 right-2 w-2 h-2 rounded-full bg-blue-500"></span>
+                }
+                if self.user_menu.as_ref() == Some(&u.name) {
+                    { self.view_user_menu(ctx, &u.name) }
+                }
+            </div>
+        }
+    }
+
+    fn view_user_menu(&self, ctx: &Context<Self>, name: &str) -> Html {
+        let message = {
+            let name = name.to_string();
+            ctx.link().batch_callback(move |e: MouseEvent| {
+                e.stop_propagation();
+                vec![Msg::ToggleUserMenu(name.clone()), Msg::OpenDm(name.clone())]
+            })
+        };
+        let nudge = {
+            let name = name.to_string();
+            ctx.link().callback(move |e: MouseEvent| {
+                e.stop_propagation();
+                Msg::Nudge(name.clone())
+            })
+        };
+        let can_nudge = self.can_nudge(name);
+        let mute = self.settings.notifications.user_mute(name);
+        let toggle_mute = {
+            let name = name.to_string();
+            ctx.link().callback(move |e: MouseEvent| {
+                e.stop_propagation();
+                Msg::SetUserMute(name.clone(), if mute.is_some() { None } else { Some(UserMute::default()) })
+            })
+        };
+        let toggle_mentions = {
+            let name = name.to_string();
+            ctx.link().callback(move |e: MouseEvent| {
+                e.stop_propagation();
+                Msg::SetUserMute(name.clone(), mute.map(|m| UserMute {
+                    allow_mentions: !m.allow_mentions,
+                }))
+            })
+        };
+
+        html! {
+            <div role="menu" class="absolute z-10 left-2 top-14 w-48 bg-white dark:bg-gray-800 border rounded-lg shadow text-sm">
+                if self.flags.dms {
+                    <div onclick={message} role="menuitem" tabindex="0" onkeydown={a11y::press_on_key} class="px-3 py-2 hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring">{ t!("profile.message") }</div>
+                }
+                if can_nudge {
+                    <div onclick={nudge} role="menuitem" tabindex="0" onkeydown={a11y::press_on_key} class="px-3 py-2 hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring">{ t!("user_menu.nudge") }</div>
+                } else {
+                    <div role="menuitem" aria-disabled="true" class="px-3 py-2 text-gray-400" title={t!("user_menu.nudge_cooldown")}>{ t!("user_menu.nudge") }</div>
+                }
+                <div
+                    onclick={toggle_mute}
+                    role="menuitemcheckbox"
+                    aria-checked={mute.is_some().to_string()}
+                    tabindex="0"
+                    onkeydown={a11y::press_on_key}
+                    class="flex items-center px-3 py-2 hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring"
+                >
+                    <input type="checkbox" checked={mute.is_some()} tabindex="-1" aria-hidden="true" class="mr-2 pointer-events-none"/>
+                    { t!("user_menu.mute") }
+                </div>
+                if let Some(mute) = mute {
+                    <div
+                        onclick={toggle_mentions}
+                        role="menuitemcheckbox"
+                        aria-checked={mute.allow_mentions.to_string()}
+                        tabindex="0"
+                        onkeydown={a11y::press_on_key}
+                        class="flex items-center pl-8 pr-3 py-2 text-xs hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring"
+                    >
+                        <input type="checkbox" checked={mute.allow_mentions} tabindex="-1" aria-hidden="true" class="mr-2 pointer-events-none"/>
+                        { t!("user_menu.unless_mentioned") }
+                    </div>
+                }
+            </div>
+        }
+    }
+
+    /// Message text with `@name` mentions as pills and, unless it is `own`, watch words marked.
+    fn view_text(&self, ctx: &Context<Self>, text: &str, own: bool) -> Html {
+        let mut parts = vec![];
+        let mut at = 0;
+        for mention in mention_spans(text) {
+            // Someone nobody has heard of stays plain text.
+            let profile = self.profile_summary(&text[mention.start + 1..mention.end]);
+            if !profile.online && profile.message_count == 0 {
+                continue;
+            }
+            parts.push(self.view_watch_words(&text[at..mention.start], own));
+            parts.push(html! {
+                <MentionPill {profile} on_open={ctx.link().callback(Msg::OpenProfile)}/>
+            });
+            at = mention.end;
+        }
+        parts.push(self.view_watch_words(&text[at..], own));
+        parts.into_iter().collect()
+    }
+
+    fn view_watch_words(&self, text: &str, own: bool) -> Html {
+        if own {
+            return html! { { text } };
+        }
+        let mut parts = vec![];
+        let mut at = 0;
+        for hit in self.settings.watch_words.matcher().find(text) {
+            parts.push(html! { { &text[at..hit.start] } });
+            parts.push(html! { <mark class="bg-yellow-200 rounded">{ &text[hit.clone()] }</mark> });
+            at = hit.end;
+        }
+        parts.push(html! { { &text[at..] } });
+        parts.into_iter().collect()
+    }
+
+    /// What a profile card shows about `name`, who may not be online or known at all.
+    fn profile_summary(&self, name: &str) -> ProfileSummary {
+        let user = self.users.iter().find(|u| u.name.eq_ignore_ascii_case(name));
+        let name = user.map(|u| u.name.clone()).unwrap_or_else(|| name.to_string());
+        ProfileSummary {
+            avatar: user
+                .map(|u| u.avatar.clone())
+                .unwrap_or_else(|| avatar_for(&name)),
+            online: user.is_some(),
+            note: self.user_notes.get(&name).cloned(),
+            message_count: self
+                .message_counts
+                .borrow_mut()
+                .get_or_insert_with(|| {
+                    count_by_sender(self.channels.channels().iter().flat_map(|c| c.messages.iter()))
+                })
+                .get(&name.to_lowercase())
+                .copied()
+                .unwrap_or(0),
+            name,
+        }
+    }
+
+    fn view_permission_prompt(&self, ctx: &Context<Self>) -> Html {
+        let permission = browser_notifications::permission();
+        if !self.permission_prompt.should_show(permission, self.has_sent_message, js_sys::Date::now()) {
+            return html! {};
+        }
+        html! {
+            <div class="flex items-center px-4 py-2 bg-blue-50 border-b text-sm">
+                <span class="flex-1">{ t!("notify_prompt.text") }</span>
+                <button onclick={ctx.link().callback(|_| Msg::EnableNotifications)} class="ml-2 px-3 py-1 rounded-full bg-blue-600 text-white focus-ring">{ t!("notify_prompt.enable") }</button>
+                <button onclick={ctx.link().callback(|_| Msg::SnoozeNotificationPrompt)} class="ml-2 px-3 py-1 rounded-full hover:bg-blue-100 focus-ring">{ t!("notify_prompt.later") }</button>
+            </div>
+        }
+    }
+
+    fn view_mentions(&self, ctx: &Context<Self>) -> Html {
+        if self.channels.mention_count() == 0 {
+            return html! {
+                <div class="flex-1 p-4 text-gray-400">{ t!("mentions.empty") }</div>
+            };
+        }
+        html! {
+            <div class="flex-1 overflow-auto border-b p-4 space-y-4">
+                {
+                    self.channels.mentions().map(|(mention, m)| {
+                        let channel = mention.channel.clone();
+                        let id = mention.message_id.clone();
+                        let onclick = ctx.link().callback(move |_| Msg::JumpToMessage {
+                            channel: channel.clone(),
+                            id: id.clone(),
+                        });
+                        html! {
+                            <div {onclick} role="button" tabindex="0" onkeydown={a11y::press_on_key} class="bg-gray-100 dark:bg-gray-700 p-3 rounded-xl max-w-lg cursor-pointer hover:bg-gray-200 dark:hover:bg-gray-600 focus-ring">
+                                <div class="text-xs text-gray-400">
+                                    { format!("# {}", mention.channel) }
+                                    if mention.source == MentionSource::Keyword {
+                                        <span class="ml-2 px-1 rounded bg-yellow-100 text-yellow-800">{ t!("mentions.watch_word") }</span>
+                                    }
                                 </div>
+                                <div class="text-sm font-medium">{ &m.from }</div>
+                                <div class="text-base">{ &m.message }</div>
                             </div>
-                        }).collect::<Html>()
+                        }
+                    }).collect::<Html>()
+                }
+            </div>
+        }
+    }
+
+    fn view_recorder(&self, ctx: &Context<Self>) -> Html {
+        if !self.recorder_open {
+            return html! {};
+        }
+        if self.recorder.is_some() {
+            return html! {
+                <div class="flex items-center px-4 pt-2 text-sm">
+                    <span class="w-2 h-2 mr-2 rounded-full bg-red-600 animate-pulse"></span>
+                    <span class="flex-1">{ t!("recorder.recording") }</span>
+                    <button onclick={ctx.link().callback(|_| Msg::StopRecording)} class="px-3 py-1 rounded-full bg-red-600 text-white focus-ring">{ t!("recorder.stop") }</button>
+                </div>
+            };
+        }
+
+        let source_button = |source: ClipSource, label: String| {
+            let class = if self.clip_source == source {
+                "px-3 py-1 rounded-full bg-blue-600 text-white"
+            } else {
+                "px-3 py-1 rounded-full bg-gray-100 dark:bg-gray-700"
+            };
+            html! {
+                <button onclick={ctx.link().callback(move |_| Msg::SetClipSource(source))} class={classes!("focus-ring", class)}>{ label }</button>
+            }
+        };
+        html! {
+            <div class="flex items-center px-4 pt-2 space-x-2 text-sm">
+                { source_button(ClipSource::Screen, t!("recorder.screen")) }
+                { source_button(ClipSource::Camera, t!("recorder.camera")) }
+                <div class="flex-1"></div>
+                <button onclick={ctx.link().callback(|_| Msg::StartRecording)} class="px-3 py-1 rounded-full bg-red-600 text-white focus-ring">{ t!("recorder.start") }</button>
+            </div>
+        }
+    }
+
+    /// "(edited)" for the author's own edits. Admins see who edited someone else's message
+    /// and why; everyone else gets a neutral label explaining only that it was policy.
+    fn view_edit_label(&self, ctx: &Context<Self>, m: &MessageData) -> Html {
+        if !m.edited {
+            return html! {};
+        }
+        match (&m.edited_by, &m.edit_reason) {
+            (None, _) => html! { <div class="text-xs text-gray-400">{ t!("edited.own") }</div> },
+            (Some(by), Some(reason)) => html! {
+                <div class="text-xs text-gray-400" title={reason.clone()}>{ t!("edited.by", name = by) }</div>
+            },
+            (Some(_), None) => html! {
+                <button
+                    onclick={ctx.link().callback(|_| Msg::ShowPolicyNotice(true))}
+                    class="text-xs text-gray-400 underline decoration-dotted hover:text-gray-600 focus-ring"
+                >
+                    { t!("edited.by_moderator") }
+                </button>
+            },
+        }
+    }
+
+    /// The right-click menu of a message: its link for everyone, locking for admins.
+    fn view_message_menu(&self, ctx: &Context<Self>) -> Html {
+        let (id, x, y) = match &self.message_menu {
+            Some(menu) => menu,
+            None => return html! {},
+        };
+        let locked = self.channels.active().find(id).map_or(false, |m| m.locked);
+        let item = |label: &str, onclick: Callback<MouseEvent>| {
+            html! { <div {onclick} role="menuitem" tabindex="0" onkeydown={a11y::press_on_key} class="px-3 py-3 md:py-2 cursor-pointer hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring">{ label }</div> }
+        };
+        let copy = {
+            let id = id.clone();
+            ctx.link().callback(move |_| Msg::CopyMessageLink(id.clone()))
+        };
+        let toggle_lock = {
+            let id = id.clone();
+            ctx.link().callback(move |_| Msg::SetLocked(id.clone(), !locked))
+        };
+        let open_thread = {
+            let id = id.clone();
+            ctx.link().callback(move |_| Msg::OpenThread(id.clone()))
+        };
+        html! {
+            <Modal onclick={ctx.link().callback(|_| Msg::CloseMessageMenu)} label={t!("dialog.message_actions")} class="fixed inset-0 z-20">
+                <div
+                    oncontextmenu={ctx.link().callback(|e: MouseEvent| {
+                        e.prevent_default();
+                        Msg::CloseMessageMenu
+                    })}
+                    class="absolute inset-0"
+                ></div>
+                <div
+                    role="menu"
+                    // Kept clear of the right edge, where a thumb on a phone usually presses.
+                    style={format!("left: min({}px, calc(100vw - 12rem)); top: {}px", x, y)}
+                    class="absolute py-1 bg-white dark:bg-gray-800 border rounded shadow-lg text-sm"
+                >
+                    { item(&t!("message_menu.copy_link"), copy) }
+                    { item(&t!("thread.open"), open_thread) }
+                    if self.is_admin() {
+                        { item(&if locked { t!("message_menu.unlock") } else { t!("message_menu.lock") }, toggle_lock) }
+                    }
+                </div>
+            </Modal>
+        }
+    }
+
+    /// Only admins get stats or moderation entries, so the panel shows up once either arrives.
+    fn view_admin_panel(&self, ctx: &Context<Self>) -> Html {
+        if self.server_stats.is_none() && self.mod_log.is_empty() {
+            return html! {};
+        }
+        let tab = |tab: AdminTab, label: String| {
+            let class = if self.admin_tab == tab {
+                "px-2 py-1 text-sm font-semibold border-b-2 border-blue-600"
+            } else {
+                "px-2 py-1 text-sm text-gray-500 hover:text-gray-800"
+            };
+            html! {
+                <button onclick={ctx.link().callback(move |_| Msg::SetAdminTab(tab))} class={classes!("focus-ring", class)}>{ label }</button>
+            }
+        };
+
+        html! {
+            <div class="m-3 bg-white dark:bg-gray-800 rounded-lg p-2">
+                <div class="flex mb-2 border-b">
+                    { tab(AdminTab::Stats, t!("admin.tab.stats")) }
+                    { tab(AdminTab::ModLog, t!("admin.tab.mod_log")) }
+                    { tab(AdminTab::Broadcast, t!("admin.tab.broadcast")) }
+                </div>
+                {
+                    match self.admin_tab {
+                        AdminTab::Stats => self.view_server_stats(),
+                        AdminTab::ModLog => html! { <ModLog entries={self.mod_log.clone()}/> },
+                        AdminTab::Broadcast => {
+                            let rooms = self.channels.channels().iter().filter(|c| !c.is_dm()).map(|c| c.name.clone()).collect::<Vec<_>>();
+                            html! { <BroadcastForm {rooms} on_send={ctx.link().callback(Msg::SendBroadcast)}/> }
+                        }
+                    }
+                }
+            </div>
+        }
+    }
+
+    fn view_server_stats(&self) -> Html {
+        let stats = match &self.server_stats {
+            Some(stats) => stats,
+            None => return html! { <div class="text-xs text-gray-400">{ t!("admin.stats_hint") }</div> },
+        };
+        let tiles = [
+            ("🏠", t!("admin.stats.rooms"), stats.total_rooms.to_string()),
+            ("👥", t!("admin.stats.users"), stats.total_users.to_string()),
+            ("💬", t!("admin.stats.messages_today"), stats.total_messages_today.to_string()),
+            ("⏱", t!("admin.stats.uptime"), format_uptime(stats.uptime_seconds)),
+        ];
+
+        html! {
+            <div class="grid grid-cols-2 gap-2">
+                {
+                    tiles.into_iter().map(|(icon, label, value)| html! {
+                        <div class="bg-gray-100 dark:bg-gray-700 rounded-lg p-2">
+                            <div class="text-lg">{ icon }</div>
+                            <div class="text-xs text-gray-400">{ label }</div>
+                            <div class="font-semibold">{ value }</div>
+                        </div>
+                    }).collect::<Html>()
+                }
+            </div>
+        }
+    }
+
+    fn view_channel_rail(&self, ctx: &Context<Self>) -> Html {
+        let entry_class = |name: &str| {
+            if name == self.channels.active_name() && !self.showing_mentions {
+                "flex items-center px-3 py-1 cursor-pointer bg-gray-700 text-white focus-ring"
+            } else {
+                "flex items-center px-3 py-1 cursor-pointer hover:bg-gray-700 focus-ring"
+            }
+        };
+        let current = |name: &str| (name == self.channels.active_name() && !self.showing_mentions).then_some("page");
+        let unread_badge = |c: &Channel| {
+            if c.unread > 0 {
+                html! { <span title={t!("channel.unread", count = c.unread)} class="ml-auto px-2 rounded-full bg-red-500 text-xs text-white">{ c.unread }</span> }
+            } else if c.has_activity {
+                html! { <span class="ml-auto w-2 h-2 rounded-full bg-gray-400"></span> }
+            } else {
+                html! {}
+            }
+        };
+
+        let channel_entry = |c: &Channel| {
+            let archived = self.settings.notifications.is_archived(&c.name);
+            let name = c.name.clone();
+            let onclick = ctx.link().callback(move |_| Msg::SwitchChannel(name.clone()));
+            let name = c.name.clone();
+            let toggle_archive = ctx.link().callback(move |e: MouseEvent| {
+                e.stop_propagation();
+                Msg::SetArchived(name.clone(), !archived)
+            });
+            let class = if c.surfaced {
+                classes!(entry_class(&c.name), "ring-2", "ring-inset", "ring-yellow-400")
+            } else {
+                classes!(entry_class(&c.name))
+            };
+            html! {
+                <div {onclick} {class}>
+                    <button type="button" aria-current={current(&c.name)} class="flex flex-1 items-center min-w-0 text-left focus-ring">
+                        <span>{ format!("# {}", c.name) }</span>
+                        if !archived || c.surfaced {
+                            { unread_badge(c) }
+                        }
+                    </button>
+                    <button
+                        onclick={toggle_archive}
+                        title={if archived { t!("sidebar.unarchive") } else { t!("sidebar.archive") }}
+                        aria-label={if archived {
+                            t!("sidebar.unarchive_channel", channel = c.name)
+                        } else {
+                            t!("sidebar.archive_channel", channel = c.name)
+                        }}
+                        class="ml-2 text-xs text-gray-500 hover:text-white focus-ring"
+                    >
+                        { if archived { "↩" } else { "🗄" } }
+                    </button>
+                </div>
+            }
+        };
+        let mut archived = self
+            .channels
+            .channels()
+            .iter()
+            .filter(|c| !c.is_dm() && !c.surfaced && self.settings.notifications.is_archived(&c.name))
+            .peekable();
+
+        html! {
+            <div class="w-44 h-screen bg-gray-800 text-gray-300 overflow-auto">
+                <div
+                    onclick={ctx.link().callback(|_| Msg::ShowMentions)}
+                    role="button" tabindex="0" onkeydown={a11y::press_on_key}
+                    aria-current={self.showing_mentions.then_some("page")}
+                    class={if self.showing_mentions {
+                        "flex items-center px-3 py-2 cursor-pointer bg-gray-700 text-white focus-ring"
+                    } else {
+                        "flex items-center px-3 py-2 cursor-pointer hover:bg-gray-700 focus-ring"
+                    }}
+                >
+                    <span>{ t!("sidebar.mentions") }</span>
+                    if self.channels.mention_count() > 0 {
+                        <span class="ml-auto px-2 rounded-full bg-red-500 text-xs text-white">{ self.channels.mention_count() }</span>
+                    }
+                </div>
+                <div class="text-xl p-3 font-semibold text-white">{ t!("sidebar.channels") }</div>
+                {
+                    self.channels.channels().iter()
+                        .filter(|c| !c.is_dm() && (c.surfaced || !self.settings.notifications.is_archived(&c.name)))
+                        .map(&channel_entry)
+                        .collect::<Html>()
+                }
+                if self.channel_list_supported {
+                    <div onclick={ctx.link().callback(|_| Msg::OpenChannelBrowser)} role="button" tabindex="0" onkeydown={a11y::press_on_key} class="px-3 py-1 cursor-pointer text-sm hover:bg-gray-700 focus-ring">
+                        { t!("sidebar.browse") }
+                    </div>
+                }
+                <div class="text-sm px-3 pt-4 pb-1 font-semibold text-white">{ t!("app_bar.direct_messages") }</div>
+                {
+                    self.channels.channels().iter().filter(|c| c.is_dm() && !c.hidden).map(|c| {
+                        let peer = c.name.trim_start_matches('@').to_string();
+                        let avatar = self
+                            .users
+                            .iter()
+                            .find(|u| u.name == peer)
+                            .map(|u| u.avatar.clone())
+                            .unwrap_or_else(|| avatar_for(&peer));
+                        let key = c.name.clone();
+                        let onclick = ctx.link().callback(move |_| Msg::SwitchChannel(key.clone()));
+                        let key = c.name.clone();
+                        let close = ctx.link().callback(move |e: MouseEvent| {
+                            e.stop_propagation();
+                            Msg::CloseDm(key.clone())
+                        });
+                        html! {
+                            <div {onclick} class={entry_class(&c.name)}>
+                                <button type="button" aria-current={current(&c.name)} class="flex flex-1 items-center min-w-0 text-left focus-ring">
+                                    <img class="w-5 h-5 mr-2 rounded-full" src={avatar} alt=""/>
+                                    <span>{ &peer }</span>
+                                    { unread_badge(c) }
+                                </button>
+                                <button onclick={close} aria-label={t!("sidebar.close_dm", name = peer)} class="ml-2 text-gray-400 hover:text-white focus-ring">{"×"}</button>
+                            </div>
+                        }
+                    }).collect::<Html>()
+                }
+                if archived.peek().is_some() {
+                    <div
+                        onclick={ctx.link().callback(|_| Msg::ToggleArchivedSection)}
+                        role="button" tabindex="0" onkeydown={a11y::press_on_key}
+                        aria-expanded={self.archived_open.to_string()}
+                        class="text-sm px-3 pt-4 pb-1 font-semibold text-gray-500 cursor-pointer focus-ring"
+                    >
+                        { format!("{} {}", if self.archived_open { "▾" } else { "▸" }, t!("sidebar.archived")) }
+                    </div>
+                    if self.archived_open {
+                        { archived.map(&channel_entry).collect::<Html>() }
+                    }
+                }
+            </div>
+        }
+    }
+}
+
+impl Chat {
+    /// Handles `msg` for [`Component::update`].
+    fn handle(&mut self, ctx: &Context<Self>, msg: Msg) -> bool {
+        match msg {
+            Msg::FlagsChanged(flags) => {
+                if !flags.video_clips {
+                    self.recorder_open = false;
+                }
+                self.flags = flags;
+                true
+            }
+            Msg::UserChanged(user) => {
+                let new = match renamed_to(&self.current_user, &user) {
+                    Some(new) => new.to_string(),
+                    None => return false,
+                };
+                // Renamed, e.g. by a nick change: say so to the server and send as the new name.
+                // What this browser keeps per user moves along with the name.
+                let old = std::mem::replace(&mut self.current_user, new);
+                self.settings.rename(&old, &self.current_user);
+                self.drafts.rename(&self.current_user);
+                self.scroll_memory.rename(&self.current_user);
+                self.message_cache.rename(&self.current_user);
+                ctx.link().send_message(Msg::SaveCache);
+                if self.connection.is_live() {
+                    Self::register(&self.wss, &user);
+                }
+                true
+            }
+            Msg::HandleMsg(data) => {
+                let msg: WebSocketMessage = serde_json::from_str(&data).unwrap();
+                match msg.message_type {
+                    MsgTypes::Users => {
+                        let usernames = msg.data_array.unwrap_or_default();
+                        // The first list is everyone already here, not people arriving.
+                        if !self.users.is_empty() {
+                            let me = self.current_user.as_str();
+                            let before: HashSet<&str> = self.users.iter().map(|u| u.name.as_str()).filter(|n| *n != me).collect();
+                            let after: HashSet<&str> = usernames.iter().map(String::as_str).filter(|n| *n != me).collect();
+                            if after.difference(&before).next().is_some() {
+                                self.play(SoundEffect::Join);
+                            } else if before.difference(&after).next().is_some() {
+                                self.play(SoundEffect::Leave);
+                            }
+                        }
+                        self.users = usernames
+                            .into_iter()
+                            .map(|name| UserProfile {
+                                avatar: avatar_for(&name),
+                                name,
+                            })
+                            .collect();
+                        true
+                    }
+                    MsgTypes::Message | MsgTypes::VideoClip | MsgTypes::Attachment | MsgTypes::EphemeralMessage => {
+                        let mut message_data: MessageData = serde_json::from_str(&msg.data.unwrap()).unwrap();
+                        // Live messages the server didn't date are dated on arrival, so they sort
+                        // and archive under one time from here on.
+                        let sent = *message_data.timestamp.get_or_insert_with(js_sys::Date::now);
+                        if let (Some(ttl), None) = (message_data.ttl_seconds, message_data.expires_at) {
+                            message_data.expires_at = Some(sent + ttl as f64 * 1000.0);
+                        }
+                        let channel = conversation_key(&message_data, &self.current_user);
+                        message_data.channel = Some(channel.clone());
+                        let own = message_data.from == self.current_user;
+                        if channel == self.channels.active_name()
+                            && follows_new_message(self.pending_scroll.as_ref(), self.is_at_bottom(), own)
+                        {
+                            self.pending_scroll = Some(ScrollTarget::Bottom);
+                        }
+                        self.typing.clear_user(&message_data.from);
+                        if !message_data.id.is_empty() {
+                            self.checkpoint_id = message_data.id.clone();
+                            self.since_checkpoint += 1;
+                            if self.since_checkpoint >= CHECKPOINT_EVERY {
+                                ctx.link().send_message(Msg::SendCheckpoint);
+                            }
+                        }
+                        let mentioned = is_mention(&message_data.message, &self.current_user);
+                        let keyword_hit = message_data.from != self.current_user
+                            && self.settings.watch_words.matcher().is_match(&message_data.message);
+                        let for_me = channel.starts_with('@') || mentioned || keyword_hit;
+                        let archived = self.settings.notifications.is_archived(&channel);
+                        let visibility = self.visibility_of(&channel);
+                        let pref = self.settings.notifications.get(&channel);
+                        let global = self.settings.notifications.global();
+                        let sender = self.settings.notifications.user_mute(&message_data.from);
+                        // Nothing about a message this user will never be shown may reach them.
+                        let hidden = spam_level(message_data.spam_score) == SpamLevel::Hidden && !self.is_admin();
+                        let vibrate_on_mention = self.settings.vibrate_on_mention;
+                        let (decision, background_level) = if hidden {
+                            (Decision::SILENT, NotifyLevel::Silent)
+                        } else {
+                            (
+                                should_notify(global, pref, sender, archived, for_me, visibility, vibrate_on_mention),
+                                should_notify(global, pref, sender, archived, for_me, Visibility::Background, false).level,
+                            )
+                        };
+                        let level = decision.level;
+                        let since_composing = self.presence.since_composing(js_sys::Date::now());
+                        if let Some(announcement) =
+                            announcement(&message_data, own, hidden, channel == self.channels.active_name())
+                        {
+                            self.announcement = announcement;
+                        }
+                        // DND silences every output; unread badges below still count.
+                        let quiet = self.settings.dnd.is_active(Now::local());
+                        if !quiet && should_cue(self.settings.sound_enabled, own, since_composing, background_level) {
+                            self.audio.play(SoundEffect::Notification);
+                        }
+                        if !quiet && visibility == Visibility::Hidden && !own && level == NotifyLevel::Alert {
+                            self.set_unread_badges(self.title.unread() + 1);
+                        }
+                        let title = t!("notification.title", from = message_data.from, channel = channel);
+                        let body = message_data.message.clone();
+                        // Ephemeral messages are kept nowhere that outlives them.
+                        if message_data.expires_at.is_none() {
+                            if let Some(archive) = &self.archive {
+                                archive.archive(std::slice::from_ref(&message_data));
+                            }
+                            self.record_transcript(&message_data);
+                        }
+                        let id = self.channels.push(message_data, level, self.is_at_bottom());
+                        if !quiet && visibility == Visibility::Hidden && !own && level == NotifyLevel::Alert {
+                            let target = NotificationTarget {
+                                channel: channel.clone(),
+                                message_id: id.clone(),
+                            };
+                            browser_notifications::show(&title, &body, &target, ctx.link().callback(Msg::OpenNotification));
+                        }
+                        if !quiet && should_cue(decision.vibrate, own, since_composing, background_level) {
+                            vibrate(&[100, 50, 100]);
+                        }
+                        if (mentioned || keyword_hit) && !hidden && visibility != Visibility::Viewing {
+                            let source = if mentioned { MentionSource::Direct } else { MentionSource::Keyword };
+                            self.channels.add_mention(&channel, id, source);
+                        }
+                        if surfaces_archived(archived, for_me, level) {
+                            self.channels.get_or_create(&channel).surfaced = true;
+                        }
+                        true
+                    }
+                    MsgTypes::SlowMode => {
+                        let (room, slow_mode) = match (
+                            msg.channel,
+                            msg.data.and_then(|data| serde_json::from_str::<SlowMode>(&data).ok()),
+                        ) {
+                            (Some(room), Some(slow_mode)) => (room, slow_mode),
+                            _ => return false,
+                        };
+                        let channel = self.channels.get_or_create(&room);
+                        channel.slow_mode_seconds = slow_mode.seconds;
+                        channel.slow_mode_exempt = slow_mode.exempt;
+                        room == self.channels.active_name()
+                    }
+                    MsgTypes::UserNote => {
+                        let note = match msg.data.and_then(|data| serde_json::from_str::<UserNote>(&data).ok()) {
+                            Some(note) => note,
+                            None => {
+                                log::error!("Malformed user note");
+                                return false;
+                            }
+                        };
+                        if note.note.is_empty() {
+                            self.user_notes.remove(&note.about_username);
+                        } else {
+                            self.user_notes.insert(note.about_username.clone(), note.note);
+                        }
+                        self.profile_open.as_deref() == Some(note.about_username.as_str())
+                    }
+                    MsgTypes::EmojiReactionCap => {
+                        let (room, cap) = match (
+                            msg.channel,
+                            msg.data.and_then(|data| serde_json::from_str::<EmojiReactionCap>(&data).ok()),
+                        ) {
+                            (Some(room), Some(cap)) => (room, cap),
+                            _ => {
+                                log::error!("Malformed reaction cap");
+                                return false;
+                            }
+                        };
+                        self.channels.get_or_create(&room).reaction_cap =
+                            (cap.max_reactions > 0).then_some(cap.max_reactions);
+                        room == self.channels.active_name()
+                    }
+                    MsgTypes::DraftSync => {
+                        let draft = match msg.data.and_then(|data| serde_json::from_str::<DraftSync>(&data).ok()) {
+                            Some(draft) => draft,
+                            None => {
+                                log::error!("Malformed draft");
+                                return false;
+                            }
+                        };
+                        // What was typed on this device is newer than anything the server kept.
+                        if self.drafts.get(&draft.room).is_some() {
+                            return false;
+                        }
+                        self.drafts.set(&draft.room, &draft.text);
+                        if draft.room == self.draft_room {
+                            if let Some(input) = self.chat_input.cast::<HtmlInputElement>() {
+                                if input.value().is_empty() {
+                                    input.set_value(&draft.text);
+                                }
+                            }
+                        }
+                        false
+                    }
+                    MsgTypes::WelcomeMessage => {
+                        let (room, welcome) = match (
+                            msg.channel,
+                            msg.data.and_then(|data| serde_json::from_str::<WelcomeMessage>(&data).ok()),
+                        ) {
+                            (Some(room), Some(welcome)) => (room, welcome),
+                            _ => return false,
+                        };
+                        if !self.seen_welcomes.claim(&room, &welcome.text) {
+                            return false;
+                        }
+                        self.channels.get_or_create(&room).welcome = Some(welcome.text);
+                        room == self.channels.active_name()
+                    }
+                    MsgTypes::Reaction | MsgTypes::ReactionRemoved => {
+                        let change = match msg.message_type {
+                            MsgTypes::Reaction => ReactionChange::Add,
+                            _ => ReactionChange::Remove,
+                        };
+                        match msg.data.and_then(|data| serde_json::from_str::<ReactionEvent>(&data).ok()) {
+                            Some(event) if event.from != self.current_user => {
+                                let channel = if event.channel.starts_with('@') {
+                                    format!("@{}", event.from)
+                                } else {
+                                    event.channel
+                                };
+                                self.channels
+                                    .apply_reaction(&channel, &event.message_id, &event.emoji, &event.from, change)
+                            }
+                            _ => false,
+                        }
+                    }
+                    MsgTypes::LikeMessage => match msg.data.and_then(|data| serde_json::from_str::<LikeMessage>(&data).ok()) {
+                        Some(like) => self.channels.apply_like(&like),
+                        None => false,
+                    },
+                    MsgTypes::ServerShutdown => {
+                        let shutdown = match msg.data.and_then(|data| serde_json::from_str::<ServerShutdown>(&data).ok()) {
+                            Some(shutdown) => shutdown,
+                            None => {
+                                log::error!("Malformed server shutdown");
+                                return false;
+                            }
+                        };
+                        if shutdown.shutdown_at == 0 {
+                            self.shutdown = None;
+                            self.shutdown_timer = None;
+                            return true;
+                        }
+                        self.shutdown = Some(shutdown);
+                        self.arm_shutdown_timer(ctx);
+                        true
+                    }
+                    MsgTypes::MessagePin => match msg.data.and_then(|data| serde_json::from_str::<MessagePin>(&data).ok()) {
+                        Some(pin) => self.channels.apply_pin(pin),
+                        None => {
+                            log::error!("Malformed pin");
+                            false
+                        }
+                    },
+                    MsgTypes::PinList => match (msg.channel, msg.data.and_then(|data| serde_json::from_str::<Vec<MessagePin>>(&data).ok())) {
+                        (Some(channel), Some(pins)) => self.channels.set_pins(&channel, pins),
+                        _ => {
+                            log::error!("Malformed pin list");
+                            false
+                        }
+                    },
+                    MsgTypes::Broadcast => match msg.data.and_then(|data| serde_json::from_str::<Broadcast>(&data).ok()) {
+                        Some(broadcast) => {
+                            let shown = broadcast.reaches(self.channels.active_name());
+                            self.broadcasts.retain(|b| b.target_rooms != broadcast.target_rooms);
+                            self.broadcasts.push(broadcast);
+                            shown
+                        }
+                        None => {
+                            log::error!("Malformed broadcast");
+                            false
+                        }
+                    },
+                    MsgTypes::Delete => match msg.data.and_then(|data| serde_json::from_str::<MessageDelete>(&data).ok()) {
+                        Some(delete) => {
+                            self.ephemeral_timers.remove(&delete.message_id);
+                            if let Some(transcript) = &mut self.transcript {
+                                if transcript.remove(&delete.message_id) {
+                                    if let Some(archive) = &self.archive {
+                                        archive.save_transcript(transcript);
+                                    }
+                                }
+                            }
+                            self.channels.apply_delete(&delete)
+                        }
+                        None => {
+                            log::error!("Malformed message delete");
+                            false
+                        }
+                    },
+                    MsgTypes::MessageLock => match msg.data.and_then(|data| serde_json::from_str::<MessageLock>(&data).ok()) {
+                        Some(lock) => self.channels.set_locked(&lock.message_id, lock.locked),
+                        None => {
+                            log::error!("Malformed message lock");
+                            false
+                        }
+                    },
+                    MsgTypes::SpamScore => match msg.data.and_then(|data| serde_json::from_str::<SpamScore>(&data).ok()) {
+                        Some(spam) => self.channels.set_spam_score(&spam.message_id, Some(spam.score)),
+                        None => {
+                            log::error!("Malformed spam score");
+                            false
+                        }
+                    },
+                    MsgTypes::Batch => match msg.data.and_then(|data| serde_json::from_str::<Batch>(&data).ok()) {
+                        // Each event goes through this same arm, so it is handled exactly as if
+                        // it had come in a frame of its own.
+                        Some(batch) => batch.flatten().into_iter().fold(false, |changed, event| {
+                            match serde_json::to_string(&event) {
+                                Ok(data) => self.update(ctx, Msg::HandleMsg(data)) || changed,
+                                Err(e) => {
+                                    log::error!("batch event: {:?}", e);
+                                    changed
+                                }
+                            }
+                        }),
+                        None => {
+                            log::error!("Malformed batch");
+                            false
+                        }
+                    },
+                    MsgTypes::Unpin => match msg.data.and_then(|data| serde_json::from_str::<Unpin>(&data).ok()) {
+                        Some(unpin) => self.channels.apply_unpin(&unpin),
+                        None => {
+                            log::error!("Malformed unpin");
+                            false
+                        }
+                    },
+                    MsgTypes::FileDelete => match msg.data.and_then(|data| serde_json::from_str::<FileDelete>(&data).ok()) {
+                        Some(delete) => {
+                            self.files.remove(&delete.file_id);
+                            self.channels.apply_file_delete(&delete)
+                        }
+                        None => {
+                            log::error!("Malformed file delete");
+                            false
+                        }
+                    },
+                    MsgTypes::Edit => match msg.data.and_then(|data| serde_json::from_str::<MessageEdit>(&data).ok()) {
+                        Some(edit) => self.channels.apply_edit(edit),
+                        None => {
+                            log::error!("Malformed edit");
+                            false
+                        }
+                    },
+                    MsgTypes::AvatarUpdate => {
+                        let update = match msg.data.and_then(|data| serde_json::from_str::<AvatarUpdate>(&data).ok()) {
+                            Some(update) => update,
+                            None => return false,
+                        };
+                        match self.users.iter_mut().find(|u| u.name == update.username) {
+                            Some(user) => {
+                                // The URL may not change when the image does, so bust the browser cache.
+                                let separator = if update.avatar_url.contains('?') { '&' } else { '?' };
+                                user.avatar = format!("{}{}v={}", update.avatar_url, separator, js_sys::Date::now() as u64);
+                                true
+                            }
+                            None => false,
+                        }
+                    }
+                    MsgTypes::UserMerge => {
+                        let merge = match msg.data.and_then(|data| serde_json::from_str::<UserMerge>(&data).ok()) {
+                            Some(merge) => merge,
+                            None => return false,
+                        };
+                        self.channels.rename_user(&merge.old_name, &merge.new_name);
+                        if let Some(user) = self.users.iter_mut().find(|u| u.name == merge.old_name) {
+                            user.avatar = avatar_for(&merge.new_name);
+                            user.name = merge.new_name;
+                        }
+                        true
+                    }
+                    MsgTypes::SearchResult => {
+                        let result = match msg.data.and_then(|data| serde_json::from_str::<SearchResult>(&data).ok()) {
+                            Some(result) => result,
+                            None => return false,
+                        };
+                        match &mut self.search {
+                            Some(search) if search.query == result.query => {
+                                search.hits = Some(result.hits);
+                                search.timeout = None;
+                                true
+                            }
+                            _ => false,
+                        }
+                    }
+                    MsgTypes::Members => {
+                        if let Some(channel) = msg.channel {
+                            self.channels.set_members(&channel, msg.data_array.unwrap_or_default());
+                        }
+                        true
+                    }
+                    MsgTypes::FileChunk => {
+                        match msg.data.and_then(|data| serde_json::from_str::<FileChunk>(&data).ok()) {
+                            Some(chunk) => self.files.add(chunk),
+                            None => false,
+                        }
+                    }
+                    MsgTypes::Typing => {
+                        match msg.data.and_then(|data| serde_json::from_str::<TypingEvent>(&data).ok()) {
+                            Some(event) if event.from != self.current_user => {
+                                self.typing.record(event, js_sys::Date::now());
+                                true
+                            }
+                            _ => false,
+                        }
+                    }
+                    MsgTypes::UserTypingList => {
+                        match msg.data.and_then(|data| serde_json::from_str::<TypingList>(&data).ok()) {
+                            Some(list) => self.typing.replace(list, &self.current_user, js_sys::Date::now()),
+                            None => false,
+                        }
+                    }
+                    MsgTypes::Nudge => {
+                        let from = match msg.data {
+                            Some(from) => from,
+                            None => return false,
+                        };
+                        vibrate(&[200, 100, 200]);
+                        self.play(SoundEffect::Nudge);
+                        self.nudged_by = Some(from);
+                        let link = ctx.link().clone();
+                        self.nudge_timeout = Some(Timeout::new(NUDGE_NOTICE_MS, move || {
+                            link.send_message(Msg::ClearNudge)
+                        }));
+                        true
+                    }
+                    MsgTypes::ChannelList => {
+                        self.channel_list_timeout = None;
+                        self.channel_list = Some(
+                            msg.data
+                                .and_then(|data| serde_json::from_str(&data).ok())
+                                .unwrap_or_default(),
+                        );
+                        true
+                    }
+                    // The server's answer to our registration, with the name it took.
+                    MsgTypes::Register => {
+                        if !self.name_remembered
+                            && last_username::should_remember(msg.data.as_deref(), &self.current_user, self.joined_as_guest)
+                        {
+                            last_username::remember(&self.current_user);
+                            self.name_remembered = true;
+                        }
+                        false
+                    }
+                    MsgTypes::GuestMode => {
+                        self.read_only = true;
+                        self.recorder_open = false;
+                        true
+                    }
+                    MsgTypes::History => {
+                        let mut batch = match msg.data.and_then(|data| serde_json::from_str::<HistoryBatch>(&data).ok()) {
+                            Some(batch) => batch,
+                            None => {
+                                log::error!("Malformed history batch");
+                                return false;
+                            }
+                        };
+                        for message in &mut batch.messages {
+                            message.channel = Some(batch.channel.clone());
+                        }
+                        if let Some(archive) = &self.archive {
+                            archive.archive(&batch.messages);
+                        }
+                        if let Some(from) = batch.from {
+                            let truncated = batch.has_more.unwrap_or(batch.messages.len() >= DAY_HISTORY_LIMIT as usize);
+                            return self.fill_day(ctx, &batch.channel, from, WindowSource::Server { truncated }, batch.messages);
+                        }
+                        self.history_timeouts.remove(&batch.channel);
+                        let has_more = batch
+                            .has_more
+                            .unwrap_or(batch.messages.len() >= HISTORY_LIMIT as usize);
+                        let anchor = self.current_anchor();
+                        let at_bottom = self.is_at_bottom();
+                        let position = self
+                            .message_list
+                            .cast::<Element>()
+                            .map(|list| (list.scroll_height(), list.scroll_top()));
+                        let previous = self.channels.merge_history(&batch.channel, batch.messages, has_more);
+                        if batch.channel == self.channels.active_name() {
+                            self.pending_scroll = match (previous, position, anchor) {
+                                (HistoryState::LoadingOlder, Some((scroll_height, scroll_top)), _) => {
+                                    Some(ScrollTarget::KeepPosition { scroll_height, scroll_top })
+                                }
+                                (_, _, Some(id)) if !at_bottom => Some(anchor_target(id, self.topmost_visible())),
+                                _ => Some(ScrollTarget::Bottom),
+                            };
+                            // Jumping now, after the scroll above is decided, so it wins.
+                            if let Some((id, search)) = self.pending_link.take() {
+                                self.follow_link(ctx, batch.channel.clone(), id, search);
+                            }
+                        }
+                        true
+                    }
+                    MsgTypes::SlashResult => {
+                        let result = match msg.data.and_then(|data| serde_json::from_str::<SlashResult>(&data).ok()) {
+                            Some(result) => result,
+                            None => {
+                                log::error!("Malformed command result");
+                                return false;
+                            }
+                        };
+                        if result.command != "room_create" {
+                            return false;
+                        }
+                        let name = match self.creating_room.take() {
+                            Some(name) => name,
+                            None => return false,
+                        };
+                        self.creating_room_timeout = None;
+                        if result.success {
+                            ctx.link().send_message(Msg::JoinChannel(name));
+                        } else {
+                            let reason = result.message.unwrap_or_else(|| t!("channel_browser.create_failed", name = name));
+                            self.toast(ToastLevel::Error, &reason);
+                        }
+                        true
+                    }
+                    MsgTypes::Quota => {
+                        match msg.data.and_then(|data| serde_json::from_str::<QuotaInfo>(&data).ok()) {
+                            Some(quota) => {
+                                self.quota = Some(quota);
+                                true
+                            }
+                            None => {
+                                log::error!("Malformed quota");
+                                false
+                            }
+                        }
+                    }
+                    MsgTypes::ServerStats => {
+                        self.server_stats = msg.data.and_then(|data| serde_json::from_str(&data).ok());
+                        true
+                    }
+                    MsgTypes::Moderation => {
+                        match msg.data.and_then(|data| serde_json::from_str::<ModerationEntry>(&data).ok()) {
+                            Some(entry) => {
+                                self.mod_log.push(entry);
+                                true
+                            }
+                            None => {
+                                log::error!("Malformed moderation entry");
+                                false
+                            }
+                        }
+                    }
+                    _ => false,
+                }
+            }
+            Msg::SubmitMessage => {
+                if self.read_only || self.connection.is_offline() {
+                    return false;
+                }
+                if let Some(id) = self.editing.clone() {
+                    let text = self.chat_input.cast::<HtmlInputElement>().map(|input| input.value());
+                    match text.as_deref().map(str::trim) {
+                        Some("") | None => {}
+                        Some(text) => {
+                            let edit = MessageEditRequest { message_id: id, message: text.to_string() };
+                            self.send(WebSocketMessage {
+                                message_type: MsgTypes::Edit,
+                                data: Some(serde_json::to_string(&edit).unwrap()),
+                                data_array: None,
+                                channel: Some(self.channels.active_name().to_string()),
+                            });
+                        }
+                    }
+                    self.cancel_edit();
+                    return true;
+                }
+                let had_sent = self.has_sent_message;
+                let mut cooling = false;
+                if let Some(input) = self.chat_input.cast::<HtmlInputElement>() {
+                    let text = input.value();
+                    match text.trim() {
+                        "" => {}
+                        command if command.starts_with("/search ") => {
+                            let query = command["/search ".len()..].trim().to_string();
+                            let room = self.channels.active_name().to_string();
+                            let request = SearchRequest {
+                                query: query.clone(),
+                                room: room.clone(),
+                                limit: SEARCH_LIMIT,
+                            };
+                            self.send(WebSocketMessage {
+                                message_type: MsgTypes::SearchRequest,
+                                data: Some(serde_json::to_string(&request).unwrap()),
+                                data_array: None,
+                                channel: None,
+                            });
+                            let link = ctx.link().clone();
+                            self.search = Some(Search {
+                                query,
+                                room,
+                                hits: None,
+                                failed: false,
+                                timeout: Some(Timeout::new(SEARCH_TIMEOUT_MS, move || {
+                                    link.send_message(Msg::SearchTimedOut)
+                                })),
+                            });
+                            input.set_value("");
+                            self.draft_sync = None;
+                            self.drafts.set(self.channels.active_name(), "");
+                            return true;
+                        }
+                        command if command.starts_with("/welcome ") => {
+                            let welcome = SetWelcome {
+                                room: self.channels.active_name().to_string(),
+                                text: command["/welcome ".len()..].trim().to_string(),
+                            };
+                            self.send(WebSocketMessage {
+                                message_type: MsgTypes::SetWelcome,
+                                data: Some(serde_json::to_string(&welcome).unwrap()),
+                                data_array: None,
+                                channel: None,
+                            });
+                        }
+                        command if command.starts_with("/slowmode ") => {
+                            match command["/slowmode ".len()..].trim().parse::<u32>() {
+                                Ok(seconds) => self.send(WebSocketMessage {
+                                    message_type: MsgTypes::SlowMode,
+                                    data: Some(serde_json::to_string(&SlowMode { seconds, exempt: false }).unwrap()),
+                                    data_array: None,
+                                    channel: Some(self.channels.active_name().to_string()),
+                                }),
+                                Err(_) => {
+                                    self.toast(ToastLevel::Warn, &t!("usage.slowmode"));
+                                    return false;
+                                }
+                            }
+                        }
+                        command if command.starts_with("/ephemeral ") && parse_ephemeral(command).is_none() => {
+                            self.toast(ToastLevel::Warn, &t!("usage.ephemeral"));
+                            return false;
+                        }
+                        "/admin stats" => self.send(WebSocketMessage {
+                            message_type: MsgTypes::ServerStats,
+                            data: None,
+                            data_array: None,
+                            channel: None,
+                        }),
+                        _ if self.daily_limit_reached() => {
+                            self.toast(ToastLevel::Warn, &t!("quota.daily_limit"));
+                            return true;
+                        }
+                        // Slow mode: keep the draft until the cooldown is over.
+                        _ if self.channels.active().cooldown_remaining(js_sys::Date::now()).is_some() => {
+                            return false;
+                        }
+                        _ => {
+                            let (message_type, data) = match parse_ephemeral(text.trim()) {
+                                Some(ephemeral) => (MsgTypes::EphemeralMessage, serde_json::to_string(&ephemeral).unwrap()),
+                                None => (MsgTypes::Message, text.clone()),
+                            };
+                            self.send(WebSocketMessage {
+                                message_type,
+                                data: Some(data),
+                                data_array: None,
+                                channel: Some(self.channels.active_name().to_string()),
+                            });
+                            self.has_sent_message = true;
+                            self.play(SoundEffect::Send);
+                            let channel = self.channels.active_mut();
+                            if channel.slow_mode_seconds > 0 && !channel.slow_mode_exempt {
+                                channel.cooldown_until = js_sys::Date::now() + channel.slow_mode_seconds as f64 * 1000.0;
+                                cooling = true;
+                            }
+                        }
+                    }
+                    input.set_value("");
+                    // The server drops its copy once the message goes through.
+                    self.draft_sync = None;
+                    self.drafts.set(self.channels.active_name(), "");
+                }
+                // The first message brings up the notification permission prompt, and a
+                // cooldown the bar under the composer.
+                had_sent != self.has_sent_message || cooling
+            }
+            Msg::React(message_id, emoji) => {
+                if self.read_only || !matches!(self.channels.active().find(&message_id), Some(m) if !m.locked) {
+                    return false;
+                }
+                let channel = self.channels.active_name().to_string();
+                let user = self.current_user.clone();
+                // Reacting again with the same emoji takes it back instead of adding a duplicate.
+                let (change, message_type) = if self.channels.has_reacted(&channel, &message_id, &emoji, &user) {
+                    (ReactionChange::Remove, MsgTypes::ReactionRemoved)
+                } else {
+                    (ReactionChange::Add, MsgTypes::Reaction)
+                };
+                self.channels.apply_reaction(&channel, &message_id, &emoji, &user, change);
+                let event = ReactionEvent {
+                    from: String::new(),
+                    channel,
+                    message_id,
+                    emoji,
+                };
+                self.send(WebSocketMessage {
+                    message_type,
+                    data: Some(serde_json::to_string(&event).unwrap()),
+                    data_array: None,
+                    channel: None,
+                });
+                true
+            }
+            Msg::ToggleLike(message_id) => {
+                if self.read_only {
+                    return false;
+                }
+                let like = match self.channels.active().find(&message_id) {
+                    Some(message) if !message.locked => LikeMessage {
+                        message_id: message.id.clone(),
+                        username: self.current_user.clone(),
+                        liked: !message.likes.contains(&self.current_user),
+                    },
+                    _ => return false,
+                };
+                self.channels.apply_like(&like);
+                self.send(WebSocketMessage {
+                    message_type: MsgTypes::LikeMessage,
+                    data: Some(serde_json::to_string(&like).unwrap()),
+                    data_array: None,
+                    channel: None,
+                });
+                true
+            }
+            Msg::OpenPinDialog(message_id) => {
+                // Nothing to pin for long: it's gone once its countdown runs out.
+                if self.channels.active().find(&message_id).map_or(true, |m| m.expires_at.is_some()) {
+                    return false;
+                }
+                self.pin_dialog = Some(message_id);
+                true
+            }
+            Msg::EphemeralTick(id) => {
+                self.ephemeral_timers.remove(&id);
+                // The user may have switched away since the timer was set.
+                let expired = self
+                    .channels
+                    .channels()
+                    .iter()
+                    .find_map(|channel| channel.find(&id))
+                    .and_then(|m| m.seconds_left(js_sys::Date::now()))
+                    == Some(0);
+                if expired {
+                    // The server's delete is on its way; nobody should see it linger meanwhile.
+                    self.channels.apply_delete(&MessageDelete { message_id: id });
+                }
+                true
+            }
+            Msg::ClosePinDialog => self.pin_dialog.take().is_some(),
+            Msg::PinMessage(pin_type) => {
+                let message_id = match self.pin_dialog.take() {
+                    Some(message_id) => message_id,
+                    None => return false,
+                };
+                let pin = MessagePin {
+                    channel: self.channels.active_name().to_string(),
+                    message_id,
+                    pin_type,
+                    by: self.current_user.clone(),
+                };
+                self.send(WebSocketMessage {
+                    message_type: MsgTypes::MessagePin,
+                    data: Some(serde_json::to_string(&pin).unwrap()),
+                    data_array: None,
+                    channel: None,
+                });
+                self.channels.apply_pin(pin);
+                true
+            }
+            Msg::UnpinMessage(message_id) => {
+                let unpin = Unpin {
+                    channel: self.channels.active_name().to_string(),
+                    message_id,
+                };
+                self.send(WebSocketMessage {
+                    message_type: MsgTypes::Unpin,
+                    data: Some(serde_json::to_string(&unpin).unwrap()),
+                    data_array: None,
+                    channel: None,
+                });
+                self.channels.apply_unpin(&unpin)
+            }
+            Msg::DeleteFile(file_id) => {
+                let delete = FileDelete { file_id };
+                self.files.remove(&delete.file_id);
+                self.channels.apply_file_delete(&delete);
+                self.send(WebSocketMessage {
+                    message_type: MsgTypes::FileDelete,
+                    data: Some(serde_json::to_string(&delete).unwrap()),
+                    data_array: None,
+                    channel: None,
+                });
+                true
+            }
+            Msg::RevealSpam(id) => self.revealed_spam.insert(id),
+            Msg::MarkSafe(id) => {
+                if !self.is_admin() {
+                    return false;
+                }
+                let flag = MessageFlag {
+                    message_id: id,
+                    reason: "safe".to_string(),
+                };
+                self.channels.set_spam_score(&flag.message_id, None);
+                self.send(WebSocketMessage {
+                    message_type: MsgTypes::MessageFlag,
+                    data: Some(serde_json::to_string(&flag).unwrap()),
+                    data_array: None,
+                    channel: None,
+                });
+                true
+            }
+            Msg::SetLocked(id, locked) => {
+                self.message_menu = None;
+                if !self.is_admin() {
+                    return true;
+                }
+                let lock = MessageLock { message_id: id, locked };
+                self.channels.set_locked(&lock.message_id, locked);
+                self.send(WebSocketMessage {
+                    message_type: MsgTypes::MessageLock,
+                    data: Some(serde_json::to_string(&lock).unwrap()),
+                    data_array: None,
+                    channel: None,
+                });
+                true
+            }
+            Msg::SwitchChannel(name) => {
+                self.cancel_edit();
+                if name == self.channels.active_name() && !self.showing_mentions {
+                    return false;
+                }
+                self.open_thread = None;
+                let anchor = self.current_anchor();
+                self.showing_mentions = false;
+                let restored = self.channels.switch_to(&name, anchor);
+                self.pending_scroll = Some(arrival_target(restored, self.scroll_memory.get(&name).cloned()));
+                self.request_members();
+                self.request_history(ctx);
+                true
+            }
+            Msg::Typing => {
+                if self.read_only {
+                    return false;
+                }
+                let room = self.channels.active_name().to_string();
+                self.presence.typing(&room, None);
+                // The composer holds an old message while editing, not a draft.
+                if self.editing.is_none() {
+                    let link = ctx.link().clone();
+                    self.draft_sync = Some(Timeout::new(DRAFT_SYNC_MS, move || link.send_message(Msg::SaveDraft)));
+                }
+                false
+            }
+            Msg::SaveDraft => {
+                self.draft_sync = None;
+                if let Some(input) = self.chat_input.cast::<HtmlInputElement>() {
+                    let room = self.draft_room.clone();
+                    self.store_draft(&room, &input.value());
+                }
+                false
+            }
+            // The bar counts down by itself; only the send button needs this.
+            Msg::CooldownOver => true,
+            Msg::ShutdownDue => {
+                let due = match &self.shutdown {
+                    Some(shutdown) => shutdown.shutdown_at as f64 * 1000.0 <= js_sys::Date::now(),
+                    None => return false,
+                };
+                if !due {
+                    self.arm_shutdown_timer(ctx);
+                    return false;
+                }
+                self.shutdown = None;
+                self.shutdown_timer = None;
+                self.transition(ctx, ConnectionEvent::ShutdownDue);
+                true
+            }
+            Msg::SocketEvent(socket, event) => socket == self.socket && self.transition(ctx, event),
+            Msg::Connection(event) => self.transition(ctx, event),
+            Msg::OpenConnection => {
+                self.reconnect_timer = None;
+                let user = match ctx.link().context::<UserCtx>(Callback::noop()) {
+                    Some((user, _)) => user,
+                    None => return false,
+                };
+                // Dropping the old socket's sender hangs it up, if it ever opened.
+                self.socket += 1;
+                self.wss = Self::connect(ctx, &user, self.socket);
+                false
+            }
+            Msg::PruneTyping => self.typing.prune(js_sys::Date::now()),
+            Msg::ShowMentions => {
+                if !self.showing_mentions {
+                    self.channels.active_mut().scroll_anchor = self.topmost_visible_message();
+                }
+                self.showing_mentions = true;
+                true
+            }
+            Msg::JumpToMessage { channel, id } => {
+                if channel != self.channels.active_name() {
+                    let anchor = self.current_anchor();
+                    self.channels.switch_to(&channel, anchor);
+                    self.request_members();
+                }
+                self.showing_mentions = false;
+                self.search = None;
+                self.channels.clear_mention(&id);
+                self.pending_scroll = Some(ScrollTarget::Message(id.clone()));
+                self.highlighted = Some(id);
+                let link = ctx.link().clone();
+                self.highlight_timeout = Some(Timeout::new(HIGHLIGHT_MS, move || {
+                    link.send_message(Msg::ClearHighlight)
+                }));
+                true
+            }
+            Msg::OpenNotification(target) => {
+                let still_there = self
+                    .channels
+                    .get(&target.channel)
+                    .map_or(false, |c| c.contains(&target.message_id));
+                if still_there {
+                    ctx.link().send_message(Msg::JumpToMessage {
+                        channel: target.channel,
+                        id: target.message_id,
+                    });
+                } else {
+                    ctx.link().send_message(Msg::SwitchChannel(target.channel));
+                    self.toast(ToastLevel::Info, &t!("messages.unavailable"));
+                }
+                false
+            }
+            Msg::ClearHighlight => {
+                self.highlight_timeout = None;
+                self.highlighted.take().is_some()
+            }
+            Msg::OpenDm(name) => {
+                if !self.flags.dms {
+                    return false;
+                }
+                ctx.link().send_message(Msg::SwitchChannel(format!("@{}", name)));
+                false
+            }
+            Msg::CloseDm(key) => {
+                self.channels.close(&key);
+                if key == self.channels.active_name() {
+                    ctx.link().send_message(Msg::SwitchChannel(self.settings.default_channel.clone()));
+                }
+                true
+            }
+            Msg::OpenRequestAccess => {
+                self.request_access_open = true;
+                true
+            }
+            Msg::CloseRequestAccess => {
+                self.request_access_open = false;
+                true
+            }
+            Msg::SendAccessRequest(message) => {
+                let request = AccessRequest { message };
+                self.send(WebSocketMessage {
+                    message_type: MsgTypes::AccessRequest,
+                    data: Some(serde_json::to_string(&request).unwrap()),
+                    data_array: None,
+                    channel: None,
+                });
+                self.request_access_open = false;
+                self.toast(ToastLevel::Success, &t!("request_access.sent"));
+                true
+            }
+            Msg::OpenChannelBrowser => {
+                self.channel_browser_open = true;
+                self.send(WebSocketMessage {
+                    message_type: MsgTypes::ChannelList,
+                    data: None,
+                    data_array: None,
+                    channel: None,
+                });
+                if self.channel_list.is_none() {
+                    let link = ctx.link().clone();
+                    self.channel_list_timeout = Some(Timeout::new(CHANNEL_LIST_TIMEOUT_MS, move || {
+                        link.send_message(Msg::ChannelListTimedOut)
+                    }));
+                }
+                true
+            }
+            Msg::CloseChannelBrowser => {
+                self.channel_browser_open = false;
+                true
+            }
+            Msg::ChannelListTimedOut => {
+                log::debug!("no channel list from server, hiding the channel browser");
+                self.channel_list_timeout = None;
+                self.channel_list_supported = false;
+                self.channel_browser_open = false;
+                true
+            }
+            Msg::JoinChannel(name) => {
+                self.channel_browser_open = false;
+                ctx.link().send_message(Msg::SwitchChannel(name));
+                true
+            }
+            Msg::CreateRoom(room) => {
+                if self.creating_room.is_some() {
+                    return false;
+                }
+                self.creating_room = Some(room.name.clone());
+                let link = ctx.link().clone();
+                self.creating_room_timeout = Some(Timeout::new(ROOM_CREATE_TIMEOUT_MS, move || {
+                    link.send_message(Msg::RoomCreateTimedOut)
+                }));
+                self.send(WebSocketMessage {
+                    message_type: MsgTypes::RoomCreate,
+                    data: Some(serde_json::to_string(&room).unwrap()),
+                    data_array: None,
+                    channel: None,
+                });
+                true
+            }
+            Msg::HistoryTimedOut(channel) => {
+                self.history_timeouts.remove(&channel);
+                if !self.channels.abandon_history_request(&channel) {
+                    return false;
+                }
+                // A link waiting on this page won't be found in it.
+                if channel == self.channels.active_name() {
+                    if let Some((id, search)) = self.pending_link.take() {
+                        self.follow_link(ctx, channel.clone(), id, search);
+                    }
+                }
+                self.toast(ToastLevel::Error, &t!("messages.history_failed", channel = channel));
+                true
+            }
+            Msg::RoomCreateTimedOut => {
+                self.creating_room_timeout = None;
+                match self.creating_room.take() {
+                    Some(name) => {
+                        self.toast(ToastLevel::Error, &t!("channel_browser.create_timed_out", name = name));
+                        true
+                    }
+                    None => false,
+                }
+            }
+            Msg::FilterUsers(filter) => {
+                self.user_filter = filter;
+                true
+            }
+            Msg::ToggleUserMenu(name) => {
+                self.user_menu = match self.user_menu.take() {
+                    Some(open) if open == name => None,
+                    _ => Some(name),
+                };
+                true
+            }
+            Msg::Nudge(name) => {
+                self.user_menu = None;
+                if !self.can_nudge(&name) {
+                    return true;
+                }
+                self.nudges_sent.insert(name.clone(), js_sys::Date::now());
+                self.send(WebSocketMessage {
+                    message_type: MsgTypes::Nudge,
+                    data: Some(name),
+                    data_array: None,
+                    channel: None,
+                });
+                true
+            }
+            Msg::ClearNudge => {
+                self.nudged_by = None;
+                self.nudge_timeout = None;
+                true
+            }
+            Msg::SetArchived(channel, archived) => {
+                self.settings.notifications.set_archived(&channel, archived);
+                self.settings.save(&self.current_user);
+                true
+            }
+            Msg::ToggleArchivedSection => {
+                self.archived_open = !self.archived_open;
+                true
+            }
+            Msg::CycleRoomPreference => {
+                let channel = self.channels.active_name().to_string();
+                let pref = self.settings.notifications.get(&channel).next();
+                self.settings.notifications.set(&channel, pref);
+                self.settings.save(&self.current_user);
+                self.request_permission_on(ctx, pref);
+                true
+            }
+            Msg::EnableNotifications => {
+                self.request_permission(ctx);
+                false
+            }
+            Msg::PermissionAnswered(permission) => {
+                self.permission_prompt.record(permission);
+                true
+            }
+            Msg::SnoozeNotificationPrompt => {
+                self.permission_prompt.snooze(js_sys::Date::now());
+                true
+            }
+            Msg::SetUserMute(user, mute) => {
+                self.settings.notifications.set_user_mute(&user, mute);
+                self.settings.save(&self.current_user);
+                true
+            }
+            Msg::CycleNotifyMode => {
+                let mode = self.settings.notifications.global().next();
+                self.settings.notifications.set_global(mode);
+                self.settings.save(&self.current_user);
+                self.request_permission_on(ctx, mode);
+                true
+            }
+            Msg::ToggleRecorder => {
+                self.recorder_open = !self.recorder_open;
+                true
+            }
+            Msg::SetClipSource(source) => {
+                self.clip_source = source;
+                true
+            }
+            Msg::StartRecording => {
+                let source = self.clip_source;
+                let on_done = ctx.link().callback(Msg::ClipRecorded);
+                ctx.link().send_future(async move {
+                    match ClipRecorder::start(source, on_done).await {
+                        Ok(recorder) => Msg::RecordingStarted(recorder),
+                        Err(e) => {
+                            log::error!("failed to start recording: {:?}", e);
+                            Msg::RecordingFailed
+                        }
+                    }
+                });
+                false
+            }
+            Msg::RecordingStarted(recorder) => {
+                self.recorder = Some(recorder);
+                let link = ctx.link().clone();
+                self.recording_timeout = Some(Timeout::new(MAX_CLIP_MS, move || {
+                    link.send_message(Msg::StopRecording)
+                }));
+                true
+            }
+            Msg::RecordingFailed => {
+                self.recorder_open = false;
+                self.toast(ToastLevel::Error, &t!("recorder.failed"));
+                true
+            }
+            Msg::StopRecording => {
+                self.recording_timeout = None;
+                if let Some(recorder) = &self.recorder {
+                    recorder.stop();
+                }
+                true
+            }
+            Msg::ClipRecorded(blob) => {
+                // This runs inside the recorder's own onstop callback, so its closures
+                // have to outlive the call: the recorder is dropped on the next tick.
+                if let Some(recorder) = self.recorder.take() {
+                    wasm_bindgen_futures::spawn_local(async move { drop(recorder) });
+                }
+                self.recorder_open = false;
+                self.upload_or_confirm(ctx, HeldUpload::Clip(blob));
+                true
+            }
+            Msg::ClipEncoded(id, url) => {
+                if !self.finish_upload(id) {
+                    return false;
+                }
+                let file_id = self.upload(url);
+                self.send(WebSocketMessage {
+                    message_type: MsgTypes::VideoClip,
+                    data: Some(file_id),
+                    data_array: None,
+                    channel: Some(self.channels.active_name().to_string()),
+                });
+                true
+            }
+            Msg::AttachFile(file) => {
+                self.upload_or_confirm(ctx, HeldUpload::File(file));
+                true
+            }
+            Msg::ConfirmUpload => {
+                if let Some((upload, _)) = self.upload_confirm.take() {
+                    self.start_reading(ctx, upload);
+                }
+                true
+            }
+            Msg::DiscardUpload => self.upload_confirm.take().is_some(),
+            Msg::AttachmentEncoded(id, mut attachment, url) => {
+                if !self.finish_upload(id) {
+                    return false;
+                }
+                attachment.file_id = self.upload(url);
+                self.send(WebSocketMessage {
+                    message_type: MsgTypes::Attachment,
+                    data: Some(serde_json::to_string(&attachment).unwrap()),
+                    data_array: None,
+                    channel: Some(self.channels.active_name().to_string()),
+                });
+                true
+            }
+            Msg::UploadFailed(id) => {
+                if !self.finish_upload(id) {
+                    return false;
+                }
+                self.toast(ToastLevel::Error, &t!("upload.read_failed"));
+                true
+            }
+            Msg::CancelUpload => self.uploads.pop().is_some(),
+            Msg::SendLocation => {
+                if self.locating {
+                    return false;
+                }
+                self.locating = true;
+                ctx.link().send_future(async {
+                    match current_position().await {
+                        Ok((lat, lng)) => Msg::LocationFound(lat, lng),
+                        Err(e) => {
+                            log::error!("geolocation failed: {:?}", e);
+                            Msg::LocationFailed
+                        }
+                    }
+                });
+                true
+            }
+            Msg::LocationFound(lat, lng) => {
+                self.locating = false;
+                self.send(WebSocketMessage {
+                    message_type: MsgTypes::Message,
+                    data: Some(map_link(lat, lng)),
+                    data_array: None,
+                    channel: Some(self.channels.active_name().to_string()),
+                });
+                true
+            }
+            Msg::LocationFailed => {
+                self.locating = false;
+                self.toast(ToastLevel::Error, &t!("composer.location_failed"));
+                true
+            }
+            Msg::OpenLightbox(url) => {
+                self.lightbox_image = Some(url);
+                true
+            }
+            Msg::CloseLightbox => {
+                self.lightbox_image = None;
+                true
+            }
+            Msg::SendCheckpoint => {
+                if self.checkpoint_id.is_empty() || self.checkpoint_id == self.sent_checkpoint_id {
+                    return false;
+                }
+                self.sent_checkpoint_id = self.checkpoint_id.clone();
+                self.since_checkpoint = 0;
+                let checkpoint = Checkpoint {
+                    last_seen_id: self.checkpoint_id.clone(),
+                };
+                self.send(WebSocketMessage {
+                    message_type: MsgTypes::Checkpoint,
+                    data: Some(serde_json::to_string(&checkpoint).unwrap()),
+                    data_array: None,
+                    channel: None,
+                });
+                false
+            }
+            Msg::VisibilityChanged => {
+                if self.visibility_of(self.channels.active_name()) != Visibility::Hidden {
+                    self.set_unread_badges(0);
+                }
+                ctx.link().send_message(Msg::SendCheckpoint);
+                false
+            }
+            // update() has moved the drawer already.
+            Msg::SetDrawer(_) => false,
+            Msg::ToggleSidebar => {
+                self.sidebar_visible = !self.sidebar_visible;
+                true
+            }
+            Msg::OpenExport => {
+                self.export_open = true;
+                true
+            }
+            Msg::CloseExport => {
+                self.export_open = false;
+                true
+            }
+            Msg::OpenStats => {
+                let messages: Vec<MessageData> = self
+                    .channels
+                    .channels()
+                    .iter()
+                    .flat_map(|c| c.messages.iter().cloned())
+                    .collect();
+                let offset = -(js_sys::Date::new_0().get_timezone_offset() as i32);
+                self.stats = Some(SessionStats::compute(&messages, offset));
+                true
+            }
+            Msg::Logout => {
+                // A courtesy so others see us leave now rather than at the presence timeout.
+                if self.connection.is_live() {
+                    self.send(unregister_message(self.current_user.clone()));
+                }
+                // Hangs up after the goodbye and keeps the socket's close from redialing.
+                self.transition(ctx, ConnectionEvent::Logout);
+                if self.settings.session_token.take().is_some() {
+                    self.settings.save(&self.current_user);
+                }
+                self.channels = ChannelStore::new(&CHANNELS);
+                self.users.clear();
+                match ctx.link().history() {
+                    Some(history) => history.push(Route::Login),
+                    None => log::error!("no router to leave the chat through"),
+                }
+                if let Some((user, _)) = ctx.link().context::<UserCtx>(Callback::noop()) {
+                    user.log_out();
+                }
+                true
+            }
+            Msg::CloseStats => {
+                self.stats = None;
+                true
+            }
+            Msg::OpenProfile(name) => {
+                let name = self.profile_summary(&name).name;
+                if !self.user_notes.contains_key(&name) {
+                    self.send(WebSocketMessage {
+                        message_type: MsgTypes::GetUserNote,
+                        data: Some(serde_json::to_string(&GetUserNote { about_username: name.clone() }).unwrap()),
+                        data_array: None,
+                        channel: None,
+                    });
+                }
+                self.profile_open = Some(name);
+                true
+            }
+            Msg::SaveUserNote(name, note) => {
+                self.send(WebSocketMessage {
+                    message_type: MsgTypes::UserNote,
+                    data: Some(serde_json::to_string(&UserNote { about_username: name.clone(), note: note.clone() }).unwrap()),
+                    data_array: None,
+                    channel: None,
+                });
+                if note.is_empty() {
+                    self.user_notes.remove(&name);
+                } else {
+                    self.user_notes.insert(name, note);
+                }
+                true
+            }
+            Msg::InstallApp => {
+                pwa::prompt_install();
+                false
+            }
+            Msg::CloseProfile => {
+                self.profile_open = None;
+                true
+            }
+            Msg::OpenMessageMenu(id, x, y) => {
+                self.message_menu = Some((id, x, y));
+                true
+            }
+            Msg::CloseMessageMenu => self.message_menu.take().is_some(),
+            Msg::PressMessage(id, x, y) => {
+                let link = ctx.link().clone();
+                let timer = Timeout::new(LONG_PRESS_MS, move || {
+                    link.send_message(Msg::OpenMessageMenu(id, x, y));
+                });
+                self.long_press = Some((timer, (x, y)));
+                false
+            }
+            Msg::MovePress(x, y) => {
+                if matches!(&self.long_press, Some((_, start)) if !within_slop(*start, (x, y))) {
+                    self.long_press = None;
+                }
+                false
+            }
+            Msg::ReleaseMessage => {
+                self.long_press = None;
+                false
+            }
+            Msg::KeyboardInset(inset) => {
+                let changed = self.keyboard_inset != inset;
+                self.keyboard_inset = inset;
+                // Keeps the newest message above the keyboard, unless the user is reading back.
+                if changed && inset > 0.0 && self.is_at_bottom() {
+                    self.pending_scroll = Some(ScrollTarget::Bottom);
+                }
+                changed
+            }
+            Msg::OpenThread(id) => {
+                self.message_menu = None;
+                self.open_thread = Some(id);
+                true
+            }
+            Msg::CloseThread => self.open_thread.take().is_some(),
+            Msg::ThreadTyping => {
+                if self.read_only {
+                    return false;
+                }
+                if let Some(thread_id) = &self.open_thread {
+                    self.presence.typing(self.channels.active_name(), Some(thread_id));
+                }
+                false
+            }
+            Msg::SendThreadReply(message) => {
+                let thread_id = match &self.open_thread {
+                    Some(id) if !self.read_only && !self.connection.is_offline() => id.clone(),
+                    _ => return false,
+                };
+                self.send(WebSocketMessage {
+                    message_type: MsgTypes::ThreadReply,
+                    data: Some(serde_json::to_string(&ThreadReply { thread_id, message }).unwrap()),
+                    data_array: None,
+                    channel: Some(self.channels.active_name().to_string()),
+                });
+                self.play(SoundEffect::Send);
+                false
+            }
+            Msg::CopyMessageLink(id) => {
+                self.message_menu = None;
+                let origin = web_sys::window()
+                    .and_then(|window| window.location().origin().ok())
+                    .unwrap_or_default();
+                let link = MessageLink {
+                    room: self.channels.active_name().to_string(),
+                    message: id,
+                };
+                let url = link.to_url(&origin, self.settings.use_history_api);
+                ctx.link().send_future(async move {
+                    match clipboard::write_text(&url).await {
+                        Ok(()) => Msg::LinkCopied(true),
+                        Err(e) => {
+                            log::error!("failed to copy the message link: {:?}", e);
+                            Msg::LinkCopied(false)
+                        }
+                    }
+                });
+                true
+            }
+            Msg::LinkCopied(copied) => {
+                if copied {
+                    self.toast(ToastLevel::Success, &t!("message_menu.link_copied"));
+                } else {
+                    self.toast(ToastLevel::Error, &t!("message_menu.copy_failed"));
+                }
+                false
+            }
+            Msg::OpenClearData => {
+                self.clear_data_open = true;
+                true
+            }
+            Msg::CloseClearData => {
+                self.clear_data_open = false;
+                true
+            }
+            Msg::ClearLocalData(include_settings) => {
+                self.clear_data_open = false;
+                forget_conversations(
+                    &mut self.channels,
+                    &self.message_cache,
+                    &mut self.scroll_memory,
+                    &mut self.drafts,
+                );
+                self.files.clear();
+                self.cache_save = None;
+                self.local_search = None;
+                if let Some(transcript) = &mut self.transcript {
+                    transcript.messages.clear();
+                }
+                if let Some(archive) = self.archive.clone() {
+                    wasm_bindgen_futures::spawn_local(async move {
+                        if let Err(e) = archive.clear().await {
+                            log::error!("failed to clear the message archive: {:?}", e);
+                        }
+                    });
+                }
+                if include_settings {
+                    self.settings = UserSettings::clear(&self.current_user);
+                    self.dnd_active = self.settings.dnd.is_active(Now::local());
+                }
+                self.toast(ToastLevel::Success, &t!("clear_data.done"));
+                true
+            }
+            Msg::ExportChat(format) => {
+                self.export_open = false;
+                let channel = self.channels.active_name().to_string();
+                let in_memory = self.channels.active().messages.clone();
+                match self.archive.clone() {
+                    Some(archive) => ctx.link().send_future(async move {
+                        let archived = archive
+                            .range(&channel, f64::NEG_INFINITY, f64::INFINITY)
+                            .await
+                            .unwrap_or_else(|e| {
+                                log::error!("failed to read the message archive: {:?}", e);
+                                vec![]
+                            });
+                        Msg::ExportReady(format, channel, combine(archived, &in_memory))
+                    }),
+                    None => ctx.link().send_message(Msg::ExportReady(format, channel, in_memory)),
+                }
+                true
+            }
+            Msg::ExportReady(format, channel, messages) => {
+                let date = js_sys::Date::new_0();
+                let date = format!("{:04}-{:02}-{:02}", date.get_full_year(), date.get_month() + 1, date.get_date());
+                let contents = match format {
+                    ExportFormat::Json => to_json(&messages),
+                    ExportFormat::Text => {
+                        let offset = -(js_sys::Date::new_0().get_timezone_offset() as i32);
+                        to_text(&messages, offset)
+                    }
+                };
+                download_text(&export_filename(&channel, &date, format), format.mime(), &contents);
+                false
+            }
+            Msg::ShowPolicyNotice(open) => {
+                self.policy_notice_open = open;
+                true
+            }
+            Msg::SetAdminTab(tab) => {
+                self.admin_tab = tab;
+                true
+            }
+            Msg::SendBroadcast(broadcast) => {
+                self.send(WebSocketMessage {
+                    message_type: MsgTypes::Broadcast,
+                    data: Some(serde_json::to_string(&broadcast).unwrap()),
+                    data_array: None,
+                    channel: None,
+                });
+                false
+            }
+            Msg::DismissBroadcast => match self.shown_broadcast() {
+                Some(index) => {
+                    self.broadcasts.remove(index);
+                    true
+                }
+                None => false,
+            },
+            Msg::ArchiveOpened(archive) => {
+                let archive = match archive {
+                    Some(archive) => archive,
+                    None => return false,
+                };
+                // Whatever arrived while it was opening, then fill in behind the quick cache.
+                for channel in self.channels.channels() {
+                    let live: Vec<MessageData> = channel.messages.iter().filter(|m| !m.cached).cloned().collect();
+                    archive.archive(&live);
+                    let name = channel.name.clone();
+                    let archive = archive.clone();
+                    ctx.link().send_future(async move {
+                        let messages = archive.recent(&name, ARCHIVE_LOAD_LIMIT).await.unwrap_or_else(|e| {
+                            log::error!("failed to read the message archive: {:?}", e);
+                            vec![]
+                        });
+                        Msg::ArchiveLoaded(name, messages)
+                    });
+                }
+                let cutoff = js_sys::Date::now() - ARCHIVE_RETENTION_MS;
+                let pruning = archive.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Err(e) = pruning.prune(cutoff).await {
+                        log::error!("failed to prune the message archive: {:?}", e);
+                    }
+                });
+                self.archive = Some(archive);
+                self.load_transcript(ctx);
+                false
+            }
+            Msg::ArchiveLoaded(channel, messages) => {
+                let is_active = channel == self.channels.active_name();
+                let at_bottom = self.is_at_bottom();
+                let added = self.channels.merge_archived(&channel, messages);
+                if is_active && added && at_bottom {
+                    self.pending_scroll = Some(ScrollTarget::Bottom);
+                }
+                is_active && added
+            }
+            Msg::SaveCache => {
+                self.cache_save = None;
+                let dirty = self.channels.take_dirty();
+                if let Some(archive) = &self.archive {
+                    archive.rewrite(&dirty);
+                }
+                if let Some(transcript) = &mut self.transcript {
+                    transcript.amend(&dirty);
+                    if let Some(archive) = &self.archive {
+                        archive.save_transcript(transcript);
+                    }
+                }
+                self.message_cache.save(
+                    self.channels
+                        .channels()
+                        .iter()
+                        .map(|c| (c.name.as_str(), c.messages.as_slice())),
+                );
+                false
+            }
+            Msg::LoadOlder => {
+                if self.showing_mentions || self.channels.active().window.is_some() {
+                    return false;
+                }
+                let name = self.channels.active_name().to_string();
+                match self.channels.claim_older_history(&name) {
+                    Some(before) => {
+                        self.send_history_request(ctx, name, Some(before));
+                        true
+                    }
+                    None => false,
+                }
+            }
+            Msg::MessageListScrolled => {
+                if self.title.unread() > 0 && self.is_at_bottom() {
+                    self.set_unread_badges(0);
+                }
+                if self.scroll_save.is_none() && !self.showing_mentions {
+                    let link = ctx.link().clone();
+                    self.scroll_save = Some(Timeout::new(SCROLL_SAVE_DEBOUNCE_MS, move || {
+                        link.send_message(Msg::SaveScrollPosition)
+                    }));
+                }
+                false
+            }
+            Msg::SaveScrollPosition => {
+                self.scroll_save = None;
+                if self.showing_mentions {
+                    return false;
+                }
+                let position = if self.is_at_bottom() { None } else { self.topmost_visible() };
+                self.scroll_memory.set(self.channels.active_name(), position);
+                false
+            }
+            Msg::OpenSettings => {
+                self.settings_open = true;
+                true
+            }
+            Msg::CloseSettings => {
+                self.settings_open = false;
+                true
+            }
+            Msg::Shortcut(Shortcut::Search) => {
+                ctx.link().send_message(Msg::OpenLocalSearch);
+                false
+            }
+            Msg::Shortcut(shortcut @ (Shortcut::PreviousChannel | Shortcut::NextChannel)) => {
+                let order = self.rail_order();
+                let at = order.iter().position(|name| name == self.channels.active_name());
+                let target = match (at, shortcut) {
+                    (Some(at), Shortcut::PreviousChannel) => at.checked_sub(1),
+                    (Some(at), _) => Some(at + 1),
+                    (None, _) => Some(0),
+                };
+                if let Some(name) = target.and_then(|i| order.get(i)) {
+                    ctx.link().send_message(Msg::SwitchChannel(name.clone()));
+                }
+                false
+            }
+            Msg::Shortcut(Shortcut::Escape) => {
+                if let Some(close) = self.close_top_layer() {
+                    ctx.link().send_message(close);
+                    return false;
+                }
+                if self.editing.is_some() {
+                    self.cancel_edit();
+                    return true;
+                }
+                if let Some(input) = self.chat_input.cast::<HtmlInputElement>() {
+                    let focused = web_sys::window()
+                        .and_then(|w| w.document())
+                        .and_then(|d| d.active_element())
+                        .map_or(false, |active| active == *input.unchecked_ref::<Element>());
+                    if focused && !input.value().is_empty() {
+                        input.set_value("");
+                        ctx.link().send_message(Msg::SaveDraft);
+                    }
+                }
+                false
+            }
+            Msg::Shortcut(Shortcut::EditMessage(id)) => {
+                ctx.link().send_message(Msg::StartEdit(id));
+                false
+            }
+            Msg::Shortcut(Shortcut::PreviousMessage(id)) => {
+                self.focus_neighbour(&id, -1);
+                false
+            }
+            Msg::Shortcut(Shortcut::NextMessage(id)) => {
+                self.focus_neighbour(&id, 1);
+                false
+            }
+            Msg::FocusMessage(id) => {
+                if self.tab_stop.as_ref() == Some(&id) {
+                    return false;
+                }
+                self.tab_stop = Some(id);
+                true
+            }
+            Msg::Shortcut(Shortcut::ShowHelp) => {
+                self.shortcut_help_open = true;
+                true
+            }
+            Msg::CloseShortcutHelp => {
+                self.shortcut_help_open = false;
+                true
+            }
+            Msg::StartEdit(id) => {
+                let (text, locked) = match self.channels.active().find(&id) {
+                    Some(m) if m.from == self.current_user && Transcript::is_final(m) => (m.message.clone(), m.locked),
+                    _ => return false,
+                };
+                if locked && !self.is_admin() {
+                    self.toast(ToastLevel::Info, &t!("edit.locked"));
+                    return false;
+                }
+                let input = match self.chat_input.cast::<HtmlInputElement>() {
+                    Some(input) => input,
+                    None => return false,
+                };
+                if self.editing.is_none() {
+                    let room = self.channels.active_name().to_string();
+                    self.draft_sync = None;
+                    self.store_draft(&room, &input.value());
+                }
+                input.set_value(&text);
+                self.focus_composer();
+                self.editing = Some(id);
+                true
+            }
+            Msg::CancelEdit => {
+                self.cancel_edit();
+                true
+            }
+            Msg::DownloadTranscript => {
+                let transcript = match &mut self.transcript {
+                    Some(transcript) => transcript,
+                    None => return false,
+                };
+                let offset = -(js_sys::Date::new_0().get_timezone_offset() as i32);
+                if let Some(finished) = transcript.roll_over(js_sys::Date::now(), offset) {
+                    if let Some(archive) = &self.archive {
+                        archive.save_transcript(&finished);
+                    }
+                }
+                download_text(
+                    &format!("transcript-{}.txt", transcript.day),
+                    ExportFormat::Text.mime(),
+                    &to_text(&transcript.messages, offset),
+                );
+                false
+            }
+            Msg::TranscriptLoaded(saved) => {
+                let (current, saved) = match (&mut self.transcript, saved) {
+                    (Some(current), Some(saved)) if saved.day == current.day => (current, saved),
+                    _ => return false,
+                };
+                // Whatever arrived while it was loading goes after what was saved.
+                let offset = -(js_sys::Date::new_0().get_timezone_offset() as i32);
+                let now = js_sys::Date::now();
+                let arrived = std::mem::replace(current, saved);
+                for message in &arrived.messages {
+                    current.append(message, now, offset);
+                }
+                false
+            }
+            Msg::ToggleSound => {
+                let mut settings = self.settings.clone();
+                settings.sound_enabled = !settings.sound_enabled;
+                // Unmuting right here, in the click, lets the audio context start out unlocked.
+                self.audio.set_muted(!settings.sound_enabled);
+                ctx.link().send_message(Msg::UpdateSettings(Box::new(settings)));
+                false
+            }
+            Msg::SearchTimedOut => match &mut self.search {
+                Some(search) if search.hits.is_none() => {
+                    search.failed = true;
+                    search.timeout = None;
+                    true
+                }
+                _ => false,
+            },
+            Msg::CloseSearch => {
+                self.search = None;
+                true
+            }
+            Msg::JumpToDate(value) => {
+                let offset = -(js_sys::Date::new_0().get_timezone_offset() as i32);
+                let from = match parse_day(&value, js_sys::Date::now(), offset) {
+                    Some(from) => from,
+                    None => return false,
+                };
+                let channel = self.channels.active_name().to_string();
+                self.showing_mentions = false;
+                if self.channels.active().covers(from) {
+                    self.channels.close_window(&channel);
+                    self.pending_scroll = Some(ScrollTarget::Day(day_key(from, offset)));
+                    return true;
+                }
+                self.open_day(ctx, channel, from);
+                true
+            }
+            Msg::DayArchived(channel, from, messages) => self.fill_day(ctx, &channel, from, WindowSource::Archive, messages),
+            Msg::DayTimedOut(channel, from) => {
+                self.day_timeout = None;
+                let fill = self.channels.expire_window(&channel, from);
+                self.show_day(ctx, &channel, fill)
+            }
+            Msg::LinkArchived(channel, sent) => {
+                let id = match self.pending_link.take() {
+                    Some((id, LinkSearch::Archive)) => id,
+                    other => {
+                        self.pending_link = other;
+                        return false;
+                    }
+                };
+                let offset = -(js_sys::Date::new_0().get_timezone_offset() as i32);
+                let from = sent.and_then(|sent| parse_day(&day_key(sent, offset), sent, offset));
+                match from {
+                    // The live messages reach back that far, and it isn't among them.
+                    Some(from) if matches!(self.channels.get(&channel), Some(c) if c.covers(from)) => {
+                        self.toast(ToastLevel::Info, &t!("messages.unavailable"));
+                        false
+                    }
+                    // Opening it pulls in the rest of that day from the server too.
+                    Some(from) => {
+                        self.pending_link = Some((id, LinkSearch::Day));
+                        self.open_day(ctx, channel, from);
+                        true
+                    }
+                    None => {
+                        self.follow_link(ctx, channel, id, LinkSearch::Archive);
+                        false
+                    }
+                }
+            }
+            Msg::BackToLatest => {
+                let channel = self.channels.active_name().to_string();
+                self.channels.close_window(&channel);
+                self.pending_scroll = Some(ScrollTarget::Bottom);
+                true
+            }
+            Msg::OpenLocalSearch => {
+                if self.local_search.is_some() {
+                    return false;
+                }
+                self.local_search = Some(LocalSearch {
+                    input: String::new(),
+                    query: SearchQuery::default(),
+                    error: None,
+                    results: self.recent_mentions(),
+                });
+                true
+            }
+            Msg::CloseLocalSearch => {
+                self.local_search = None;
+                true
+            }
+            Msg::LocalSearch(input) => {
+                let offset = -(js_sys::Date::new_0().get_timezone_offset() as i32);
+                let query = match SearchQuery::parse(&input, js_sys::Date::now(), offset) {
+                    Ok(query) => query,
+                    Err(error) => {
+                        if let Some(search) = &mut self.local_search {
+                            search.error = Some(error);
+                        }
+                        return true;
+                    }
+                };
+                let results = if query.is_empty() {
+                    self.recent_mentions()
+                } else {
+                    let messages = self
+                        .channels
+                        .channels()
+                        .iter()
+                        .flat_map(|c| c.messages.iter().map(move |m| (c.name.as_str(), m)));
+                    let scope = self.settings.search_scope;
+                    let active = self.channels.active_name().to_string();
+                    let results = search_messages(messages, &query, scope, &active);
+                    if let Some(archive) = self.archive.clone() {
+                        let query = query.clone();
+                        ctx.link().send_future(async move {
+                            let keep = {
+                                let query = query.clone();
+                                move |channel: &str, m: &MessageData| query.matches_in(scope, &active, channel, m)
+                            };
+                            let mut archived = archive.search(keep, MAX_RESULTS + 1).await.unwrap_or_else(|e| {
+                                log::error!("failed to search the message archive: {:?}", e);
+                                vec![]
+                            });
+                            for m in &mut archived {
+                                m.channel.get_or_insert_with(|| DEFAULT_CHANNEL.to_string());
+                            }
+                            Msg::LocalSearchArchived(query, archived)
+                        });
+                    }
+                    results
+                };
+                self.local_search = Some(LocalSearch {
+                    input,
+                    query,
+                    error: None,
+                    results,
+                });
+                true
+            }
+            Msg::SetSearchScope(scope) => {
+                let mut settings = self.settings.clone();
+                settings.search_scope = scope;
+                ctx.link().send_message(Msg::UpdateSettings(Box::new(settings)));
+                if let Some(search) = &self.local_search {
+                    ctx.link().send_message(Msg::LocalSearch(search.input.clone()));
+                }
+                false
+            }
+            Msg::LocalSearchArchived(query, archived) => match &mut self.local_search {
+                Some(search) if search.query == query => {
+                    search.results = merge_results(std::mem::take(&mut search.results), archived);
+                    true
+                }
+                _ => false,
+            },
+            Msg::RefreshDnd => {
+                let now = Now::local();
+                if let Some(Snooze::Until(end)) = self.settings.dnd.snooze {
+                    if now.epoch_ms >= end {
+                        self.settings.dnd.snooze = None;
+                        self.settings.save(&self.current_user);
+                    }
+                }
+                let active = self.settings.dnd.is_active(now);
+                let changed = active != self.dnd_active;
+                self.dnd_active = active;
+                changed
+            }
+            Msg::SettingsRejected(reason) => {
+                self.toast(ToastLevel::Error, &reason);
+                false
+            }
+            Msg::UpdateSettings(settings) => {
+                self.audio.set_muted(!settings.sound_enabled);
+                if settings.theme != self.settings.theme {
+                    self._theme_watch = theme::apply(settings.theme);
+                }
+                display::apply_font_size(settings.font_size);
+                i18n::set_locale(settings.locale.unwrap_or_else(Locale::from_browser));
+                if settings.avatar_template != self.settings.avatar_template {
+                    // Everyone still on the old template's picture moves to the new one;
+                    // avatars people set themselves stay.
+                    let templated: Vec<bool> = self.users.iter().map(|u| u.avatar == avatar_for(&u.name)).collect();
+                    avatar::set_template(settings.avatar_template.as_deref());
+                    for (user, templated) in self.users.iter_mut().zip(templated) {
+                        if templated {
+                            user.avatar = avatar_for(&user.name);
+                        }
+                    }
+                }
+                if settings.save_transcript != self.transcript.is_some() {
+                    if settings.save_transcript {
+                        let offset = -(js_sys::Date::new_0().get_timezone_offset() as i32);
+                        self.transcript = Some(Transcript::starting(js_sys::Date::now(), offset));
+                        self.load_transcript(ctx);
+                    } else {
+                        self.transcript = None;
                     }
+                }
+                settings.save(&self.current_user);
+                self.settings = *settings;
+                self.dnd_active = self.settings.dnd.is_active(Now::local());
+                true
+            }
+        }
+    }
+}
+
+impl Component for Chat {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let (user, user_handle) = ctx
+            .link()
+            .context::<UserCtx>(ctx.link().callback(Msg::UserChanged))
+            .expect("context to be set");
+        let (flags, flags_handle) = ctx
+            .link()
+            .context::<FeatureFlags>(ctx.link().callback(Msg::FlagsChanged))
+            .map(|(flags, handle)| (flags, Some(handle)))
+            .unwrap_or_default();
+
+        let wss = Self::connect(ctx, &user, 0);
+        let username = user.username.clone();
+        let presence = PresenceService::new();
+
+        let settings = UserSettings::load(&username);
+        let save_transcript = settings.save_transcript;
+        let mut channels = ChannelStore::new(&CHANNELS);
+        // A link to a particular room, e.g. one the login page sent back here, beats the default.
+        let linked = ctx
+            .link()
+            .location()
+            .and_then(|location| location.query::<ChatQuery>().ok())
+            .unwrap_or(ChatQuery { channel: None, message: None });
+        let message_cache = MessageCache::new(&username);
+        channels.restore(message_cache.load());
+        // Anyone can hand out a link, so only a well-formed room name or a channel this
+        // browser already knows gets opened from one.
+        let linked_channel = linked
+            .channel
+            .filter(|name| validate_room_name(name).as_ref() == Ok(name) || channels.get(name).is_some());
+        channels.switch_to(linked_channel.as_deref().unwrap_or(&settings.default_channel), None);
+        let scroll_memory = ScrollMemory::load(&username);
+        let pending_scroll = resume_reading(&mut channels, &scroll_memory);
+        let audio = AudioService::new(!settings.sound_enabled);
+        let chat_input = NodeRef::default();
+        let _theme_watch = theme::apply(settings.theme);
+        display::apply_font_size(settings.font_size);
+        i18n::set_locale(settings.locale.unwrap_or_else(Locale::from_browser));
+        avatar::set_template(settings.avatar_template.as_deref());
+
+        let mut chat = Chat {
+            users: vec![],
+            chat_input: chat_input.clone(),
+            message_list: NodeRef::default(),
+            history_sentinel: NodeRef::default(),
+            sentinel: ScrollSentinel::new(ctx.link().callback(|_| Msg::LoadOlder)),
+            wss,
+            channels,
+            pending_scroll: Some(pending_scroll),
+            scroll_memory,
+            scroll_save: None,
+            settings,
+            server_stats: None,
+            quota: None,
+            mod_log: vec![],
+            admin_tab: AdminTab::Stats,
+            policy_notice_open: false,
+            upload_confirm: None,
+            announcement: String::new(),
+            drawer_open: false,
+            sidebar_visible: true,
+            connection: ConnectionState::Connecting { failures: 0 },
+            socket: 0,
+            reconnect_timer: None,
+            _network_listeners: {
+                let window = web_sys::window().unwrap();
+                let online = ctx.link().callback(|_| Msg::Connection(ConnectionEvent::BrowserOnline));
+                let offline = ctx.link().callback(|_| Msg::Connection(ConnectionEvent::BrowserOffline));
+                [
+                    EventListener::new(&window, "online", move |_| online.emit(())),
+                    EventListener::new(&window, "offline", move |_| offline.emit(())),
+                ]
+            },
+            export_open: false,
+            stats: None,
+            profile_open: None,
+            message_menu: None,
+            open_thread: None,
+            pending_link: linked.message.filter(|_| linked_channel.is_some()).map(|id| (id, LinkSearch::Latest)),
+            user_notes: HashMap::new(),
+            message_counts: RefCell::new(None),
+            clear_data_open: false,
+            settings_open: false,
+            pin_dialog: None,
+            channel_browser_open: false,
+            channel_list: None,
+            creating_room: None,
+            creating_room_timeout: None,
+            channel_list_supported: true,
+            channel_list_timeout: None,
+            day_timeout: None,
+            history_timeouts: HashMap::new(),
+            user_filter: String::new(),
+            recorder_open: false,
+            read_only: false,
+            joined_as_guest: user.is_guest,
+            name_remembered: false,
+            request_access_open: false,
+            clip_source: ClipSource::Screen,
+            recorder: None,
+            recording_timeout: None,
+            files: ChunkAssembler::default(),
+            lightbox_image: None,
+            locating: false,
+            permission_prompt: PermissionPrompt::load(),
+            has_sent_message: false,
+            seen_welcomes: SeenWelcomes::load(&username),
+            drafts: Drafts::load(&username),
+            draft_room: String::new(),
+            draft_sync: None,
+            audio,
+            _theme_watch,
+            ephemeral_timers: HashMap::new(),
+            long_press: None,
+            keyboard_inset: 0.0,
+            _keyboard_watch: KeyboardWatch::attach(ctx.link().callback(Msg::KeyboardInset)),
+            prefers_reduced_motion: display::prefers_reduced_motion(),
+            checkpoint_id: String::new(),
+            sent_checkpoint_id: String::new(),
+            since_checkpoint: 0,
+            _checkpoint_timer: {
+                let link = ctx.link().clone();
+                Interval::new(CHECKPOINT_INTERVAL_MS, move || link.send_message(Msg::SendCheckpoint))
+            },
+            _visibility_listener: {
+                let link = ctx.link().clone();
+                let document = web_sys::window().unwrap().document().unwrap();
+                EventListener::new(&document, "visibilitychange", move |_| {
+                    link.send_message(Msg::VisibilityChanged)
+                })
+            },
+            title: TitleBadge::new(),
+            favicon: FaviconBadge::new(),
+            dnd_active: false,
+            _dnd_timer: {
+                let link = ctx.link().clone();
+                Interval::new(30_000, move || link.send_message(Msg::RefreshDnd))
+            },
+            showing_mentions: false,
+            highlighted: None,
+            tab_stop: None,
+            revealed_spam: HashSet::new(),
+            highlight_timeout: None,
+            search: None,
+            local_search: None,
+            _shortcuts: Shortcuts::attach(chat_input, ctx.link().callback(Msg::Shortcut)),
+            shortcut_help_open: false,
+            editing: None,
+            uploads: Vec::new(),
+            next_upload: 0,
+            archived_open: false,
+            user_menu: None,
+            nudges_sent: HashMap::new(),
+            nudged_by: None,
+            nudge_timeout: None,
+            typing: TypingTracker::default(),
+            shutdown: None,
+            shutdown_timer: None,
+            broadcasts: Vec::new(),
+            message_cache,
+            cache_save: None,
+            archive: None,
+            transcript: save_transcript.then(|| Transcript::starting(js_sys::Date::now(), -(js_sys::Date::new_0().get_timezone_offset() as i32))),
+            presence,
+            _typing_prune: {
+                let link = ctx.link().clone();
+                Interval::new(1_000, move || link.send_message(Msg::PruneTyping))
+            },
+            _producer: EventBus::bridge(ctx.link().callback(Msg::HandleMsg)),
+            toasts: ToastBus::dispatcher(),
+            flags,
+            _flags_handle: flags_handle,
+            _user_handle: user_handle,
+            current_user: username,
+        };
+        chat.dnd_active = chat.settings.dnd.is_active(Now::local());
+        chat.request_members();
+        chat.request_history(ctx);
+        if !web_sys::window().unwrap().navigator().on_line() {
+            ctx.link().send_message(Msg::Connection(ConnectionEvent::BrowserOffline));
+        }
+        let username = chat.current_user.clone();
+        ctx.link().send_future(async move {
+            match MessageArchive::open(&username).await {
+                Ok(archive) => Msg::ArchiveOpened(Some(archive)),
+                Err(e) => {
+                    log::error!("failed to open the message archive: {:?}", e);
+                    Msg::ArchiveOpened(None)
+                }
+            }
+        });
+        chat
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        if msg.is_user_initiated() {
+            self.presence.notify_activity();
+        }
+        let refocus = msg.refocuses_composer();
+        let drawer_open = msg.drawer_after(self.drawer_open);
+        let drawer_moved = drawer_open != self.drawer_open;
+        self.drawer_open = drawer_open;
+        let render = self.handle(ctx, msg) || drawer_moved;
+        if refocus {
+            self.focus_composer();
+        }
+        // Whatever changed the stored messages, be it a frame or something done here, gets
+        // written out with the next batch.
+        if self.channels.has_unsaved() && self.cache_save.is_none() {
+            let link = ctx.link().clone();
+            self.cache_save = Some(Timeout::new(CACHE_SAVE_DEBOUNCE_MS, move || link.send_message(Msg::SaveCache)));
+        }
+        render
+    }
+
+    fn destroy(&mut self, _ctx: &Context<Self>) {
+        self.presence.stop();
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        self.schedule_ephemeral_ticks(ctx);
+        self.swap_draft();
+        // A message that isn't rendered any more sends the view to the bottom instead.
+        let placed = match self.pending_scroll.take() {
+            Some(ScrollTarget::Message(id)) => self.scroll_to_message(&id),
+            Some(ScrollTarget::Position(position)) => self.scroll_to_position(&position),
+            Some(ScrollTarget::Bottom) => false,
+            Some(ScrollTarget::KeepPosition { scroll_height, scroll_top }) => {
+                if let Some(list) = self.message_list.cast::<Element>() {
+                    list.set_scroll_top(preserved_scroll_top(scroll_height, scroll_top, list.scroll_height()));
+                }
+                true
+            }
+            Some(ScrollTarget::Day(day)) => {
+                self.scroll_to_day(&day);
+                true
+            }
+            None => true,
+        };
+        if !placed {
+            self.scroll_to_bottom();
+        }
+        if let Some(sentinel) = &mut self.sentinel {
+            sentinel.watch(self.history_sentinel.cast::<Element>());
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        self.message_counts.replace(None);
+        let submit = ctx.link().callback(|_| Msg::SubmitMessage);
+        let cooldown = self.channels.active().cooldown_remaining(js_sys::Date::now());
+        // Nothing can be sent as a guest, or with no connection to send it on.
+        let locked = self.read_only || self.connection.is_offline();
+        let react = ctx.link().callback(|(id, emoji): (String, String)| Msg::React(id, emoji));
+        let set_skin_tone = {
+            let settings = self.settings.clone();
+            ctx.link().callback(move |skin_tone| Msg::UpdateSettings(Box::new(UserSettings { skin_tone, ..settings.clone() })))
+        };
+        let utc_offset = -(js_sys::Date::new_0().get_timezone_offset() as i32);
+        let compact = self.settings.density == Density::Compact;
+        let tab_stop = tab_stop(&self.shown_message_ids(), self.tab_stop.as_deref()).map(str::to_string);
+        let reply_counts = self.channels.active().reply_counts();
+
+        html! {
+            // Components that translate subscribe to this, so switching language re-renders
+            // them in place instead of rebuilding the tree.
+            <ContextProvider<Locale> context={i18n::locale()}>
+            <div class="flex w-screen">
+                if self.drawer_open {
+                    <div onclick={ctx.link().callback(|_| Msg::SetDrawer(false))} class="md:hidden fixed inset-0 z-20 bg-black bg-opacity-50"></div>
+                }
+                <div class={if self.drawer_open { "fixed inset-y-0 left-0 z-30 flex md:static md:z-auto" } else { "hidden md:flex" }}>
+                    { self.view_channel_rail(ctx) }
+                    <div class="relative flex">
+                        <div class={classes!(
+                            "h-screen", "bg-gray-100", "dark:bg-gray-700",
+                            (!self.prefers_reduced_motion).then_some("transition-[width] duration-300"),
+                            // Inside the drawer the list always shows; collapsing it is for wide screens.
+                            if self.sidebar_visible { "w-56 overflow-auto" } else { "w-56 overflow-auto md:w-0 md:overflow-hidden" },
+                        )}>
+                            <div class="text-xl p-3 font-semibold">{ t!("sidebar.users") }</div>
+                            <input
+                                oninput={ctx.link().callback(|e: InputEvent| Msg::FilterUsers(e.target_unchecked_into::<HtmlInputElement>().value()))}
+                                value={self.user_filter.clone()}
+                                placeholder={t!("sidebar.filter_users")}
+                                class="mx-3 w-48 rounded-full bg-white dark:bg-gray-800 px-3 py-1 text-sm focus:outline-none focus-ring"
+                            />
+                            { self.view_users(ctx) }
+                            { self.view_admin_panel(ctx) }
+                        </div>
+                        <button
+                            onclick={ctx.link().callback(|_| Msg::ToggleSidebar)}
+                            title={if self.sidebar_visible { t!("sidebar.hide_users") } else { t!("sidebar.show_users") }}
+                            class="hidden md:block absolute top-1/2 -right-3 z-10 w-6 h-6 rounded-full border bg-white dark:bg-gray-800 text-xs text-gray-500 shadow hover:text-gray-800 focus-ring"
+                        >
+                            { if self.sidebar_visible { "<" } else { ">" } }
+                        </button>
+                    </div>
                 </div>
-                <div class="flex-1 flex flex-col h-screen">
-                    <div class="h-14 border-b p-3 text-xl font-semibold">{"💬 Chat!"}</div>
-                    <div class="flex-1 overflow-auto border-b p-4 space-y-4">
-                        {
-                            self.messages.iter().enumerate().map(|(i, m)| {
-                                let fallback = UserProfile {
-                                    name: m.from.clone(),
-                                    avatar: format!("https://avatars.dicebear.com/api/adventurer-neutral/{}.svg", m.from),
-                                };
-                                let user_ref: &UserProfile = self.users.iter().find(|u| u.name == m.from).unwrap_or(&fallback);
-                                html! {
-                                    <div class="flex items-start space-x-3 bg-gray-100 p-3 rounded-xl max-w-lg">
-                                        <img class="w-10 h-10 rounded-full" src={user_ref.avatar.clone()} alt="avatar"/>
-                                        <div>
-                                            <div class="text-sm font-medium">{ &m.from }</div>
-                                            <div class="text-base">{ &m.message }</div>
-                                            <div class="mt-2 flex flex-wrap gap-1">
-                                                {
-                                                    emojis.iter().map(|&emoji| {
-                                                        let count = m.reactions.as_ref()
-                                                            .and_then(|rs| rs.iter().find(|(e, _)| e == emoji))
-                                                            .map(|(_, users)| users.len())
-                                                            .unwrap_or(0);
-                                                        let emoji_cb = emoji.to_string();
-                                                        let react_cb = react.clone();
-                                                        let onclick = Callback::from(move |_| react_cb.emit((i, emoji_cb.clone())));
-                                                        html! {
-                                                            <button {onclick} class="flex items-center bg-white px-2 py-1 text-sm rounded-full border hover:bg-gray-200 transition">
-                                                                <span>{ emoji }</span>
-                                                                {
-                                                                    if count > 0 {
-                                                                        html! { <span class="ml-1 text-xs font-semibold">{ count }</span> }
-                                                                    } else {
-                                                                        html! {}
-                                                                    }
-                                                                }
-                                                            </button>
+                // Padding the column by the keyboard's height keeps the composer above it.
+                <div
+                    style={(self.keyboard_inset > 0.0).then(|| format!("padding-bottom: {}px", self.keyboard_inset))}
+                    class="flex-1 w-full min-w-0 flex flex-col h-screen pb-14 md:pb-0"
+                >
+                    <AppBar
+                        title={if self.channels.active().is_dm() {
+                            self.channels.active_name().to_string()
+                        } else {
+                            format!("# {}", self.channels.active_name())
+                        }}
+                        user_count={self.users.len()}
+                        connected={self.connection.is_live()}
+                        on_menu={ctx.link().callback(|_| Msg::SetDrawer(true))}
+                        on_mentions={ctx.link().callback(|_| Msg::ShowMentions)}
+                        on_channels={ctx.link().callback(|_| Msg::OpenChannelBrowser)}
+                        on_export={ctx.link().callback(|_| Msg::OpenExport)}
+                        on_stats={ctx.link().callback(|_| Msg::OpenStats)}
+                        on_settings={ctx.link().callback(|_| Msg::OpenSettings)}
+                        on_logout={ctx.link().callback(|_| Msg::Logout)}
+                        on_install={ctx.link().callback(|_| Msg::InstallApp)}
+                    >
+                        <button
+                            onclick={ctx.link().callback(|_| Msg::CycleRoomPreference)}
+                            title={self.settings.notifications.get(self.channels.active_name()).label()}
+                            class="ml-2 w-8 h-8 rounded-full hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring"
+                        >
+                            { self.settings.notifications.get(self.channels.active_name()).icon() }
+                        </button>
+                        if self.channels.active().slow_mode_seconds > 0 {
+                            <span class="ml-2 px-2 py-0.5 rounded-full bg-yellow-100 text-yellow-800 text-xs">
+                                { t!("app_bar.slow_mode", seconds = self.channels.active().slow_mode_seconds) }
+                            </span>
+                        }
+                        <button
+                            onclick={ctx.link().callback(|_| Msg::ToggleSound)}
+                            title={if self.settings.sound_enabled { t!("app_bar.sound_on") } else { t!("app_bar.sound_off") }}
+                            class="ml-1 w-8 h-8 rounded-full hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring"
+                        >
+                            { if self.settings.sound_enabled { "🔊" } else { "🔇" } }
+                        </button>
+                        <button
+                            onclick={ctx.link().callback(|_| Msg::CycleNotifyMode)}
+                            title={if self.dnd_active { t!("app_bar.dnd_on") } else { t!("app_bar.notify_all_channels") }}
+                            class="ml-auto flex items-center px-3 h-8 rounded-full text-sm hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring"
+                        >
+                            <span class="mr-1">{ if self.dnd_active { "🌙" } else { self.settings.notifications.global().icon() } }</span>
+                            { self.settings.notifications.global().label() }
+                        </button>
+                    </AppBar>
+                    if let Some(shutdown) = &self.shutdown {
+                        <ShutdownBanner shutdown_at={shutdown.shutdown_at} reason={shutdown.reason.clone()}/>
+                    }
+                    { self.view_broadcast_banner(ctx) }
+                    { self.view_permission_prompt(ctx) }
+                    if self.showing_mentions {
+                        { self.view_mentions(ctx) }
+                    } else {
+                        <div class="relative flex-1 flex flex-col min-h-0">
+                            { self.view_pins(ctx) }
+                            if let Some(window) = &self.channels.active().window {
+                                <div class="flex items-center px-4 py-2 border-b bg-blue-50 text-sm text-blue-900">
+                                    <span class="flex-1">{ t!("messages.viewing_day", day = window.day) }</span>
+                                    <button onclick={ctx.link().callback(|_| Msg::BackToLatest)} class="px-3 py-1 rounded-full bg-blue-600 text-white text-xs focus-ring">
+                                        { t!("messages.back_to_latest") }
+                                    </button>
+                                </div>
+                            }
+                            <div aria-live="polite" aria-atomic="true" class="sr-only">{ &self.announcement }</div>
+                            <div
+                                ref={self.message_list.clone()}
+                                onscroll={ctx.link().callback(|_| Msg::MessageListScrolled)}
+                                role="log"
+                                aria-live="off"
+                                aria-label={t!("messages.label", channel = self.channels.active_name())}
+                                class={classes!(
+                                "relative", "flex-1", "overflow-auto", "border-b",
+                                self.settings.density.list_classes(),
+                            )}>
+                                <div ref={self.history_sentinel.clone()} class="h-px"></div>
+                                if self.channels.active().history == HistoryState::LoadingOlder {
+                                    <div class="flex justify-center" title={t!("messages.loading_earlier")}>
+                                        <div class="w-4 h-4 border-2 border-blue-600 border-t-transparent rounded-full animate-spin"></div>
+                                    </div>
+                                }
+                                if let Some(welcome) = &self.channels.active().welcome {
+                                    <div class="mx-auto max-w-lg p-3 rounded-xl bg-blue-50 border border-blue-200 text-sm text-blue-900 text-center">
+                                        <span class="mr-1">{"👋"}</span>{ welcome }
+                                    </div>
+                                }
+                                if let Some(window) = &self.channels.active().window {
+                                    if window.messages.is_empty() {
+                                        if window.pending > 0 {
+                                            <EmptyState variant={EmptyStateVariant::Loading}/>
+                                        } else if window.timed_out {
+                                            <EmptyState variant={EmptyStateVariant::NoMessages} text={t!("messages.day_failed")}/>
+                                        } else {
+                                            <EmptyState variant={EmptyStateVariant::NoMessages} text={t!("messages.day_empty")}/>
+                                        }
+                                    }
+                                } else if self.channels.active().messages.is_empty() {
+                                    if self.channels.active().history == HistoryState::Loading {
+                                        <EmptyState variant={EmptyStateVariant::Loading}/>
+                                    } else {
+                                        <EmptyState variant={EmptyStateVariant::NoMessages}/>
+                                    }
+                                }
+                                {
+                                    // Replies stay in their thread.
+                                    self.channels.active().visible().iter().enumerate().filter(|(_, m)| m.thread_id.is_none()).map(|(i, m)| {
+                                        let fallback = UserProfile {
+                                            name: m.from.clone(),
+                                            avatar: avatar_for(&m.from),
+                                        };
+                                        let user_ref: &UserProfile = self.users.iter().find(|u| u.name == m.from).unwrap_or(&fallback);
+                                        let spam = spam_level(m.spam_score);
+                                        // Keyed by id, so rows keep their own state when older history is
+                                        // prepended or the trim drops earlier ones.
+                                        if spam == SpamLevel::Hidden && !self.is_admin() {
+                                            return html! { <key={m.id.clone()}>{ self.view_day_separator(i, m, utc_offset) }</> };
+                                        }
+                                        let collapsed = spam != SpamLevel::Clean && !self.revealed_spam.contains(&m.id);
+                                        let on_pointer = {
+                                            let id = m.id.clone();
+                                            let linkable = Transcript::is_final(m);
+                                            ctx.link().batch_callback(move |e: PointerEvent| {
+                                                let at = (e.client_x(), e.client_y());
+                                                touch_msg(&e.type_(), &e.pointer_type(), &id, linkable, at)
+                                            })
+                                        };
+                                        html! {
+                                            <key={m.id.clone()}>
+                                            if self.channels.active().history_start.as_ref() == Some(&m.id) {
+                                                <div class="text-center text-xs text-gray-400">{ t!("messages.earlier") }</div>
+                                            }
+                                            { self.view_day_separator(i, m, utc_offset) }
+                                            <div
+                                                data-message-id={m.id.clone()}
+                                                tabindex={if tab_stop.as_ref() == Some(&m.id) { "0" } else { "-1" }}
+                                                onfocus={{
+                                                    let id = m.id.clone();
+                                                    ctx.link().callback(move |_| Msg::FocusMessage(id.clone()))
+                                                }}
+                                                role="article"
+                                                aria-label={message_label(m)}
+                                                oncontextmenu={{
+                                                    let id = m.id.clone();
+                                                    // Messages not yet confirmed have no id anyone else could open.
+                                                    let linkable = Transcript::is_final(m);
+                                                    ctx.link().batch_callback(move |e: MouseEvent| {
+                                                        if !linkable {
+                                                            return None;
                                                         }
-                                                    }).collect::<Html>()
+                                                        e.prevent_default();
+                                                        Some(Msg::OpenMessageMenu(id.clone(), e.client_x(), e.client_y()))
+                                                    })
+                                                }}
+                                                onpointerdown={on_pointer.clone()}
+                                                onpointerup={on_pointer.clone()}
+                                                onpointermove={on_pointer.clone()}
+                                                onpointercancel={on_pointer.clone()}
+                                                // iOS would otherwise pop its own callout over the menu.
+                                                style="-webkit-touch-callout: none"
+                                                class={classes!(
+                                                "focus-ring", "flex", "items-start", "bg-gray-100", "dark:bg-gray-700", "max-w-lg",
+                                                (!self.prefers_reduced_motion).then_some("transition"),
+                                                self.settings.density.row_classes(),
+                                                (self.highlighted.as_ref() == Some(&m.id)).then_some("ring-2 ring-yellow-400"),
+                                                m.cached.then_some("opacity-75"),
+                                                m.locked.then_some("outline outline-1 outline-gray-400"),
+                                                (!self.settings.hide_sentiment).then(|| sentiment_border(m.sentiment)).flatten(),
+                                            )}>
+                                                if !compact {
+                                                    <img class="w-10 h-10 rounded-full" src={user_ref.avatar.clone()} alt=""/>
                                                 }
+                                                <div>
+                                                    <div class="flex items-center text-sm font-medium">
+                                                        if compact {
+                                                            <img class="w-5 h-5 mr-1 rounded-full" src={user_ref.avatar.clone()} alt=""/>
+                                                        }
+                                                        { &m.from }
+                                                        if let Some(left) = m.seconds_left(js_sys::Date::now()) {
+                                                            <span title={t!("ephemeral.title")} class="ml-2 text-xs font-normal text-gray-400">
+                                                                { format!("⏳ {}", t!("ephemeral.remaining", seconds = left)) }
+                                                            </span>
+                                                        }
+                                                    </div>
+                                                    { self.view_spam_badge(ctx, m) }
+                                                    if collapsed {
+                                                        <button
+                                                            onclick={{
+                                                                let id = m.id.clone();
+                                                                ctx.link().callback(move |_| Msg::RevealSpam(id.clone()))
+                                                            }}
+                                                            class="mt-1 text-xs text-blue-600 hover:underline focus-ring"
+                                                        >
+                                                            { t!("spam.show_anyway") }
+                                                        </button>
+                                                    } else {
+                                                        {
+                                                            match parse_map_link(&m.message) {
+                                                                Some((lat, lng)) => html! {
+                                                                    <div class="mt-1 w-72 bg-white dark:bg-gray-800 rounded-lg overflow-hidden border">
+                                                                        <img class="w-72 h-48 object-cover" src={static_map_url(lat, lng)} alt={t!("map.alt")}/>
+                                                                        <a href={m.message.clone()} target="_blank" rel="noopener" class="block p-2 text-center text-sm text-blue-600 hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring">{ t!("map.view") }</a>
+                                                                    </div>
+                                                                },
+                                                                None => html! { <div class="text-base">{ self.view_text(ctx, &m.message, m.from == self.current_user) }</div> },
+                                                            }
+                                                        }
+                                                        { self.view_edit_label(ctx, m) }
+                                                        {
+                                                            match m.video_clip.as_ref().map(|id| self.files.get(id)) {
+                                                                Some(Some(url)) => html! {
+                                                                    <VideoPlayer src={url.clone()} name={t!("status.video_clip")}/>
+                                                                },
+                                                                Some(None) => html! {
+                                                                    <div class="mt-2 text-xs text-gray-400">{ t!("video.receiving", name = t!("status.video_clip")) }</div>
+                                                                },
+                                                                None => html! {},
+                                                            }
+                                                        }
+                                                        { m.attachments.iter().map(|a| self.view_attachment(ctx, m, a)).collect::<Html>() }
+                                                        { self.view_like(ctx, m) }
+                                                        { self.view_pin_button(ctx, m) }
+                                                        { self.view_thread_link(ctx, m, reply_counts.get(m.id.as_str()).copied().unwrap_or(0)) }
+                                                        if self.flags.reactions {
+                                                            <ReactionBar
+                                                                reactions={m.reactions.clone().unwrap_or_default()}
+                                                                current_user={self.current_user.clone()}
+                                                                read_only={self.read_only}
+                                                                locked={m.locked}
+                                                                reaction_cap={self.channels.active().reaction_cap}
+                                                                reduced_motion={self.prefers_reduced_motion}
+                                                                skin_tone={self.settings.skin_tone}
+                                                                on_skin_tone={set_skin_tone.clone()}
+                                                                on_react={{
+                                                                    let id = m.id.clone();
+                                                                    react.reform(move |emoji| (id.clone(), emoji))
+                                                                }}
+                                                            />
+                                                        }
+                                                    }
+                                                </div>
                                             </div>
-                                        </div>
+                                            </>
+                                        }
+                                    }).collect::<Html>()
+                                }
+                                if matches!(&self.channels.active().window, Some(window) if window.truncated) {
+                                    <div class="mx-auto my-2 px-3 py-1 rounded-full bg-gray-100 dark:bg-gray-700 text-xs text-gray-500">
+                                        { t!("messages.day_truncated") }
                                     </div>
                                 }
-                            }).collect::<Html>()
+                            </div>
+                            if let Some(search) = &self.search {
+                                <SearchResultsPanel
+                                    query={search.query.clone()}
+                                    room={search.room.clone()}
+                                    hits={search.hits.clone()}
+                                    failed={search.failed}
+                                    on_jump={ctx.link().callback(|(channel, id)| Msg::JumpToMessage { channel, id })}
+                                    on_close={ctx.link().callback(|_| Msg::CloseSearch)}
+                                />
+                            }
+                            if let Some(root) = self.open_thread.as_ref().and_then(|id| self.channels.active().find(id)) {
+                                <ThreadPanel
+                                    root={root.clone()}
+                                    replies={self.channels.active().thread(&root.id).into_iter().cloned().collect::<Vec<_>>()}
+                                    typing={typing_label(&self.typing.in_thread(&root.id))}
+                                    {locked}
+                                    on_typing={ctx.link().callback(|_| Msg::ThreadTyping)}
+                                    on_send={ctx.link().callback(Msg::SendThreadReply)}
+                                    on_close={ctx.link().callback(|_| Msg::CloseThread)}
+                                />
+                            }
+                        </div>
+                    }
+                    {
+                        match typing_label(&self.typing.in_room(self.channels.active_name())) {
+                            Some(label) => html! { <div class="px-4 pt-1 text-xs text-gray-400">{ label }</div> },
+                            None => html! {},
                         }
-                    </div>
-                    <div class="h-16 flex items-center p-4">
+                    }
+                    { self.view_recorder(ctx) }
+                    <StatusBar
+                        status={Status::pick(self.editing.is_some(), self.uploads.last().map(|(_, name)| name.as_str()))}
+                        on_cancel_edit={ctx.link().callback(|_| Msg::CancelEdit)}
+                        on_cancel_upload={ctx.link().callback(|_| Msg::CancelUpload)}
+                    />
+                    if cooldown.is_some() {
+                        <CooldownBar
+                            until={self.channels.active().cooldown_until}
+                            seconds={self.channels.active().slow_mode_seconds}
+                            on_done={ctx.link().callback(|_| Msg::CooldownOver)}
+                        />
+                    }
+                    if self.read_only {
+                        <div class="flex items-center mx-4 mt-2 px-3 py-2 rounded-lg bg-gray-100 dark:bg-gray-700 text-sm text-gray-600 dark:text-gray-300">
+                            <span class="flex-1">{ t!("banner.read_only") }</span>
+                            <button onclick={ctx.link().callback(|_| Msg::OpenRequestAccess)} class="px-3 py-1 rounded-full bg-blue-600 text-white focus-ring">
+                                { t!("banner.request_access") }
+                            </button>
+                        </div>
+                    }
+                    if self.connection == ConnectionState::Offline {
+                        <div class="flex items-center mx-4 mt-2 px-3 py-2 rounded-lg bg-gray-100 dark:bg-gray-700 text-sm text-gray-600 dark:text-gray-300">
+                            <span class="flex-1">{ t!("composer.offline") }</span>
+                            <button onclick={ctx.link().callback(|_| Msg::Connection(ConnectionEvent::Retry))} class="px-3 py-1 rounded-full bg-blue-600 text-white focus-ring">
+                                { t!("common.retry") }
+                            </button>
+                        </div>
+                    }
+                    <div class="h-16 flex items-center px-2 py-4 md:px-4">
                         <input
                             ref={self.chat_input.clone()}
+                            oninput={ctx.link().callback(|_| Msg::Typing)}
+                            disabled={locked}
                             type="text"
-                            placeholder="Type a message..."
-                            class="flex-1 rounded-full bg-gray-100 px-4 py-2 focus:outline-none"
+                            aria-label={t!("composer.label")}
+                            placeholder={if self.connection.is_offline() {
+                                t!("composer.offline")
+                            } else if self.read_only {
+                                t!("composer.read_only")
+                            } else {
+                                t!("composer.placeholder")
+                            }}
+                            class="flex-1 min-w-0 rounded-full bg-gray-100 dark:bg-gray-700 px-4 py-2 focus:outline-none focus-ring"
                         />
-                        <button onclick={submit} class="ml-2 w-10 h-10 bg-blue-600 rounded-full flex items-center justify-center text-white">
-                            <svg class="w-5 h-5 fill-current" viewBox="0 0 24 24"><path d="M2.01 21L23 12 2.01 3 2 10l15 2-15 2z"/></svg>
+                        if self.flags.location_sharing && !locked {
+                            if self.locating {
+                                <div class="flex-none ml-2 w-10 h-10 flex items-center justify-center" title={t!("composer.locating")}>
+                                    <div class="w-5 h-5 border-2 border-blue-600 border-t-transparent rounded-full animate-spin"></div>
+                                </div>
+                            } else {
+                                <button onclick={ctx.link().callback(|_| Msg::SendLocation)} title={t!("composer.share_location")} class="flex-none ml-2 w-10 h-10 bg-gray-100 dark:bg-gray-700 rounded-full flex items-center justify-center hover:bg-gray-200 dark:hover:bg-gray-600 focus-ring">
+                                    {"📍"}
+                                </button>
+                            }
+                        }
+                        if self.flags.file_uploads && !locked {
+                            <label title={t!("composer.attach")} class="flex-none ml-2 w-10 h-10 bg-gray-100 dark:bg-gray-700 rounded-full flex items-center justify-center cursor-pointer hover:bg-gray-200 dark:hover:bg-gray-600">
+                                {"📎"}
+                                <input
+                                    type="file"
+                                    class="hidden"
+                                    onchange={ctx.link().batch_callback(|e: Event| {
+                                        let input: HtmlInputElement = e.target_unchecked_into();
+                                        let file = input.files().and_then(|files| files.get(0));
+                                        input.set_value("");
+                                        file.map(Msg::AttachFile)
+                                    })}
+                                />
+                            </label>
+                        }
+                        if self.flags.video_clips && !locked {
+                            <button onclick={ctx.link().callback(|_| Msg::ToggleRecorder)} title={t!("composer.record")} class="flex-none ml-2 w-10 h-10 bg-gray-100 dark:bg-gray-700 rounded-full flex items-center justify-center hover:bg-gray-200 dark:hover:bg-gray-600 focus-ring">
+                                {"🎥"}
+                            </button>
+                        }
+                        <button
+                            onclick={submit}
+                            disabled={locked || cooldown.is_some() || self.daily_limit_reached()}
+                            title={self.daily_limit_reached().then(|| t!("quota.daily_limit"))}
+                            aria-label={t!("composer.send")}
+                            class="flex-none ml-2 w-10 h-10 bg-blue-600 rounded-full flex items-center justify-center text-white disabled:opacity-40 focus-ring"
+                        >
+                            <svg class="w-5 h-5 fill-current" viewBox="0 0 24 24" aria-hidden="true"><path d="M2.01 21L23 12 2.01 3 2 10l15 2-15 2z"/></svg>
                         </button>
                     </div>
                 </div>
+                if let Some(search) = &self.local_search {
+                    <MessageSearchPanel
+                        query={search.query.clone()}
+                        error={search.error.clone()}
+                        results={search.results.clone()}
+                        from_archive={self.archive.is_some()}
+                        scope={self.settings.search_scope}
+                        on_scope={ctx.link().callback(Msg::SetSearchScope)}
+                        on_query={ctx.link().callback(Msg::LocalSearch)}
+                        on_jump_to_date={ctx.link().callback(Msg::JumpToDate)}
+                        on_jump={ctx.link().callback(|(channel, id)| Msg::JumpToMessage { channel, id })}
+                        on_close={ctx.link().callback(|_| Msg::CloseLocalSearch)}
+                    />
+                }
+                if let Some(current) = self.lightbox_image.clone() {
+                    <ImageLightbox
+                        images={self.channel_images()}
+                        {current}
+                        on_select={ctx.link().callback(Msg::OpenLightbox)}
+                        on_close={ctx.link().callback(|_| Msg::CloseLightbox)}
+                    />
+                }
+                if self.channel_browser_open {
+                    <ChannelBrowser
+                        channels={self.channel_list.clone()}
+                        joined={self.channels.channels().iter().map(|c| c.name.clone()).collect::<Vec<_>>()}
+                        creating={self.creating_room.clone()}
+                        on_join={ctx.link().callback(Msg::JoinChannel)}
+                        on_create={ctx.link().callback(Msg::CreateRoom)}
+                        on_close={ctx.link().callback(|_| Msg::CloseChannelBrowser)}
+                    />
+                }
+                if self.export_open {
+                    <ExportDialog
+                        channel={self.channels.active_name().to_string()}
+                        from_archive={self.archive.is_some()}
+                        on_export={ctx.link().callback(Msg::ExportChat)}
+                        on_close={ctx.link().callback(|_| Msg::CloseExport)}
+                    />
+                }
+                { self.view_message_menu(ctx) }
+                if self.settings_open {
+                    <SettingsPanel
+                        settings={self.settings.clone()}
+                        dnd_active={self.dnd_active}
+                        notifications_blocked={self.permission_prompt.denied || browser_notifications::permission() == NotificationPermission::Denied}
+                        has_transcript={self.transcript.is_some()}
+                        quota={self.quota.clone()}
+                        on_change={ctx.link().callback(|settings| Msg::UpdateSettings(Box::new(settings)))}
+                        on_reject={ctx.link().callback(Msg::SettingsRejected)}
+                        on_download_transcript={ctx.link().callback(|_| Msg::DownloadTranscript)}
+                        on_clear_data={ctx.link().batch_callback(|_| vec![Msg::CloseSettings, Msg::OpenClearData])}
+                        on_close={ctx.link().callback(|_| Msg::CloseSettings)}
+                    />
+                }
+                if let Some(name) = &self.profile_open {
+                    <Modal label={name.clone()} class="fixed inset-0 z-10 flex items-center justify-center bg-black bg-opacity-50">
+                        <div class="relative">
+                            <UserProfileCard
+                                profile={self.profile_summary(name)}
+                                on_message={self.flags.dms.then(|| ctx.link().batch_callback(|name| vec![Msg::CloseProfile, Msg::OpenDm(name)]))}
+                                on_note={{
+                                    let name = name.clone();
+                                    ctx.link().callback(move |note| Msg::SaveUserNote(name.clone(), note))
+                                }}
+                            />
+                            <button
+                                onclick={ctx.link().callback(|_| Msg::CloseProfile)}
+                                aria-label={t!("common.close")}
+                                class="absolute top-2 right-3 text-gray-400 hover:text-gray-800 focus-ring"
+                            >
+                                {"×"}
+                            </button>
+                        </div>
+                    </Modal>
+                }
+                if let Some(stats) = &self.stats {
+                    <StatsDialog
+                        stats={stats.clone()}
+                        on_close={ctx.link().callback(|_| Msg::CloseStats)}
+                    />
+                }
+                if self.connection == ConnectionState::ShutDown {
+                    <Modal label={t!("dialog.server_offline")} class="fixed inset-0 z-40 flex flex-col items-center justify-center bg-gray-100 dark:bg-gray-700 text-center">
+                        <div class="text-2xl font-semibold">{ t!("dialog.server_offline") }</div>
+                        <div class="mt-2 text-sm text-gray-500">{ t!("shutdown.offline_text") }</div>
+                        <button onclick={ctx.link().callback(|_| Msg::Connection(ConnectionEvent::Retry))} class="mt-4 px-4 py-2 rounded-full bg-blue-600 text-white focus-ring">
+                            { t!("shutdown.reconnect") }
+                        </button>
+                    </Modal>
+                }
+                if let Some(message) = self.pin_dialog.as_ref().and_then(|id| self.channels.active().find(id)) {
+                    <PinDialog
+                        excerpt={format!("{}: {}", message.from, message.message)}
+                        on_pin={ctx.link().callback(Msg::PinMessage)}
+                        on_close={ctx.link().callback(|_| Msg::ClosePinDialog)}
+                    />
+                }
+                if self.clear_data_open {
+                    <ClearDataDialog
+                        from_archive={self.archive.is_some()}
+                        on_clear={ctx.link().callback(Msg::ClearLocalData)}
+                        on_close={ctx.link().callback(|_| Msg::CloseClearData)}
+                    />
+                }
+                if self.policy_notice_open {
+                    <Modal onclick={ctx.link().callback(|_| Msg::ShowPolicyNotice(false))} label={t!("dialog.policy_notice")} class="fixed inset-0 z-10 flex items-center justify-center bg-black bg-opacity-50">
+                        <div class="w-80 p-4 bg-white dark:bg-gray-800 rounded-xl text-sm">
+                            <div>{ t!("edited.policy_notice") }</div>
+                            <div class="mt-3 text-right">
+                                <button class="px-3 py-1 rounded-full bg-blue-600 text-white focus-ring">{ t!("common.ok") }</button>
+                            </div>
+                        </div>
+                    </Modal>
+                }
+                if let Some((_, warnings)) = &self.upload_confirm {
+                    <Modal onclick={ctx.link().callback(|_| Msg::DiscardUpload)} label={t!("dialog.confirm_upload")} class="fixed inset-0 z-20 flex items-center justify-center bg-black bg-opacity-50">
+                        <div onclick={Callback::from(|e: MouseEvent| e.stop_propagation())} class="w-80 p-4 bg-white dark:bg-gray-800 rounded-xl text-sm">
+                            { for warnings.iter().map(|warning| html! { <p class="mb-2">{ warning }</p> }) }
+                            <div class="mt-3 text-right">
+                                <button onclick={ctx.link().callback(|_| Msg::DiscardUpload)} class="px-3 py-1 rounded-full hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring">{ t!("common.cancel") }</button>
+                                <button onclick={ctx.link().callback(|_| Msg::ConfirmUpload)} class="ml-2 px-3 py-1 rounded-full bg-blue-600 text-white focus-ring">{ t!("upload.send") }</button>
+                            </div>
+                        </div>
+                    </Modal>
+                }
+                if self.shortcut_help_open {
+                    <ShortcutHelp on_close={ctx.link().callback(|_| Msg::CloseShortcutHelp)}/>
+                }
+                if self.request_access_open {
+                    <RequestAccessForm
+                        on_submit={ctx.link().callback(Msg::SendAccessRequest)}
+                        on_close={ctx.link().callback(|_| Msg::CloseRequestAccess)}
+                    />
+                }
             </div>
+            </ContextProvider<Locale>>
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::storage::MemoryStore;
+    use crate::services::theme::Theme;
+
+    fn user(username: &str) -> UserState {
+        UserState {
+            username: username.to_string(),
+            is_guest: false,
+        }
+    }
+
+    fn message(from: &str, text: &str) -> MessageData {
+        serde_json::from_value(serde_json::json!({ "from": from, "message": text, "reactions": null })).unwrap()
+    }
+
+    /// A message from the server, with an id and a time.
+    fn at(id: &str, ms: f64) -> MessageData {
+        MessageData { id: id.into(), timestamp: Some(ms), ..message("ann", id) }
+    }
+
+    #[test]
+    fn new_messages_only_pull_down_readers_at_the_bottom() {
+        assert!(follows_new_message(None, true, false));
+        assert!(!follows_new_message(None, false, false));
+        assert!(follows_new_message(None, false, true));
+    }
+
+    #[test]
+    fn new_messages_leave_a_pending_scroll_alone() {
+        let restored = ScrollTarget::Position(ReadingPosition { message_id: "1".into(), offset: 12 });
+        assert!(!follows_new_message(Some(&restored), true, false));
+        assert!(!follows_new_message(Some(&ScrollTarget::Message("1".into())), true, true));
+    }
+
+    #[test]
+    fn an_anchor_keeps_the_offset_saved_for_it() {
+        let saved = ReadingPosition { message_id: "7".into(), offset: -40 };
+        assert!(matches!(
+            anchor_target("7".into(), Some(saved.clone())),
+            ScrollTarget::Position(ReadingPosition { offset: -40, .. })
+        ));
+        assert!(matches!(anchor_target("8".into(), Some(saved)), ScrollTarget::Message(id) if id == "8"));
+        assert!(matches!(anchor_target("8".into(), None), ScrollTarget::Message(_)));
+    }
+
+    #[test]
+    fn switching_between_populated_channels_goes_back_to_each_reading_position() {
+        let mut channels = ChannelStore::new(&[DEFAULT_CHANNEL, "random"]);
+        channels.merge_history(DEFAULT_CHANNEL, vec![at("g1", 1.0), at("g2", 2.0)], false);
+        channels.merge_history("random", vec![at("r1", 1.0), at("r2", 2.0)], false);
+        let mut switch = |name: &str, reading: &str| {
+            arrival_target(channels.switch_to(name, Some(reading.into())), None)
+        };
+        // Never opened before: the bottom.
+        assert!(matches!(switch("random", "g1"), ScrollTarget::Bottom));
+        assert!(matches!(switch(DEFAULT_CHANNEL, "r2"), ScrollTarget::Message(id) if id == "g1"));
+        assert!(matches!(switch("random", "g2"), ScrollTarget::Message(id) if id == "r2"));
+        assert!(matches!(switch(DEFAULT_CHANNEL, "r1"), ScrollTarget::Message(id) if id == "g2"));
+    }
+
+    #[test]
+    fn an_anchor_no_longer_loaded_opens_the_channel_at_the_bottom() {
+        let mut channels = ChannelStore::new(&[DEFAULT_CHANNEL, "random"]);
+        channels.switch_to("random", Some("gone".into()));
+        assert!(matches!(arrival_target(channels.switch_to(DEFAULT_CHANNEL, None), None), ScrollTarget::Bottom));
+    }
+
+    /// Two channels as the cache hands them back after a reload, with `general` open.
+    fn reloaded_channels() -> ChannelStore {
+        let mut channels = ChannelStore::new(&[DEFAULT_CHANNEL, "random"]);
+        let cached = [
+            (DEFAULT_CHANNEL.to_string(), vec![at("g1", 1.0), at("g2", 2.0)]),
+            ("random".to_string(), vec![at("r1", 1.0), at("r2", 2.0)]),
+        ];
+        channels.restore(cached.into_iter().collect());
+        channels.switch_to(DEFAULT_CHANNEL, None);
+        channels
+    }
+
+    #[test]
+    fn a_reload_goes_back_to_where_the_reader_was() {
+        let mut memory = ScrollMemory::load_from(MemoryStore::default(), "ann");
+        memory.set(DEFAULT_CHANNEL, Some(ReadingPosition { message_id: "g1".into(), offset: -30 }));
+        memory.set("random", Some(ReadingPosition { message_id: "r1".into(), offset: 0 }));
+        let mut channels = reloaded_channels();
+        assert!(matches!(
+            resume_reading(&mut channels, &memory),
+            ScrollTarget::Position(ReadingPosition { message_id, offset: -30 }) if message_id == "g1"
+        ));
+        assert_eq!(channels.switch_to("random", None).as_deref(), Some("r1"));
+    }
+
+    #[test]
+    fn clearing_local_data_keeps_the_username_and_theme() {
+        let store = MemoryStore::default();
+        store.set(last_username::KEY, &"ann").unwrap();
+        let settings = UserSettings {
+            theme: Theme::Dark,
+            ..UserSettings::default()
+        };
+        store.set("yewchat.settings.ann", &settings).unwrap();
+
+        let mut channels = reloaded_channels();
+        let mut cache = MessageCache::new_in(store.clone(), "ann");
+        cache.save(channels.channels().iter().map(|c| (c.name.as_str(), c.messages.as_slice())));
+        let mut memory = ScrollMemory::load_from(store.clone(), "ann");
+        memory.set(DEFAULT_CHANNEL, Some(ReadingPosition { message_id: "g1".into(), offset: 0 }));
+        let mut drafts = Drafts::load_from(store.clone(), "ann");
+        drafts.set("random", "half a thought");
+        assert!(store.raw("yewchat.message_cache.ann").is_some());
+
+        forget_conversations(&mut channels, &cache, &mut memory, &mut drafts);
+
+        // What the next mount reads back.
+        let mut channels = ChannelStore::new(&[DEFAULT_CHANNEL, "random"]);
+        channels.restore(MessageCache::new_in(store.clone(), "ann").load());
+        assert!(channels.channels().iter().all(|c| c.messages.is_empty()));
+        assert_eq!(ScrollMemory::load_from(store.clone(), "ann").positions().count(), 0);
+        assert_eq!(Drafts::load_from(store.clone(), "ann").get("random"), None);
+        assert_eq!(store.get::<String>(last_username::KEY).as_deref(), Some("ann"));
+        let kept: UserSettings = store.get("yewchat.settings.ann").unwrap();
+        assert_eq!(kept.theme, Theme::Dark);
+    }
+
+    #[test]
+    fn a_reload_whose_anchor_is_not_cached_opens_at_the_bottom() {
+        let mut memory = ScrollMemory::load_from(MemoryStore::default(), "ann");
+        memory.set(DEFAULT_CHANNEL, Some(ReadingPosition { message_id: "gone".into(), offset: -30 }));
+        memory.set("random", Some(ReadingPosition { message_id: "gone".into(), offset: 0 }));
+        let mut channels = reloaded_channels();
+        assert!(matches!(resume_reading(&mut channels, &memory), ScrollTarget::Bottom));
+        assert_eq!(channels.switch_to("random", None), None);
+    }
+
+    #[test]
+    fn only_a_new_name_is_a_rename() {
+        assert_eq!(renamed_to("ann", &user("anna")), Some("anna"));
+        assert_eq!(renamed_to("ann", &user("ann")), None);
+        assert_eq!(renamed_to("ann", &user("")), None);
+    }
+
+    #[test]
+    fn renamed_users_register_under_the_new_name() {
+        let renamed = user("anna");
+        let frame = register_message(renamed_to("ann", &renamed).unwrap().to_string(), false);
+        assert!(matches!(frame.message_type, MsgTypes::Register));
+        assert_eq!(frame.data.as_deref(), Some("anna"));
+        assert_eq!(frame.data_array, None);
+        assert_eq!(register_message("guest1".into(), true).data_array, Some(vec!["guest".to_string()]));
+    }
+
+    #[test]
+    fn logging_out_says_goodbye_under_the_current_name() {
+        let frame = unregister_message("ann".into());
+        assert!(matches!(frame.message_type, MsgTypes::Unregister));
+        assert_eq!(frame.data.as_deref(), Some("ann"));
+        assert_eq!(frame.channel, None);
+    }
+
+    #[test]
+    fn each_rename_in_the_context_is_sent_once() {
+        let mut current = "ann".to_string();
+        let mut registered = vec![];
+        for state in [user("anna"), user("anna"), user("ann_b"), user("")] {
+            if let Some(new) = renamed_to(&current, &state) {
+                current = new.to_string();
+                registered.extend(register_message(current.clone(), false).data);
+            }
+        }
+        assert_eq!(registered, ["anna", "ann_b"]);
+        assert_eq!(current, "ann_b");
+    }
+
+    #[test]
+    fn online_users_split_by_membership_in_order() {
+        let users: Vec<UserProfile> = ["ann", "bob", "cat", "dan"]
+            .iter()
+            .map(|name| UserProfile { name: name.to_string(), avatar: String::new() })
+            .collect();
+        let (members, others) = split_by_membership(&users, &["dan".to_string(), "bob".to_string()]);
+        let names = |list: Vec<&UserProfile>| list.iter().map(|u| u.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(members), ["bob", "dan"]);
+        assert_eq!(names(others), ["ann", "cat"]);
+    }
+
+    #[test]
+    fn senders_are_counted_regardless_of_case() {
+        let messages: Vec<MessageData> = ["Ann", "bob", "ann"]
+            .iter()
+            .map(|from| {
+                serde_json::from_value(serde_json::json!({
+                    "from": from,
+                    "message": "hi",
+                    "reactions": null,
+                }))
+                .unwrap()
+            })
+            .collect();
+        let counts = count_by_sender(messages.iter());
+        assert_eq!(counts.get("ann"), Some(&2));
+        assert_eq!(counts.get("bob"), Some(&1));
+        assert_eq!(counts.get("cat"), None);
+    }
+
+    #[test]
+    fn the_list_has_one_tab_stop_that_sticks_while_shown() {
+        let shown = ["1", "2", "3"];
+        assert_eq!(tab_stop(&shown, None), Some("3"));
+        assert_eq!(tab_stop(&shown, Some("2")), Some("2"));
+        assert_eq!(tab_stop(&shown, Some("gone")), Some("3"));
+        assert_eq!(tab_stop(&[], Some("2")), None);
+    }
+
+    #[test]
+    fn arrows_stop_at_the_ends_of_the_list() {
+        let shown = ["1", "2", "3"];
+        assert_eq!(neighbour(&shown, "2", -1), Some("1"));
+        assert_eq!(neighbour(&shown, "2", 1), Some("3"));
+        assert_eq!(neighbour(&shown, "1", -1), None);
+        assert_eq!(neighbour(&shown, "3", 1), None);
+        assert_eq!(neighbour(&shown, "gone", 1), None);
+    }
+
+    #[test]
+    fn messages_read_out_as_sender_and_text() {
+        assert_eq!(message_label(&message("bob", "hi")), "bob: hi");
+    }
+
+    #[test]
+    fn only_what_others_post_in_the_open_channel_is_announced() {
+        let m = message("bob", "hi");
+        assert_eq!(announcement(&m, false, false, true).as_deref(), Some("bob: hi"));
+        assert_eq!(announcement(&m, true, false, true), None);
+        assert_eq!(announcement(&m, false, true, true), None);
+        assert_eq!(announcement(&m, false, false, false), None);
+    }
+
+    #[test]
+    fn sending_hands_focus_back_to_the_composer() {
+        assert!(Msg::SubmitMessage.refocuses_composer());
+        assert!(!Msg::Typing.refocuses_composer());
+        assert!(!Msg::OpenSettings.refocuses_composer());
+    }
+
+    #[test]
+    fn the_menu_button_and_backdrop_open_and_close_the_drawer() {
+        assert!(Msg::SetDrawer(true).drawer_after(false));
+        assert!(!Msg::SetDrawer(false).drawer_after(true));
+        assert!(Msg::Typing.drawer_after(true));
+        assert!(!Msg::Typing.drawer_after(false));
+    }
+
+    #[test]
+    fn going_somewhere_from_the_drawer_closes_it() {
+        let picks = [Msg::SwitchChannel("random".into()), Msg::ShowMentions, Msg::OpenChannelBrowser, Msg::OpenSettings];
+        for msg in picks {
+            assert!(!msg.drawer_after(true));
         }
     }
+
+    #[test]
+    fn holding_a_finger_on_a_message_starts_a_long_press() {
+        assert!(matches!(
+            touch_msg("pointerdown", "touch", "7", true, (10, 20)),
+            Some(Msg::PressMessage(id, 10, 20)) if id == "7"
+        ));
+        assert!(matches!(touch_msg("pointermove", "touch", "7", true, (12, 20)), Some(Msg::MovePress(12, 20))));
+        assert!(matches!(touch_msg("pointerup", "touch", "7", true, (12, 20)), Some(Msg::ReleaseMessage)));
+        assert!(matches!(touch_msg("pointercancel", "touch", "7", true, (12, 20)), Some(Msg::ReleaseMessage)));
+    }
+
+    #[test]
+    fn mice_and_unconfirmed_messages_get_no_long_press() {
+        assert!(touch_msg("pointerdown", "mouse", "7", true, (10, 20)).is_none());
+        assert!(touch_msg("pointerdown", "pen", "7", true, (10, 20)).is_none());
+        assert!(touch_msg("pointerdown", "touch", "7", false, (10, 20)).is_none());
+    }
+
+    #[test]
+    fn only_user_actions_count_as_activity() {
+        assert!(Msg::SubmitMessage.is_user_initiated());
+        assert!(Msg::SwitchChannel("general".into()).is_user_initiated());
+        assert!(Msg::Connection(ConnectionEvent::Retry).is_user_initiated());
+        assert!(!Msg::CooldownOver.is_user_initiated());
+        assert!(!Msg::EphemeralTick("1".into()).is_user_initiated());
+        assert!(!Msg::LoadOlder.is_user_initiated());
+        assert!(!Msg::Connection(ConnectionEvent::BrowserOffline).is_user_initiated());
+    }
 }
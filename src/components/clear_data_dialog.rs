@@ -0,0 +1,68 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::hooks::use_focus_trap::use_focus_trap;
+use crate::hooks::use_locale::use_locale;
+use crate::t;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// The on-device archive is open, so it is listed too.
+    pub from_archive: bool,
+    /// Called with whether settings should go as well.
+    pub on_clear: Callback<bool>,
+    pub on_close: Callback<()>,
+}
+
+/// Asks before wiping what this browser keeps of the chat, saying exactly what goes.
+/// Settings stay unless the box for them is ticked.
+#[function_component(ClearDataDialog)]
+pub fn clear_data_dialog(props: &Props) -> Html {
+    use_locale();
+    let trap = use_focus_trap(true);
+    let include_settings = use_state(|| false);
+
+    let toggle_settings = {
+        let include_settings = include_settings.clone();
+        Callback::from(move |e: Event| include_settings.set(e.target_unchecked_into::<HtmlInputElement>().checked()))
+    };
+    let clear = {
+        let include_settings = include_settings.clone();
+        props.on_clear.reform(move |_| *include_settings)
+    };
+    let close = props.on_close.reform(|_| ());
+
+    html! {
+        <div ref={trap} role="dialog" aria-modal="true" aria-label={t!("dialog.clear_data")} class="fixed inset-0 z-10 flex items-center justify-center bg-black bg-opacity-50">
+            <div class="w-96 flex flex-col bg-white dark:bg-gray-800 rounded-xl overflow-hidden text-sm">
+                <div class="flex items-center p-3 border-b">
+                    <div class="flex-1 text-lg font-semibold">{ t!("dialog.clear_data") }</div>
+                    <button onclick={close.clone()} aria-label={t!("common.close")} class="text-gray-400 hover:text-gray-800 focus-ring">{"×"}</button>
+                </div>
+                <div class="p-3 space-y-1">
+                    <div>{ t!("clear_data.intro") }</div>
+                    <ul class="pl-5 list-disc text-gray-600 dark:text-gray-300">
+                        <li>{ t!("clear_data.messages") }</li>
+                        <li>{ t!("clear_data.files") }</li>
+                        <li>{ t!("clear_data.cache") }</li>
+                        if props.from_archive {
+                            <li>{ t!("clear_data.archive") }</li>
+                        }
+                        if *include_settings {
+                            <li>{ t!("clear_data.settings") }</li>
+                        }
+                    </ul>
+                    <div class="text-xs text-gray-400">{ t!("clear_data.server_untouched") }</div>
+                </div>
+                <label class="flex items-center px-3">
+                    <input type="checkbox" checked={*include_settings} onchange={toggle_settings} class="mr-2 focus-ring"/>
+                    { t!("clear_data.reset_settings") }
+                </label>
+                <div class="flex justify-end p-3 space-x-2">
+                    <button onclick={close} class="px-3 py-1 rounded-full hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring">{ t!("common.cancel") }</button>
+                    <button onclick={clear} class="px-3 py-1 rounded-full bg-red-600 text-white focus-ring">{ t!("clear_data.clear") }</button>
+                </div>
+            </div>
+        </div>
+    }
+}
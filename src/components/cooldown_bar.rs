@@ -0,0 +1,68 @@
+use gloo_timers::callback::Interval;
+use yew::prelude::*;
+
+use crate::hooks::use_locale::use_locale;
+use crate::t;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// When sending is allowed again, in milliseconds since the epoch.
+    pub until: f64,
+    /// The room's slow-mode interval, which a full bar stands for.
+    pub seconds: u32,
+    /// Called once the cooldown is over.
+    pub on_done: Callback<()>,
+}
+
+/// How full the bar is with `remaining` milliseconds of a `seconds`-long cooldown to go, in
+/// percent.
+fn fill_percent(remaining: f64, seconds: u32) -> f64 {
+    (remaining / (seconds as f64 * 10.0)).clamp(0.0, 100.0)
+}
+
+/// A bar above the composer draining while slow mode holds the next message back. It keeps
+/// its own clock and only tells the chat when the wait is over.
+#[function_component(CooldownBar)]
+pub fn cooldown_bar(props: &Props) -> Html {
+    use_locale();
+    let now = use_state(js_sys::Date::now);
+    {
+        let now = now.clone();
+        let on_done = props.on_done.clone();
+        use_effect_with_deps(
+            move |&until| {
+                let tick = Interval::new(250, move || {
+                    let time = js_sys::Date::now();
+                    now.set(time);
+                    if time >= until {
+                        on_done.emit(());
+                    }
+                });
+                move || drop(tick)
+            },
+            props.until,
+        );
+    }
+    let remaining = (props.until - *now).max(0.0);
+
+    html! {
+        <div class="px-4 pt-2" title={t!("composer.cooldown", seconds = (remaining / 1000.0).ceil())}>
+            <div class="h-1 rounded-full bg-gray-200 overflow-hidden">
+                <div class="h-1 bg-yellow-500" style={format!("width: {:.1}%", fill_percent(remaining, props.seconds))}></div>
+            </div>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_bar_drains_over_the_room_interval() {
+        assert_eq!(fill_percent(10_000.0, 10), 100.0);
+        assert_eq!(fill_percent(2_500.0, 10), 25.0);
+        assert_eq!(fill_percent(0.0, 10), 0.0);
+        assert_eq!(fill_percent(30_000.0, 10), 100.0);
+    }
+}
@@ -0,0 +1,86 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::hooks::use_locale::use_locale;
+use crate::services::dnd::{format_minutes, parse_minutes, DndSettings, Now, Snooze};
+use crate::t;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub settings: DndSettings,
+    pub active: bool,
+    pub on_change: Callback<DndSettings>,
+}
+
+#[function_component(DndSettingsPanel)]
+pub fn dnd_settings_panel(props: &Props) -> Html {
+    use_locale();
+    let update = |f: fn(&mut DndSettings, String)| {
+        let settings = props.settings.clone();
+        let on_change = props.on_change.clone();
+        move |value: String| {
+            let mut settings = settings.clone();
+            f(&mut settings, value);
+            on_change.emit(settings);
+        }
+    };
+    let snooze = |snooze: Option<fn(f64) -> Snooze>| {
+        let settings = props.settings.clone();
+        let on_change = props.on_change.clone();
+        Callback::from(move |_| {
+            let mut settings = settings.clone();
+            settings.snooze = snooze.map(|make| make(Now::local().epoch_ms));
+            on_change.emit(settings);
+        })
+    };
+    let toggle_schedule = {
+        let set = update(|s, _| s.schedule_enabled = !s.schedule_enabled);
+        Callback::from(move |_| set(String::new()))
+    };
+    let set_start = {
+        let set = update(|s, value| {
+            if let Some(minutes) = parse_minutes(&value) {
+                s.schedule.start = minutes;
+            }
+        });
+        Callback::from(move |e: Event| set(e.target_unchecked_into::<HtmlInputElement>().value()))
+    };
+    let set_end = {
+        let set = update(|s, value| {
+            if let Some(minutes) = parse_minutes(&value) {
+                s.schedule.end = minutes;
+            }
+        });
+        Callback::from(move |e: Event| set(e.target_unchecked_into::<HtmlInputElement>().value()))
+    };
+
+    let button = "flex-1 bg-white dark:bg-gray-800 rounded-lg p-1 border hover:bg-gray-200 dark:hover:bg-gray-600 focus-ring";
+    html! {
+        <div class="m-3 text-sm">
+            <div class="font-semibold">
+                { t!("dnd.title") }
+                if props.active {
+                    <span class="ml-1">{"🌙"}</span>
+                }
+            </div>
+            if props.settings.snooze.is_some() {
+                <button onclick={snooze(None)} class="mt-1 w-full bg-white dark:bg-gray-800 rounded-lg p-1 border hover:bg-gray-200 dark:hover:bg-gray-600 focus-ring">{ t!("dnd.turn_off") }</button>
+            } else {
+                <div class="mt-1 flex space-x-1">
+                    <button onclick={snooze(Some(|_| Snooze::UntilTurnedOff))} class={button}>{ t!("dnd.on") }</button>
+                    <button onclick={snooze(Some(|now| Snooze::for_hours(1.0, now)))} class={button}>{ t!("dnd.hours", count = 1) }</button>
+                    <button onclick={snooze(Some(|now| Snooze::for_hours(8.0, now)))} class={button}>{ t!("dnd.hours", count = 8) }</button>
+                </div>
+            }
+            <label class="mt-2 flex items-center">
+                <input type="checkbox" checked={props.settings.schedule_enabled} onchange={toggle_schedule} class="mr-2 focus-ring"/>
+                { t!("dnd.every_day_from") }
+            </label>
+            <div class="mt-1 flex items-center space-x-1">
+                <input type="time" value={format_minutes(props.settings.schedule.start)} onchange={set_start} class="rounded px-1 border focus-ring"/>
+                <span>{ t!("dnd.to") }</span>
+                <input type="time" value={format_minutes(props.settings.schedule.end)} onchange={set_end} class="rounded px-1 border focus-ring"/>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,76 @@
+use yew::prelude::*;
+
+use crate::hooks::use_locale::use_locale;
+use crate::t;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum EmptyStateVariant {
+    NoMessages,
+    Loading,
+    SearchNoResults,
+    Error,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub variant: EmptyStateVariant,
+    /// Replaces the variant's default text.
+    #[prop_or_default]
+    pub text: Option<String>,
+}
+
+/// What an area shows instead of its content when there is none (yet).
+#[function_component(EmptyState)]
+pub fn empty_state(props: &Props) -> Html {
+    use_locale();
+    let (default_text, icon) = match props.variant {
+        EmptyStateVariant::Loading => {
+            return html! {
+                <div class="p-4 space-y-4 animate-pulse" aria-label={t!("empty.loading")}>
+                    <div class="h-12 w-2/3 rounded-xl bg-gray-200"></div>
+                    <div class="h-12 w-1/2 rounded-xl bg-gray-200"></div>
+                    <div class="h-12 w-3/5 rounded-xl bg-gray-200"></div>
+                </div>
+            };
+        }
+        EmptyStateVariant::NoMessages => (t!("empty.no_messages"), bubble()),
+        EmptyStateVariant::SearchNoResults => (t!("empty.no_results"), magnifier()),
+        EmptyStateVariant::Error => (t!("empty.error"), warning()),
+    };
+    html! {
+        <div class="flex flex-col items-center justify-center h-full p-8 text-gray-400">
+            { icon }
+            <div class="mt-3 text-sm">{ props.text.clone().unwrap_or(default_text) }</div>
+        </div>
+    }
+}
+
+fn bubble() -> Html {
+    html! {
+        <svg class="w-24 h-24" viewBox="0 0 64 64" fill="none" stroke="currentColor" stroke-width="2">
+            <path d="M10 14h44a4 4 0 0 1 4 4v22a4 4 0 0 1-4 4H28l-10 8v-8h-8a4 4 0 0 1-4-4V18a4 4 0 0 1 4-4z"/>
+            <circle cx="22" cy="29" r="2" fill="currentColor"/>
+            <circle cx="32" cy="29" r="2" fill="currentColor"/>
+            <circle cx="42" cy="29" r="2" fill="currentColor"/>
+        </svg>
+    }
+}
+
+fn magnifier() -> Html {
+    html! {
+        <svg class="w-24 h-24" viewBox="0 0 64 64" fill="none" stroke="currentColor" stroke-width="2">
+            <circle cx="28" cy="28" r="14"/>
+            <path d="M38 38l14 14"/>
+        </svg>
+    }
+}
+
+fn warning() -> Html {
+    html! {
+        <svg class="w-24 h-24" viewBox="0 0 64 64" fill="none" stroke="currentColor" stroke-width="2">
+            <path d="M32 8l26 46H6z"/>
+            <path d="M32 26v14"/>
+            <circle cx="32" cy="46" r="1.5" fill="currentColor"/>
+        </svg>
+    }
+}
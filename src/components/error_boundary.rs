@@ -0,0 +1,98 @@
+use std::cell::RefCell;
+use std::panic;
+
+use serde::Serialize;
+use web_sys::Element;
+use yew::prelude::*;
+
+use crate::t;
+
+/// Where crash reports are sent. Reporting is off unless set at build time.
+const CRASH_ENDPOINT: Option<&str> = option_env!("YEWCHAT_CRASH_ENDPOINT");
+
+/// The reload prompt, in the language picked when the panic happened. Looking the locale
+/// up only reads a `Cell`, so it is safe from inside the panic hook.
+fn recovery_html() -> String {
+    format!(
+        r#"<div class="flex flex-col items-center justify-center w-full h-full text-gray-600 dark:text-gray-300">
+    <div class="text-4xl">😵</div>
+    <button class="mt-3 px-4 py-2 rounded-full bg-blue-600 text-white focus-ring" onclick="location.reload()">{}</button>
+</div>"#,
+        t!("error.reload")
+    )
+}
+
+thread_local! {
+    /// The element to draw the recovery UI into. Panic hooks must be `Send`, so it lives here.
+    static ROOT: RefCell<Option<Element>> = const { RefCell::new(None) };
+}
+
+#[derive(Serialize)]
+struct CrashReport {
+    message: String,
+    stack: String,
+    url: String,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub children: Children,
+}
+
+/// Replaces its children with a reload prompt when the app panics.
+///
+/// Yew 0.19 can't recover from a panic (the module is poisoned afterwards), so the
+/// recovery UI is written straight into the DOM from the panic hook.
+#[function_component(ErrorBoundary)]
+pub fn error_boundary(props: &Props) -> Html {
+    let node = use_node_ref();
+    {
+        let node = node.clone();
+        use_effect_with_deps(
+            move |_| {
+                ROOT.with(|root| *root.borrow_mut() = node.cast::<Element>());
+                panic::set_hook(Box::new(|info| {
+                    console_error_panic_hook::hook(info);
+                    report(&info.to_string());
+                    ROOT.with(|root| {
+                        if let Some(root) = &*root.borrow() {
+                            root.set_inner_html(&recovery_html());
+                        }
+                    });
+                }));
+                || {
+                    drop(panic::take_hook());
+                    ROOT.with(|root| root.borrow_mut().take());
+                }
+            },
+            (),
+        );
+    }
+    html! {
+        <div ref={node} class="flex w-full h-full">{ for props.children.iter() }</div>
+    }
+}
+
+/// Fire-and-forget POST of the panic. A beacon needs no executor, which may no longer run.
+fn report(message: &str) {
+    let endpoint = match CRASH_ENDPOINT {
+        Some(endpoint) => endpoint,
+        None => return,
+    };
+    let window = match web_sys::window() {
+        Some(window) => window,
+        None => return,
+    };
+    let report = CrashReport {
+        message: message.to_string(),
+        stack: js_sys::Reflect::get(&js_sys::Error::new(""), &"stack".into())
+            .ok()
+            .and_then(|stack| stack.as_string())
+            .unwrap_or_default(),
+        url: window.location().href().unwrap_or_default(),
+    };
+    let body = serde_json::to_string(&report).unwrap_or_default();
+    if let Err(e) = window.navigator().send_beacon_with_opt_str(endpoint, Some(&body)) {
+        log::error!("failed to send crash report: {:?}", e);
+    }
+}
@@ -0,0 +1,70 @@
+use yew::prelude::*;
+
+use crate::hooks::use_focus_trap::use_focus_trap;
+use crate::hooks::use_locale::use_locale;
+use crate::services::a11y;
+use crate::services::chat_export::ExportFormat;
+use crate::t;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub channel: String,
+    /// The archive is open, so the export reaches back further than what's on screen.
+    pub from_archive: bool,
+    pub on_export: Callback<ExportFormat>,
+    pub on_close: Callback<()>,
+}
+
+#[function_component(ExportDialog)]
+pub fn export_dialog(props: &Props) -> Html {
+    use_locale();
+    let trap = use_focus_trap(true);
+    let format = use_state(|| ExportFormat::Json);
+
+    let option = |value: ExportFormat, label: String, hint: String| {
+        let onclick = {
+            let format = format.clone();
+            Callback::from(move |_| format.set(value))
+        };
+        html! {
+            <div {onclick} role="radio" aria-checked={(*format == value).to_string()} tabindex="0" onkeydown={a11y::press_on_key} class="flex items-start px-3 py-2 rounded-lg cursor-pointer hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring">
+                <input type="radio" checked={*format == value} tabindex="-1" aria-hidden="true" class="mt-1 mr-2 pointer-events-none"/>
+                <div>
+                    <div class="font-medium">{ label }</div>
+                    <div class="text-xs text-gray-400">{ hint }</div>
+                </div>
+            </div>
+        }
+    };
+    let export = {
+        let format = format.clone();
+        props.on_export.reform(move |_| *format)
+    };
+    let close = props.on_close.reform(|_| ());
+
+    html! {
+        <div ref={trap} role="dialog" aria-modal="true" aria-label={t!("dialog.export", channel = props.channel)} class="fixed inset-0 z-10 flex items-center justify-center bg-black bg-opacity-50">
+            <div class="w-96 flex flex-col bg-white dark:bg-gray-800 rounded-xl overflow-hidden text-sm">
+                <div class="flex items-center p-3 border-b">
+                    <div class="flex-1 text-lg font-semibold">{ t!("dialog.export", channel = props.channel) }</div>
+                    <button onclick={close.clone()} aria-label={t!("common.close")} class="text-gray-400 hover:text-gray-800 focus-ring">{"×"}</button>
+                </div>
+                <div class="p-2">
+                    { option(ExportFormat::Json, "JSON".to_string(), t!("export.json_hint")) }
+                    { option(ExportFormat::Text, t!("export.text"), t!("export.text_hint")) }
+                </div>
+                <div class="px-3 text-xs text-gray-400">
+                    if props.from_archive {
+                        { t!("export.from_archive") }
+                    } else {
+                        { t!("export.from_loaded") }
+                    }
+                </div>
+                <div class="flex justify-end p-3 space-x-2">
+                    <button onclick={close} class="px-3 py-1 rounded-full hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring">{ t!("common.cancel") }</button>
+                    <button onclick={export} class="px-3 py-1 rounded-full bg-blue-600 text-white focus-ring">{ t!("export.export") }</button>
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use gloo_events::EventListener;
+use wasm_bindgen::JsCast;
+use web_sys::KeyboardEvent;
+use yew::prelude::*;
+use yew::virtual_dom::AttrValue;
+
+use crate::hooks::use_focus_trap::use_focus_trap;
+use crate::hooks::use_locale::use_locale;
+use crate::t;
+
+const MAX_ZOOM: f64 = 5.0;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// Every image in the conversation, oldest first.
+    pub images: Vec<Rc<str>>,
+    pub current: Rc<str>,
+    pub on_select: Callback<Rc<str>>,
+    pub on_close: Callback<()>,
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+/// How far the image is zoomed in. A reducer, so that several pointer moves between two
+/// renders each scale the latest zoom rather than the one their render saw.
+#[derive(PartialEq, Debug)]
+struct Zoom(f64);
+
+enum ZoomAction {
+    Reset,
+    Scale(f64),
+}
+
+impl Reducible for Zoom {
+    type Action = ZoomAction;
+
+    fn reduce(self: Rc<Self>, action: ZoomAction) -> Rc<Self> {
+        match action {
+            ZoomAction::Reset => Zoom(1.0),
+            ZoomAction::Scale(factor) => Zoom((self.0 * factor).clamp(1.0, MAX_ZOOM)),
+        }
+        .into()
+    }
+}
+
+#[function_component(ImageLightbox)]
+pub fn image_lightbox(props: &Props) -> Html {
+    use_locale();
+    let trap = use_focus_trap(true);
+    let index = props.images.iter().position(|url| url == &props.current);
+    let prev = index
+        .and_then(|i| i.checked_sub(1))
+        .and_then(|i| props.images.get(i))
+        .cloned();
+    let next = index.and_then(|i| props.images.get(i + 1)).cloned();
+
+    let zoom = use_reducer(|| Zoom(1.0));
+    let pointers = use_mut_ref(HashMap::<i32, (f64, f64)>::new);
+
+    {
+        let zoom = zoom.clone();
+        use_effect_with_deps(
+            move |_| {
+                zoom.dispatch(ZoomAction::Reset);
+                || ()
+            },
+            props.current.clone(),
+        );
+    }
+
+    {
+        let on_select = props.on_select.clone();
+        use_effect_with_deps(
+            move |(prev, next): &(Option<Rc<str>>, Option<Rc<str>>)| {
+                let (prev, next) = (prev.clone(), next.clone());
+                let document = web_sys::window().unwrap().document().unwrap();
+                let listener = EventListener::new(&document, "keydown", move |e| {
+                    let key = match e.dyn_ref::<KeyboardEvent>() {
+                        Some(e) => e.key(),
+                        None => return,
+                    };
+                    match (key.as_str(), &prev, &next) {
+                        ("ArrowLeft", Some(prev), _) => on_select.emit(prev.clone()),
+                        ("ArrowRight", _, Some(next)) => on_select.emit(next.clone()),
+                        _ => {}
+                    }
+                });
+                move || drop(listener)
+            },
+            (prev.clone(), next.clone()),
+        );
+    }
+
+    let onpointerdown = {
+        let pointers = pointers.clone();
+        Callback::from(move |e: PointerEvent| {
+            pointers
+                .borrow_mut()
+                .insert(e.pointer_id(), (e.client_x() as f64, e.client_y() as f64));
+        })
+    };
+    let onpointermove = {
+        let pointers = pointers.clone();
+        let zoom = zoom.clone();
+        Callback::from(move |e: PointerEvent| {
+            let mut pointers = pointers.borrow_mut();
+            if pointers.len() != 2 || !pointers.contains_key(&e.pointer_id()) {
+                return;
+            }
+            let other = *pointers
+                .iter()
+                .find(|(id, _)| **id != e.pointer_id())
+                .map(|(_, pos)| pos)
+                .unwrap();
+            let before = distance(pointers[&e.pointer_id()], other);
+            let now = (e.client_x() as f64, e.client_y() as f64);
+            pointers.insert(e.pointer_id(), now);
+            if before > 0.0 {
+                zoom.dispatch(ZoomAction::Scale(distance(now, other) / before));
+            }
+        })
+    };
+    let onpointerup = {
+        let pointers = pointers.clone();
+        Callback::from(move |e: PointerEvent| {
+            pointers.borrow_mut().remove(&e.pointer_id());
+        })
+    };
+
+    let onpointercancel = onpointerup.clone();
+
+    let close = props.on_close.reform(|_| ());
+    let arrow = |target: Option<Rc<str>>, label: &'static str, side: &'static str| match target {
+        Some(url) => {
+            let on_select = props.on_select.clone();
+            let onclick = Callback::from(move |e: MouseEvent| {
+                e.stop_propagation();
+                on_select.emit(url.clone());
+            });
+            html! {
+                <button {onclick} class={classes!("focus-ring", "absolute", side, "top-1/2", "text-4xl", "text-white", "px-4")}>{ label }</button>
+            }
+        }
+        None => html! {},
+    };
+
+    html! {
+        <div ref={trap} role="dialog" aria-modal="true" aria-label={t!("dialog.image")} onclick={close} class="fixed inset-0 z-20 flex items-center justify-center bg-black bg-opacity-75">
+            <img
+                src={AttrValue::from(props.current.clone())}
+                alt="attachment"
+                onclick={Callback::from(|e: MouseEvent| e.stop_propagation())}
+                {onpointerdown}
+                {onpointermove}
+                {onpointerup}
+                {onpointercancel}
+                style={format!("transform: scale({}); touch-action: none;", zoom.0)}
+                class="max-w-full max-h-full"
+            />
+            { arrow(prev, "‹", "left-0") }
+            { arrow(next, "›", "right-0") }
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pinch_steps_build_on_each_other_within_bounds() {
+        let zoom = Rc::new(Zoom(1.0));
+        let zoom = zoom.reduce(ZoomAction::Scale(2.0)).reduce(ZoomAction::Scale(1.5));
+        assert_eq!(*zoom, Zoom(3.0));
+        assert_eq!(*zoom.clone().reduce(ZoomAction::Scale(10.0)), Zoom(MAX_ZOOM));
+        assert_eq!(*zoom.clone().reduce(ZoomAction::Scale(0.1)), Zoom(1.0));
+        assert_eq!(*zoom.reduce(ZoomAction::Reset), Zoom(1.0));
+    }
+}
@@ -0,0 +1,83 @@
+use std::rc::Rc;
+
+use yew::prelude::*;
+use yew::virtual_dom::AttrValue;
+
+/// Images never render wider than this.
+const MAX_WIDTH: u32 = 320;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// `None` while the image is still on its way.
+    pub src: Option<Rc<str>>,
+    /// A tiny version shown blurred until the real image has loaded.
+    #[prop_or_default]
+    pub thumbnail: Option<String>,
+    /// Size of the full image, to reserve its space up front.
+    #[prop_or_default]
+    pub width: Option<u32>,
+    #[prop_or_default]
+    pub height: Option<u32>,
+    pub alt: String,
+    #[prop_or_default]
+    pub onclick: Callback<MouseEvent>,
+}
+
+/// An image that holds its final size from the first render, showing a blurred thumbnail
+/// (or a grey skeleton) until the real thing loads, so nothing below it jumps.
+#[function_component(LazyImage)]
+pub fn lazy_image(props: &Props) -> Html {
+    let loaded = use_state(|| false);
+    {
+        let loaded = loaded.clone();
+        use_effect_with_deps(
+            move |_| {
+                loaded.set(false);
+                || ()
+            },
+            props.src.clone(),
+        );
+    }
+
+    let size = match (props.width, props.height) {
+        (Some(width), Some(height)) if width > 0 && height > 0 => format!(
+            "width: {}px; aspect-ratio: {} / {};",
+            width.min(MAX_WIDTH),
+            width,
+            height
+        ),
+        _ if *loaded => format!("max-width: {}px;", MAX_WIDTH),
+        _ => format!("width: {}px; height: 12rem;", MAX_WIDTH),
+    };
+    let onload = {
+        let loaded = loaded.clone();
+        Callback::from(move |_| loaded.set(true))
+    };
+    let blur = if *loaded { "blur(0)" } else { "blur(20px)" };
+
+    html! {
+        <div
+            onclick={props.onclick.clone()}
+            style={size}
+            class={classes!(
+                "relative", "mt-2", "overflow-hidden", "rounded-lg", "bg-gray-200", "cursor-pointer",
+                (props.thumbnail.is_none() && !*loaded).then_some("animate-pulse"),
+            )}
+        >
+            if let Some(thumbnail) = &props.thumbnail {
+                if !*loaded {
+                    <img class="absolute inset-0 w-full h-full object-cover" style="filter: blur(20px);" src={thumbnail.clone()} alt=""/>
+                }
+            }
+            if let Some(src) = &props.src {
+                <img
+                    {onload}
+                    class="relative block w-full h-full object-cover"
+                    style={format!("filter: {}; opacity: {}; transition: filter 0.3s, opacity 0.3s;", blur, if *loaded { 1 } else { 0 })}
+                    src={AttrValue::from(src.clone())}
+                    alt={props.alt.clone()}
+                />
+            }
+        </div>
+    }
+}
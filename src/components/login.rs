@@ -1,39 +1,168 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gloo_timers::callback::Timeout;
 use web_sys::HtmlInputElement;
 use yew::functional::*;
 use yew::prelude::*;
 use yew_router::prelude::*;
 
+use crate::components::require_user::{login_error, next_chat_query};
+use crate::hooks::use_local_storage::use_local_storage;
+use crate::services::avatar::{self, avatar_for};
+use crate::services::last_username;
+use crate::services::message_link::MessageLink;
+use crate::services::settings::UserSettings;
+use crate::services::username::validate_username;
+use crate::t;
 use crate::Route;
-use crate::User;
+use crate::UserCtx;
+
+/// Prefix that joins as a read-only guest, e.g. `guest:alice`.
+const GUEST_PREFIX: &str = "guest:";
+
+/// A link into the chat carrying the query `next` asked for.
+type ChatLink = Link<Route, Vec<(String, String)>>;
+
+/// Typing pauses this long before a problem with the name is pointed out.
+const ERROR_DEBOUNCE_MS: u32 = 400;
+
+fn page_search() -> String {
+    web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .unwrap_or_default()
+}
+
+fn page_hash() -> String {
+    web_sys::window()
+        .and_then(|window| window.location().hash().ok())
+        .unwrap_or_default()
+}
+
+/// Splits what was typed into a username and whether to join as a guest, either by
+/// the `guest:` prefix or a `?guest=1` query on the page.
+fn parse_login(input: &str, search: &str) -> (String, bool) {
+    let from_query = search
+        .trim_start_matches('?')
+        .split('&')
+        .any(|pair| pair == "guest=1" || pair == "guest=true");
+    match input.strip_prefix(GUEST_PREFIX) {
+        Some(name) => (name.trim().to_string(), true),
+        None => (input.to_string(), from_query),
+    }
+}
 
 #[function_component(Login)]
 pub fn login() -> Html {
     let username = use_state(|| String::new());
-    let user = use_context::<User>().expect("No context found.");
+    let user = use_context::<UserCtx>().expect("No context found.");
+    // Shown under the box once typing pauses, so half-typed names aren't flagged. A name
+    // the chat turned away comes back with what was wrong with it.
+    let shown_error = use_state(|| login_error(&page_search()));
+    let debounce: Rc<RefCell<Option<Timeout>>> = use_mut_ref(|| None);
+    let (name, is_guest) = parse_login(&username, &page_search());
+    let valid = validate_username(&name);
+    // A remembered name that no longer passes would only be turned away again.
+    let (last, set_last) = use_local_storage(last_username::KEY, None::<String>);
+    let last = last.filter(|name| validate_username(name).is_ok());
+    // Their avatar comes from their own template, not the build's or that of whoever
+    // just logged out.
+    avatar::set_template(last.as_deref().and_then(|name| UserSettings::load(name).avatar_template).as_deref());
+    // Where the chat route was headed before it sent us here, or the message a link
+    // opened the site on.
+    let next = next_chat_query(&page_search())
+        .or_else(|| MessageLink::parse(&page_search(), &page_hash()).map(|link| link.chat_query()));
 
     let oninput = {
         let current_username = username.clone();
+        let shown_error = shown_error.clone();
 
         Callback::from(move |e: InputEvent| {
             let input: HtmlInputElement = e.target_unchecked_into();
-            current_username.set(input.value());
+            let value = input.value();
+            let error = if value.trim().is_empty() {
+                None
+            } else {
+                validate_username(&parse_login(&value, &page_search()).0).err().map(|e| e.to_string())
+            };
+            current_username.set(value);
+            let shown_error = shown_error.clone();
+            *debounce.borrow_mut() = Some(Timeout::new(ERROR_DEBOUNCE_MS, move || shown_error.set(error)));
         })
     };
 
     let onclick = {
-        let username = username.clone();
         let user = user.clone();
-        Callback::from(move |_| *user.username.borrow_mut() = (*username).clone())
+        let valid = valid.clone();
+        Callback::from(move |_| {
+            if let Ok(name) = &valid {
+                user.log_in(name.clone(), is_guest);
+            }
+        })
     };
 
+    let continue_as = last.map(|name| {
+        let onclick = {
+            let user = user.clone();
+            let name = name.clone();
+            Callback::from(move |_| user.log_in(name.clone(), false))
+        };
+        let not_you = {
+            let set_last = set_last.clone();
+            Callback::from(move |_| set_last.emit(None))
+        };
+        html! {
+            <div class="flex flex-col items-center">
+                <ChatLink to={Route::Chat} query={next.clone()}>
+                    <button {onclick} class="flex items-center px-6 py-3 rounded-lg bg-violet-600 text-white font-bold focus-ring">
+                        <img class="w-10 h-10 mr-3 rounded-full bg-white" src={avatar_for(&name)} alt=""/>
+                        { t!("login.continue_as", name = name) }
+                    </button>
+                </ChatLink>
+                <button onclick={not_you} class="mt-2 text-sm text-gray-300 hover:underline focus-ring">{ t!("login.not_you") }</button>
+            </div>
+        }
+    });
+
     html! {
         <div class="bg-gray-800 flex w-screen">
             <div class="container mx-auto flex flex-col justify-center items-center	">
-                <form class="m-4 flex">
-                    <input {oninput} class="rounded-l-lg p-4 border-t mr-0 border-b border-l text-gray-800 border-gray-200 bg-white" placeholder="Username"/>
-                    <Link<Route> to={Route::Chat}> <button {onclick} disabled={username.len()<1} class="px-8 rounded-r-lg bg-violet-600	  text-white font-bold p-4 uppercase border-violet-600 border-t border-b border-r" >{"Go Chatting!"}</button></Link<Route>>
-                </form>
+                if let Some(continue_as) = continue_as {
+                    { continue_as }
+                } else {
+                    <form class="m-4 flex">
+                        <input {oninput} class="rounded-l-lg p-4 border-t mr-0 border-b border-l text-gray-800 border-gray-200 bg-white focus-ring" placeholder={t!("login.username")}/>
+                        // Only a valid name gets the link, so a bare `guest:` can't click through it.
+                        if valid.is_ok() {
+                            <ChatLink to={Route::Chat} query={next}> <button {onclick} class="px-8 rounded-r-lg bg-violet-600	  text-white font-bold p-4 uppercase border-violet-600 border-t border-b border-r focus-ring" >{ t!("login.go") }</button></ChatLink>
+                        } else {
+                            <button disabled=true class="px-8 rounded-r-lg bg-violet-600	  text-white font-bold p-4 uppercase border-violet-600 border-t border-b border-r opacity-50 focus-ring" >{ t!("login.go") }</button>
+                        }
+                    </form>
+                    if let Some(error) = &*shown_error {
+                        <div class="text-sm text-red-300">{ error }</div>
+                    }
+                }
             </div>
         </div>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guest_prefix_marks_a_guest() {
+        assert_eq!(parse_login("guest:ann", ""), ("ann".to_string(), true));
+        assert_eq!(parse_login("guest: ann ", ""), ("ann".to_string(), true));
+        assert_eq!(parse_login("ann", "?guest=1"), ("ann".to_string(), true));
+        assert_eq!(parse_login("ann", "?guest=0"), ("ann".to_string(), false));
+    }
+
+    #[test]
+    fn bare_guest_prefix_leaves_no_name() {
+        assert_eq!(parse_login("guest:", ""), (String::new(), true));
+        assert_eq!(parse_login("guest:   ", ""), (String::new(), true));
+    }
+}
@@ -0,0 +1,57 @@
+use yew::prelude::*;
+
+use crate::components::user_profile_card::{ProfileSummary, UserProfileCard};
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub profile: ProfileSummary,
+    /// Called with the username when the pill is clicked.
+    pub on_open: Callback<String>,
+}
+
+/// Hue for `name`'s pill, the same on every device.
+pub fn pill_hue(name: &str) -> u32 {
+    // FNV-1a; stable across builds, unlike the std hasher.
+    let hash = name
+        .to_lowercase()
+        .bytes()
+        .fold(0x811c_9dc5u32, |hash, b| (hash ^ b as u32).wrapping_mul(0x0100_0193));
+    hash % 360
+}
+
+/// An `@username` in a message, as a tinted badge that shows a small profile card on hover
+/// and opens the full one on click.
+#[function_component(MentionPill)]
+pub fn mention_pill(props: &Props) -> Html {
+    let hovered = use_state(|| false);
+    let hue = pill_hue(&props.profile.name);
+    let onmouseenter = {
+        let hovered = hovered.clone();
+        Callback::from(move |_| hovered.set(true))
+    };
+    let onmouseleave = {
+        let hovered = hovered.clone();
+        Callback::from(move |_| hovered.set(false))
+    };
+    let onclick = {
+        let name = props.profile.name.clone();
+        props.on_open.reform(move |_| name.clone())
+    };
+
+    html! {
+        <span class="relative inline-block" {onmouseenter} {onmouseleave}>
+            <span
+                {onclick}
+                class="px-1 rounded-full cursor-pointer font-medium"
+                style={format!("background-color: hsl({}, 70%, 90%); color: hsl({}, 60%, 30%)", hue, hue)}
+            >
+                { format!("@{}", props.profile.name) }
+            </span>
+            if *hovered {
+                <span class="absolute z-10 left-0 top-full mt-1">
+                    <UserProfileCard profile={props.profile.clone()} compact=true/>
+                </span>
+            }
+        </span>
+    }
+}
@@ -0,0 +1,211 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gloo_timers::callback::Timeout;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::services::a11y;
+use crate::components::chat::MessageData;
+use crate::components::empty_state::{EmptyState, EmptyStateVariant};
+use crate::components::search_results::format_timestamp;
+use crate::hooks::use_locale::use_locale;
+use crate::services::search::{group_by_channel, highlight, without_filter, SearchQuery, SearchScope, MAX_RESULTS};
+use crate::t;
+
+/// Keystrokes closer together than this are searched as one.
+const DEBOUNCE_MS: u32 = 200;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// The query `results` answer, which trails what's in the box while typing.
+    pub query: SearchQuery,
+    /// Shown under the box when the latest input doesn't parse.
+    pub error: Option<String>,
+    /// Newest first; one more than [`MAX_RESULTS`] means there were too many to show.
+    pub results: Vec<MessageData>,
+    /// Searching the on-device archive as well as what's loaded.
+    pub from_archive: bool,
+    pub scope: SearchScope,
+    pub on_scope: Callback<SearchScope>,
+    pub on_query: Callback<String>,
+    /// Called with the `YYYY-MM-DD` picked from the calendar.
+    pub on_jump_to_date: Callback<String>,
+    /// Called with `(channel, message id)`.
+    pub on_jump: Callback<(String, String)>,
+    pub on_close: Callback<()>,
+}
+
+/// Right-hand panel searching the messages on this device as you type, with `from:`,
+/// `before:`, `after:` and `in:` filters shown as removable chips. With the box empty it
+/// lists recent mentions instead. Searching all channels groups the results by channel.
+#[function_component(MessageSearchPanel)]
+pub fn message_search_panel(props: &Props) -> Html {
+    use_locale();
+    let input = use_node_ref();
+    let debounce: Rc<RefCell<Option<Timeout>>> = use_mut_ref(|| None);
+
+    {
+        let input = input.clone();
+        use_effect_with_deps(
+            move |_| {
+                if let Some(input) = input.cast::<HtmlInputElement>() {
+                    let _ = input.focus();
+                }
+                || ()
+            },
+            (),
+        );
+    }
+
+    let oninput = {
+        let on_query = props.on_query.clone();
+        let debounce = debounce.clone();
+        Callback::from(move |e: InputEvent| {
+            let query = e.target_unchecked_into::<HtmlInputElement>().value();
+            let on_query = on_query.clone();
+            *debounce.borrow_mut() = Some(Timeout::new(DEBOUNCE_MS, move || on_query.emit(query)));
+        })
+    };
+    let jump_to_date = props
+        .on_jump_to_date
+        .reform(|e: Event| e.target_unchecked_into::<HtmlInputElement>().value());
+    let close = props.on_close.reform(|_| ());
+    let searching = !props.query.is_empty();
+    let chips = props
+        .query
+        .filters
+        .iter()
+        .map(|(key, value)| {
+            let onclick = {
+                let input = input.clone();
+                let debounce = debounce.clone();
+                let on_query = props.on_query.clone();
+                let key = *key;
+                Callback::from(move |_| {
+                    if let Some(input) = input.cast::<HtmlInputElement>() {
+                        let rest = without_filter(&input.value(), key);
+                        input.set_value(&rest);
+                        debounce.borrow_mut().take();
+                        on_query.emit(rest);
+                    }
+                })
+            };
+            html! {
+                <span class="inline-flex items-center mr-1 mb-1 pl-2 rounded-full bg-blue-100 text-blue-800 text-xs">
+                    { format!("{}: {}", key.label(), value) }
+                    <button {onclick} title={t!("search.remove_filter")} class="w-5 h-5 rounded-full hover:bg-blue-200 focus-ring">{"×"}</button>
+                </span>
+            }
+        })
+        .collect::<Html>();
+
+    let body = if props.results.is_empty() {
+        if searching {
+            html! {
+                <EmptyState variant={EmptyStateVariant::SearchNoResults} text={t!("search.no_match")}/>
+            }
+        } else {
+            html! { <div class="text-sm text-gray-400">{ t!("search.no_mentions") }</div> }
+        }
+    } else {
+        let view_hit = |hit: &MessageData| {
+            let channel = hit.channel.clone().unwrap_or_default();
+            let id = hit.id.clone();
+            let onclick = props.on_jump.reform(move |_| (channel.clone(), id.clone()));
+            html! {
+                <div {onclick} role="button" tabindex="0" onkeydown={a11y::press_on_key} class="bg-gray-100 dark:bg-gray-700 p-3 rounded-xl cursor-pointer hover:bg-gray-200 dark:hover:bg-gray-600 focus-ring">
+                    <div class="flex text-xs text-gray-400">
+                        <span class="flex-1">{ hit.channel.as_deref().map(|c| format!("# {}", c)).unwrap_or_default() }</span>
+                        <span>{ hit.timestamp.map(format_timestamp).unwrap_or_default() }</span>
+                    </div>
+                    <div class="text-sm font-medium">{ &hit.from }</div>
+                    <div class="text-sm break-words">
+                        {
+                            highlight(&hit.message, &props.query.spans(&hit.message)).into_iter().map(|(part, hit)| {
+                                if hit {
+                                    html! { <mark class="bg-yellow-200 rounded">{ part }</mark> }
+                                } else {
+                                    html! { part }
+                                }
+                            }).collect::<Html>()
+                        }
+                    </div>
+                </div>
+            }
+        };
+        let shown = &props.results[..props.results.len().min(MAX_RESULTS)];
+        if searching && props.scope == SearchScope::All {
+            group_by_channel(shown)
+                .into_iter()
+                .map(|(channel, hits)| {
+                    html! {
+                        <div class="space-y-3">
+                            <div class="text-xs font-semibold text-gray-500">{ format!("# {}", channel) }</div>
+                            { hits.iter().map(view_hit).collect::<Html>() }
+                        </div>
+                    }
+                })
+                .collect::<Html>()
+        } else {
+            shown.iter().map(view_hit).collect::<Html>()
+        }
+    };
+    let scope_button = |scope: SearchScope, label: String| {
+        let active = props.scope == scope;
+        html! {
+            <button
+                onclick={props.on_scope.reform(move |_| scope)}
+                class={classes!("focus-ring", "px-2", "py-1", "rounded-full", if active { "bg-blue-600 text-white" } else { "hover:bg-gray-100 dark:hover:bg-gray-700" })}
+            >
+                { label }
+            </button>
+        }
+    };
+
+    html! {
+        <div class="w-96 flex flex-col h-screen border-l bg-white dark:bg-gray-800">
+            <div class="flex items-center p-3 border-b">
+                <input
+                    ref={input}
+                    type="search"
+                    placeholder={t!("search.placeholder")}
+                    class="flex-1 py-1 px-3 bg-gray-100 dark:bg-gray-700 rounded-full outline-none focus-ring"
+                    {oninput}
+                />
+                <button onclick={close} class="ml-2 w-8 h-8 rounded-full hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring">{"✕"}</button>
+            </div>
+            <div class="flex items-center px-3 pt-2 space-x-1 text-xs">
+                { scope_button(SearchScope::Channel, t!("search.scope.channel")) }
+                { scope_button(SearchScope::All, t!("search.scope.all")) }
+            </div>
+            <label class="flex items-center px-3 pt-2 text-xs text-gray-500">
+                { t!("search.jump_to_date") }
+                <input type="date" onchange={jump_to_date} class="ml-2 px-1 rounded border focus-ring"/>
+            </label>
+            if let Some(error) = &props.error {
+                <div class="px-3 pt-2 text-xs text-red-600">{ error }</div>
+            }
+            if !props.query.filters.is_empty() {
+                <div class="px-3 pt-2 flex flex-wrap">{ chips }</div>
+            }
+            <div class="px-3 pt-2 text-xs text-gray-400">
+                if !searching {
+                    { t!("search.recent_mentions") }
+                } else if props.from_archive {
+                    { t!("search.source.archive") }
+                } else {
+                    { t!("search.source.loaded") }
+                }
+            </div>
+            <div class="flex-1 overflow-auto p-3 space-y-3">
+                { body }
+                if props.results.len() > MAX_RESULTS {
+                    <div class="text-center text-xs text-gray-400">
+                        { t!("search.truncated", count = MAX_RESULTS) }
+                    </div>
+                }
+            </div>
+        </div>
+    }
+}
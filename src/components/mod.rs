@@ -1,2 +1,34 @@
 pub mod chat;
 pub mod login;
+pub mod settings_import_export;
+pub mod channel_browser;
+pub mod image_lightbox;
+pub mod reaction_bar;
+pub mod search_results;
+pub mod dnd_settings;
+pub mod empty_state;
+pub mod toasts;
+pub mod error_boundary;
+pub mod notification_exceptions;
+pub mod watch_words;
+pub mod request_access;
+pub mod mod_log;
+pub mod export_dialog;
+pub mod lazy_image;
+pub mod message_search;
+pub mod app_bar;
+pub mod clear_data_dialog;
+pub mod pin_dialog;
+pub mod stats_dialog;
+pub mod mention_pill;
+pub mod user_profile_card;
+pub mod require_user;
+pub mod settings_panel;
+pub mod shortcut_help;
+pub mod status_bar;
+pub mod modal;
+pub mod video_player;
+pub mod broadcast_form;
+pub mod cooldown_bar;
+pub mod shutdown_banner;
+pub mod thread_panel;
@@ -0,0 +1,92 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::components::chat::ModerationEntry;
+use crate::components::search_results::format_timestamp;
+use crate::hooks::use_locale::use_locale;
+use crate::services::i18n::relative_time;
+use crate::t;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// In the order they arrived.
+    pub entries: Vec<ModerationEntry>,
+}
+
+/// The admin panel's record of kicks, bans, mutes and resolved flags, newest first.
+#[function_component(ModLog)]
+pub fn mod_log(props: &Props) -> Html {
+    use_locale();
+    let action = use_state(|| None::<String>);
+    let target = use_state(String::new);
+
+    let mut actions: Vec<&str> = props.entries.iter().map(|e| e.action.as_str()).collect();
+    actions.sort_unstable();
+    actions.dedup();
+
+    let on_target = {
+        let target = target.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            target.set(input.value());
+        })
+    };
+    let chip = |label: String, value: Option<String>| {
+        let selected = *action == value;
+        let onclick = {
+            let action = action.clone();
+            Callback::from(move |_| action.set(value.clone()))
+        };
+        html! {
+            <button {onclick} class={classes!(
+                "focus-ring", "px-2", "py-0.5", "rounded-full", "border", "text-xs",
+                if selected { "bg-blue-600 border-blue-600 text-white" } else { "bg-white dark:bg-gray-800 hover:bg-gray-100 dark:hover:bg-gray-700" },
+            )}>{ label }</button>
+        }
+    };
+
+    let needle = target.trim().to_lowercase();
+    let shown: Vec<&ModerationEntry> = props
+        .entries
+        .iter()
+        .rev()
+        .filter(|e| action.is_none() || action.as_ref() == Some(&e.action))
+        .filter(|e| needle.is_empty() || e.target.to_lowercase().contains(&needle))
+        .collect();
+
+    html! {
+        <div>
+            <div class="flex flex-wrap gap-1">
+                { chip(t!("mod_log.all"), None) }
+                { actions.iter().map(|a| chip(a.to_string(), Some(a.to_string()))).collect::<Html>() }
+            </div>
+            <input
+                oninput={on_target}
+                value={(*target).clone()}
+                placeholder={t!("mod_log.filter_user")}
+                class="mt-2 w-full rounded-full bg-gray-100 dark:bg-gray-700 px-3 py-1 text-sm focus:outline-none focus-ring"
+            />
+            <div class="mt-2 space-y-1 max-h-64 overflow-auto">
+                if props.entries.is_empty() {
+                    <div class="text-xs text-gray-400">{ t!("mod_log.empty") }</div>
+                } else if shown.is_empty() {
+                    <div class="text-xs text-gray-400">{ t!("mod_log.no_match") }</div>
+                }
+                {
+                    shown.into_iter().map(|e| html! {
+                        <div class="bg-gray-100 dark:bg-gray-700 rounded-lg p-2 text-xs">
+                            <div>
+                                <span class="font-semibold">{ &e.by }</span>
+                                { format!(" {} ", e.action) }
+                                <span class="font-semibold">{ &e.target }</span>
+                            </div>
+                            <div class="text-gray-400" title={format_timestamp(e.timestamp as f64)}>
+                                { relative_time(e.timestamp as f64, js_sys::Date::now()) }
+                            </div>
+                        </div>
+                    }).collect::<Html>()
+                }
+            </div>
+        </div>
+    }
+}
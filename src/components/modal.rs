@@ -0,0 +1,31 @@
+use yew::prelude::*;
+
+use crate::hooks::use_focus_trap::use_focus_trap;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// Classes for the backdrop, which is also the element focus is trapped in.
+    #[prop_or_default]
+    pub class: Classes,
+    /// What a screen reader announces the dialog as.
+    #[prop_or_default]
+    pub label: Option<String>,
+    /// Clicks on the backdrop, and on anything inside that doesn't stop them.
+    #[prop_or_default]
+    pub onclick: Option<Callback<MouseEvent>>,
+    #[prop_or_default]
+    pub children: Children,
+}
+
+/// A dialog backdrop that keeps keyboard focus inside while it's open, for dialogs drawn
+/// inline by struct components, which can't use the hook themselves.
+#[function_component(Modal)]
+pub fn modal(props: &Props) -> Html {
+    let trap = use_focus_trap(true);
+
+    html! {
+        <div ref={trap} role="dialog" aria-modal="true" aria-label={props.label.clone()} onclick={props.onclick.clone()} class={props.class.clone()}>
+            { for props.children.iter() }
+        </div>
+    }
+}
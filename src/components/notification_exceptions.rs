@@ -0,0 +1,51 @@
+use yew::prelude::*;
+
+use crate::hooks::use_locale::use_locale;
+use crate::services::notifications::UserMute;
+use crate::t;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub muted: Vec<(String, UserMute)>,
+    /// `None` unmutes the user.
+    pub on_change: Callback<(String, Option<UserMute>)>,
+}
+
+/// Lists users whose messages don't notify, with a way to undo it.
+#[function_component(NotificationExceptions)]
+pub fn notification_exceptions(props: &Props) -> Html {
+    use_locale();
+    if props.muted.is_empty() {
+        return html! {};
+    }
+    html! {
+        <div class="m-3 text-sm">
+            <div class="font-semibold">{ t!("notification_exceptions.title") }</div>
+            {
+                props.muted.iter().map(|(user, mute)| {
+                    let toggle_mentions = {
+                        let user = user.clone();
+                        let mute = *mute;
+                        props.on_change.reform(move |_| (user.clone(), Some(UserMute {
+                            allow_mentions: !mute.allow_mentions,
+                        })))
+                    };
+                    let unmute = {
+                        let user = user.clone();
+                        props.on_change.reform(move |_| (user.clone(), None))
+                    };
+                    html! {
+                        <div class="mt-1 flex items-center">
+                            <span class="flex-1 truncate">{ user }</span>
+                            <label class="mr-2 text-xs text-gray-500" title={t!("notification_exceptions.allow_mentions")}>
+                                <input type="checkbox" checked={mute.allow_mentions} onchange={toggle_mentions} class="mr-1 focus-ring"/>
+                                {"@"}
+                            </label>
+                            <button onclick={unmute} title={t!("notification_exceptions.unmute")} aria-label={t!("notification_exceptions.unmute")} class="w-6 h-6 rounded-full hover:bg-gray-200 dark:hover:bg-gray-600 focus-ring">{"✕"}</button>
+                        </div>
+                    }
+                }).collect::<Html>()
+            }
+        </div>
+    }
+}
@@ -0,0 +1,64 @@
+use yew::prelude::*;
+
+use crate::hooks::use_focus_trap::use_focus_trap;
+use crate::hooks::use_locale::use_locale;
+use crate::services::a11y;
+use crate::components::chat::PinType;
+use crate::t;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// The start of the message being pinned, to show what it is.
+    pub excerpt: String,
+    pub on_pin: Callback<PinType>,
+    pub on_close: Callback<()>,
+}
+
+/// Confirms pinning a message and asks which kind of pin it should be.
+#[function_component(PinDialog)]
+pub fn pin_dialog(props: &Props) -> Html {
+    use_locale();
+    let trap = use_focus_trap(true);
+    let pin_type = use_state(|| PinType::Permanent);
+
+    let option = |value: PinType, label: String, hint: String| {
+        let onclick = {
+            let pin_type = pin_type.clone();
+            Callback::from(move |_| pin_type.set(value))
+        };
+        html! {
+            <div {onclick} role="radio" aria-checked={(*pin_type == value).to_string()} tabindex="0" onkeydown={a11y::press_on_key} class="flex items-start px-3 py-2 rounded-lg cursor-pointer hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring">
+                <input type="radio" checked={*pin_type == value} tabindex="-1" aria-hidden="true" class="mt-1 mr-2 pointer-events-none"/>
+                <div>
+                    <div class="font-medium">{ label }</div>
+                    <div class="text-xs text-gray-400">{ hint }</div>
+                </div>
+            </div>
+        }
+    };
+    let pin = {
+        let pin_type = pin_type.clone();
+        props.on_pin.reform(move |_| *pin_type)
+    };
+    let close = props.on_close.reform(|_| ());
+
+    html! {
+        <div ref={trap} role="dialog" aria-modal="true" aria-label={t!("dialog.pin_message")} class="fixed inset-0 z-10 flex items-center justify-center bg-black bg-opacity-50">
+            <div class="w-96 flex flex-col bg-white dark:bg-gray-800 rounded-xl overflow-hidden text-sm">
+                <div class="flex items-center p-3 border-b">
+                    <div class="flex-1 text-lg font-semibold">{ t!("dialog.pin_message") }</div>
+                    <button onclick={close.clone()} class="text-gray-400 hover:text-gray-800 focus-ring">{"×"}</button>
+                </div>
+                <div class="px-3 pt-3 text-gray-600 dark:text-gray-300 truncate">{ &props.excerpt }</div>
+                <div class="p-2">
+                    { option(PinType::Permanent, t!("pin_dialog.permanent"), t!("pin_dialog.permanent_hint")) }
+                    { option(PinType::DailyHighlight, t!("pin_dialog.highlight"), t!("pin_dialog.highlight_hint")) }
+                </div>
+                <div class="flex justify-end p-3 space-x-2">
+                    <button onclick={close} class="px-3 py-1 rounded-full hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring">{ t!("common.cancel") }</button>
+                    <button onclick={pin} class="px-3 py-1 rounded-full bg-blue-600 text-white focus-ring">{ t!("pins.pin") }</button>
+                </div>
+            </div>
+        </div>
+    }
+}
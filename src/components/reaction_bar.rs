@@ -0,0 +1,179 @@
+use yew::prelude::*;
+
+use crate::hooks::use_locale::use_locale;
+use crate::services::skin_tone::{self, SkinTone};
+use crate::services::viewport::TOUCH_TARGET;
+use crate::t;
+
+/// Always offered under a message, whether or not anyone has used them yet.
+const QUICK_REACTIONS: [&str; 6] = ["👍", "❤️", "😂", "😮", "😢", "👏"];
+
+/// What the "+" picker offers on top of the quick reactions.
+const PICKER_EMOJIS: [&str; 24] = [
+    "😀", "😁", "😅", "😊", "😍", "😎", "🤔", "😴", "😡", "🤯", "🥳", "🙈",
+    "🙏", "💪", "👀", "🔥", "✨", "🎉", "💯", "✅", "❌", "🚀", "☕", "🍕",
+];
+
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub reactions: Vec<(String, Vec<String>)>,
+    pub current_user: String,
+    /// Show who reacted without letting the user react themselves.
+    #[prop_or_default]
+    pub read_only: bool,
+    /// Frozen by an admin: everything shows, nothing can be clicked.
+    #[prop_or_default]
+    pub locked: bool,
+    /// Most distinct emoji the message may collect, set per room by the server.
+    #[prop_or_default]
+    pub reaction_cap: Option<u32>,
+    /// No burst or count-up, for users who asked their system for less motion.
+    #[prop_or_default]
+    pub reduced_motion: bool,
+    /// Applied to the quick reactions and the picker; reactions already on the message keep theirs.
+    #[prop_or_default]
+    pub skin_tone: SkinTone,
+    pub on_react: Callback<String>,
+    /// A tone picked from the picker's corner, to save as the user's preference.
+    #[prop_or_default]
+    pub on_skin_tone: Callback<SkinTone>,
+}
+
+#[function_component(ReactionBar)]
+pub fn reaction_bar(props: &Props) -> Html {
+    use_locale();
+    let picker_open = use_state(|| false);
+    // The emoji playing its burst animation, cleared once the animation ends.
+    let bursting = use_state(|| None::<String>);
+
+    let react = {
+        let on_react = props.on_react.clone();
+        let bursting = bursting.clone();
+        let picker_open = picker_open.clone();
+        Callback::from(move |emoji: String| {
+            bursting.set(Some(emoji.clone()));
+            picker_open.set(false);
+            on_react.emit(emoji);
+        })
+    };
+
+    // Nothing new to add to a locked message, so only the emoji already on it show.
+    let frozen = props.read_only || props.locked;
+    let mut shown: Vec<String> = if frozen {
+        vec![]
+    } else {
+        QUICK_REACTIONS.iter().map(|emoji| skin_tone::apply(emoji, props.skin_tone)).collect()
+    };
+    for (emoji, _) in &props.reactions {
+        if !shown.contains(emoji) {
+            shown.push(emoji.clone());
+        }
+    }
+
+    // Once the message is full, only someone who already reacted may pick something new;
+    // anyone can still take their own reaction back.
+    let capped = props.reaction_cap.map_or(false, |cap| {
+        props.reactions.len() >= cap as usize
+            && !props.reactions.iter().any(|(_, users)| users.contains(&props.current_user))
+    });
+
+    let button = |emoji: &str| {
+        let users = props
+            .reactions
+            .iter()
+            .find(|(e, _)| e == emoji)
+            .map(|(_, users)| users.as_slice())
+            .unwrap_or_default();
+        let mine = users.contains(&props.current_user);
+        let onclick = {
+            let react = react.clone();
+            let emoji = emoji.to_string();
+            Callback::from(move |_| react.emit(emoji.clone()))
+        };
+        let onanimationend = {
+            let bursting = bursting.clone();
+            Callback::from(move |_| bursting.set(None))
+        };
+        let burst = bursting.as_deref() == Some(emoji) && !props.reduced_motion;
+        html! {
+            <button
+                {onclick}
+                disabled={frozen || (capped && users.is_empty())}
+                aria-pressed={mine.to_string()}
+                title={if capped && users.is_empty() { t!("reactions.cap_reached") } else { users.join(", ") }}
+                class={classes!(
+                    "focus-ring", "flex", "items-center", "justify-center", "px-2", "py-1", "text-sm", "rounded-full", "border", TOUCH_TARGET,
+                    (!props.reduced_motion).then_some("transition"),
+                    if mine { "bg-blue-100 border-blue-400 hover:bg-blue-200" } else { "bg-white dark:bg-gray-800 hover:bg-gray-200 dark:hover:bg-gray-600" },
+                )}
+            >
+                <span {onanimationend} class={classes!("inline-block", burst.then_some("burst"))}>{ emoji }</span>
+                if !users.is_empty() {
+                    // Keyed by the count so every change remounts the badge and replays the count-up.
+                    <span key={users.len()} class={classes!("ml-1", "text-xs", "font-semibold", (!props.reduced_motion).then_some("count-up"))}>{ users.len() }</span>
+                }
+            </button>
+        }
+    };
+
+    let toggle_picker = {
+        let picker_open = picker_open.clone();
+        Callback::from(move |_| picker_open.set(!*picker_open))
+    };
+
+    html! {
+        <div class="relative mt-2 flex flex-wrap gap-1">
+            { shown.iter().map(|emoji| button(emoji)).collect::<Html>() }
+            if props.locked {
+                <span title={t!("edit.locked")} class="absolute -top-2 -right-2 text-xs">{"🔒"}</span>
+            }
+            if !frozen {
+                <button
+                    onclick={toggle_picker}
+                    aria-expanded={picker_open.to_string()}
+                    disabled={capped}
+                    title={if capped { t!("reactions.cap_reached") } else { t!("reactions.more") }}
+                    class={classes!("focus-ring", "px-2", "py-1", "text-sm", "rounded-full", "border", TOUCH_TARGET,
+                        "bg-white", "dark:bg-gray-800", "hover:bg-gray-200", "dark:hover:bg-gray-600", "disabled:opacity-50", "disabled:cursor-not-allowed")}
+                >
+                    {"+"}
+                </button>
+            }
+            if *picker_open && !frozen && !capped {
+                <div class="absolute z-10 top-full mt-1 grid grid-cols-6 gap-1 p-2 bg-white dark:bg-gray-800 rounded-lg border shadow-lg">
+                    {
+                        PICKER_EMOJIS.iter().map(|emoji| {
+                            let emoji = skin_tone::apply(emoji, props.skin_tone);
+                            let react = react.clone();
+                            let onclick = {
+                                let emoji = emoji.clone();
+                                Callback::from(move |_| react.emit(emoji.clone()))
+                            };
+                            html! { <button {onclick} class="w-10 h-10 md:w-8 md:h-8 rounded hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring">{ emoji }</button> }
+                        }).collect::<Html>()
+                    }
+                    <div class="col-span-6 flex justify-end gap-1 pt-1 border-t">
+                        {
+                            SkinTone::ALL.iter().map(|&tone| {
+                                let onclick = props.on_skin_tone.reform(move |_| tone);
+                                html! {
+                                    <button
+                                        {onclick}
+                                        aria-label={tone.label()}
+                                        aria-pressed={(tone == props.skin_tone).to_string()}
+                                        title={tone.label()}
+                                        class={classes!("w-8", "h-8", "md:w-6", "md:h-6", "text-xs", "rounded", "focus-ring",
+                                            if tone == props.skin_tone { "bg-blue-100" } else { "hover:bg-gray-100 dark:hover:bg-gray-700" })}
+                                    >
+                                        { tone.swatch() }
+                                    </button>
+                                }
+                            }).collect::<Html>()
+                        }
+                    </div>
+                </div>
+            }
+        </div>
+    }
+}
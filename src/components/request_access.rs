@@ -0,0 +1,62 @@
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+
+use crate::hooks::use_focus_trap::use_focus_trap;
+use crate::hooks::use_locale::use_locale;
+use crate::t;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub on_submit: Callback<String>,
+    pub on_close: Callback<()>,
+}
+
+/// Lets a read-only guest write to the admins asking to be allowed to post.
+#[function_component(RequestAccessForm)]
+pub fn request_access_form(props: &Props) -> Html {
+    use_locale();
+    let trap = use_focus_trap(true);
+    let reason = use_state(String::new);
+
+    let oninput = {
+        let reason = reason.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlTextAreaElement = e.target_unchecked_into();
+            reason.set(input.value());
+        })
+    };
+    let onsubmit = {
+        let reason = reason.clone();
+        let on_submit = props.on_submit.clone();
+        Callback::from(move |e: FocusEvent| {
+            e.prevent_default();
+            on_submit.emit(reason.trim().to_string());
+        })
+    };
+    let close = props.on_close.reform(|_| ());
+
+    html! {
+        <div ref={trap} role="dialog" aria-modal="true" aria-label={t!("dialog.request_access")} class="fixed inset-0 z-10 flex items-center justify-center bg-black bg-opacity-50">
+            <form {onsubmit} class="w-96 flex flex-col bg-white dark:bg-gray-800 rounded-xl overflow-hidden">
+                <div class="flex items-center p-3 border-b">
+                    <div class="flex-1 text-lg font-semibold">{ t!("dialog.request_access") }</div>
+                    <button type="button" onclick={close.clone()} class="text-gray-400 hover:text-gray-800 focus-ring">{"×"}</button>
+                </div>
+                <div class="p-3 text-sm text-gray-500">
+                    { t!("request_access.intro") }
+                </div>
+                <textarea
+                    {oninput}
+                    value={(*reason).clone()}
+                    rows="4"
+                    placeholder={t!("request_access.placeholder")}
+                    class="mx-3 rounded-lg bg-gray-100 dark:bg-gray-700 px-3 py-2 text-sm focus:outline-none focus-ring"
+                />
+                <div class="flex justify-end p-3 space-x-2">
+                    <button type="button" onclick={close} class="px-3 py-1 rounded-full text-sm hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring">{ t!("common.cancel") }</button>
+                    <button type="submit" disabled={reason.trim().is_empty()} class="px-3 py-1 rounded-full text-sm bg-blue-600 text-white disabled:opacity-40 focus-ring">{ t!("composer.send") }</button>
+                </div>
+            </form>
+        </div>
+    }
+}
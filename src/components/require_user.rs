@@ -0,0 +1,153 @@
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::services::uri_component;
+use crate::services::username::validate_username;
+use crate::{Route, UserCtx};
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    #[prop_or_default]
+    pub children: Children,
+}
+
+fn decode(text: &str) -> Option<String> {
+    uri_component::decode(&text.replace('+', " "))
+}
+
+/// The `key=value` pairs of a `?`-prefixed or bare query string, decoded.
+fn query_pairs(search: &str) -> Vec<(String, String)> {
+    search
+        .trim_start_matches('?')
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .filter_map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Some((decode(key)?, decode(value)?))
+        })
+        .collect()
+}
+
+/// The query to open the chat route with after logging in, from the `next` parameter on
+/// the login page. Anything that doesn't lead back into the chat is ignored, so `next`
+/// can't send anyone off the site.
+pub fn next_chat_query(login_search: &str) -> Option<Vec<(String, String)>> {
+    next_query(login_search, |path| Route::recognize(path) == Some(Route::Chat))
+}
+
+/// [`next_chat_query`] with the route check passed in, since recognizing a route reads the
+/// page's `<base>`.
+fn next_query(login_search: &str, is_chat: impl Fn(&str) -> bool) -> Option<Vec<(String, String)>> {
+    let next = query_pairs(login_search).into_iter().find(|(key, _)| key == "next")?.1;
+    let (path, search) = next.split_once('?').unwrap_or((&next, ""));
+    if !is_chat(path) {
+        return None;
+    }
+    let pairs = query_pairs(search);
+    (!pairs.is_empty()).then_some(pairs)
+}
+
+/// Why the login page was sent back to, from its `error` parameter.
+pub fn login_error(login_search: &str) -> Option<String> {
+    query_pairs(login_search)
+        .into_iter()
+        .find(|(key, _)| key == "error")
+        .map(|(_, value)| value)
+        .filter(|value| !value.is_empty())
+}
+
+/// Renders its children only once someone has logged in with a valid name. Otherwise it
+/// sends the browser to the login page with where it was headed as `next`, and what was
+/// wrong with the name as `error`, so nothing underneath, e.g. the chat's socket, is ever
+/// set up for a missing or bogus user.
+#[function_component(RequireUser)]
+pub fn require_user(props: &Props) -> Html {
+    let user = use_context::<UserCtx>();
+    let history = use_history();
+    let location = use_location();
+    // No name at all is just someone who hasn't logged in yet.
+    let (logged_in, error) = match user.as_ref().map(|user| (user.username.is_empty(), validate_username(&user.username))) {
+        Some((false, Ok(_))) => (true, None),
+        Some((false, Err(e))) => (false, Some(e.to_string())),
+        _ => (false, None),
+    };
+
+    use_effect_with_deps(
+        move |(logged_in, error)| {
+            if !logged_in {
+                let next = location
+                    .map(|location| format!("{}{}", location.pathname(), location.search()))
+                    .unwrap_or_else(|| Route::Chat.to_path());
+                let mut query = vec![("next", next)];
+                query.extend(error.clone().map(|error| ("error", error)));
+                match history {
+                    Some(history) => {
+                        if let Err(e) = history.replace_with_query(Route::Login, query) {
+                            log::error!("failed to redirect to the login page: {:?}", e);
+                        }
+                    }
+                    None => log::error!("no router to redirect to the login page with"),
+                }
+            }
+            || ()
+        },
+        (logged_in, error),
+    );
+
+    if logged_in {
+        html! { <>{ for props.children.iter() }</> }
+    } else {
+        html! {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The login page's query string as the guard's redirect writes it.
+    fn login_search(pairs: &[(&str, &str)]) -> String {
+        let pairs: Vec<String> = pairs
+            .iter()
+            .map(|(key, value)| format!("{}={}", uri_component::encode(key), uri_component::encode(value)))
+            .collect();
+        format!("?{}", pairs.join("&"))
+    }
+
+    /// [`next_chat_query`] against the chat route as declared, with no `<base>`.
+    fn next_for(login_search: &str) -> Option<Vec<(String, String)>> {
+        next_query(login_search, |path| path == Route::Chat.to_path())
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Option<Vec<(String, String)>> {
+        Some(pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect())
+    }
+
+    #[test]
+    fn next_round_trips_through_the_login_page() {
+        let search = login_search(&[("next", "/chat?channel=c%2B%2B&message=42")]);
+        assert_eq!(next_for(&search), pairs(&[("channel", "c++"), ("message", "42")]));
+    }
+
+    #[test]
+    fn form_encoded_spaces_survive() {
+        assert_eq!(next_for("?next=%2Fchat%3Fchannel%3Dsome+room"), pairs(&[("channel", "some room")]));
+    }
+
+    #[test]
+    fn next_only_leads_back_into_the_chat() {
+        assert_eq!(next_for(&login_search(&[("next", "/chat")])), None);
+        assert_eq!(next_for(&login_search(&[("next", "/?channel=x")])), None);
+        assert_eq!(next_for(&login_search(&[("next", "https://example.com/chat?channel=x")])), None);
+        assert_eq!(next_for(&login_search(&[("next", "//example.com/chat?channel=x")])), None);
+        assert_eq!(next_for(""), None);
+    }
+
+    #[test]
+    fn error_comes_back_decoded() {
+        let search = login_search(&[("next", "/chat"), ("error", "Names can't contain spaces.")]);
+        assert_eq!(login_error(&search).as_deref(), Some("Names can't contain spaces."));
+        assert_eq!(login_error("?error="), None);
+        assert_eq!(login_error("?next=%2Fchat"), None);
+    }
+}
@@ -0,0 +1,121 @@
+use yew::prelude::*;
+
+use crate::services::a11y;
+use crate::components::chat::MessageData;
+use crate::components::empty_state::{EmptyState, EmptyStateVariant};
+use crate::hooks::use_locale::use_locale;
+use crate::services::i18n;
+use crate::t;
+
+/// Characters of context kept on either side of the first match.
+const SNIPPET_CONTEXT: usize = 40;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub query: String,
+    /// The room searched, for hits the server didn't give a channel.
+    pub room: String,
+    /// `None` while the server is still searching.
+    pub hits: Option<Vec<MessageData>>,
+    /// The server never answered.
+    pub failed: bool,
+    /// Called with `(channel, message id)`.
+    pub on_jump: Callback<(String, String)>,
+    pub on_close: Callback<()>,
+}
+
+/// Cuts `text` down to the area around the first case-insensitive match of `query`,
+/// split into `(text, is_match)` parts.
+pub fn highlight_snippet(text: &str, query: &str) -> Vec<(String, bool)> {
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let needle: Vec<char> = query.trim().chars().flat_map(char::to_lowercase).collect();
+    // Lowercasing can change the length of some characters; fall back to no highlight then.
+    let found = if needle.is_empty() || lower.len() != chars.len() {
+        None
+    } else {
+        lower.windows(needle.len()).position(|w| w == needle.as_slice())
+    };
+    let at = match found {
+        Some(at) => at,
+        None => return vec![(text.to_string(), false)],
+    };
+    let end = at + needle.len();
+    let from = at.saturating_sub(SNIPPET_CONTEXT);
+    let to = (end + SNIPPET_CONTEXT).min(chars.len());
+
+    let mut before: String = chars[from..at].iter().collect();
+    if from > 0 {
+        before.insert(0, '…');
+    }
+    let mut after: String = chars[end..to].iter().collect();
+    if to < chars.len() {
+        after.push('…');
+    }
+    vec![
+        (before, false),
+        (chars[at..end].iter().collect(), true),
+        (after, false),
+    ]
+}
+
+pub fn format_timestamp(ms: f64) -> String {
+    i18n::format_datetime(ms)
+}
+
+#[function_component(SearchResultsPanel)]
+pub fn search_results_panel(props: &Props) -> Html {
+    use_locale();
+    let close = props.on_close.reform(|_| ());
+
+    let body = match &props.hits {
+        None if props.failed => html! {
+            <EmptyState variant={EmptyStateVariant::Error} text={t!("search.unavailable")}/>
+        },
+        None => html! { <EmptyState variant={EmptyStateVariant::Loading}/> },
+        Some(hits) if hits.is_empty() => html! {
+            <EmptyState
+                variant={EmptyStateVariant::SearchNoResults}
+                text={t!("search.no_match_query", query = props.query)}
+            />
+        },
+        Some(hits) => hits
+            .iter()
+            .map(|hit| {
+                let channel = hit.channel.clone().unwrap_or_else(|| props.room.clone());
+                let id = hit.id.clone();
+                let onclick = props.on_jump.reform(move |_| (channel.clone(), id.clone()));
+                html! {
+                    <div {onclick} role="button" tabindex="0" onkeydown={a11y::press_on_key} class="bg-gray-100 dark:bg-gray-700 p-3 rounded-xl cursor-pointer hover:bg-gray-200 dark:hover:bg-gray-600 focus-ring">
+                        <div class="flex text-xs text-gray-400">
+                            <span class="flex-1">{ hit.channel.as_deref().map(|c| format!("# {}", c)).unwrap_or_default() }</span>
+                            <span>{ hit.timestamp.map(format_timestamp).unwrap_or_default() }</span>
+                        </div>
+                        <div class="text-sm font-medium">{ &hit.from }</div>
+                        <div class="text-base">
+                            {
+                                highlight_snippet(&hit.message, &props.query).into_iter().map(|(part, hit)| {
+                                    if hit {
+                                        html! { <mark class="bg-yellow-200 rounded">{ part }</mark> }
+                                    } else {
+                                        html! { part }
+                                    }
+                                }).collect::<Html>()
+                            }
+                        </div>
+                    </div>
+                }
+            })
+            .collect::<Html>(),
+    };
+
+    html! {
+        <div class="absolute inset-0 z-10 flex flex-col bg-white dark:bg-gray-800">
+            <div class="flex items-center p-3 border-b">
+                <span class="flex-1 font-semibold">{ t!("search.results_for", query = props.query) }</span>
+                <button onclick={close} aria-label={t!("common.close")} class="w-8 h-8 rounded-full hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring">{"✕"}</button>
+            </div>
+            <div class="flex-1 overflow-auto p-4 space-y-3">{ body }</div>
+        </div>
+    }
+}
@@ -0,0 +1,88 @@
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::hooks::use_locale::use_locale;
+use crate::services::download::download_text;
+use crate::services::settings::{FieldError, ImportError, UserSettings};
+use crate::t;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub settings: UserSettings,
+    pub on_import: Callback<UserSettings>,
+    /// A file that was refused outright, described for the user.
+    pub on_reject: Callback<String>,
+}
+
+#[function_component(SettingsImportExport)]
+pub fn settings_import_export(props: &Props) -> Html {
+    use_locale();
+    let errors = use_state(Vec::<FieldError>::new);
+
+    let export = {
+        let settings = props.settings.clone();
+        Callback::from(move |_| {
+            download_text("yewchat-settings.json", "application/json", &settings.to_export_json())
+        })
+    };
+
+    let import = {
+        let settings = props.settings.clone();
+        let on_import = props.on_import.clone();
+        let on_reject = props.on_reject.clone();
+        let errors = errors.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let file = match input.files().and_then(|files| files.get(0)) {
+                Some(file) => file,
+                None => return,
+            };
+            input.set_value("");
+
+            let settings = settings.clone();
+            let on_import = on_import.clone();
+            let on_reject = on_reject.clone();
+            let errors = errors.clone();
+            spawn_local(async move {
+                let text = match JsFuture::from(file.text()).await {
+                    Ok(text) => text.as_string().unwrap_or_default(),
+                    Err(e) => {
+                        log::error!("failed to read settings file: {:?}", e);
+                        return;
+                    }
+                };
+                match settings.merge_import(&text) {
+                    Ok(merged) => {
+                        errors.set(vec![]);
+                        on_import.emit(merged);
+                    }
+                    Err(ImportError::UnsupportedVersion(version)) => {
+                        errors.set(vec![]);
+                        on_reject.emit(t!("settings.import_unsupported", version = version));
+                    }
+                    Err(ImportError::Invalid(field_errors)) => errors.set(field_errors),
+                }
+            });
+        })
+    };
+
+    html! {
+        <div class="m-3 text-sm">
+            <div class="flex space-x-2">
+                <button onclick={export} class="flex-1 bg-white dark:bg-gray-800 rounded-lg p-2 border hover:bg-gray-200 dark:hover:bg-gray-600 focus-ring">{ t!("settings.export") }</button>
+                <label class="flex-1 bg-white dark:bg-gray-800 rounded-lg p-2 border text-center cursor-pointer hover:bg-gray-200 dark:hover:bg-gray-600">
+                    { t!("settings.import") }
+                    <input type="file" accept="application/json" class="hidden" onchange={import}/>
+                </label>
+            </div>
+            {
+                errors.iter().map(|e| html! {
+                    <div class="mt-1 text-xs text-red-600">
+                        <span class="font-semibold">{ &e.field }</span>{": "}{ &e.message }
+                    </div>
+                }).collect::<Html>()
+            }
+        </div>
+    }
+}
@@ -0,0 +1,347 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::hooks::use_focus_trap::use_focus_trap;
+use crate::components::chat::{format_megabytes, QuotaInfo};
+use crate::components::dnd_settings::DndSettingsPanel;
+use crate::components::notification_exceptions::NotificationExceptions;
+use crate::components::settings_import_export::SettingsImportExport;
+use crate::components::watch_words::WatchWordsPanel;
+use crate::hooks::use_locale::use_locale;
+use crate::services::avatar;
+use crate::services::display::{Density, FontSize};
+use crate::services::i18n::{self, Locale};
+use crate::services::settings::UserSettings;
+use crate::services::skin_tone::SkinTone;
+use crate::services::theme::Theme;
+use crate::t;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub settings: UserSettings,
+    /// Do Not Disturb is in effect right now.
+    pub dnd_active: bool,
+    /// The browser refuses desktop notifications for this site.
+    pub notifications_blocked: bool,
+    /// A transcript is being kept, so there is one to download.
+    pub has_transcript: bool,
+    /// Storage and message allowance, on deployments that set one.
+    #[prop_or_default]
+    pub quota: Option<QuotaInfo>,
+    /// Every change, as the whole new settings; applied as soon as it is made.
+    pub on_change: Callback<UserSettings>,
+    /// An imported file that was refused outright, described for the user.
+    pub on_reject: Callback<String>,
+    pub on_download_transcript: Callback<()>,
+    pub on_clear_data: Callback<()>,
+    pub on_close: Callback<()>,
+}
+
+/// A callback turning what a control reports into the whole new settings, via `apply`.
+fn update<T: 'static>(
+    settings: &UserSettings,
+    on_change: &Callback<UserSettings>,
+    apply: fn(&mut UserSettings, T),
+) -> Callback<T> {
+    let settings = settings.clone();
+    let on_change = on_change.clone();
+    Callback::from(move |value| {
+        let mut settings = settings.clone();
+        apply(&mut settings, value);
+        on_change.emit(settings);
+    })
+}
+
+/// `settings` with the flag `field` picks out turned the other way.
+fn toggled(settings: &UserSettings, field: fn(&mut UserSettings) -> &mut bool) -> UserSettings {
+    let mut settings = settings.clone();
+    let flag = field(&mut settings);
+    *flag = !*flag;
+    settings
+}
+
+/// A row of buttons, one per option, with `current` lit up.
+fn choice<T: Copy + PartialEq + 'static>(
+    name: String,
+    options: &[T],
+    current: T,
+    label: fn(T) -> String,
+    on_pick: Callback<T>,
+) -> Html {
+    html! {
+        <>
+        <div class="mx-3 mt-2 text-sm">{ name }</div>
+        <div class="mx-3 mt-1 mb-2 flex rounded-lg border overflow-hidden text-sm">
+            { for options.iter().map(|&option| html! {
+                <button
+                    onclick={on_pick.reform(move |_| option)}
+                    class={classes!(
+                        "focus-ring", "flex-1", "p-1",
+                        if option == current { "bg-blue-600 text-white" } else { "hover:bg-gray-100 dark:hover:bg-gray-700" },
+                    )}
+                >
+                    { label(option) }
+                </button>
+            }) }
+        </div>
+        </>
+    }
+}
+
+/// A labelled bar filled to `used` out of `limit`, turning red close to the limit.
+fn meter(label: String, detail: String, used: f64, limit: f64) -> Html {
+    let fraction = (used / limit).clamp(0.0, 1.0);
+    html! {
+        <div class="mx-3 my-2 text-sm">
+            <div class="flex justify-between">
+                <span>{ label }</span>
+                <span class="text-xs text-gray-500">{ detail }</span>
+            </div>
+            <div class="mt-1 h-2 rounded-full bg-gray-200 dark:bg-gray-600 overflow-hidden">
+                <div
+                    style={format!("width: {:.1}%", fraction * 100.0)}
+                    class={classes!("h-full", if fraction > 0.9 { "bg-red-500" } else { "bg-blue-600" })}
+                ></div>
+            </div>
+        </div>
+    }
+}
+
+fn section(title: String, body: Html) -> Html {
+    html! {
+        <div class="py-2 border-b">
+            <div class="px-3 pt-1 text-xs font-semibold uppercase tracking-wide text-gray-500">{ title }</div>
+            { body }
+        </div>
+    }
+}
+
+/// Everything the user can set, in a slide-over from the right. There is no save button:
+/// each change goes out through `on_change` as it is made.
+#[function_component(SettingsPanel)]
+pub fn settings_panel(props: &Props) -> Html {
+    use_locale();
+    let trap = use_focus_trap(true);
+    let settings = &props.settings;
+    let on_change = &props.on_change;
+    // A checkbox for the flag `field` picks out.
+    let checkbox = |label: String, field: fn(&mut UserSettings) -> &mut bool| {
+        let checked = *field(&mut settings.clone());
+        let onchange = {
+            let settings = settings.clone();
+            let on_change = on_change.clone();
+            Callback::from(move |_: Event| on_change.emit(toggled(&settings, field)))
+        };
+        html! {
+            <label class="m-3 flex items-center text-sm">
+                <input type="checkbox" {checked} {onchange} class="mr-2 focus-ring"/>
+                { label }
+            </label>
+        }
+    };
+
+    // Without a template of their own, a broken build default is what they're getting.
+    let avatar_error = avatar::validate(settings.avatar_template.as_deref().unwrap_or(avatar::DEFAULT_TEMPLATE)).err();
+    let on_avatar_template = update(settings, on_change, |s, template: Option<String>| s.avatar_template = template).reform(|e: Event| {
+        let input: HtmlInputElement = e.target_unchecked_into();
+        Some(input.value().trim().to_string()).filter(|template| !template.is_empty())
+    });
+    let appearance = html! {
+        <>
+            { choice(
+                t!("settings.language"),
+                &Locale::ALL,
+                settings.locale.unwrap_or_else(i18n::locale),
+                |locale| locale.label().to_string(),
+                update(settings, on_change, |s, locale| s.locale = Some(locale)),
+            ) }
+            { choice(t!("settings.theme"), &Theme::ALL, settings.theme, Theme::label, update(settings, on_change, |s, theme| s.theme = theme)) }
+            { choice(t!("settings.density"), &Density::ALL, settings.density, Density::label, update(settings, on_change, |s, density| s.density = density)) }
+            { choice(t!("settings.font_size"), &FontSize::ALL, settings.font_size, FontSize::label, update(settings, on_change, |s, size| s.font_size = size)) }
+            { choice(t!("settings.skin_tone"), &SkinTone::ALL, settings.skin_tone, SkinTone::swatch, update(settings, on_change, |s, tone| s.skin_tone = tone)) }
+            <label class="block mx-3 mt-2 mb-2 text-sm">
+                { t!("settings.avatar_template") }
+                <input
+                    type="url"
+                    value={settings.avatar_template.clone().unwrap_or_default()}
+                    placeholder={avatar::DEFAULT_TEMPLATE}
+                    onchange={on_avatar_template}
+                    aria-invalid={(avatar_error.is_some() && settings.avatar_template.is_some()).to_string()}
+                    class="mt-1 w-full px-2 py-1 rounded border text-xs bg-white dark:bg-gray-900 focus-ring"
+                />
+            </label>
+            if let Some(error) = avatar_error {
+                <div role="alert" class="mx-3 mb-2 text-xs text-red-600">
+                    if settings.avatar_template.is_some() {
+                        { t!("settings.avatar_template_invalid", reason = error.message()) }
+                    } else {
+                        { t!("settings.avatar_default_invalid", reason = error.message()) }
+                    }
+                </div>
+            }
+        </>
+    };
+    let usage = props.quota.as_ref().map(|quota| html! {
+        <>
+            if quota.files_quota_bytes > 0 {
+                { meter(
+                    t!("quota.storage"),
+                    t!(
+                        "quota.storage_detail",
+                        used = format_megabytes(quota.files_used_bytes),
+                        quota = format_megabytes(quota.files_quota_bytes),
+                    ),
+                    quota.files_used_bytes as f64,
+                    quota.files_quota_bytes as f64,
+                ) }
+            }
+            if quota.messages_daily_limit > 0 {
+                { meter(
+                    t!("quota.messages"),
+                    t!("quota.messages_detail", used = quota.messages_today, limit = quota.messages_daily_limit),
+                    quota.messages_today as f64,
+                    quota.messages_daily_limit as f64,
+                ) }
+            }
+        </>
+    });
+    let notifications = html! {
+        <>
+            if props.notifications_blocked {
+                <div class="m-3 text-xs text-gray-500">
+                    { t!("settings.notifications_blocked") }
+                </div>
+            }
+            { checkbox(t!("settings.sound"), |s| &mut s.sound_enabled) }
+            { checkbox(t!("settings.vibrate"), |s| &mut s.vibrate_on_mention) }
+            <WatchWordsPanel
+                words={settings.watch_words.words().to_vec()}
+                on_change={update(settings, on_change, |s, words: Vec<String>| s.watch_words.set(words))}
+            />
+            <NotificationExceptions
+                muted={settings.notifications.muted_users()}
+                on_change={update(settings, on_change, |s, (user, mute): (String, _)| s.notifications.set_user_mute(&user, mute))}
+            />
+            <DndSettingsPanel
+                settings={settings.dnd.clone()}
+                active={props.dnd_active}
+                on_change={update(settings, on_change, |s, dnd| s.dnd = dnd)}
+            />
+        </>
+    };
+    let messages = html! {
+        <>
+            { checkbox(t!("settings.hide_sentiment"), |s| &mut s.hide_sentiment) }
+            { checkbox(t!("settings.history_api"), |s| &mut s.use_history_api) }
+        </>
+    };
+    let data = html! {
+        <>
+            { checkbox(t!("settings.transcript"), |s| &mut s.save_transcript) }
+            if props.has_transcript {
+                <button
+                    onclick={props.on_download_transcript.reform(|_| ())}
+                    class="mx-3 mb-3 text-xs text-blue-600 hover:underline focus-ring"
+                >
+                    { t!("settings.download_transcript") }
+                </button>
+            }
+            <SettingsImportExport
+                settings={settings.clone()}
+                on_import={props.on_change.clone()}
+                on_reject={props.on_reject.clone()}
+            />
+            <div class="m-3 text-sm">
+                <button
+                    onclick={props.on_clear_data.reform(|_| ())}
+                    class="w-full bg-white dark:bg-gray-800 rounded-lg p-2 border text-red-600 hover:bg-gray-200 dark:hover:bg-gray-600 focus-ring"
+                >
+                    { t!("settings.clear_data") }
+                </button>
+            </div>
+        </>
+    };
+
+    html! {
+        <div ref={trap} role="dialog" aria-modal="true" aria-label={t!("settings.title")} class="fixed inset-0 z-20 flex justify-end">
+            <div onclick={props.on_close.reform(|_| ())} class="absolute inset-0 bg-black bg-opacity-50"></div>
+            <div class="relative w-80 max-w-full h-full flex flex-col bg-white dark:bg-gray-800 shadow-xl">
+                <div class="flex items-center p-3 border-b">
+                    <div class="flex-1 text-lg font-semibold">{ t!("settings.title") }</div>
+                    <button onclick={props.on_close.reform(|_| ())} class="text-gray-400 hover:text-gray-800 focus-ring">{"×"}</button>
+                </div>
+                <div class="flex-1 overflow-auto">
+                    { section(t!("settings.appearance"), appearance) }
+                    { section(t!("settings.notifications"), notifications) }
+                    { section(t!("settings.messages"), messages) }
+                    if let Some(usage) = usage {
+                        { section(t!("settings.usage"), usage) }
+                    }
+                    { section(t!("settings.data"), data) }
+                </div>
+            </div>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::services::notifications::{
+        should_cue, should_notify, NotifyLevel, RoomPreference, Visibility, COMPOSING_QUIET_MS,
+    };
+
+    /// What `on_change` was last given.
+    fn recorder() -> (Callback<UserSettings>, Rc<RefCell<Option<UserSettings>>>) {
+        let last = Rc::new(RefCell::new(None));
+        let on_change = {
+            let last = last.clone();
+            Callback::from(move |settings| *last.borrow_mut() = Some(settings))
+        };
+        (on_change, last)
+    }
+
+    #[test]
+    fn the_sound_checkbox_turns_the_pop_on_and_off() {
+        let pops = |settings: &UserSettings| {
+            should_cue(settings.sound_enabled, false, COMPOSING_QUIET_MS, NotifyLevel::Alert)
+        };
+        let off = UserSettings::default();
+        let on = toggled(&off, |s| &mut s.sound_enabled);
+        assert!(!pops(&off));
+        assert!(pops(&on));
+        assert!(!pops(&toggled(&on, |s| &mut s.sound_enabled)));
+    }
+
+    #[test]
+    fn the_vibrate_checkbox_decides_whether_mentions_buzz() {
+        let buzzes = |settings: &UserSettings| {
+            let all = RoomPreference::All;
+            should_notify(all, all, None, false, true, Visibility::Hidden, settings.vibrate_on_mention).vibrate
+        };
+        let off = UserSettings::default();
+        assert!(!buzzes(&off));
+        assert!(buzzes(&toggled(&off, |s| &mut s.vibrate_on_mention)));
+    }
+
+    #[test]
+    fn a_toggle_leaves_every_other_setting_alone() {
+        let before = UserSettings { sound_enabled: true, theme: Theme::Dark, ..UserSettings::default() };
+        let after = toggled(&before, |s| &mut s.hide_sentiment);
+        assert!(after.hide_sentiment);
+        assert_eq!(UserSettings { hide_sentiment: false, ..after }, before);
+    }
+
+    #[test]
+    fn a_pick_goes_out_as_the_whole_new_settings() {
+        let (on_change, last) = recorder();
+        let before = UserSettings { sound_enabled: true, ..UserSettings::default() };
+        update(&before, &on_change, |s, density| s.density = density).emit(Density::Compact);
+        let after = last.borrow_mut().take().expect("a change");
+        assert_eq!(after.density, Density::Compact);
+        assert_eq!(UserSettings { density: before.density, ..after }, before);
+    }
+}
@@ -0,0 +1,40 @@
+use yew::prelude::*;
+
+use crate::hooks::use_focus_trap::use_focus_trap;
+use crate::hooks::use_locale::use_locale;
+use crate::services::shortcuts::CHEAT_SHEET;
+use crate::t;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub on_close: Callback<()>,
+}
+
+/// The keyboard shortcuts cheat sheet, opened with `?`.
+#[function_component(ShortcutHelp)]
+pub fn shortcut_help(props: &Props) -> Html {
+    use_locale();
+    let trap = use_focus_trap(true);
+    let close = props.on_close.reform(|_| ());
+
+    html! {
+        <div ref={trap} role="dialog" aria-modal="true" aria-label={t!("shortcuts.title")} class="fixed inset-0 z-20 flex items-center justify-center bg-black bg-opacity-50">
+            <div class="w-80 flex flex-col bg-white dark:bg-gray-800 rounded-xl overflow-hidden text-sm">
+                <div class="flex items-center p-3 border-b">
+                    <div class="flex-1 text-lg font-semibold">{ t!("shortcuts.title") }</div>
+                    <button onclick={close} class="text-gray-400 hover:text-gray-800 focus-ring">{"×"}</button>
+                </div>
+                <div class="p-3 space-y-2">
+                    {
+                        CHEAT_SHEET.iter().map(|(keys, description)| html! {
+                            <div class="flex items-center">
+                                <kbd class="w-16 px-1 rounded border bg-gray-100 dark:bg-gray-700 font-mono text-xs text-center">{ keys }</kbd>
+                                <span class="ml-3">{ t!(description) }</span>
+                            </div>
+                        }).collect::<Html>()
+                    }
+                </div>
+            </div>
+        </div>
+    }
+}
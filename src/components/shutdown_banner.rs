@@ -0,0 +1,75 @@
+use gloo_timers::callback::Interval;
+use yew::prelude::*;
+
+use crate::hooks::use_locale::use_locale;
+use crate::t;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// When the server goes down, in seconds since the epoch.
+    pub shutdown_at: u64,
+    pub reason: String,
+}
+
+/// `4:32`, or `1:04:32` once there are hours to show.
+pub fn format_countdown(seconds: u64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3_600, seconds % 3_600 / 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// Whole seconds from `now`, in milliseconds since the epoch, until `shutdown_at`.
+fn seconds_left(shutdown_at: u64, now: f64) -> u64 {
+    (shutdown_at as f64 - now / 1000.0).max(0.0).ceil() as u64
+}
+
+/// The red strip counting down to an announced shutdown. It keeps its own clock, so the
+/// chat around it doesn't render again every second.
+#[function_component(ShutdownBanner)]
+pub fn shutdown_banner(props: &Props) -> Html {
+    use_locale();
+    let now = use_state(js_sys::Date::now);
+    {
+        let now = now.clone();
+        use_effect_with_deps(
+            move |_| {
+                let tick = Interval::new(1_000, move || now.set(js_sys::Date::now()));
+                move || drop(tick)
+            },
+            (),
+        );
+    }
+
+    html! {
+        <div class="px-4 py-2 bg-red-600 text-white text-sm">
+            <span class="font-semibold">
+                { t!("shutdown.countdown", time = format_countdown(seconds_left(props.shutdown_at, *now))) }
+            </span>
+            if !props.reason.is_empty() {
+                <span class="ml-2">{ &props.reason }</span>
+            }
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn countdowns_show_hours_only_when_there_are_any() {
+        assert_eq!(format_countdown(0), "0:00");
+        assert_eq!(format_countdown(309), "5:09");
+        assert_eq!(format_countdown(3_909), "1:05:09");
+    }
+
+    #[test]
+    fn partial_seconds_round_up_and_the_past_is_zero() {
+        assert_eq!(seconds_left(100, 90_000.0), 10);
+        assert_eq!(seconds_left(100, 90_500.0), 10);
+        assert_eq!(seconds_left(100, 101_000.0), 0);
+    }
+}
@@ -0,0 +1,96 @@
+use yew::prelude::*;
+
+use crate::hooks::use_focus_trap::use_focus_trap;
+use crate::hooks::use_locale::use_locale;
+use crate::components::empty_state::{EmptyState, EmptyStateVariant};
+use crate::services::session_stats::SessionStats;
+use crate::t;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub stats: SessionStats,
+    pub on_close: Callback<()>,
+}
+
+/// Shows the aggregates in `stats`; working them out is left to the caller.
+#[function_component(StatsDialog)]
+pub fn stats_dialog(props: &Props) -> Html {
+    use_locale();
+    let trap = use_focus_trap(true);
+    let stats = &props.stats;
+    let close = props.on_close.reform(|_| ());
+    let busiest = stats.per_user.first().map(|(_, count)| *count).unwrap_or(0).max(1);
+
+    let body = if stats.total == 0 {
+        html! {
+            <EmptyState variant={EmptyStateVariant::NoMessages} text={t!("stats.empty")}/>
+        }
+    } else {
+        html! {
+            <div class="space-y-4">
+                <div class="flex space-x-3">
+                    <div class="flex-1 p-3 rounded-lg bg-gray-100 dark:bg-gray-700">
+                        <div class="text-xs text-gray-500">{ t!("settings.messages") }</div>
+                        <div class="text-2xl font-semibold">{ stats.total }</div>
+                    </div>
+                    <div class="flex-1 p-3 rounded-lg bg-gray-100 dark:bg-gray-700">
+                        <div class="text-xs text-gray-500">{ t!("stats.average_length") }</div>
+                        <div class="text-2xl font-semibold">
+                            { stats.average_length.map(|l| format!("{:.0}", l)).unwrap_or_default() }
+                            <span class="ml-1 text-xs font-normal text-gray-500">{ t!("stats.chars") }</span>
+                        </div>
+                    </div>
+                    <div class="flex-1 p-3 rounded-lg bg-gray-100 dark:bg-gray-700">
+                        <div class="text-xs text-gray-500">{ t!("stats.busiest_hour") }</div>
+                        <div class="text-2xl font-semibold">
+                            { stats.busiest_hour.map(|(hour, _)| format!("{:02}:00", hour)).unwrap_or_else(|| "–".to_string()) }
+                        </div>
+                    </div>
+                </div>
+                <div>
+                    <div class="mb-1 font-medium">{ t!("stats.per_person") }</div>
+                    {
+                        stats.per_user.iter().map(|(user, count)| html! {
+                            <div class="flex items-center mb-1">
+                                <span class="w-24 truncate text-gray-600 dark:text-gray-300">{ user }</span>
+                                <div class="flex-1 h-3 mx-2 bg-gray-100 dark:bg-gray-700 rounded">
+                                    <div class="h-3 bg-blue-500 rounded" style={format!("width: {:.1}%", *count as f64 * 100.0 / busiest as f64)}></div>
+                                </div>
+                                <span class="w-8 text-right text-xs text-gray-500">{ count }</span>
+                            </div>
+                        }).collect::<Html>()
+                    }
+                </div>
+                <div>
+                    <div class="mb-1 font-medium">{ t!("stats.top_reactions") }</div>
+                    if stats.top_reactions.is_empty() {
+                        <div class="text-xs text-gray-400">{ t!("stats.no_reactions") }</div>
+                    } else {
+                        <div class="flex flex-wrap">
+                            {
+                                stats.top_reactions.iter().map(|(emoji, count)| html! {
+                                    <span class="mr-2 mb-1 px-2 py-1 rounded-full border">{ format!("{} {}", emoji, count) }</span>
+                                }).collect::<Html>()
+                            }
+                        </div>
+                    }
+                </div>
+            </div>
+        }
+    };
+
+    html! {
+        <div ref={trap} role="dialog" aria-modal="true" aria-label={t!("dialog.session_stats")} class="fixed inset-0 z-10 flex items-center justify-center bg-black bg-opacity-50">
+            <div class="w-96 max-h-screen flex flex-col bg-white dark:bg-gray-800 rounded-xl overflow-hidden text-sm">
+                <div class="flex items-center p-3 border-b">
+                    <div class="flex-1 text-lg font-semibold">{ t!("dialog.session_stats") }</div>
+                    <button onclick={close} class="text-gray-400 hover:text-gray-800 focus-ring">{"×"}</button>
+                </div>
+                <div class="p-3 overflow-auto">
+                    { body }
+                    <div class="mt-3 text-xs text-gray-400">{ t!("stats.footnote") }</div>
+                </div>
+            </div>
+        </div>
+    }
+}
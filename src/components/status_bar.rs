@@ -0,0 +1,55 @@
+use yew::prelude::*;
+
+use crate::hooks::use_locale::use_locale;
+use crate::t;
+
+/// What the strip above the composer is showing.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Status {
+    Editing,
+    /// A file or clip being read and sent, by name.
+    Uploading(String),
+}
+
+impl Status {
+    /// The more pressing of what's going on, if anything: an edit in progress beats an
+    /// upload. Slow mode has its own bar under this one.
+    pub fn pick(editing: bool, uploading: Option<&str>) -> Option<Status> {
+        if editing {
+            return Some(Status::Editing);
+        }
+        uploading.map(|name| Status::Uploading(name.to_string()))
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub status: Option<Status>,
+    pub on_cancel_edit: Callback<()>,
+    pub on_cancel_upload: Callback<()>,
+}
+
+/// One line of context between the message list and the composer; empty when idle.
+#[function_component(StatusBar)]
+pub fn status_bar(props: &Props) -> Html {
+    use_locale();
+    let status = match &props.status {
+        Some(status) => status,
+        None => return html! {},
+    };
+    let (text, cancel) = match status {
+        Status::Editing => (t!("edit.banner"), Some(&props.on_cancel_edit)),
+        Status::Uploading(name) => (t!("status.uploading", name = name), Some(&props.on_cancel_upload)),
+    };
+
+    html! {
+        <div class="flex items-center mx-4 mt-2 px-3 py-2 rounded-lg bg-blue-50 text-sm text-blue-900">
+            <span class="flex-1 truncate">{ text }</span>
+            if let Some(cancel) = cancel {
+                <button onclick={cancel.reform(|_| ())} class="px-3 py-1 rounded-full hover:bg-blue-100 focus-ring">
+                    { t!("common.cancel") }
+                </button>
+            }
+        </div>
+    }
+}
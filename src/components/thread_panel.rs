@@ -0,0 +1,101 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::components::chat::MessageData;
+use crate::hooks::use_locale::use_locale;
+use crate::t;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// The message the thread hangs off.
+    pub root: MessageData,
+    /// Oldest first.
+    pub replies: Vec<MessageData>,
+    /// "Alice is typing…" for this thread, if anyone is.
+    pub typing: Option<String>,
+    /// Nothing can be sent: a guest, or no connection.
+    pub locked: bool,
+    pub on_typing: Callback<()>,
+    pub on_send: Callback<String>,
+    pub on_close: Callback<()>,
+}
+
+/// A thread opened over the message list, with its own composer.
+#[function_component(ThreadPanel)]
+pub fn thread_panel(props: &Props) -> Html {
+    use_locale();
+    let input = use_node_ref();
+
+    let send = {
+        let input = input.clone();
+        let on_send = props.on_send.clone();
+        move || {
+            if let Some(input) = input.cast::<HtmlInputElement>() {
+                let text = input.value();
+                if !text.trim().is_empty() {
+                    on_send.emit(text.trim().to_string());
+                    input.set_value("");
+                }
+            }
+        }
+    };
+    let onkeydown = {
+        let send = send.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            if e.key() == "Enter" {
+                send();
+            }
+        })
+    };
+    let row = |m: &MessageData| {
+        html! {
+            <div class="bg-gray-100 dark:bg-gray-700 p-3 rounded-xl">
+                <div class="text-sm font-medium">{ &m.from }</div>
+                <div class="text-base">{ &m.message }</div>
+            </div>
+        }
+    };
+
+    html! {
+        <div role="complementary" aria-label={t!("thread.title")} class="absolute inset-0 z-10 flex flex-col bg-white dark:bg-gray-800">
+            <div class="flex items-center p-3 border-b">
+                <span class="flex-1 font-semibold">{ t!("thread.title") }</span>
+                <button
+                    onclick={props.on_close.reform(|_| ())}
+                    aria-label={t!("common.close")}
+                    class="w-8 h-8 rounded-full hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring"
+                >
+                    {"✕"}
+                </button>
+            </div>
+            <div class="flex-1 overflow-auto p-4 space-y-3">
+                { row(&props.root) }
+                <div class="text-xs text-gray-400">{ t!("thread.reply_count", count = props.replies.len()) }</div>
+                { props.replies.iter().map(row).collect::<Html>() }
+            </div>
+            if let Some(typing) = &props.typing {
+                <div class="px-4 pt-1 text-xs text-gray-400">{ typing }</div>
+            }
+            <div class="flex items-center p-3 border-t">
+                <input
+                    ref={input}
+                    oninput={props.on_typing.reform(|_| ())}
+                    {onkeydown}
+                    disabled={props.locked}
+                    type="text"
+                    aria-label={t!("thread.placeholder")}
+                    placeholder={t!("thread.placeholder")}
+                    class="flex-1 min-w-0 rounded-full bg-gray-100 dark:bg-gray-700 px-4 py-2 focus:outline-none focus-ring"
+                />
+                <button
+                    onclick={Callback::from(move |_| send())}
+                    disabled={props.locked}
+                    aria-label={t!("thread.send")}
+                    class="ml-2 w-10 h-10 rounded-full bg-blue-600 text-white disabled:opacity-50 focus-ring"
+                >
+                    {"➤"}
+                </button>
+            </div>
+        </div>
+    }
+}
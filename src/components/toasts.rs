@@ -0,0 +1,106 @@
+use gloo_timers::callback::Interval;
+use yew::prelude::*;
+use yew_agent::{Bridge, Bridged};
+
+use crate::services::toasts::{ShownToast, ToastBus, ToastLevel, ToastMsg, ToastQueue};
+use crate::t;
+
+pub enum Msg {
+    Bus(ToastMsg),
+    Tick,
+    Dismiss(u64),
+    Hover(u64, bool),
+    Act(u64, String),
+}
+
+/// Fixed stack in the bottom-right corner showing whatever is published on `ToastBus`.
+pub struct ToastStack {
+    queue: ToastQueue,
+    bus: Box<dyn Bridge<ToastBus>>,
+    _tick: Interval,
+}
+
+impl Component for ToastStack {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let link = ctx.link().clone();
+        Self {
+            queue: ToastQueue::default(),
+            bus: ToastBus::bridge(ctx.link().callback(Msg::Bus)),
+            _tick: Interval::new(250, move || link.send_message(Msg::Tick)),
+        }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let now = js_sys::Date::now();
+        match msg {
+            Msg::Bus(ToastMsg::Show(toast)) => {
+                self.queue.push(toast, now);
+                true
+            }
+            Msg::Bus(ToastMsg::Action(_)) => false,
+            Msg::Tick => self.queue.tick(now),
+            Msg::Dismiss(id) => {
+                self.queue.dismiss(id, now);
+                true
+            }
+            Msg::Hover(id, hovered) => {
+                self.queue.set_hovered(id, hovered, now);
+                false
+            }
+            Msg::Act(id, action_id) => {
+                self.bus.send(ToastMsg::Action(action_id));
+                self.queue.dismiss(id, now);
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <div class="fixed bottom-4 right-4 z-50 flex flex-col items-end space-y-2">
+                { self.queue.visible().iter().map(|shown| view_toast(ctx, shown)).collect::<Html>() }
+            </div>
+        }
+    }
+}
+
+fn view_toast(ctx: &Context<ToastStack>, shown: &ShownToast) -> Html {
+    let id = shown.id;
+    let (class, icon) = match shown.toast.level {
+        ToastLevel::Info => ("bg-white dark:bg-gray-800 border-gray-300", "ℹ️"),
+        ToastLevel::Success => ("bg-green-50 border-green-400", "✅"),
+        ToastLevel::Warn => ("bg-yellow-50 border-yellow-400", "⚠️"),
+        ToastLevel::Error => ("bg-red-50 border-red-400", "⛔"),
+    };
+    html! {
+        <div
+            key={id}
+            onmouseenter={ctx.link().callback(move |_| Msg::Hover(id, true))}
+            onmouseleave={ctx.link().callback(move |_| Msg::Hover(id, false))}
+            class={classes!("flex", "items-center", "w-80", "p-3", "rounded-lg", "border", "shadow-lg", "text-sm", class)}
+        >
+            <span class="mr-2">{ icon }</span>
+            <span class="flex-1">{ &shown.toast.text }</span>
+            {
+                match &shown.toast.action {
+                    Some((label, action_id)) => {
+                        let action_id = action_id.clone();
+                        html! {
+                            <button
+                                onclick={ctx.link().callback(move |_| Msg::Act(id, action_id.clone()))}
+                                class="ml-2 font-semibold text-blue-600 hover:underline focus-ring"
+                            >
+                                { label }
+                            </button>
+                        }
+                    }
+                    None => html! {},
+                }
+            }
+            <button onclick={ctx.link().callback(move |_| Msg::Dismiss(id))} title={t!("toasts.dismiss")} aria-label={t!("toasts.dismiss")} class="ml-2 text-gray-400 hover:text-gray-600 focus-ring">{"✕"}</button>
+        </div>
+    }
+}
@@ -0,0 +1,139 @@
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+
+use crate::hooks::use_locale::use_locale;
+use crate::t;
+
+/// What the card shows about someone, gathered from the user list and loaded history.
+#[derive(Clone, PartialEq)]
+pub struct ProfileSummary {
+    pub name: String,
+    pub avatar: String,
+    /// In the server's user list right now.
+    pub online: bool,
+    /// Messages of theirs loaded on this device.
+    pub message_count: usize,
+    /// What the current user privately noted about them.
+    pub note: Option<String>,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub profile: ProfileSummary,
+    /// The small pop-over version shown while hovering a mention.
+    #[prop_or_default]
+    pub compact: bool,
+    /// Shows a button to open a DM with them.
+    #[prop_or_default]
+    pub on_message: Option<Callback<String>>,
+    /// Lets the user edit their note; called with the new text, empty to delete it.
+    #[prop_or_default]
+    pub on_note: Option<Callback<String>>,
+}
+
+#[function_component(UserProfileCard)]
+pub fn user_profile_card(props: &Props) -> Html {
+    use_locale();
+    let editing = use_state(|| false);
+    let note_input = use_node_ref();
+    let profile = &props.profile;
+    let status = html! {
+        <span class="flex items-center text-xs text-gray-500">
+            <span class={classes!("w-2", "h-2", "mr-1", "rounded-full", if profile.online { "bg-green-500" } else { "bg-gray-300" })}></span>
+            { if profile.online { t!("profile.online") } else { t!("profile.offline") } }
+        </span>
+    };
+    let count = t!("profile.messages_loaded", count = profile.message_count);
+
+    if props.compact {
+        return html! {
+            <div class="flex items-center w-56 p-2 bg-white dark:bg-gray-800 border rounded-lg shadow text-sm">
+                <img class="w-10 h-10 rounded-full" src={profile.avatar.clone()} alt="avatar"/>
+                <div class="ml-2 min-w-0">
+                    <div class="font-semibold truncate">{ &profile.name }</div>
+                    { status }
+                    <div class="text-xs text-gray-400">{ count }</div>
+                    if let Some(note) = &profile.note {
+                        <div class="mt-1 text-xs italic text-gray-600 dark:text-gray-300 truncate" title={note.clone()}>{ note }</div>
+                    }
+                </div>
+            </div>
+        };
+    }
+
+    html! {
+        <div class="w-72 flex flex-col items-center p-6 bg-white dark:bg-gray-800 rounded-xl text-sm">
+            <img class="w-20 h-20 rounded-full" src={profile.avatar.clone()} alt="avatar"/>
+            <div class="mt-3 text-lg font-semibold">{ &profile.name }</div>
+            { status }
+            <div class="mt-1 text-xs text-gray-400">{ count }</div>
+            if let Some(on_note) = &props.on_note {
+                if *editing {
+                    <div class="w-full mt-3">
+                        <textarea
+                            ref={note_input.clone()}
+                            value={profile.note.clone().unwrap_or_default()}
+                            placeholder={t!("profile.note_placeholder")}
+                            rows="3"
+                            class="w-full p-2 border rounded text-sm resize-none focus-ring"
+                        />
+                        <div class="flex justify-end gap-2 mt-1">
+                            <button
+                                onclick={{
+                                    let editing = editing.clone();
+                                    Callback::from(move |_| editing.set(false))
+                                }}
+                                class="px-3 py-1 rounded text-gray-600 dark:text-gray-300 hover:bg-gray-100 dark:hover:bg-gray-700 focus-ring"
+                            >
+                                { t!("common.cancel") }
+                            </button>
+                            <button
+                                onclick={{
+                                    let editing = editing.clone();
+                                    let note_input = note_input.clone();
+                                    let on_note = on_note.clone();
+                                    Callback::from(move |_| {
+                                        if let Some(input) = note_input.cast::<HtmlTextAreaElement>() {
+                                            on_note.emit(input.value().trim().to_string());
+                                        }
+                                        editing.set(false);
+                                    })
+                                }}
+                                class="px-3 py-1 rounded bg-blue-600 text-white focus-ring"
+                            >
+                                { t!("common.save") }
+                            </button>
+                        </div>
+                    </div>
+                } else {
+                    <div class="flex items-start w-full mt-3 text-gray-600 dark:text-gray-300">
+                        <span class="flex-1 italic whitespace-pre-wrap">
+                            { profile.note.clone().unwrap_or_else(|| t!("profile.no_note")) }
+                        </span>
+                        <button
+                            onclick={{
+                                let editing = editing.clone();
+                                Callback::from(move |_| editing.set(true))
+                            }}
+                            title={t!("profile.edit_note")}
+                            class="ml-2 text-gray-400 hover:text-gray-800 focus-ring"
+                        >
+                            {"✏️"}
+                        </button>
+                    </div>
+                }
+            }
+            if let Some(on_message) = &props.on_message {
+                <button
+                    onclick={{
+                        let name = profile.name.clone();
+                        on_message.reform(move |_| name.clone())
+                    }}
+                    class="mt-4 px-4 py-1 rounded-full bg-blue-600 text-white focus-ring"
+                >
+                    { t!("profile.message") }
+                </button>
+            }
+        </div>
+    }
+}
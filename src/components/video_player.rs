@@ -0,0 +1,67 @@
+use std::rc::Rc;
+
+use wasm_bindgen::JsCast;
+use web_sys::HtmlMediaElement;
+use yew::prelude::*;
+use yew::virtual_dom::AttrValue;
+
+use crate::hooks::use_locale::use_locale;
+use crate::services::playback::PlaybackContext;
+use crate::t;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// `None` while the video is still on its way.
+    pub src: Option<Rc<str>>,
+    /// The first frame, extracted by the server, shown until the user hits play. Without
+    /// one the video itself stands in, stopped on its first frame.
+    #[prop_or_default]
+    pub thumbnail: Option<String>,
+    pub name: String,
+}
+
+/// A video that shows its first frame with a play button over it, and only loads a real
+/// player once that is clicked.
+#[function_component(VideoPlayer)]
+pub fn video_player(props: &Props) -> Html {
+    use_locale();
+    let playing = use_state(|| false);
+    let playback = use_context::<PlaybackContext>();
+
+    if let (true, Some(src)) = (*playing, &props.src) {
+        let onplay = Callback::from(move |e: Event| {
+            if let (Some(playback), Some(video)) = (&playback, e.target().and_then(|t| t.dyn_into::<HtmlMediaElement>().ok())) {
+                playback.started(video);
+            }
+        });
+        return html! {
+            <video controls=true autoplay=true {onplay} class="mt-2 max-w-xs rounded-lg" src={AttrValue::from(src.clone())}></video>
+        };
+    }
+
+    let play = {
+        let playing = playing.clone();
+        Callback::from(move |_| playing.set(true))
+    };
+
+    html! {
+        <div class="relative mt-2 w-80 max-w-full h-48 overflow-hidden rounded-lg bg-gray-800">
+            if let Some(thumbnail) = &props.thumbnail {
+                <img class="w-full h-full object-cover" src={thumbnail.clone()} alt=""/>
+            } else if let Some(src) = &props.src {
+                <video preload="metadata" muted=true class="w-full h-full object-cover" src={AttrValue::from(src.clone())}></video>
+            }
+            if props.src.is_some() {
+                <button
+                    onclick={play}
+                    aria-label={t!("video.play", name = props.name)}
+                    class="absolute inset-0 flex items-center justify-center bg-black bg-opacity-25 hover:bg-opacity-40 focus-ring"
+                >
+                    <span class="w-12 h-12 flex items-center justify-center rounded-full bg-white bg-opacity-90 text-gray-900 text-xl">{"▶"}</span>
+                </button>
+            } else {
+                <div class="absolute inset-x-0 bottom-0 p-2 text-xs text-white">{ t!("video.receiving", name = props.name) }</div>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,75 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::hooks::use_locale::use_locale;
+use crate::services::keywords::MAX_WATCH_WORDS;
+use crate::t;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub words: Vec<String>,
+    pub on_change: Callback<Vec<String>>,
+}
+
+/// Settings section for words that notify like a mention.
+#[function_component(WatchWordsPanel)]
+pub fn watch_words_panel(props: &Props) -> Html {
+    use_locale();
+    let input = use_node_ref();
+    let full = props.words.len() >= MAX_WATCH_WORDS;
+
+    let add = {
+        let input = input.clone();
+        let words = props.words.clone();
+        let on_change = props.on_change.clone();
+        Callback::from(move |e: FocusEvent| {
+            e.prevent_default();
+            if let Some(input) = input.cast::<HtmlInputElement>() {
+                let word = input.value();
+                if !word.trim().is_empty() {
+                    let mut words = words.clone();
+                    words.push(word);
+                    on_change.emit(words);
+                }
+                input.set_value("");
+            }
+        })
+    };
+
+    html! {
+        <div class="m-3 text-sm">
+            <div class="font-semibold">
+                { t!("watch_words.title") }
+                <span class="ml-1 text-xs font-normal text-gray-400">{ format!("{}/{}", props.words.len(), MAX_WATCH_WORDS) }</span>
+            </div>
+            <div class="mt-1 flex flex-wrap gap-1">
+                {
+                    props.words.iter().enumerate().map(|(i, word)| {
+                        let remove = {
+                            let words = props.words.clone();
+                            props.on_change.reform(move |_| {
+                                let mut words = words.clone();
+                                words.remove(i);
+                                words
+                            })
+                        };
+                        html! {
+                            <span class="flex items-center px-2 py-0.5 rounded-full bg-white dark:bg-gray-800 border">
+                                { word }
+                                <button onclick={remove} title={t!("watch_words.remove")} aria-label={t!("watch_words.remove")} class="ml-1 text-gray-400 hover:text-gray-600 focus-ring">{"✕"}</button>
+                            </span>
+                        }
+                    }).collect::<Html>()
+                }
+            </div>
+            <form onsubmit={add} class="mt-1">
+                <input
+                    ref={input}
+                    disabled={full}
+                    placeholder={if full { t!("watch_words.full") } else { t!("watch_words.placeholder") }}
+                    class="w-full rounded-full bg-white dark:bg-gray-800 px-3 py-1 focus:outline-none focus-ring"
+                />
+            </form>
+        </div>
+    }
+}
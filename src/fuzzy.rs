@@ -0,0 +1,65 @@
+/// Scores `candidate` against `query` as a case-insensitive subsequence match.
+///
+/// Returns `None` when the query characters don't all appear in order. Matches at
+/// the start and runs of consecutive characters score higher; gaps cost a point each.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut last: Option<usize> = None;
+
+    for q in query.to_lowercase().chars() {
+        let found = next + candidate[next..].iter().position(|&c| c == q)?;
+        score += match last {
+            None if found == 0 => 4,
+            None => 1 - found as i32,
+            Some(last) if found == last + 1 => 3,
+            Some(last) => 1 - (found - last - 1) as i32,
+        };
+        last = Some(found);
+        next = found + 1;
+    }
+    Some(score)
+}
+
+/// Keeps the items whose `key` matches `query`, best matches first. Ties keep their
+/// original order, and an empty query keeps everything as-is.
+pub fn fuzzy_filter<'a, T>(query: &str, items: &'a [T], key: impl Fn(&T) -> &str) -> Vec<&'a T> {
+    let mut scored: Vec<(i32, &T)> = items
+        .iter()
+        .filter_map(|item| fuzzy_score(query, key(item)).map(|score| (score, item)))
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, item)| item).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_in_order_subsequences_match() {
+        assert!(fuzzy_score("gnl", "general").is_some());
+        assert!(fuzzy_score("GEN", "general").is_some());
+        assert_eq!(fuzzy_score("lg", "general"), None);
+        assert_eq!(fuzzy_score("x", "general"), None);
+        assert_eq!(fuzzy_score("", "general"), Some(0));
+    }
+
+    #[test]
+    fn prefixes_and_runs_beat_scattered_matches() {
+        let prefix = fuzzy_score("ran", "random").unwrap();
+        let inner = fuzzy_score("ran", "fran").unwrap();
+        let scattered = fuzzy_score("rdm", "random").unwrap();
+        assert!(prefix > inner);
+        assert!(inner > scattered);
+    }
+
+    #[test]
+    fn filter_ranks_best_first_and_keeps_ties_in_order() {
+        let rooms = ["off-topic", "dev", "devops", "random"];
+        assert_eq!(fuzzy_filter("dev", &rooms, |r| r), [&"dev", &"devops"]);
+        assert_eq!(fuzzy_filter("o", &rooms, |r| r), [&"off-topic", &"devops", &"random"]);
+        assert_eq!(fuzzy_filter("", &rooms, |r| r).len(), rooms.len());
+    }
+}
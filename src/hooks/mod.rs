@@ -0,0 +1,3 @@
+pub mod use_local_storage;
+pub mod use_focus_trap;
+pub mod use_locale;
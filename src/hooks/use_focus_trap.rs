@@ -0,0 +1,131 @@
+use gloo_events::{EventListener, EventListenerOptions};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement, KeyboardEvent};
+use yew::prelude::*;
+
+/// Everything Tab can land on inside a dialog.
+const FOCUSABLE: &str = "a[href], button:not([disabled]), input:not([disabled]):not([type=hidden]), \
+    select:not([disabled]), textarea:not([disabled]), [tabindex]:not([tabindex='-1'])";
+
+fn focusable_in(container: &Element) -> Vec<HtmlElement> {
+    let nodes = match container.query_selector_all(FOCUSABLE) {
+        Ok(nodes) => nodes,
+        Err(_) => return vec![],
+    };
+    (0..nodes.length())
+        .filter_map(|i| nodes.get(i))
+        .filter_map(|node| node.dyn_into::<HtmlElement>().ok())
+        .collect()
+}
+
+fn active_element() -> Option<HtmlElement> {
+    web_sys::window()?.document()?.active_element()?.dyn_into().ok()
+}
+
+/// Where Tab has to wrap to among `count` controls, with the one at `active` focused:
+/// from the last forwards to the first, or from the first backwards to the last. `None`
+/// leaves Tab to the browser.
+fn wrap_target(count: usize, active: Option<usize>, backwards: bool) -> Option<usize> {
+    let last = count.checked_sub(1)?;
+    match (active, backwards) {
+        (Some(0), true) => Some(last),
+        (Some(i), false) if i == last => Some(0),
+        _ => None,
+    }
+}
+
+fn focus(element: &HtmlElement) {
+    if let Err(e) = element.focus() {
+        log::error!("failed to move focus: {:?}", e);
+    }
+}
+
+/// Keeps Tab cycling inside a dialog, and hands focus back to where it came from once the
+/// dialog goes.
+struct FocusTrap {
+    restore: Option<HtmlElement>,
+    _keydown: EventListener,
+}
+
+impl FocusTrap {
+    fn new(container: Element) -> Self {
+        let restore = active_element();
+        // A field the dialog focused itself keeps focus; otherwise the first control gets it.
+        if !restore.as_ref().map_or(false, |active| container.contains(Some(active))) {
+            if let Some(first) = focusable_in(&container).first() {
+                focus(first);
+            }
+        }
+        let target = container.clone();
+        let options = EventListenerOptions::enable_prevent_default();
+        let _keydown = EventListener::new_with_options(&target, "keydown", options, move |e| {
+            let e = match e.dyn_ref::<KeyboardEvent>() {
+                Some(e) if e.key() == "Tab" => e,
+                _ => return,
+            };
+            let controls = focusable_in(&container);
+            let active = active_element().and_then(|active| controls.iter().position(|c| *c == active));
+            if let Some(wrap_to) = wrap_target(controls.len(), active, e.shift_key()) {
+                e.prevent_default();
+                focus(&controls[wrap_to]);
+            }
+        });
+        Self { restore, _keydown }
+    }
+}
+
+impl Drop for FocusTrap {
+    fn drop(&mut self) {
+        if let Some(restore) = self.restore.take().filter(|e| e.is_connected()) {
+            focus(&restore);
+        }
+    }
+}
+
+/// A ref for the root of a dialog. While `active`, focus moves into the dialog and Tab
+/// can't leave it; when it stops being active (or unmounts) focus goes back.
+pub fn use_focus_trap(active: bool) -> NodeRef {
+    let node_ref = use_node_ref();
+    {
+        let node_ref = node_ref.clone();
+        use_effect_with_deps(
+            move |&active| {
+                let trap = active.then(|| node_ref.cast::<Element>()).flatten().map(FocusTrap::new);
+                move || drop(trap)
+            },
+            active,
+        );
+    }
+    node_ref
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tab_wraps_at_either_end() {
+        assert_eq!(wrap_target(3, Some(2), false), Some(0));
+        assert_eq!(wrap_target(3, Some(0), true), Some(2));
+    }
+
+    #[test]
+    fn tab_inside_is_left_to_the_browser() {
+        assert_eq!(wrap_target(3, Some(1), false), None);
+        assert_eq!(wrap_target(3, Some(1), true), None);
+        assert_eq!(wrap_target(3, Some(0), false), None);
+        assert_eq!(wrap_target(3, Some(2), true), None);
+    }
+
+    #[test]
+    fn a_single_control_keeps_focus() {
+        assert_eq!(wrap_target(1, Some(0), false), Some(0));
+        assert_eq!(wrap_target(1, Some(0), true), Some(0));
+    }
+
+    #[test]
+    fn focus_from_outside_or_nothing_to_focus() {
+        assert_eq!(wrap_target(3, None, false), None);
+        assert_eq!(wrap_target(0, None, true), None);
+    }
+}
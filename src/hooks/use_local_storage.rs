@@ -0,0 +1,67 @@
+use serde::{de::DeserializeOwned, Serialize};
+use yew::prelude::*;
+
+use crate::services::storage::{Local, Store};
+
+/// State mirrored to localStorage under `key`: read once on mount (falling back to
+/// `default`), written back on every call of the returned setter.
+pub fn use_local_storage<T>(key: &'static str, default: T) -> (T, Callback<T>)
+where
+    T: Serialize + DeserializeOwned + Clone + 'static,
+{
+    let state = use_state(move || read(&Local, key, default));
+
+    let set = {
+        let state = state.clone();
+        Callback::from(move |value: T| {
+            write(&Local, key, &value);
+            state.set(value);
+        })
+    };
+
+    ((*state).clone(), set)
+}
+
+fn read<S: Store, T: DeserializeOwned>(store: &S, key: &str, default: T) -> T {
+    store.get(key).unwrap_or(default)
+}
+
+/// A value that serialises to null, like `None`, clears the key rather than storing it.
+fn write<S: Store, T: Serialize>(store: &S, key: &str, value: &T) {
+    if serde_json::to_value(value).map_or(false, |json| json.is_null()) {
+        store.delete(key);
+    } else if let Err(e) = store.set(key, value) {
+        log::error!("failed to write {} to localStorage: {:?}", key, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::storage::MemoryStore;
+
+    #[test]
+    fn falls_back_to_the_default() {
+        let store = MemoryStore::default();
+        assert_eq!(read(&store, "k", 7), 7);
+        store.set("k", &"not a number").unwrap();
+        assert_eq!(read(&store, "k", 7), 7);
+    }
+
+    #[test]
+    fn reads_back_what_was_written() {
+        let store = MemoryStore::default();
+        write(&store, "k", &Some("ann".to_string()));
+        assert_eq!(read(&store, "k", None), Some("ann".to_string()));
+        write(&store, "k", &Some("bo".to_string()));
+        assert_eq!(read(&store, "k", None), Some("bo".to_string()));
+    }
+
+    #[test]
+    fn writing_none_clears_the_key() {
+        let store = MemoryStore::default();
+        write(&store, "k", &Some(1));
+        write(&store, "k", &None::<u32>);
+        assert_eq!(store.raw("k"), None);
+    }
+}
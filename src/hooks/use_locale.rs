@@ -0,0 +1,9 @@
+use yew::prelude::*;
+
+use crate::services::i18n::Locale;
+
+/// Re-renders the calling component whenever the language changes, so its `t!` strings
+/// follow without it being rebuilt. Chat provides the locale; outside it this is a no-op.
+pub fn use_locale() -> Option<Locale> {
+    use_context::<Locale>()
+}
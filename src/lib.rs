@@ -1,21 +1,65 @@
 #![recursion_limit = "512"]
 
 mod components;
+mod fuzzy;
 use components::login::Login;
 use components::chat::Chat;
+use components::error_boundary::ErrorBoundary;
+use components::require_user::RequireUser;
+use components::toasts::ToastStack;
 use wasm_bindgen::prelude::*;
 use yew::functional::*;
 use yew::prelude::*;
 use yew_router::prelude::*;
-use std::cell::RefCell;
+use std::ops::Deref;
 use std::rc::Rc;
+use services::feature_flags::FeatureFlags;
+use services::playback::PlaybackContext;
+use services::{pwa, theme};
 pub mod services;
+pub mod hooks;
 
-pub type User = Rc<UserInner>;
+/// Who is logged in; an empty `username` means nobody.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UserState {
+    pub username: String,
+    /// Joined through the guest flow; the server decides whether that means read-only.
+    pub is_guest: bool,
+}
+
+/// The `UserState` context. Every setter replaces the state, so each component reading the
+/// context re-renders with the change.
+#[derive(Clone, PartialEq)]
+pub struct UserCtx {
+    state: UseStateHandle<Rc<UserState>>,
+}
+
+impl Deref for UserCtx {
+    type Target = UserState;
+
+    fn deref(&self) -> &UserState {
+        &self.state
+    }
+}
+
+impl UserCtx {
+    fn update(&self, change: impl FnOnce(&mut UserState)) {
+        let mut state = (**self.state).clone();
+        change(&mut state);
+        self.state.set(Rc::new(state));
+    }
+
+    pub fn log_in(&self, username: String, is_guest: bool) {
+        self.state.set(Rc::new(UserState { username, is_guest }));
+    }
+
+    pub fn log_out(&self) {
+        self.state.set(Rc::new(UserState::default()));
+    }
 
-#[derive(Debug, PartialEq)]
-pub struct UserInner {
-    pub username: RefCell<String>,
+    pub fn set_username(&self, username: String) {
+        self.update(|state| state.username = username);
+    }
 }
 
 // When the `wee_alloc` feature is enabled, this uses `wee_alloc` as the global
@@ -40,34 +84,59 @@ pub enum Route {
 fn switch(selected_route: &Route) -> Html {
     match selected_route {
         Route::Login => html! {<Login />},
-        Route::Chat => html! {<Chat/>},
+        Route::Chat => html! {<ErrorBoundary><RequireUser><Chat/></RequireUser></ErrorBoundary>},
         Route::NotFound => html! {<h1>{"404 baby"}</h1>},
     }
 }
 
 #[function_component(Main)]
 fn main() -> Html {
-    let ctx = use_state(|| {
-        Rc::new(UserInner {
-            username: RefCell::new("initial".into()),
-        })
-    });
-
+    let user = UserCtx {
+        state: use_state(|| Rc::new(UserState::default())),
+    };
+    let flags = use_state(FeatureFlags::default);
+    let playback = use_state(PlaybackContext::default);
+    use_effect_with_deps(
+        |_| {
+            let watch = pwa::UpdateWatch::start();
+            move || drop(watch)
+        },
+        (),
+    );
+    {
+        let flags = flags.clone();
+        use_effect_with_deps(
+            move |_| {
+                wasm_bindgen_futures::spawn_local(async move { flags.set(FeatureFlags::fetch().await) });
+                || ()
+            },
+            (),
+        );
+    }
 
     html! {
-        <ContextProvider<User> context={(*ctx).clone()}>
+        <ContextProvider<UserCtx> context={user}>
+        <ContextProvider<FeatureFlags> context={(*flags).clone()}>
+        <ContextProvider<PlaybackContext> context={(*playback).clone()}>
         <BrowserRouter>
             <div class="flex w-screen h-screen">
                 <Switch<Route> render={Switch::render(switch)}/>
             </div>
+            <ToastStack/>
         </BrowserRouter>
-        </ContextProvider<User>>
+        </ContextProvider<PlaybackContext>>
+        </ContextProvider<FeatureFlags>>
+        </ContextProvider<UserCtx>>
     }
 }
 
 #[wasm_bindgen]
 pub fn run_app() -> Result<(), JsValue> {
     wasm_logger::init(wasm_logger::Config::default());
+    // Before the first render, so a dark page never flashes white.
+    theme::apply(theme::load());
+    pwa::capture_install_prompt();
+    pwa::register_service_worker();
     yew::start_app::<Main>();
     Ok(())
 }
@@ -0,0 +1,14 @@
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlElement, KeyboardEvent};
+
+/// Keydown handler for elements with `role="button"`: Enter and Space click them, as they
+/// would a real button. Keys meant for a button nested inside are left to it.
+pub fn press_on_key(e: KeyboardEvent) {
+    if !matches!(e.key().as_str(), "Enter" | " ") || e.target() != e.current_target() {
+        return;
+    }
+    if let Some(element) = e.current_target().and_then(|t| t.dyn_into::<HtmlElement>().ok()) {
+        e.prevent_default();
+        element.click();
+    }
+}
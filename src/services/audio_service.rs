@@ -0,0 +1,76 @@
+use crate::services::sound::PopSound;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SoundEffect {
+    Notification,
+    Send,
+    Join,
+    Leave,
+    Nudge,
+}
+
+impl SoundEffect {
+    /// Speed the pop is played back at, which sets its pitch; each effect gets its own.
+    fn rate(self) -> f32 {
+        match self {
+            SoundEffect::Notification => 1.0,
+            SoundEffect::Send => 1.25,
+            SoundEffect::Join => 1.5,
+            SoundEffect::Leave => 0.75,
+            SoundEffect::Nudge => 0.5,
+        }
+    }
+}
+
+/// Every sound the chat makes, all played from the one synthesized pop, so there are no
+/// files to fetch.
+pub struct AudioService {
+    /// Made on unmuting only: browsers warn about audio contexts created before a gesture.
+    pop: Option<PopSound>,
+    muted: bool,
+}
+
+impl AudioService {
+    pub fn new(muted: bool) -> Self {
+        let mut audio = Self { pop: None, muted: true };
+        audio.set_muted(muted);
+        audio
+    }
+
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+        if !muted && self.pop.is_none() {
+            self.pop = PopSound::new();
+        }
+    }
+
+    pub fn play(&self, sound: SoundEffect) {
+        if self.muted {
+            return;
+        }
+        if let Some(pop) = &self.pop {
+            pop.play_at(sound.rate());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_effect_sounds_different() {
+        let effects = [
+            SoundEffect::Notification,
+            SoundEffect::Send,
+            SoundEffect::Join,
+            SoundEffect::Leave,
+            SoundEffect::Nudge,
+        ];
+        for (i, a) in effects.iter().enumerate() {
+            for b in &effects[i + 1..] {
+                assert_ne!(a.rate(), b.rate(), "{:?} and {:?}", a, b);
+            }
+        }
+    }
+}
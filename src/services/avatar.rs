@@ -0,0 +1,145 @@
+use std::cell::RefCell;
+
+use crate::services::uri_component;
+use crate::t;
+
+/// Where avatars come from unless the user set a template of their own. Deployments with
+/// an avatar service of their own set `AVATAR_TEMPLATE` at build time.
+pub const DEFAULT_TEMPLATE: &str = match option_env!("AVATAR_TEMPLATE") {
+    Some(template) => template,
+    None => "https://avatars.dicebear.com/api/adventurer-neutral/{seed}.svg",
+};
+
+/// Replaced with the URL-encoded username.
+const SEED: &str = "{seed}";
+
+thread_local! {
+    /// The template in use, or `None` when it didn't validate and local identicons stand in.
+    static CURRENT: RefCell<Option<String>> = RefCell::new(checked(DEFAULT_TEMPLATE));
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TemplateError {
+    NotHttps,
+    NoPlaceholder,
+}
+
+impl TemplateError {
+    pub fn message(self) -> String {
+        match self {
+            TemplateError::NotHttps => t!("avatar.not_https"),
+            TemplateError::NoPlaceholder => t!("avatar.no_placeholder"),
+        }
+    }
+}
+
+/// Templates have to be https, so avatars never mix content into the page, and have to
+/// contain `{seed}`, or everyone would get the same picture.
+pub fn validate(template: &str) -> Result<(), TemplateError> {
+    if !template.starts_with("https://") {
+        return Err(TemplateError::NotHttps);
+    }
+    if !template.contains(SEED) {
+        return Err(TemplateError::NoPlaceholder);
+    }
+    Ok(())
+}
+
+fn checked(template: &str) -> Option<String> {
+    match validate(template) {
+        Ok(()) => Some(template.to_string()),
+        Err(e) => {
+            log::error!("avatar template {:?} rejected: {:?}", template, e);
+            None
+        }
+    }
+}
+
+/// `template` with every `{seed}` replaced by `seed`, URL-encoded.
+pub fn substitute(template: &str, seed: &str) -> String {
+    template.replace(SEED, &uri_component::encode(seed))
+}
+
+/// Switches to the user's template, or back to the build's when they have none.
+pub fn set_template(custom: Option<&str>) {
+    let template = checked(custom.unwrap_or(DEFAULT_TEMPLATE));
+    CURRENT.with(|current| *current.borrow_mut() = template);
+}
+
+/// The avatar URL for `name`: from the template in use, or a local identicon without one.
+pub fn avatar_for(name: &str) -> String {
+    CURRENT.with(|current| match &*current.borrow() {
+        Some(template) => substitute(template, name),
+        None => identicon(name),
+    })
+}
+
+/// FNV-1a, so the same name always gets the same picture.
+fn hash(name: &str) -> u32 {
+    name.bytes().fold(0x811c_9dc5, |h, b| (h ^ b as u32).wrapping_mul(0x0100_0193))
+}
+
+/// A mirrored 5x5 pattern in a colour of its own, built from `name` and made into a
+/// `data:` URL, so nothing is fetched from anywhere.
+fn identicon(name: &str) -> String {
+    let hash = hash(name);
+    let mut cells = String::new();
+    for row in 0..5 {
+        for col in 0..3 {
+            if (hash >> (row * 3 + col)) & 1 == 1 {
+                cells.push_str(&format!("<rect x='{}' y='{}' width='1' height='1'/>", col, row));
+                if col < 2 {
+                    cells.push_str(&format!("<rect x='{}' y='{}' width='1' height='1'/>", 4 - col, row));
+                }
+            }
+        }
+    }
+    let svg = format!(
+        "<svg xmlns='http://www.w3.org/2000/svg' viewBox='-1 -1 7 7'><g fill='hsl({},55%,55%)'>{}</g></svg>",
+        (hash >> 16) % 360,
+        cells
+    );
+    format!("data:image/svg+xml,{}", uri_component::encode(&svg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_need_https_and_the_placeholder() {
+        assert_eq!(validate("https://avatars.internal/{seed}.svg"), Ok(()));
+        assert_eq!(validate("https://avatars.internal/{seed}/{seed}.png"), Ok(()));
+        assert_eq!(validate("http://avatars.internal/{seed}.svg"), Err(TemplateError::NotHttps));
+        assert_eq!(validate("//avatars.internal/{seed}.svg"), Err(TemplateError::NotHttps));
+        assert_eq!(validate("https://avatars.internal/me.svg"), Err(TemplateError::NoPlaceholder));
+        assert_eq!(validate("https://avatars.internal/{SEED}.svg"), Err(TemplateError::NoPlaceholder));
+    }
+
+    #[test]
+    fn the_build_default_is_valid() {
+        assert_eq!(validate(DEFAULT_TEMPLATE), Ok(()));
+    }
+
+    #[test]
+    fn seeds_are_url_encoded_everywhere() {
+        assert_eq!(substitute("https://a.example/{seed}.svg", "ann"), "https://a.example/ann.svg");
+        assert_eq!(substitute("https://a.example/{seed}.svg", "a b/c?d"), "https://a.example/a%20b%2Fc%3Fd.svg");
+        assert_eq!(substitute("https://a.example/{seed}?s={seed}", "José"), "https://a.example/Jos%C3%A9?s=Jos%C3%A9");
+    }
+
+    #[test]
+    fn identicons_are_stable_and_local() {
+        assert_eq!(identicon("ann"), identicon("ann"));
+        assert_ne!(identicon("ann"), identicon("bob"));
+        assert!(identicon("ann").starts_with("data:image/svg+xml,"));
+    }
+
+    #[test]
+    fn rejected_templates_fall_back_to_identicons() {
+        set_template(Some("http://avatars.internal/{seed}.svg"));
+        assert_eq!(avatar_for("ann"), identicon("ann"));
+        set_template(Some("https://avatars.internal/{seed}.svg"));
+        assert_eq!(avatar_for("ann"), "https://avatars.internal/ann.svg");
+    }
+}
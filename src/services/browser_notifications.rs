@@ -0,0 +1,165 @@
+use std::future::Future;
+
+use gloo_storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Event, Notification, NotificationOptions, NotificationPermission};
+use yew::Callback;
+
+use crate::services::notifications::RoomPreference;
+
+/// Where a notification leads, carried in its `data` so the click handler can find it again.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct NotificationTarget {
+    pub channel: String,
+    pub message_id: String,
+}
+
+const PROMPT_KEY: &str = "yewchat.notify_prompt";
+
+/// How long "Not now" keeps the permission prompt away.
+const PROMPT_SNOOZE_MS: f64 = 7.0 * 24.0 * 60.0 * 60.0 * 1000.0;
+
+/// Where the inline "Enable desktop notifications" prompt stands.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(default)]
+pub struct PermissionPrompt {
+    /// "Not now" was picked; ms since the epoch.
+    pub snoozed_until: Option<f64>,
+    /// The browser said no. It won't ask again, so neither do we.
+    pub denied: bool,
+}
+
+impl PermissionPrompt {
+    pub fn load() -> Self {
+        LocalStorage::get(PROMPT_KEY).unwrap_or_default()
+    }
+
+    fn save(&self) {
+        if let Err(e) = LocalStorage::set(PROMPT_KEY, self) {
+            log::error!("failed to save notification prompt state: {:?}", e);
+        }
+    }
+
+    /// Whether to show the prompt: only once the user has engaged, while the browser
+    /// can still ask, and not while snoozed or after a denial.
+    pub fn should_show(&self, permission: NotificationPermission, has_sent_message: bool, now: f64) -> bool {
+        has_sent_message
+            && permission == NotificationPermission::Default
+            && !self.denied
+            && self.snoozed_until.map_or(true, |until| now >= until)
+    }
+
+    /// Whether picking `pref` should ask the browser right away: the user just opted into
+    /// alerts with a click, which is the gesture browsers want, and they haven't answered yet.
+    /// A snooze only applies to the inline prompt.
+    pub fn should_request_on(&self, permission: NotificationPermission, pref: RoomPreference) -> bool {
+        pref != RoomPreference::Muted && permission == NotificationPermission::Default && !self.denied
+    }
+
+    pub fn snooze(&mut self, now: f64) {
+        self.snoozed_until = Some(now + PROMPT_SNOOZE_MS);
+        self.save();
+    }
+
+    /// Records how the browser answered the permission request.
+    pub fn record(&mut self, permission: NotificationPermission) {
+        if permission == NotificationPermission::Denied {
+            self.denied = true;
+        }
+        self.snoozed_until = None;
+        self.save();
+    }
+}
+
+pub fn permission() -> NotificationPermission {
+    Notification::permission()
+}
+
+/// Asks the browser for permission and resolves to its answer.
+///
+/// The request goes out as soon as this is called, so call it from the click handler
+/// itself; only the answer is awaited.
+pub fn request_permission() -> impl Future<Output = NotificationPermission> {
+    let request = Notification::request_permission();
+    async move {
+        let promise = match request {
+            Ok(promise) => promise,
+            Err(e) => {
+                log::error!("failed to request notification permission: {:?}", e);
+                return NotificationPermission::Default;
+            }
+        };
+        match JsFuture::from(promise).await {
+            Ok(answer) => NotificationPermission::from_js_value(&answer).unwrap_or(NotificationPermission::Default),
+            Err(_) => NotificationPermission::Default,
+        }
+    }
+}
+
+/// Shows a system notification; clicking it focuses the window and reports its target.
+pub fn show(title: &str, body: &str, target: &NotificationTarget, on_click: Callback<NotificationTarget>) {
+    if Notification::permission() != NotificationPermission::Granted {
+        return;
+    }
+    let data = JsValue::from_str(&serde_json::to_string(target).unwrap());
+    let notification = match Notification::new_with_options(
+        title,
+        NotificationOptions::new().body(body).data(&data).tag(&target.channel),
+    ) {
+        Ok(notification) => notification,
+        Err(e) => {
+            log::error!("failed to show notification: {:?}", e);
+            return;
+        }
+    };
+    // Fires outside of any component, so the target is read back from the notification itself.
+    let onclick = Closure::once_into_js(move |e: Event| {
+        let notification: Notification = match e.target().and_then(|t| t.dyn_into().ok()) {
+            Some(notification) => notification,
+            None => return,
+        };
+        if let Some(window) = web_sys::window() {
+            let _ = window.focus();
+        }
+        notification.close();
+        match notification
+            .data()
+            .as_string()
+            .and_then(|data| serde_json::from_str(&data).ok())
+        {
+            Some(target) => on_click.emit(target),
+            None => log::error!("notification without a target: {:?}", notification.data()),
+        }
+    });
+    notification.set_onclick(Some(onclick.unchecked_ref()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opting_into_alerts_asks_until_the_browser_has_answered() {
+        let prompt = PermissionPrompt::default();
+        assert!(prompt.should_request_on(NotificationPermission::Default, RoomPreference::All));
+        assert!(prompt.should_request_on(NotificationPermission::Default, RoomPreference::MentionsOnly));
+        assert!(!prompt.should_request_on(NotificationPermission::Default, RoomPreference::Muted));
+        assert!(!prompt.should_request_on(NotificationPermission::Granted, RoomPreference::All));
+        assert!(!prompt.should_request_on(NotificationPermission::Denied, RoomPreference::All));
+
+        let denied = PermissionPrompt { denied: true, ..Default::default() };
+        assert!(!denied.should_request_on(NotificationPermission::Default, RoomPreference::All));
+    }
+
+    #[test]
+    fn the_inline_prompt_waits_for_engagement_and_snoozes() {
+        let prompt = PermissionPrompt { snoozed_until: Some(100.0), denied: false };
+        assert!(!prompt.should_show(NotificationPermission::Default, false, 200.0));
+        assert!(!prompt.should_show(NotificationPermission::Default, true, 50.0));
+        assert!(prompt.should_show(NotificationPermission::Default, true, 100.0));
+        assert!(!prompt.should_show(NotificationPermission::Granted, true, 100.0));
+    }
+}
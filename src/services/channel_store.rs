@@ -0,0 +1,1239 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::components::chat::{FileDelete, LikeMessage, MessageData, MessageDelete, MessageEdit, MessagePin, Unpin};
+use crate::services::message_cache;
+use crate::services::notifications::NotifyLevel;
+
+pub const DEFAULT_CHANNEL: &str = "general";
+
+/// Messages kept in memory per channel. Past this the oldest are dropped as new ones arrive
+/// and scrolling back fetches them again.
+pub const MAX_CHANNEL_MESSAGES: usize = 2_000;
+
+/// Oldest mentions are dropped from the index past this many.
+const MAX_MENTIONS: usize = 200;
+
+/// How long a channel's member list is trusted before it is requested again.
+const MEMBERS_TTL_MS: f64 = 60_000.0;
+
+/// Where a channel is in paging back through its history.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum HistoryState {
+    /// Nothing asked for yet.
+    Unrequested,
+    /// The latest page is on its way.
+    Loading,
+    /// Have at least one page; older ones may exist.
+    Idle,
+    /// An older page is on its way.
+    LoadingOlder,
+    /// The server has nothing older.
+    Exhausted,
+}
+
+pub struct Channel {
+    pub name: String,
+    pub messages: Vec<MessageData>,
+    /// Id of the topmost fully-visible message when the user last switched away.
+    pub scroll_anchor: Option<String>,
+    pub unread: usize,
+    /// New messages arrived that weren't worth a count, e.g. in a muted channel.
+    pub has_activity: bool,
+    /// An archived channel pulled back into view by a mention, until it is opened.
+    pub surfaced: bool,
+    /// Closed DM tabs keep their history but stay out of the rail until the next message.
+    pub hidden: bool,
+    /// Who is in the channel, once the server has told us.
+    pub members: Option<Vec<String>>,
+    /// The room's greeting, shown above its history when it is new to the user.
+    pub welcome: Option<String>,
+    /// Minimum seconds between two of my messages; 0 when slow mode is off.
+    pub slow_mode_seconds: u32,
+    /// Admins aren't held to slow mode.
+    pub slow_mode_exempt: bool,
+    /// When I may send here again, in ms since the epoch.
+    pub cooldown_until: f64,
+    /// Most distinct emoji a message here may collect; `None` when there is no limit.
+    pub reaction_cap: Option<u32>,
+    /// Id of the oldest message fetched as history; the "earlier messages" marker sits above it.
+    pub history_start: Option<String>,
+    pub history: HistoryState,
+    /// A day picked from the calendar that isn't joined up with `messages` yet.
+    pub window: Option<HistoryWindow>,
+    /// Oldest first. The messages themselves may not be loaded.
+    pub pins: Vec<MessagePin>,
+    members_requested_at: Option<f64>,
+}
+
+/// A day of history fetched out of order. It is a segment of its own, with a gap of
+/// unknown size between it and the live messages, until it turns out to reach them.
+pub struct HistoryWindow {
+    /// `YYYY-MM-DD`, local time.
+    pub day: String,
+    /// Start and end of that day, in ms since the epoch.
+    pub from: f64,
+    pub to: f64,
+    /// Oldest first.
+    pub messages: Vec<MessageData>,
+    /// Sources (the server, the archive) that haven't answered yet.
+    pub pending: u8,
+    /// The server stopped at its page limit, so the day may go on past the last message here.
+    pub truncated: bool,
+    /// Gave up waiting on a source that never answered.
+    pub timed_out: bool,
+}
+
+/// Who answered for a [`HistoryWindow`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindowSource {
+    /// The local archive, which only has what this device has seen.
+    Archive,
+    /// The server; `truncated` when it sent as many as it was asked for.
+    Server { truncated: bool },
+}
+
+/// Where a batch for a [`HistoryWindow`] ended up.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WindowFill {
+    /// The window was closed or replaced while the batch was on its way.
+    Stale,
+    /// Kept apart from the live messages; holds the window's day.
+    Detached(String),
+    /// The day reaches the live messages, so it was spliced into them and the window closed.
+    Joined(String),
+}
+
+impl Channel {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            messages: vec![],
+            scroll_anchor: None,
+            unread: 0,
+            has_activity: false,
+            surfaced: false,
+            hidden: false,
+            members: None,
+            welcome: None,
+            slow_mode_seconds: 0,
+            slow_mode_exempt: false,
+            cooldown_until: 0.0,
+            reaction_cap: None,
+            history_start: None,
+            history: HistoryState::Unrequested,
+            window: None,
+            pins: vec![],
+            members_requested_at: None,
+        }
+    }
+
+    pub fn is_dm(&self) -> bool {
+        self.name.starts_with('@')
+    }
+
+    /// Milliseconds until I may send again, if slow mode is holding me back.
+    pub fn cooldown_remaining(&self, now: f64) -> Option<f64> {
+        let remaining = self.cooldown_until - now;
+        (self.slow_mode_seconds > 0 && !self.slow_mode_exempt && remaining > 0.0).then_some(remaining)
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.messages.iter().any(|m| m.id == id)
+    }
+
+    /// Replies in the thread hanging off `root_id`, oldest first.
+    pub fn thread(&self, root_id: &str) -> Vec<&MessageData> {
+        self.messages.iter().filter(|m| m.thread_id.as_deref() == Some(root_id)).collect()
+    }
+
+    /// How many replies each thread has, by the id of the message it hangs off.
+    pub fn reply_counts(&self) -> HashMap<&str, usize> {
+        let mut counts = HashMap::new();
+        for root in self.messages.iter().filter_map(|m| m.thread_id.as_deref()) {
+            *counts.entry(root).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// What the message list shows: the picked day while there is one, else the live messages.
+    pub fn visible(&self) -> &[MessageData] {
+        match &self.window {
+            Some(window) => &window.messages,
+            None => &self.messages,
+        }
+    }
+
+    /// A message from either segment.
+    pub fn find(&self, id: &str) -> Option<&MessageData> {
+        self.messages
+            .iter()
+            .chain(self.window.iter().flat_map(|w| w.messages.iter()))
+            .find(|m| m.id == id)
+    }
+
+    fn all_messages_mut(&mut self) -> impl Iterator<Item = &mut MessageData> {
+        self.messages
+            .iter_mut()
+            .chain(self.window.iter_mut().flat_map(|w| w.messages.iter_mut()))
+    }
+
+    /// Timestamp of the oldest live message, where the gap behind a window ends.
+    fn live_start(&self) -> Option<f64> {
+        self.messages.iter().find_map(|m| m.timestamp)
+    }
+
+    /// Joins the window up with the live messages once every source has answered, nothing
+    /// was cut off, and the day runs into them. Until then it stays a segment of its own.
+    fn settle_window(&mut self) -> WindowFill {
+        let live_start = self.live_start();
+        let window = match &self.window {
+            Some(window) => window,
+            None => return WindowFill::Stale,
+        };
+        let reaches_live = matches!(live_start, Some(start) if window.to >= start);
+        if window.pending > 0 || window.truncated || !reaches_live {
+            return WindowFill::Detached(window.day.clone());
+        }
+        let window = self.window.take().unwrap();
+        self.merge_history(window.messages, false);
+        WindowFill::Joined(window.day)
+    }
+
+    /// Whether the live messages already reach back to `ts`.
+    pub fn covers(&self, ts: f64) -> bool {
+        self.history == HistoryState::Exhausted || matches!(self.live_start(), Some(start) if start <= ts)
+    }
+
+    /// Drops the oldest messages beyond `max` and returns their ids. Whatever pointed at them
+    /// is let go, and history counts as unfinished again so paging back refetches them.
+    fn trim(&mut self, max: usize) -> Vec<String> {
+        let excess = self.messages.len().saturating_sub(max);
+        if excess == 0 {
+            return vec![];
+        }
+        let dropped: Vec<String> = self.messages.drain(..excess).map(|m| m.id).collect();
+        if matches!(&self.history_start, Some(id) if dropped.contains(id)) {
+            self.history_start = None;
+        }
+        if matches!(&self.scroll_anchor, Some(id) if dropped.contains(id)) {
+            self.scroll_anchor = None;
+        }
+        if self.history == HistoryState::Exhausted {
+            self.history = HistoryState::Idle;
+        }
+        dropped
+    }
+
+    /// Folds a history batch in underneath whatever arrived live while it was in flight.
+    ///
+    /// Messages already in the channel are dropped from the batch and the rest are ordered by
+    /// timestamp, then interleaved with the live messages: a history message goes ahead of every
+    /// live message that isn't older than it. Anything without a timestamp can't be placed, so
+    /// history without one goes first and live messages without one count as newest.
+    fn merge_history(&mut self, mut batch: Vec<MessageData>, mark_start: bool) {
+        let mut seen: HashSet<String> = self.messages.iter().map(|m| m.id.clone()).collect();
+        batch.retain(|m| seen.insert(m.id.clone()));
+        batch.sort_by(|a, b| {
+            a.timestamp
+                .unwrap_or(f64::NEG_INFINITY)
+                .total_cmp(&b.timestamp.unwrap_or(f64::NEG_INFINITY))
+        });
+        match batch.first() {
+            Some(first) if mark_start => self.history_start = Some(first.id.clone()),
+            Some(_) => {}
+            None => return,
+        }
+
+        let live = std::mem::take(&mut self.messages);
+        let mut batch = batch.into_iter().peekable();
+        for message in live {
+            while let Some(older) = batch.next_if(|h| precedes(h, &message)) {
+                self.messages.push(older);
+            }
+            self.messages.push(message);
+        }
+        self.messages.extend(batch);
+    }
+}
+
+fn precedes(history: &MessageData, live: &MessageData) -> bool {
+    match (history.timestamp, live.timestamp) {
+        (Some(history), Some(live)) => history <= live,
+        _ => true,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReactionChange {
+    Add,
+    Remove,
+}
+
+/// Key a message is filed under. DMs are keyed by the other participant, e.g. `@bob`.
+pub fn conversation_key(message: &MessageData, me: &str) -> String {
+    match message.channel.as_deref() {
+        Some(channel) if channel.starts_with('@') && message.from != me => format!("@{}", message.from),
+        Some(channel) => channel.to_string(),
+        None => DEFAULT_CHANNEL.to_string(),
+    }
+}
+
+/// What made a message count as a mention.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MentionSource {
+    /// `@me`, or a DM.
+    Direct,
+    /// One of the user's watch words.
+    Keyword,
+}
+
+/// A message that mentions the user and hasn't been looked at yet.
+pub struct Mention {
+    pub channel: String,
+    pub message_id: String,
+    pub source: MentionSource,
+}
+
+pub struct ChannelStore {
+    channels: Vec<Channel>,
+    active: String,
+    next_id: u64,
+    /// Newest first.
+    mentions: VecDeque<Mention>,
+    /// Ids of messages changed since they were stored: reacted to, liked, edited and so on.
+    dirty: HashSet<String>,
+    /// Messages were added or removed since they were last stored.
+    unsaved: bool,
+}
+
+impl ChannelStore {
+    pub fn new(names: &[&str]) -> Self {
+        let mut store = Self {
+            channels: names.iter().map(|name| Channel::new(name)).collect(),
+            active: DEFAULT_CHANNEL.to_string(),
+            next_id: 0,
+            mentions: VecDeque::new(),
+            dirty: HashSet::new(),
+            unsaved: false,
+        };
+        store.get_or_create(DEFAULT_CHANNEL);
+        store
+    }
+
+    pub fn channels(&self) -> &[Channel] {
+        &self.channels
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Channel> {
+        self.channels.iter().find(|c| c.name == name)
+    }
+
+    pub fn active_name(&self) -> &str {
+        &self.active
+    }
+
+    pub fn active(&self) -> &Channel {
+        self.get(&self.active).expect("active channel to exist")
+    }
+
+    pub fn active_mut(&mut self) -> &mut Channel {
+        let active = self.active.clone();
+        self.get_or_create(&active)
+    }
+
+    pub fn get_or_create(&mut self, name: &str) -> &mut Channel {
+        let index = match self.channels.iter().position(|c| c.name == name) {
+            Some(index) => index,
+            None => {
+                self.channels.push(Channel::new(name));
+                self.channels.len() - 1
+            }
+        };
+        &mut self.channels[index]
+    }
+
+    /// Files a message under its channel, assigning a local id when the server didn't send one.
+    /// `level` decides whether it counts towards the channel's unread badge. `at_bottom` is
+    /// whether the active channel's list is scrolled to the end: while the user reads back,
+    /// or an older page is on its way, the oldest messages are kept, since dropping them
+    /// would only have paging fetch them again. Returns the message's id.
+    pub fn push(&mut self, mut message: MessageData, level: NotifyLevel, at_bottom: bool) -> String {
+        if message.id.is_empty() {
+            self.next_id += 1;
+            message.id = format!("local-{}", self.next_id);
+        }
+        let name = message
+            .channel
+            .clone()
+            .unwrap_or_else(|| DEFAULT_CHANNEL.to_string());
+        let is_active = name == self.active;
+        self.unsaved = true;
+        let channel = self.get_or_create(&name);
+        if !is_active {
+            match level {
+                NotifyLevel::Silent => channel.has_activity = true,
+                NotifyLevel::Badge | NotifyLevel::Alert => channel.unread += 1,
+            }
+        }
+        channel.hidden = false;
+        let id = message.id.clone();
+        channel.messages.push(message);
+        let reading_back = (is_active && !at_bottom) || channel.history == HistoryState::LoadingOlder;
+        let dropped = if reading_back { vec![] } else { channel.trim(MAX_CHANNEL_MESSAGES) };
+        if !dropped.is_empty() {
+            self.mentions.retain(|m| m.channel != name || !dropped.contains(&m.message_id));
+        }
+        id
+    }
+
+    pub fn add_mention(&mut self, channel: &str, message_id: String, source: MentionSource) {
+        self.mentions.push_front(Mention {
+            channel: channel.to_string(),
+            message_id,
+            source,
+        });
+        self.mentions.truncate(MAX_MENTIONS);
+    }
+
+    /// Unviewed mentions, newest first, alongside the messages they point at.
+    pub fn mentions(&self) -> impl Iterator<Item = (&Mention, &MessageData)> {
+        self.mentions.iter().filter_map(move |mention| {
+            self.get(&mention.channel)?
+                .messages
+                .iter()
+                .find(|m| m.id == mention.message_id)
+                .map(|message| (mention, message))
+        })
+    }
+
+    pub fn mention_count(&self) -> usize {
+        self.mentions().count()
+    }
+
+    pub fn clear_mention(&mut self, message_id: &str) {
+        self.mentions.retain(|m| m.message_id != message_id);
+    }
+
+    pub fn set_members(&mut self, name: &str, members: Vec<String>) {
+        self.get_or_create(name).members = Some(members);
+    }
+
+    /// Whether the member list of `name` should be (re)requested at `now` (ms since epoch).
+    /// Marks it as requested when it returns true so repeated switches don't spam the server.
+    pub fn claim_members_request(&mut self, name: &str, now: f64) -> bool {
+        let channel = self.get_or_create(name);
+        if channel.is_dm() {
+            return false;
+        }
+        match channel.members_requested_at {
+            Some(at) if now - at < MEMBERS_TTL_MS => false,
+            _ => {
+                channel.members_requested_at = Some(now);
+                true
+            }
+        }
+    }
+
+    /// Fills channels with messages from the local cache, before anything live has arrived.
+    pub fn restore(&mut self, cached: HashMap<String, Vec<MessageData>>) {
+        for (name, messages) in cached {
+            self.get_or_create(&name).messages = messages;
+        }
+    }
+
+    /// Sets the scroll anchor a reload left `name` with, if its cached messages still hold it.
+    pub fn restore_anchor(&mut self, name: &str, id: &str) {
+        if let Some(channel) = self.channels.iter_mut().find(|c| c.name == name && c.contains(id)) {
+            channel.scroll_anchor = Some(id.to_string());
+        }
+    }
+
+    /// Forgets every message and mention, leaving channels, members and room settings. History
+    /// is fetched again the next time each channel is opened.
+    pub fn clear(&mut self) {
+        for channel in &mut self.channels {
+            channel.messages.clear();
+            channel.window = None;
+            channel.scroll_anchor = None;
+            channel.history_start = None;
+            channel.history = HistoryState::Unrequested;
+            channel.unread = 0;
+            channel.has_activity = false;
+        }
+        self.mentions.clear();
+        self.dirty.clear();
+        self.unsaved = false;
+    }
+
+    /// Marks every channel's history as not asked for, so after a reconnect each fetches its
+    /// latest page again and merges in what it missed.
+    pub fn refetch_history(&mut self) {
+        for channel in &mut self.channels {
+            channel.history = HistoryState::Unrequested;
+        }
+    }
+
+    /// Sets `day` (from `from` to `to`) up as the channel's window, waiting on `pending` sources.
+    pub fn open_window(&mut self, name: &str, day: String, from: f64, to: f64, pending: u8) {
+        self.get_or_create(name).window = Some(HistoryWindow {
+            day,
+            from,
+            to,
+            messages: vec![],
+            pending,
+            truncated: false,
+            timed_out: false,
+        });
+    }
+
+    /// Adds one source's messages for the window starting at `from`, merged by id with what
+    /// the other source sent; the server's copy of a message wins over the archive's. Once
+    /// both have answered and the day is seen to run into the live messages, everything is
+    /// spliced into them and the gap is gone.
+    pub fn fill_window(&mut self, name: &str, from: f64, source: WindowSource, mut batch: Vec<MessageData>) -> WindowFill {
+        for message in batch.iter_mut().filter(|m| m.id.is_empty()) {
+            self.next_id += 1;
+            message.id = format!("local-{}", self.next_id);
+        }
+        let channel = match self.channels.iter_mut().find(|c| c.name == name) {
+            Some(channel) => channel,
+            None => return WindowFill::Stale,
+        };
+        let window = match &mut channel.window {
+            Some(window) if window.from == from => window,
+            _ => return WindowFill::Stale,
+        };
+        window.pending = window.pending.saturating_sub(1);
+        for message in batch {
+            match window.messages.iter_mut().find(|m| m.id == message.id) {
+                Some(existing) if matches!(source, WindowSource::Server { .. }) => *existing = message,
+                Some(_) => {}
+                None => window.messages.push(message),
+            }
+        }
+        window.messages.sort_by(|a, b| {
+            a.timestamp
+                .unwrap_or(f64::NEG_INFINITY)
+                .total_cmp(&b.timestamp.unwrap_or(f64::NEG_INFINITY))
+        });
+        if let WindowSource::Server { truncated: true } = source {
+            window.truncated = true;
+        }
+        let fill = channel.settle_window();
+        self.unsaved |= matches!(fill, WindowFill::Joined(_));
+        fill
+    }
+
+    /// Stops waiting on whichever source hasn't answered for the window starting at `from`.
+    pub fn expire_window(&mut self, name: &str, from: f64) -> WindowFill {
+        let channel = match self.channels.iter_mut().find(|c| c.name == name) {
+            Some(channel) => channel,
+            None => return WindowFill::Stale,
+        };
+        match &mut channel.window {
+            Some(window) if window.from == from && window.pending > 0 => {
+                window.pending = 0;
+                window.timed_out = true;
+            }
+            _ => return WindowFill::Stale,
+        }
+        let fill = channel.settle_window();
+        self.unsaved |= matches!(fill, WindowFill::Joined(_));
+        fill
+    }
+
+    /// Drops the window and goes back to the live messages. Returns whether there was one.
+    pub fn close_window(&mut self, name: &str) -> bool {
+        match self.channels.iter_mut().find(|c| c.name == name) {
+            Some(channel) => channel.window.take().is_some(),
+            None => false,
+        }
+    }
+
+    /// Adds messages read back from the local archive to `name`. Only confirmed messages are
+    /// archived, so unlike the local cache these aren't marked as cached. Returns whether any
+    /// were new.
+    pub fn merge_archived(&mut self, name: &str, messages: Vec<MessageData>) -> bool {
+        let channel = self.get_or_create(name);
+        let before = channel.messages.len();
+        channel.merge_history(messages, false);
+        let added = channel.messages.len() != before;
+        self.unsaved |= added;
+        added
+    }
+
+    /// Whether history for `name` still needs asking for. Only true once per channel.
+    pub fn claim_history_request(&mut self, name: &str) -> bool {
+        let channel = self.get_or_create(name);
+        if channel.history != HistoryState::Unrequested {
+            return false;
+        }
+        channel.history = HistoryState::Loading;
+        true
+    }
+
+    /// Stops waiting on a page of `name`'s history that never came: the latest is asked for
+    /// again the next time the channel is opened, an older one the next time the top of
+    /// the list comes into view. Returns whether one was in flight.
+    pub fn abandon_history_request(&mut self, name: &str) -> bool {
+        let channel = self.get_or_create(name);
+        channel.history = match channel.history {
+            HistoryState::Loading => HistoryState::Unrequested,
+            HistoryState::LoadingOlder => HistoryState::Idle,
+            _ => return false,
+        };
+        true
+    }
+
+    /// The id to page back from when an older page of `name` should be asked for now,
+    /// marking it as in flight. `None` while another request is out, before the first
+    /// page has arrived, and once the server has run out.
+    pub fn claim_older_history(&mut self, name: &str) -> Option<String> {
+        let channel = self.get_or_create(name);
+        if channel.history != HistoryState::Idle {
+            return None;
+        }
+        // Local ids mean nothing to the server.
+        let oldest = channel
+            .messages
+            .iter()
+            .find(|m| !m.id.starts_with("local-"))?
+            .id
+            .clone();
+        channel.history = HistoryState::LoadingOlder;
+        Some(oldest)
+    }
+
+    /// Merges a page of history the server sent for `name`; see [`Channel::merge_history`].
+    /// Returns the state the channel was in, so callers can tell an older page from the first.
+    pub fn merge_history(&mut self, name: &str, mut batch: Vec<MessageData>, has_more: bool) -> HistoryState {
+        for message in batch.iter_mut().filter(|m| m.id.is_empty()) {
+            self.next_id += 1;
+            message.id = format!("local-{}", self.next_id);
+        }
+        let channel = self.get_or_create(name);
+        let previous = channel.history;
+        let first_page = previous != HistoryState::LoadingOlder;
+        if first_page {
+            message_cache::reconcile(&mut channel.messages, &batch, has_more);
+        }
+        channel.history = if has_more { HistoryState::Idle } else { HistoryState::Exhausted };
+        channel.merge_history(batch, first_page);
+        self.unsaved = true;
+        previous
+    }
+
+    pub fn has_reacted(&self, channel: &str, message_id: &str, emoji: &str, user: &str) -> bool {
+        self.get(channel)
+            .and_then(|c| c.find(message_id))
+            .and_then(|m| m.reactions.as_ref())
+            .and_then(|rs| rs.iter().find(|(e, _)| e == emoji))
+            .map_or(false, |(_, users)| users.iter().any(|u| u == user))
+    }
+
+    /// Adds or removes `user`'s `emoji` on a message, for both my own clicks and other
+    /// people's reactions. Returns false when nothing changed, e.g. a repeated add.
+    pub fn apply_reaction(
+        &mut self,
+        channel: &str,
+        message_id: &str,
+        emoji: &str,
+        user: &str,
+        change: ReactionChange,
+    ) -> bool {
+        if self.has_reacted(channel, message_id, emoji, user) == (change == ReactionChange::Add) {
+            return false;
+        }
+        let message = match self
+            .channels
+            .iter_mut()
+            .find(|c| c.name == channel)
+            .and_then(|c| c.all_messages_mut().find(|m| m.id == message_id))
+        {
+            Some(message) => message,
+            None => return false,
+        };
+        let reactions = message.reactions.get_or_insert_with(Vec::new);
+        match change {
+            ReactionChange::Add => match reactions.iter_mut().find(|(e, _)| e == emoji) {
+                Some((_, users)) => users.push(user.to_string()),
+                None => reactions.push((emoji.to_string(), vec![user.to_string()])),
+            },
+            ReactionChange::Remove => {
+                for (_, users) in reactions.iter_mut().filter(|(e, _)| e == emoji) {
+                    users.retain(|u| u != user);
+                }
+                reactions.retain(|(_, users)| !users.is_empty());
+            }
+        }
+        self.dirty.insert(message_id.to_string());
+        true
+    }
+
+    /// Records or takes back a like on whichever loaded message it is for. Returns false when
+    /// nothing changed, e.g. the echo of my own like.
+    pub fn apply_like(&mut self, like: &LikeMessage) -> bool {
+        let message = match self
+            .channels
+            .iter_mut()
+            .flat_map(Channel::all_messages_mut)
+            .find(|m| m.id == like.message_id)
+        {
+            Some(message) => message,
+            None => return false,
+        };
+        let has_liked = message.likes.contains(&like.username);
+        match (like.liked, has_liked) {
+            (true, false) => message.likes.push(like.username.clone()),
+            (false, true) => message.likes.retain(|u| *u != like.username),
+            _ => return false,
+        }
+        self.dirty.insert(like.message_id.clone());
+        true
+    }
+
+    /// Puts a pin up in its channel, or changes the kind of one already there. Returns false
+    /// when nothing changed, e.g. the echo of my own pin.
+    pub fn apply_pin(&mut self, pin: MessagePin) -> bool {
+        let channel = self.get_or_create(&pin.channel);
+        match channel.pins.iter_mut().find(|p| p.message_id == pin.message_id) {
+            Some(existing) if *existing == pin => false,
+            Some(existing) => {
+                *existing = pin;
+                true
+            }
+            None => {
+                channel.pins.push(pin);
+                true
+            }
+        }
+    }
+
+    /// Replaces every pin of `name` with the server's list. Returns whether anything changed.
+    pub fn set_pins(&mut self, name: &str, pins: Vec<MessagePin>) -> bool {
+        let channel = self.get_or_create(name);
+        if channel.pins == pins {
+            return false;
+        }
+        channel.pins = pins;
+        true
+    }
+
+    /// Takes a pin down. Returns whether there was one.
+    pub fn apply_unpin(&mut self, unpin: &Unpin) -> bool {
+        let channel = match self.channels.iter_mut().find(|c| c.name == unpin.channel) {
+            Some(channel) => channel,
+            None => return false,
+        };
+        let before = channel.pins.len();
+        channel.pins.retain(|p| p.message_id != unpin.message_id);
+        channel.pins.len() != before
+    }
+
+    /// Marks the attachment with `delete.file_id` as removed wherever it is. Returns false if
+    /// it isn't loaded or was already gone.
+    pub fn apply_file_delete(&mut self, delete: &FileDelete) -> bool {
+        for message in self.channels.iter_mut().flat_map(Channel::all_messages_mut) {
+            match message.attachments.iter_mut().find(|a| a.file_id == delete.file_id) {
+                Some(attachment) if !attachment.removed => {
+                    attachment.removed = true;
+                    self.dirty.insert(message.id.clone());
+                    return true;
+                }
+                Some(_) => return false,
+                None => {}
+            }
+        }
+        false
+    }
+
+    /// Takes a message out of whichever channel holds it, with any mention of it. Returns
+    /// false if it isn't loaded.
+    pub fn apply_delete(&mut self, delete: &MessageDelete) -> bool {
+        let mut removed = false;
+        for channel in self.channels.iter_mut() {
+            let before = channel.messages.len();
+            channel.messages.retain(|m| m.id != delete.message_id);
+            removed |= channel.messages.len() != before;
+            if let Some(window) = &mut channel.window {
+                let before = window.messages.len();
+                window.messages.retain(|m| m.id != delete.message_id);
+                removed |= window.messages.len() != before;
+            }
+        }
+        if removed {
+            self.clear_mention(&delete.message_id);
+            self.dirty.remove(&delete.message_id);
+            self.unsaved = true;
+        }
+        removed
+    }
+
+    /// Sets or clears the spam score of a loaded message. Returns false if it isn't loaded or
+    /// already had that score.
+    pub fn set_spam_score(&mut self, id: &str, score: Option<f32>) -> bool {
+        match self.channels.iter_mut().flat_map(Channel::all_messages_mut).find(|m| m.id == id) {
+            Some(message) if message.spam_score != score => {
+                message.spam_score = score;
+                self.dirty.insert(id.to_string());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn set_locked(&mut self, id: &str, locked: bool) -> bool {
+        match self.channels.iter_mut().flat_map(Channel::all_messages_mut).find(|m| m.id == id) {
+            Some(message) if message.locked != locked => {
+                message.locked = locked;
+                self.dirty.insert(id.to_string());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Swaps in a message's new text wherever it is. Returns false if it isn't loaded.
+    pub fn apply_edit(&mut self, edit: MessageEdit) -> bool {
+        let message = match self
+            .channels
+            .iter_mut()
+            .flat_map(Channel::all_messages_mut)
+            .find(|m| m.id == edit.message_id)
+        {
+            Some(message) => message,
+            None => return false,
+        };
+        message.message = edit.message;
+        message.edited = true;
+        message.edited_by = (edit.by != message.from).then_some(edit.by);
+        message.edit_reason = edit.reason;
+        self.dirty.insert(edit.message_id);
+        true
+    }
+
+    /// Re-attributes everything `old` wrote, edited, reacted with or liked to `new` after a
+    /// confirmed rename.
+    pub fn rename_user(&mut self, old: &str, new: &str) {
+        for message in self.channels.iter_mut().flat_map(Channel::all_messages_mut) {
+            let mut changed = false;
+            if message.from == old {
+                message.from = new.to_string();
+                changed = true;
+            }
+            if message.edited_by.as_deref() == Some(old) {
+                message.edited_by = Some(new.to_string());
+                changed = true;
+            }
+            for (_, users) in message.reactions.iter_mut().flatten() {
+                for user in users.iter_mut().filter(|u| *u == old) {
+                    *user = new.to_string();
+                    changed = true;
+                }
+            }
+            for user in message.likes.iter_mut().filter(|u| *u == old) {
+                *user = new.to_string();
+                changed = true;
+            }
+            if changed {
+                self.dirty.insert(message.id.clone());
+            }
+        }
+    }
+
+    /// Whether anything the caches keep has changed since [`ChannelStore::take_dirty`].
+    pub fn has_unsaved(&self) -> bool {
+        self.unsaved || !self.dirty.is_empty()
+    }
+
+    /// The messages changed since they were stored, for the caches to write again. Ones no
+    /// longer loaded are let go.
+    pub fn take_dirty(&mut self) -> Vec<MessageData> {
+        self.unsaved = false;
+        let dirty = std::mem::take(&mut self.dirty);
+        self.channels
+            .iter()
+            .flat_map(|c| c.messages.iter().chain(c.window.iter().flat_map(|w| w.messages.iter())))
+            .filter(|m| dirty.contains(&m.id))
+            .cloned()
+            .collect()
+    }
+
+    /// Removes a DM from the rail without forgetting its history.
+    pub fn close(&mut self, name: &str) {
+        if let Some(channel) = self.channels.iter_mut().find(|c| c.name == name) {
+            channel.hidden = true;
+        }
+    }
+
+    /// Makes `name` the active channel, remembering `anchor` for the one being left.
+    ///
+    /// Returns the anchor to restore in the new channel, or `None` to scroll to the
+    /// bottom (never visited, or the anchored message has since been pruned).
+    pub fn switch_to(&mut self, name: &str, anchor: Option<String>) -> Option<String> {
+        self.active_mut().scroll_anchor = anchor;
+        self.active = name.to_string();
+        self.mentions.retain(|m| m.channel != name);
+        let channel = self.get_or_create(name);
+        channel.unread = 0;
+        channel.has_activity = false;
+        channel.surfaced = false;
+        channel.hidden = false;
+        channel
+            .scroll_anchor
+            .clone()
+            .filter(|id| channel.contains(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: f64 = 86_400_000.0;
+
+    fn message(id: &str, timestamp: f64, text: &str) -> MessageData {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "from": "ann",
+            "message": text,
+            "reactions": null,
+            "timestamp": timestamp,
+        }))
+        .unwrap()
+    }
+
+    /// A store whose live messages start late in the day at 0..DAY.
+    fn store() -> ChannelStore {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL]);
+        store.merge_history(DEFAULT_CHANNEL, vec![message("live", DAY - 1.0, "now")], true);
+        store.open_window(DEFAULT_CHANNEL, "1970-01-01".into(), 0.0, DAY, 2);
+        store
+    }
+
+    fn ids(messages: &[MessageData]) -> Vec<&str> {
+        messages.iter().map(|m| m.id.as_str()).collect()
+    }
+
+    #[test]
+    fn window_waits_for_both_sources_before_joining() {
+        let mut store = store();
+        let fill = store.fill_window(DEFAULT_CHANNEL, 0.0, WindowSource::Archive, vec![message("b", 2.0, "")]);
+        assert_eq!(fill, WindowFill::Detached("1970-01-01".into()));
+
+        let server = vec![message("a", 1.0, ""), message("b", 2.0, "edited"), message("c", 3.0, "")];
+        let fill = store.fill_window(DEFAULT_CHANNEL, 0.0, WindowSource::Server { truncated: false }, server);
+        assert_eq!(fill, WindowFill::Joined("1970-01-01".into()));
+        let channel = store.active();
+        assert_eq!(ids(&channel.messages), ["a", "b", "c", "live"]);
+        assert_eq!(channel.messages[1].message, "edited");
+    }
+
+    #[test]
+    fn archive_does_not_replace_the_server_copy() {
+        let mut store = store();
+        store.fill_window(DEFAULT_CHANNEL, 0.0, WindowSource::Server { truncated: false }, vec![message("a", 1.0, "server")]);
+        store.fill_window(DEFAULT_CHANNEL, 0.0, WindowSource::Archive, vec![message("a", 1.0, "archive")]);
+        assert_eq!(store.active().messages[0].message, "server");
+    }
+
+    #[test]
+    fn truncated_window_stays_detached() {
+        let mut store = store();
+        store.fill_window(DEFAULT_CHANNEL, 0.0, WindowSource::Archive, vec![]);
+        let fill = store.fill_window(DEFAULT_CHANNEL, 0.0, WindowSource::Server { truncated: true }, vec![message("a", 1.0, "")]);
+        assert_eq!(fill, WindowFill::Detached("1970-01-01".into()));
+        assert!(store.active().window.as_ref().unwrap().truncated);
+    }
+
+    #[test]
+    fn expired_window_settles_with_what_it_has() {
+        let mut store = store();
+        store.fill_window(DEFAULT_CHANNEL, 0.0, WindowSource::Archive, vec![message("a", 1.0, "")]);
+        assert_eq!(store.expire_window(DEFAULT_CHANNEL, 0.0), WindowFill::Joined("1970-01-01".into()));
+        assert_eq!(store.expire_window(DEFAULT_CHANNEL, 0.0), WindowFill::Stale);
+    }
+
+    fn fill(store: &mut ChannelStore, at_bottom: bool) {
+        for i in 0..=MAX_CHANNEL_MESSAGES {
+            let mut m = message(&i.to_string(), i as f64, "");
+            m.channel = Some(DEFAULT_CHANNEL.into());
+            store.push(m, NotifyLevel::Badge, at_bottom);
+        }
+    }
+
+    #[test]
+    fn push_trims_the_oldest_at_the_bottom() {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL]);
+        fill(&mut store, true);
+        assert_eq!(store.active().messages.len(), MAX_CHANNEL_MESSAGES);
+        assert_eq!(store.active().messages[0].id, "1");
+    }
+
+    #[test]
+    fn push_keeps_everything_while_reading_back() {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL]);
+        fill(&mut store, false);
+        assert_eq!(store.active().messages.len(), MAX_CHANNEL_MESSAGES + 1);
+    }
+
+    #[test]
+    fn push_keeps_everything_while_an_older_page_loads() {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL]);
+        store.get_or_create(DEFAULT_CHANNEL).history = HistoryState::LoadingOlder;
+        fill(&mut store, true);
+        assert_eq!(store.active().messages.len(), MAX_CHANNEL_MESSAGES + 1);
+    }
+
+    #[test]
+    fn answer_for_another_day_is_stale() {
+        let mut store = store();
+        let fill = store.fill_window(DEFAULT_CHANNEL, DAY, WindowSource::Archive, vec![]);
+        assert_eq!(fill, WindowFill::Stale);
+    }
+
+    #[test]
+    fn history_is_only_requested_once() {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL]);
+        assert!(store.claim_history_request(DEFAULT_CHANNEL));
+        assert!(!store.claim_history_request(DEFAULT_CHANNEL));
+        store.merge_history(DEFAULT_CHANNEL, vec![], true);
+        assert!(!store.claim_history_request(DEFAULT_CHANNEL));
+    }
+
+    #[test]
+    fn history_goes_under_live_messages_by_timestamp() {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL]);
+        store.claim_history_request(DEFAULT_CHANNEL);
+        store.push(message("live1", 2.0, ""), NotifyLevel::Badge, true);
+        store.push(message("live2", 4.0, ""), NotifyLevel::Badge, true);
+        let history = vec![message("h3", 3.0, ""), message("live1", 2.0, ""), message("h1", 1.0, "")];
+        store.merge_history(DEFAULT_CHANNEL, history, true);
+        let channel = store.active();
+        assert_eq!(ids(&channel.messages), ["h1", "live1", "h3", "live2"]);
+        assert_eq!(channel.history_start.as_deref(), Some("h1"));
+    }
+
+    #[test]
+    fn older_pages_go_back_from_the_oldest_server_message_one_at_a_time() {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL]);
+        assert_eq!(store.claim_older_history(DEFAULT_CHANNEL), None);
+        store.claim_history_request(DEFAULT_CHANNEL);
+        store.merge_history(DEFAULT_CHANNEL, vec![message("", 1.0, ""), message("b", 2.0, "")], true);
+        assert_eq!(store.claim_older_history(DEFAULT_CHANNEL).as_deref(), Some("b"));
+        assert_eq!(store.claim_older_history(DEFAULT_CHANNEL), None);
+
+        store.merge_history(DEFAULT_CHANNEL, vec![message("a", 0.5, "")], false);
+        assert_eq!(ids(&store.active().messages)[0], "a");
+        assert_eq!(store.claim_older_history(DEFAULT_CHANNEL), None);
+    }
+
+    #[test]
+    fn abandoned_requests_can_be_made_again() {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL]);
+        assert!(store.claim_history_request(DEFAULT_CHANNEL));
+        assert!(store.abandon_history_request(DEFAULT_CHANNEL));
+        assert!(store.claim_history_request(DEFAULT_CHANNEL));
+
+        store.merge_history(DEFAULT_CHANNEL, vec![message("a", 1.0, "")], true);
+        assert_eq!(store.claim_older_history(DEFAULT_CHANNEL).as_deref(), Some("a"));
+        assert!(store.abandon_history_request(DEFAULT_CHANNEL));
+        assert_eq!(store.claim_older_history(DEFAULT_CHANNEL).as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn nothing_to_abandon_once_answered() {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL]);
+        store.claim_history_request(DEFAULT_CHANNEL);
+        store.merge_history(DEFAULT_CHANNEL, vec![], false);
+        assert!(!store.abandon_history_request(DEFAULT_CHANNEL));
+        assert_eq!(store.active().history, HistoryState::Exhausted);
+    }
+
+    #[test]
+    fn switching_back_restores_the_anchor_left_behind() {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL, "random"]);
+        store.merge_history(DEFAULT_CHANNEL, vec![message("a", 1.0, ""), message("b", 2.0, "")], false);
+        assert_eq!(store.switch_to("random", Some("a".into())), None);
+        assert_eq!(store.switch_to(DEFAULT_CHANNEL, None).as_deref(), Some("a"));
+        assert_eq!(store.get("random").unwrap().scroll_anchor, None);
+    }
+
+    #[test]
+    fn an_anchor_that_is_gone_falls_back_to_the_bottom() {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL, "random"]);
+        store.switch_to("random", Some("missing".into()));
+        assert_eq!(store.switch_to(DEFAULT_CHANNEL, None), None);
+    }
+
+    #[test]
+    fn replies_are_gathered_under_their_thread() {
+        let reply = |id: &str, root: &str| MessageData { thread_id: Some(root.into()), ..message(id, 2.0, "") };
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL]);
+        store.merge_history(
+            DEFAULT_CHANNEL,
+            vec![message("a", 1.0, ""), message("b", 1.0, ""), reply("r1", "a"), reply("r2", "a"), reply("r3", "b")],
+            false,
+        );
+        let channel = store.active();
+        assert_eq!(channel.thread("a").iter().map(|m| m.id.as_str()).collect::<Vec<_>>(), ["r1", "r2"]);
+        assert!(channel.thread("r1").is_empty());
+        let counts = channel.reply_counts();
+        assert_eq!((counts.get("a"), counts.get("b"), counts.get("r1")), (Some(&2), Some(&1), None));
+    }
+
+    fn from(from: &str, channel: Option<&str>) -> MessageData {
+        let mut m = message("", 0.0, "");
+        m.from = from.into();
+        m.channel = channel.map(String::from);
+        m
+    }
+
+    #[test]
+    fn dms_are_keyed_by_the_other_person() {
+        assert_eq!(conversation_key(&from("bob", Some("@ann")), "ann"), "@bob");
+        assert_eq!(conversation_key(&from("ann", Some("@bob")), "ann"), "@bob");
+        assert_eq!(conversation_key(&from("bob", Some("random")), "ann"), "random");
+        assert_eq!(conversation_key(&from("bob", None), "ann"), DEFAULT_CHANNEL);
+    }
+
+    #[test]
+    fn background_dms_count_unread_until_opened() {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL]);
+        store.push(from("bob", Some("@bob")), NotifyLevel::Badge, true);
+        store.push(from("bob", Some("@bob")), NotifyLevel::Badge, true);
+        store.push(from("ann", Some(DEFAULT_CHANNEL)), NotifyLevel::Badge, true);
+        assert_eq!(store.get("@bob").unwrap().unread, 2);
+        assert_eq!(store.active().unread, 0);
+        store.switch_to("@bob", None);
+        assert_eq!(store.active().unread, 0);
+    }
+
+    #[test]
+    fn closed_dms_come_back_with_the_next_message() {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL]);
+        store.push(from("bob", Some("@bob")), NotifyLevel::Badge, true);
+        store.close("@bob");
+        let dm = store.get("@bob").unwrap();
+        assert!(dm.hidden);
+        assert_eq!(dm.messages.len(), 1);
+        store.push(from("bob", Some("@bob")), NotifyLevel::Badge, true);
+        assert!(!store.get("@bob").unwrap().hidden);
+    }
+
+    #[test]
+    fn members_are_requested_again_only_after_the_ttl() {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL]);
+        assert!(store.claim_members_request(DEFAULT_CHANNEL, 0.0));
+        assert!(!store.claim_members_request(DEFAULT_CHANNEL, MEMBERS_TTL_MS - 1.0));
+        assert!(store.claim_members_request(DEFAULT_CHANNEL, MEMBERS_TTL_MS));
+        assert!(!store.claim_members_request("@bob", 0.0));
+    }
+
+    #[test]
+    fn mentions_are_listed_newest_first_until_their_channel_is_opened() {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL, "random"]);
+        for (channel, id) in [("random", "1"), ("@bob", "2"), ("random", "3")] {
+            let mut m = from("bob", Some(channel));
+            m.id = id.into();
+            store.push(m, NotifyLevel::Alert, true);
+            store.add_mention(channel, id.into(), MentionSource::Direct);
+        }
+        let ids = |store: &ChannelStore| store.mentions().map(|(m, _)| m.message_id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&store), ["3", "2", "1"]);
+        store.clear_mention("2");
+        assert_eq!(ids(&store), ["3", "1"]);
+        store.switch_to("random", None);
+        assert_eq!(store.mention_count(), 0);
+    }
+
+    #[test]
+    fn mentions_of_messages_that_are_gone_are_not_listed() {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL, "random"]);
+        store.add_mention("random", "missing".into(), MentionSource::Keyword);
+        assert_eq!(store.mention_count(), 0);
+    }
+
+    #[test]
+    fn local_changes_are_unsaved_until_taken() {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL]);
+        assert!(!store.has_unsaved());
+        store.push(message("a", 1.0, ""), NotifyLevel::Badge, true);
+        assert!(store.has_unsaved());
+        store.take_dirty();
+        assert!(!store.has_unsaved());
+
+        store.apply_reaction(DEFAULT_CHANNEL, "a", "👍", "ann", ReactionChange::Add);
+        assert!(store.has_unsaved());
+        assert_eq!(ids(&store.take_dirty()), ["a"]);
+
+        store.apply_delete(&MessageDelete { message_id: "a".into() });
+        assert!(store.has_unsaved());
+        store.clear();
+        assert!(!store.has_unsaved());
+    }
+
+    #[test]
+    fn clearing_forgets_messages_but_keeps_the_channels() {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL, "random"]);
+        store.claim_history_request("random");
+        store.merge_history("random", vec![message("a", 1.0, "")], true);
+        store.push(from("bob", Some("@bob")), NotifyLevel::Badge, true);
+        store.add_mention("random", "a".into(), MentionSource::Direct);
+        store.set_members("random", vec!["ann".into()]);
+        store.clear();
+
+        let random = store.get("random").unwrap();
+        assert!(random.messages.is_empty());
+        assert_eq!(random.members.as_deref(), Some(&["ann".to_string()][..]));
+        assert!(store.get("@bob").unwrap().messages.is_empty());
+        assert_eq!(store.get("@bob").unwrap().unread, 0);
+        assert_eq!(store.mention_count(), 0);
+        assert!(store.claim_history_request("random"));
+    }
+
+    #[test]
+    fn reactions_are_added_and_taken_back_once() {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL]);
+        store.push(message("a", 1.0, ""), NotifyLevel::Badge, true);
+        let react =
+            |store: &mut ChannelStore, change| store.apply_reaction(DEFAULT_CHANNEL, "a", "👍", "bob", change);
+        assert!(react(&mut store, ReactionChange::Add));
+        assert!(!react(&mut store, ReactionChange::Add));
+        assert!(store.has_reacted(DEFAULT_CHANNEL, "a", "👍", "bob"));
+        assert!(react(&mut store, ReactionChange::Remove));
+        assert!(!react(&mut store, ReactionChange::Remove));
+        assert!(!store.has_reacted(DEFAULT_CHANNEL, "a", "👍", "bob"));
+    }
+
+    #[test]
+    fn rename_replaces_every_instance_of_the_old_name() {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL, "random"]);
+        let mut a = message("a", 1.0, "");
+        a.edited_by = Some("ann".into());
+        a.reactions = Some(vec![("👍".into(), vec!["bob".into(), "ann".into()])]);
+        a.likes = vec!["ann".into()];
+        let mut b = message("b", 2.0, "");
+        b.from = "bob".into();
+        store.merge_history(DEFAULT_CHANNEL, vec![a, b], false);
+        store.merge_history("random", vec![message("c", 3.0, "")], false);
+
+        store.rename_user("ann", "anna");
+        let messages: Vec<&MessageData> = store.channels().iter().flat_map(|c| &c.messages).collect();
+        assert_eq!(messages.iter().map(|m| m.from.as_str()).collect::<Vec<_>>(), ["anna", "bob", "anna"]);
+        assert_eq!(messages[0].edited_by.as_deref(), Some("anna"));
+        assert_eq!(messages[0].reactions.as_ref().unwrap()[0].1, ["bob", "anna"]);
+        assert_eq!(messages[0].likes, ["anna"]);
+        let mut dirty: Vec<String> = store.take_dirty().into_iter().map(|m| m.id).collect();
+        dirty.sort();
+        assert_eq!(dirty, ["a", "c"]);
+    }
+
+    #[test]
+    fn archived_messages_are_not_cached() {
+        let mut store = ChannelStore::new(&[DEFAULT_CHANNEL]);
+        assert!(store.merge_archived(DEFAULT_CHANNEL, vec![message("a", 1.0, "")]));
+        assert!(!store.active().messages[0].cached);
+        assert!(!store.merge_archived(DEFAULT_CHANNEL, vec![message("a", 1.0, "")]));
+    }
+}
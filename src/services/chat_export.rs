@@ -0,0 +1,174 @@
+use std::collections::HashSet;
+
+use crate::components::chat::MessageData;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    Json,
+    Text,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Text => "txt",
+        }
+    }
+
+    pub fn mime(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Text => "text/plain",
+        }
+    }
+}
+
+/// `chat-<channel>-<date>.<ext>`, with the channel's `@` dropped so DMs make sane file names.
+pub fn export_filename(channel: &str, date: &str, format: ExportFormat) -> String {
+    let channel: String = channel
+        .trim_start_matches('@')
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("chat-{}-{}.{}", channel, date, format.extension())
+}
+
+/// Folds archived messages in with the ones in memory: one copy per id, oldest first.
+/// Messages without a timestamp keep their place after everything that has one.
+pub fn combine(archived: Vec<MessageData>, in_memory: &[MessageData]) -> Vec<MessageData> {
+    let mut seen = HashSet::new();
+    let mut all: Vec<MessageData> = in_memory
+        .iter()
+        .cloned()
+        .chain(archived)
+        .filter(|m| seen.insert(m.id.clone()))
+        .collect();
+    all.sort_by(|a, b| {
+        a.timestamp
+            .unwrap_or(f64::INFINITY)
+            .total_cmp(&b.timestamp.unwrap_or(f64::INFINITY))
+    });
+    all
+}
+
+/// Every field of every message, so nothing is lost. Reactions come out as
+/// `[emoji, [users]]` pairs, exactly as they are kept.
+pub fn to_json(messages: &[MessageData]) -> String {
+    serde_json::to_string_pretty(messages).unwrap()
+}
+
+/// One line per message, e.g. `[14:32] alice: hello`, with attachments, clips, edits and
+/// deleted files noted in brackets and reactions on an indented line underneath. Messages
+/// without a sender are the server's own and read `[14:32] * text`.
+///
+/// Times are shown `utc_offset_minutes` ahead of UTC.
+pub fn to_text(messages: &[MessageData], utc_offset_minutes: i32) -> String {
+    let mut out = String::new();
+    for m in messages {
+        let time = m
+            .timestamp
+            .map(|ts| clock(ts, utc_offset_minutes))
+            .unwrap_or_else(|| "--:--".to_string());
+        if m.from.is_empty() {
+            out.push_str(&format!("[{}] * {}", time, m.message));
+        } else {
+            out.push_str(&format!("[{}] {}: {}", time, m.from, m.message));
+        }
+        for attachment in &m.attachments {
+            if attachment.removed {
+                out.push_str(&format!(" [attachment removed: {}]", attachment.name));
+            } else {
+                out.push_str(&format!(" [attachment: {}]", attachment.name));
+            }
+        }
+        if m.video_clip.is_some() {
+            out.push_str(" [video clip]");
+        }
+        match &m.edited_by {
+            Some(by) => out.push_str(&format!(" [edited by {}]", by)),
+            None if m.edited => out.push_str(" [edited]"),
+            None => {}
+        }
+        out.push('\n');
+        let reactions: Vec<String> = m
+            .reactions
+            .iter()
+            .flatten()
+            .filter(|(_, users)| !users.is_empty())
+            .map(|(emoji, users)| format!("{} {}", emoji, users.join(", ")))
+            .collect();
+        if !reactions.is_empty() {
+            out.push_str(&format!("    {}\n", reactions.join("  ")));
+        }
+    }
+    out
+}
+
+/// `HH:MM` of a timestamp in ms since the epoch.
+fn clock(ts: f64, utc_offset_minutes: i32) -> String {
+    let minutes = (ts / 60_000.0).floor() as i64 + utc_offset_minutes as i64;
+    let of_day = minutes.rem_euclid(24 * 60);
+    format!("{:02}:{:02}", of_day / 60, of_day % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: f64 = 60_000.0;
+
+    fn message(id: &str, from: &str, text: &str, timestamp: Option<f64>) -> MessageData {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "from": from,
+            "message": text,
+            "reactions": null,
+            "timestamp": timestamp,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn file_names_are_safe() {
+        assert_eq!(export_filename("@bob", "2024-05-01", ExportFormat::Text), "chat-bob-2024-05-01.txt");
+        assert_eq!(export_filename("a b/c", "2024-05-01", ExportFormat::Json), "chat-a_b_c-2024-05-01.json");
+    }
+
+    #[test]
+    fn combine_keeps_one_copy_in_time_order() {
+        let archived = vec![message("b", "ann", "archived", Some(2.0)), message("a", "ann", "", Some(1.0))];
+        let in_memory = vec![
+            message("b", "ann", "live", Some(2.0)),
+            message("u", "ann", "", None),
+            message("c", "ann", "", Some(3.0)),
+        ];
+        let all = combine(archived, &in_memory);
+        let ids: Vec<&str> = all.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b", "c", "u"]);
+        assert_eq!(all[1].message, "live");
+    }
+
+    #[test]
+    fn text_notes_everything_in_brackets() {
+        let mut edited = message("1", "ann", "hello", Some(14.0 * 60.0 * MINUTE + 32.0 * MINUTE));
+        edited.edited_by = Some("mod".into());
+        edited.reactions = Some(vec![("👍".into(), vec!["bob".into(), "cat".into()]), ("🎉".into(), vec![])]);
+        edited.attachments = serde_json::from_value(serde_json::json!([
+            { "file_id": "f1", "name": "a.png", "mime": "image/png" },
+            { "file_id": "f2", "name": "b.pdf", "mime": "application/pdf", "removed": true },
+        ]))
+        .unwrap();
+        let server = message("2", "", "bob joined", None);
+        assert_eq!(
+            to_text(&[edited, server], 0),
+            "[14:32] ann: hello [attachment: a.png] [attachment removed: b.pdf] [edited by mod]\n    👍 bob, cat\n[--:--] * bob joined\n"
+        );
+    }
+
+    #[test]
+    fn clock_wraps_around_the_day_in_either_direction() {
+        assert_eq!(clock(30.0 * MINUTE, -60), "23:30");
+        assert_eq!(clock(23.0 * 60.0 * MINUTE, 90), "00:30");
+    }
+}
@@ -0,0 +1,166 @@
+/// Failed attempts in a row after which the chat stops retrying and shows what it has cached.
+pub const MAX_FAILURES: u32 = 3;
+
+/// First retry delay; each failure after it doubles the wait.
+const RETRY_BASE_MS: u32 = 1_000;
+
+/// Where the chat stands with the server.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConnectionState {
+    /// An attempt is under way after `failures` failed ones in a row.
+    Connecting { failures: u32 },
+    Live,
+    /// Showing cached messages read-only until the browser is back online or the user retries.
+    Offline,
+    /// The server shut down as announced and we hung up.
+    ShutDown,
+    /// The user logged out; nothing brings the connection back.
+    LoggedOut,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConnectionEvent {
+    /// The socket opened.
+    Opened,
+    /// The socket closed, or never opened.
+    Closed,
+    /// The browser says it lost or regained the network.
+    BrowserOffline,
+    BrowserOnline,
+    /// The user asked to try again.
+    Retry,
+    /// An announced shutdown time has come.
+    ShutdownDue,
+    /// The user logged out.
+    Logout,
+}
+
+/// What the chat has to do on the way into the new state.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConnectionAction {
+    None,
+    /// Open a new socket after this long.
+    Reconnect { delay_ms: u32 },
+    /// The socket is up: start presence and catch up on what was missed.
+    Resume,
+    /// Give up on the socket and stop presence.
+    HangUp,
+}
+
+impl ConnectionState {
+    pub fn is_live(self) -> bool {
+        self == ConnectionState::Live
+    }
+
+    /// Whether sending is impossible until a new connection is made.
+    pub fn is_offline(self) -> bool {
+        matches!(self, ConnectionState::Offline | ConnectionState::ShutDown | ConnectionState::LoggedOut)
+    }
+
+    /// The state after `event`, and what it takes to get there. `browser_online` is what the
+    /// browser reports right now.
+    pub fn next(self, event: ConnectionEvent, browser_online: bool) -> (Self, ConnectionAction) {
+        use ConnectionAction as A;
+        use ConnectionEvent as E;
+        use ConnectionState as S;
+        match (self, event) {
+            (S::Connecting { .. }, E::Opened) => (S::Live, A::Resume),
+            (S::Live, E::Closed) if browser_online => (S::Connecting { failures: 0 }, A::Reconnect { delay_ms: 0 }),
+            (S::Live, E::Closed) => (S::Offline, A::HangUp),
+            (S::Connecting { failures }, E::Closed) => {
+                let failures = failures + 1;
+                if failures >= MAX_FAILURES || !browser_online {
+                    (S::Offline, A::HangUp)
+                } else {
+                    let delay_ms = RETRY_BASE_MS << (failures - 1);
+                    (S::Connecting { failures }, A::Reconnect { delay_ms })
+                }
+            }
+            (S::Connecting { .. }, E::BrowserOffline) => (S::Offline, A::HangUp),
+            (S::Offline, E::BrowserOnline) | (S::Offline | S::ShutDown, E::Retry) => {
+                (S::Connecting { failures: 0 }, A::Reconnect { delay_ms: 0 })
+            }
+            (S::Live | S::Connecting { .. }, E::ShutdownDue) => (S::ShutDown, A::HangUp),
+            (S::LoggedOut, _) => (S::LoggedOut, A::None),
+            (_, E::Logout) => (S::LoggedOut, A::HangUp),
+            // A socket we gave up on closing, a late open after hanging up, and the rest.
+            (state, _) => (state, A::None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ConnectionAction as A;
+    use ConnectionEvent as E;
+    use ConnectionState as S;
+
+    #[test]
+    fn retries_back_off_then_give_up() {
+        let mut state = S::Connecting { failures: 0 };
+        let mut delays = vec![];
+        loop {
+            let (next, action) = state.next(E::Closed, true);
+            state = next;
+            match action {
+                A::Reconnect { delay_ms } => delays.push(delay_ms),
+                _ => break,
+            }
+        }
+        assert_eq!(delays, [1_000, 2_000]);
+        assert_eq!(state, S::Offline);
+        assert!(state.is_offline());
+    }
+
+    #[test]
+    fn a_dropped_live_socket_reconnects_at_once_unless_the_browser_is_offline() {
+        assert_eq!(S::Live.next(E::Closed, true), (S::Connecting { failures: 0 }, A::Reconnect { delay_ms: 0 }));
+        assert_eq!(S::Live.next(E::Closed, false), (S::Offline, A::HangUp));
+        assert_eq!(S::Connecting { failures: 0 }.next(E::Closed, false), (S::Offline, A::HangUp));
+    }
+
+    #[test]
+    fn coming_back_online_or_retrying_reconnects() {
+        let reconnect = (S::Connecting { failures: 0 }, A::Reconnect { delay_ms: 0 });
+        assert_eq!(S::Offline.next(E::BrowserOnline, true), reconnect);
+        assert_eq!(S::Offline.next(E::Retry, true), reconnect);
+        assert_eq!(S::ShutDown.next(E::Retry, true), reconnect);
+        assert_eq!(S::ShutDown.next(E::BrowserOnline, true), (S::ShutDown, A::None));
+        assert_eq!(S::Connecting { failures: 1 }.next(E::Opened, true), (S::Live, A::Resume));
+    }
+
+    #[test]
+    fn logging_out_is_final() {
+        assert_eq!(S::Live.next(E::Logout, true), (S::LoggedOut, A::HangUp));
+        for event in [E::Opened, E::Closed, E::BrowserOnline, E::Retry, E::ShutdownDue] {
+            assert_eq!(S::LoggedOut.next(event, true), (S::LoggedOut, A::None));
+        }
+    }
+
+    #[test]
+    fn nothing_redials_after_a_logout() {
+        let states = [S::Connecting { failures: 0 }, S::Connecting { failures: 2 }, S::Live, S::Offline, S::ShutDown];
+        let events = [E::Closed, E::Opened, E::BrowserOffline, E::BrowserOnline, E::Retry, E::ShutdownDue, E::Logout];
+        for start in states {
+            let (mut state, _) = start.next(E::Logout, true);
+            // The hung-up socket's close comes in first, then whatever else happens.
+            for event in events {
+                for online in [true, false] {
+                    let (next, action) = state.next(event, online);
+                    assert!(!matches!(action, A::Reconnect { .. }), "{:?} after logging out of {:?}", event, start);
+                    state = next;
+                }
+            }
+            assert_eq!(state, S::LoggedOut);
+            assert!(state.is_offline());
+        }
+    }
+
+    #[test]
+    fn late_events_change_nothing() {
+        assert_eq!(S::Offline.next(E::Closed, true), (S::Offline, A::None));
+        assert_eq!(S::Offline.next(E::Opened, true), (S::Offline, A::None));
+        assert_eq!(S::Live.next(E::ShutdownDue, true), (S::ShutDown, A::HangUp));
+    }
+}
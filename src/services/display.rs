@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+
+use crate::t;
+
+/// How much room each message takes.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Density {
+    /// Cards with a full-size avatar.
+    #[default]
+    Comfortable,
+    /// IRC-like lines: a small inline avatar and little padding.
+    Compact,
+}
+
+impl Density {
+    pub const ALL: [Density; 2] = [Density::Comfortable, Density::Compact];
+
+    pub fn label(self) -> String {
+        match self {
+            Density::Comfortable => t!("settings.density.comfortable"),
+            Density::Compact => t!("settings.density.compact"),
+        }
+    }
+
+    /// Padding of the message list and the gap between its rows.
+    pub fn list_classes(self) -> &'static str {
+        match self {
+            Density::Comfortable => "p-4 space-y-4",
+            Density::Compact => "p-2 space-y-1",
+        }
+    }
+
+    /// Padding, corners and the gap after the avatar of one message row.
+    pub fn row_classes(self) -> &'static str {
+        match self {
+            Density::Comfortable => "space-x-3 p-3 rounded-xl",
+            Density::Compact => "space-x-2 px-2 py-0.5 rounded",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FontSize {
+    Small,
+    #[default]
+    Default,
+    Large,
+}
+
+impl FontSize {
+    pub const ALL: [FontSize; 3] = [FontSize::Small, FontSize::Default, FontSize::Large];
+
+    pub fn label(self) -> String {
+        match self {
+            FontSize::Small => t!("settings.font_size.small"),
+            FontSize::Default => t!("settings.font_size.default"),
+            FontSize::Large => t!("settings.font_size.large"),
+        }
+    }
+
+    /// Class on the root element; the sizes are in static/index.html. Tailwind's sizes are
+    /// in rem, so everything scales with it.
+    fn class(self) -> Option<&'static str> {
+        match self {
+            FontSize::Small => Some("font-small"),
+            FontSize::Default => None,
+            FontSize::Large => Some("font-large"),
+        }
+    }
+}
+
+/// Every font size class, and whether the root should have it for `size`.
+fn root_classes(size: FontSize) -> Vec<(&'static str, bool)> {
+    FontSize::ALL
+        .iter()
+        .filter_map(|&other| Some((other.class()?, other == size)))
+        .collect()
+}
+
+/// Sets the root element's class for `size`, clearing the other sizes'.
+pub fn apply_font_size(size: FontSize) {
+    let root = web_sys::window().and_then(|w| w.document()).and_then(|d| d.document_element());
+    let root = match root {
+        Some(root) => root,
+        None => return,
+    };
+    for (class, on) in root_classes(size) {
+        if let Err(e) = root.class_list().toggle_with_force(class, on) {
+            log::error!("failed to set the font size: {:?}", e);
+        }
+    }
+}
+
+/// The user has asked their system to keep animation to a minimum.
+pub fn prefers_reduced_motion() -> bool {
+    web_sys::window()
+        .and_then(|w| w.match_media("(prefers-reduced-motion: reduce)").ok().flatten())
+        .map_or(false, |query| query.matches())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_font_size_sets_only_its_own_class() {
+        assert_eq!(root_classes(FontSize::Small), [("font-small", true), ("font-large", false)]);
+        assert_eq!(root_classes(FontSize::Default), [("font-small", false), ("font-large", false)]);
+        assert_eq!(root_classes(FontSize::Large), [("font-small", false), ("font-large", true)]);
+    }
+
+    #[test]
+    fn the_default_size_has_no_class() {
+        assert_eq!(FontSize::default().class(), None);
+        let classes: Vec<_> = FontSize::ALL.iter().filter_map(|size| size.class()).collect();
+        assert_eq!(classes, ["font-small", "font-large"]);
+    }
+
+    #[test]
+    fn compact_rows_are_tighter() {
+        assert_eq!(Density::default(), Density::Comfortable);
+        assert_eq!(Density::Comfortable.list_classes(), "p-4 space-y-4");
+        assert_eq!(Density::Compact.list_classes(), "p-2 space-y-1");
+        assert_eq!(Density::Comfortable.row_classes(), "space-x-3 p-3 rounded-xl");
+        assert_eq!(Density::Compact.row_classes(), "space-x-2 px-2 py-0.5 rounded");
+    }
+
+    #[test]
+    fn settings_store_snake_case_names() {
+        assert_eq!(serde_json::to_string(&Density::Compact).unwrap(), "\"compact\"");
+        assert_eq!(serde_json::from_str::<FontSize>("\"large\"").unwrap(), FontSize::Large);
+    }
+}
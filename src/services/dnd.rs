@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+
+const HOUR_MS: f64 = 60.0 * 60.0 * 1000.0;
+
+/// A manual Do Not Disturb.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Snooze {
+    UntilTurnedOff,
+    /// Ends at this many ms since the epoch.
+    Until(f64),
+}
+
+impl Snooze {
+    pub fn for_hours(hours: f64, now_ms: f64) -> Self {
+        Snooze::Until(now_ms + hours * HOUR_MS)
+    }
+}
+
+/// Daily quiet hours, as minutes since local midnight. `start > end` wraps past midnight.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Schedule {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self {
+            start: 18 * 60,
+            end: 9 * 60,
+        }
+    }
+}
+
+impl Schedule {
+    pub fn contains(&self, minute_of_day: u32) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&minute_of_day)
+        } else {
+            minute_of_day >= self.start || minute_of_day < self.end
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(default)]
+pub struct DndSettings {
+    pub snooze: Option<Snooze>,
+    pub schedule_enabled: bool,
+    pub schedule: Schedule,
+}
+
+/// A point in time as both an instant and a local wall-clock reading.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Now {
+    pub epoch_ms: f64,
+    pub minute_of_day: u32,
+}
+
+impl Now {
+    pub fn local() -> Self {
+        let date = js_sys::Date::new_0();
+        Self {
+            epoch_ms: date.get_time(),
+            minute_of_day: date.get_hours() * 60 + date.get_minutes(),
+        }
+    }
+}
+
+impl DndSettings {
+    pub fn is_active(&self, now: Now) -> bool {
+        let snoozed = match self.snooze {
+            Some(Snooze::UntilTurnedOff) => true,
+            Some(Snooze::Until(end)) => now.epoch_ms < end,
+            None => false,
+        };
+        snoozed || (self.schedule_enabled && self.schedule.contains(now.minute_of_day))
+    }
+}
+
+/// `"HH:MM"`, as used by `<input type="time">`.
+pub fn format_minutes(minutes: u32) -> String {
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+pub fn parse_minutes(text: &str) -> Option<u32> {
+    let (hours, minutes) = text.split_once(':')?;
+    let (hours, minutes): (u32, u32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(epoch_ms: f64, minute_of_day: u32) -> Now {
+        Now { epoch_ms, minute_of_day }
+    }
+
+    #[test]
+    fn overnight_schedules_wrap_past_midnight() {
+        let night = Schedule::default();
+        assert!(night.contains(18 * 60));
+        assert!(night.contains(23 * 60 + 59));
+        assert!(night.contains(0));
+        assert!(!night.contains(9 * 60));
+        assert!(!night.contains(12 * 60));
+
+        let lunch = Schedule { start: 12 * 60, end: 13 * 60 };
+        assert!(lunch.contains(12 * 60 + 30));
+        assert!(!lunch.contains(13 * 60));
+    }
+
+    #[test]
+    fn a_timed_snooze_ends_on_time() {
+        let dnd = DndSettings { snooze: Some(Snooze::for_hours(1.0, 0.0)), ..Default::default() };
+        assert!(dnd.is_active(at(HOUR_MS - 1.0, 12 * 60)));
+        assert!(!dnd.is_active(at(HOUR_MS, 12 * 60)));
+
+        let dnd = DndSettings { snooze: Some(Snooze::UntilTurnedOff), ..Default::default() };
+        assert!(dnd.is_active(at(f64::MAX, 12 * 60)));
+    }
+
+    #[test]
+    fn the_schedule_only_counts_when_enabled() {
+        let mut dnd = DndSettings::default();
+        assert!(!dnd.is_active(at(0.0, 20 * 60)));
+        dnd.schedule_enabled = true;
+        assert!(dnd.is_active(at(0.0, 20 * 60)));
+        assert!(!dnd.is_active(at(0.0, 12 * 60)));
+    }
+
+    #[test]
+    fn times_round_trip_through_the_time_input() {
+        assert_eq!(format_minutes(9 * 60 + 5), "09:05");
+        assert_eq!(parse_minutes("09:05"), Some(9 * 60 + 5));
+        assert_eq!(parse_minutes("24:00"), None);
+        assert_eq!(parse_minutes("12:60"), None);
+        assert_eq!(parse_minutes("noon"), None);
+    }
+}
@@ -0,0 +1,33 @@
+use wasm_bindgen::JsCast;
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Url};
+
+/// Hands `contents` to the browser as a file download named `filename`.
+pub fn download_text(filename: &str, mime: &str, contents: &str) {
+    let parts = js_sys::Array::of1(&contents.into());
+    let blob = match Blob::new_with_str_sequence_and_options(&parts, BlobPropertyBag::new().type_(mime)) {
+        Ok(blob) => blob,
+        Err(e) => {
+            log::error!("failed to create blob: {:?}", e);
+            return;
+        }
+    };
+    let url = match Url::create_object_url_with_blob(&blob) {
+        Ok(url) => url,
+        Err(e) => {
+            log::error!("failed to create object url: {:?}", e);
+            return;
+        }
+    };
+
+    let document = web_sys::window().unwrap().document().unwrap();
+    if let Some(anchor) = document
+        .create_element("a")
+        .ok()
+        .and_then(|el| el.dyn_into::<HtmlAnchorElement>().ok())
+    {
+        anchor.set_href(&url);
+        anchor.set_download(filename);
+        anchor.click();
+    }
+    let _ = Url::revoke_object_url(&url);
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use crate::services::storage::{Local, Store};
+
+/// Unsent text per room, so a reload or a trip to another room doesn't lose it. The server
+/// keeps a copy too; this one covers being offline and answers first.
+pub struct Drafts<S: Store = Local> {
+    store: S,
+    key: String,
+    drafts: HashMap<String, String>,
+}
+
+impl Drafts {
+    pub fn load(username: &str) -> Self {
+        Self::load_from(Local, username)
+    }
+}
+
+impl<S: Store> Drafts<S> {
+    pub fn load_from(store: S, username: &str) -> Self {
+        let key = format!("yewchat.drafts.{}", username);
+        Self {
+            drafts: store.get(&key).unwrap_or_default(),
+            store,
+            key,
+        }
+    }
+
+    pub fn get(&self, room: &str) -> Option<&str> {
+        self.drafts.get(room).map(String::as_str)
+    }
+
+    /// Stores `text` as the draft for `room`; blank text drops it. Returns whether it changed.
+    pub fn set(&mut self, room: &str, text: &str) -> bool {
+        let changed = if text.trim().is_empty() {
+            self.drafts.remove(room).is_some()
+        } else if self.get(room) == Some(text) {
+            false
+        } else {
+            self.drafts.insert(room.to_string(), text.to_string());
+            true
+        };
+        if changed {
+            if let Err(e) = self.store.set(&self.key, &self.drafts) {
+                log::error!("failed to save drafts: {:?}", e);
+            }
+        }
+        changed
+    }
+
+    /// Files the drafts under `username` from now on, after a rename.
+    pub fn rename(&mut self, username: &str) {
+        let old = std::mem::replace(&mut self.key, format!("yewchat.drafts.{}", username));
+        match self.store.set(&self.key, &self.drafts) {
+            Ok(()) => self.store.delete(&old),
+            Err(e) => log::error!("failed to save drafts: {:?}", e),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.drafts.clear();
+        self.store.delete(&self.key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::storage::MemoryStore;
+
+    #[test]
+    fn a_saved_draft_is_restored_after_a_reload() {
+        let store = MemoryStore::default();
+        let mut drafts = Drafts::load_from(store.clone(), "ann");
+        assert!(drafts.set("general", "half a tho"));
+        assert!(!drafts.set("general", "half a tho"));
+        assert_eq!(Drafts::load_from(store, "ann").get("general"), Some("half a tho"));
+    }
+
+    #[test]
+    fn sending_clears_the_draft() {
+        let store = MemoryStore::default();
+        let mut drafts = Drafts::load_from(store.clone(), "ann");
+        drafts.set("general", "hello");
+        assert!(drafts.set("general", ""));
+        assert_eq!(drafts.get("general"), None);
+        assert!(!drafts.set("general", "   "));
+        assert_eq!(Drafts::load_from(store, "ann").get("general"), None);
+    }
+
+    #[test]
+    fn each_channel_keeps_its_own_draft() {
+        let mut drafts = Drafts::load_from(MemoryStore::default(), "ann");
+        drafts.set("general", "one");
+        drafts.set("random", "two");
+        drafts.set("general", "");
+        assert_eq!(drafts.get("general"), None);
+        assert_eq!(drafts.get("random"), Some("two"));
+    }
+
+    #[test]
+    fn a_rename_moves_the_drafts() {
+        let store = MemoryStore::default();
+        let mut drafts = Drafts::load_from(store.clone(), "ann");
+        drafts.set("general", "hello");
+        drafts.rename("anna");
+        assert_eq!(store.raw("yewchat.drafts.ann"), None);
+        assert_eq!(Drafts::load_from(store, "anna").get("general"), Some("hello"));
+    }
+
+    #[test]
+    fn clear_forgets_everything() {
+        let store = MemoryStore::default();
+        let mut drafts = Drafts::load_from(store.clone(), "ann");
+        drafts.set("general", "hello");
+        drafts.clear();
+        assert_eq!(drafts.get("general"), None);
+        assert_eq!(store.raw("yewchat.drafts.ann"), None);
+    }
+}
@@ -0,0 +1,175 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use gloo_events::EventListener;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, HtmlImageElement, HtmlLinkElement};
+
+const ICON_SIZE: u32 = 32;
+
+/// What the badge says for `unread` messages, or `None` for no badge at all.
+pub fn badge_text(unread: usize) -> Option<String> {
+    match unread {
+        0 => None,
+        1..=9 => Some(unread.to_string()),
+        _ => Some("9+".to_string()),
+    }
+}
+
+/// Rendered icons by badge text. Counts past 9 all share the "9+" icon.
+#[derive(Default)]
+pub struct BadgeCache {
+    icons: HashMap<String, String>,
+}
+
+impl BadgeCache {
+    pub fn get_or_render(&mut self, text: &str, render: impl FnOnce(&str) -> Option<String>) -> Option<String> {
+        if let Some(url) = self.icons.get(text) {
+            return Some(url.clone());
+        }
+        let url = render(text)?;
+        self.icons.insert(text.to_string(), url.clone());
+        Some(url)
+    }
+}
+
+struct State {
+    link: HtmlLinkElement,
+    base_href: String,
+    /// Set once the base icon has loaded; badges can't be drawn before that.
+    base: Option<HtmlImageElement>,
+    unread: usize,
+    cache: BadgeCache,
+}
+
+impl State {
+    fn apply(&mut self) {
+        let text = match badge_text(self.unread) {
+            Some(text) => text,
+            None => {
+                self.link.set_href(&self.base_href);
+                return;
+            }
+        };
+        let base = match &self.base {
+            Some(base) => base,
+            None => return,
+        };
+        if let Some(url) = self.cache.get_or_render(&text, |text| draw_badge(base, text)) {
+            self.link.set_href(&url);
+        }
+    }
+}
+
+/// Swaps the page's `<link rel="icon">` for a copy with an unread badge drawn on it.
+pub struct FaviconBadge {
+    state: Rc<RefCell<State>>,
+    _onload: EventListener,
+}
+
+impl FaviconBadge {
+    pub fn new() -> Option<Self> {
+        let document = web_sys::window()?.document()?;
+        let link: HtmlLinkElement = document
+            .query_selector("link[rel~='icon']")
+            .ok()
+            .flatten()?
+            .dyn_into()
+            .ok()?;
+        let state = Rc::new(RefCell::new(State {
+            base_href: link.href(),
+            link,
+            base: None,
+            unread: 0,
+            cache: BadgeCache::default(),
+        }));
+
+        let image = HtmlImageElement::new().ok()?;
+        let onload = {
+            let state = state.clone();
+            let image = image.clone();
+            EventListener::once(&image.clone(), "load", move |_| {
+                let mut state = state.borrow_mut();
+                state.base = Some(image);
+                state.apply();
+            })
+        };
+        image.set_src(&state.borrow().base_href);
+        Some(Self {
+            state,
+            _onload: onload,
+        })
+    }
+
+    pub fn set_unread(&mut self, n: usize) {
+        let mut state = self.state.borrow_mut();
+        if state.unread != n {
+            state.unread = n;
+            state.apply();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.set_unread(0);
+    }
+}
+
+/// Composites a red badge with `text` onto the top-right corner of `base`.
+fn draw_badge(base: &HtmlImageElement, text: &str) -> Option<String> {
+    let document = web_sys::window()?.document()?;
+    let canvas: HtmlCanvasElement = document.create_element("canvas").ok()?.dyn_into().ok()?;
+    canvas.set_width(ICON_SIZE);
+    canvas.set_height(ICON_SIZE);
+    let ctx: CanvasRenderingContext2d = canvas.get_context("2d").ok()??.dyn_into().ok()?;
+
+    let size = ICON_SIZE as f64;
+    let radius = size * 0.3;
+    let (x, y) = (size - radius, radius);
+    ctx.draw_image_with_html_image_element_and_dw_and_dh(base, 0.0, 0.0, size, size)
+        .ok()?;
+    ctx.begin_path();
+    ctx.arc(x, y, radius, 0.0, std::f64::consts::TAU).ok()?;
+    ctx.set_fill_style(&JsValue::from_str("#ef4444"));
+    ctx.fill();
+    ctx.set_fill_style(&JsValue::from_str("#ffffff"));
+    ctx.set_font(&format!("bold {}px sans-serif", if text.len() > 1 { 11 } else { 14 }));
+    ctx.set_text_align("center");
+    ctx.set_text_baseline("middle");
+    ctx.fill_text(text, x, y + 1.0).ok()?;
+    canvas.to_data_url().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn badge_caps_at_nine_plus() {
+        assert_eq!(badge_text(0), None);
+        assert_eq!(badge_text(1).as_deref(), Some("1"));
+        assert_eq!(badge_text(9).as_deref(), Some("9"));
+        assert_eq!(badge_text(10).as_deref(), Some("9+"));
+    }
+
+    #[test]
+    fn each_badge_is_rendered_once() {
+        let mut cache = BadgeCache::default();
+        let mut renders = 0;
+        for _ in 0..3 {
+            let url = cache.get_or_render("9+", |text| {
+                renders += 1;
+                Some(format!("data:{}", text))
+            });
+            assert_eq!(url.as_deref(), Some("data:9+"));
+        }
+        assert_eq!(renders, 1);
+    }
+
+    #[test]
+    fn failed_renders_are_retried() {
+        let mut cache = BadgeCache::default();
+        assert_eq!(cache.get_or_render("1", |_| None), None);
+        assert_eq!(cache.get_or_render("1", |_| Some("data:1".into())).as_deref(), Some("data:1"));
+    }
+}
@@ -0,0 +1,48 @@
+use reqwasm::http::Request;
+use serde::Deserialize;
+
+const CONFIG_URL: &str = "/api/config";
+
+/// Features a deployment can switch off. Provided as context from `Main`.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct FeatureFlags {
+    pub dms: bool,
+    pub file_uploads: bool,
+    pub video_clips: bool,
+    pub location_sharing: bool,
+    pub reactions: bool,
+}
+
+impl Default for FeatureFlags {
+    fn default() -> Self {
+        Self {
+            dms: true,
+            file_uploads: true,
+            video_clips: true,
+            location_sharing: true,
+            reactions: true,
+        }
+    }
+}
+
+impl FeatureFlags {
+    /// The deployment's flags, or the defaults when it doesn't serve any.
+    pub async fn fetch() -> Self {
+        let response = match Request::get(CONFIG_URL).send().await {
+            Ok(response) if response.ok() => response,
+            Ok(response) => {
+                log::debug!("no feature config ({}), using defaults", response.status());
+                return Self::default();
+            }
+            Err(e) => {
+                log::debug!("failed to fetch feature config: {:?}", e);
+                return Self::default();
+            }
+        };
+        response.json().await.unwrap_or_else(|e| {
+            log::error!("invalid feature config: {:?}", e);
+            Self::default()
+        })
+    }
+}
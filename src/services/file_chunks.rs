@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+/// Characters of a data URL carried by a single `filechunk` frame.
+pub const CHUNK_SIZE: usize = 32 * 1024;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FileChunk {
+    pub file_id: String,
+    pub index: usize,
+    pub total: usize,
+    pub data: String,
+}
+
+/// Cuts `data` into `size`-character pieces, ready to be wrapped in `FileChunk`s.
+pub fn split_into_chunks(data: &str, size: usize) -> Vec<String> {
+    data.chars()
+        .collect::<Vec<_>>()
+        .chunks(size)
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
+
+/// Collects incoming chunks until every piece of a file has arrived.
+#[derive(Default)]
+pub struct ChunkAssembler {
+    pending: HashMap<String, Vec<Option<String>>>,
+    /// Shared, since every render hands the data URLs out again.
+    complete: HashMap<String, Rc<str>>,
+}
+
+impl ChunkAssembler {
+    /// Stores a chunk and returns true if it completed its file.
+    pub fn add(&mut self, chunk: FileChunk) -> bool {
+        if self.complete.contains_key(&chunk.file_id) || chunk.index >= chunk.total {
+            return false;
+        }
+        let parts = self
+            .pending
+            .entry(chunk.file_id.clone())
+            .or_insert_with(|| vec![None; chunk.total]);
+        if let Some(slot) = parts.get_mut(chunk.index) {
+            *slot = Some(chunk.data);
+        }
+        if parts.iter().all(Option::is_some) {
+            let parts = self.pending.remove(&chunk.file_id).unwrap();
+            self.complete
+                .insert(chunk.file_id, parts.into_iter().flatten().collect::<String>().into());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Registers a file we already have in full, e.g. one we uploaded ourselves.
+    pub fn insert(&mut self, file_id: String, data: String) {
+        self.complete.insert(file_id, data.into());
+    }
+
+    pub fn get(&self, file_id: &str) -> Option<&Rc<str>> {
+        self.complete.get(file_id)
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.complete.clear();
+    }
+
+    /// Forgets a file, whole or in pieces, once it has been deleted.
+    pub fn remove(&mut self, file_id: &str) {
+        self.pending.remove(file_id);
+        self.complete.remove(file_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(index: usize, total: usize, data: &str) -> FileChunk {
+        FileChunk { file_id: "f".into(), index, total, data: data.into() }
+    }
+
+    #[test]
+    fn chunks_reassemble_in_any_order() {
+        let pieces = split_into_chunks("abcdé", 2);
+        assert_eq!(pieces, ["ab", "cd", "é"]);
+        let mut files = ChunkAssembler::default();
+        assert!(!files.add(chunk(2, 3, &pieces[2])));
+        assert!(!files.add(chunk(0, 3, &pieces[0])));
+        assert!(!files.add(chunk(9, 3, "x")));
+        assert!(files.add(chunk(1, 3, &pieces[1])));
+        assert_eq!(files.get("f").map(|data| &**data), Some("abcdé"));
+        assert!(!files.add(chunk(0, 3, "late")));
+    }
+
+    #[test]
+    fn clearing_forgets_whole_and_partial_files() {
+        let mut files = ChunkAssembler::default();
+        files.insert("mine".into(), "data".into());
+        files.add(chunk(0, 2, "half"));
+        files.clear();
+        assert!(files.get("mine").is_none());
+        assert!(!files.add(chunk(1, 2, "rest")));
+    }
+}
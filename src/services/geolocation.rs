@@ -0,0 +1,35 @@
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::Position;
+
+const MAP_LINK_PREFIX: &str = "https://maps.google.com/?q=";
+
+/// Asks the browser for the device position and resolves to `(latitude, longitude)`.
+pub async fn current_position() -> Result<(f64, f64), JsValue> {
+    let geolocation = web_sys::window().unwrap().navigator().geolocation()?;
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        if let Err(e) = geolocation.get_current_position_with_error_callback(&resolve, Some(&reject)) {
+            let _ = reject.call1(&JsValue::NULL, &e);
+        }
+    });
+    let position: Position = JsFuture::from(promise).await?.dyn_into()?;
+    let coords = position.coords();
+    Ok((coords.latitude(), coords.longitude()))
+}
+
+pub fn map_link(lat: f64, lng: f64) -> String {
+    format!("{}{},{}", MAP_LINK_PREFIX, lat, lng)
+}
+
+/// Coordinates of a message that is exactly a map link produced by `map_link`.
+pub fn parse_map_link(text: &str) -> Option<(f64, f64)> {
+    let (lat, lng) = text.trim().strip_prefix(MAP_LINK_PREFIX)?.split_once(',')?;
+    Some((lat.parse().ok()?, lng.parse().ok()?))
+}
+
+pub fn static_map_url(lat: f64, lng: f64) -> String {
+    format!(
+        "https://staticmap.openstreetmap.de/staticmap.php?center={},{}&zoom=14&size=300x200",
+        lat, lng
+    )
+}
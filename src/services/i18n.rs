@@ -0,0 +1,955 @@
+use std::cell::Cell;
+
+use serde::{Deserialize, Serialize};
+
+/// Looks up a UI string in the current locale, filling in `{name}` placeholders:
+/// `t!("composer.placeholder")`, `t!("typing.two", first = a, second = b)`. A `count`
+/// argument picks the plural form, e.g. `t!("profile.messages_loaded", count = n)`.
+#[macro_export]
+macro_rules! t {
+    ($key:expr) => {
+        $crate::services::i18n::translate($key, &[])
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::services::i18n::translate($key, &[$((stringify!($name), $value.to_string())),+])
+    };
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Es];
+
+    /// What the locale switcher shows: each language in its own words.
+    pub fn label(self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::Es => "Español",
+        }
+    }
+
+    /// BCP 47 tag, for the browser's date formatting.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Es => "es",
+        }
+    }
+
+    /// The bundled locale for a tag like `es-MX`, by its language alone.
+    pub fn from_tag(tag: &str) -> Option<Locale> {
+        let language = tag.split(['-', '_']).next()?.to_ascii_lowercase();
+        Locale::ALL.into_iter().find(|locale| locale.tag() == language)
+    }
+
+    /// The browser's preferred language if it is bundled, English otherwise.
+    pub fn from_browser() -> Locale {
+        web_sys::window()
+            .and_then(|w| w.navigator().language())
+            .and_then(|tag| Locale::from_tag(&tag))
+            .unwrap_or_default()
+    }
+
+    fn strings(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => EN,
+            Locale::Es => ES,
+        }
+    }
+
+    /// CLDR plural category of `n`; English and Spanish only tell one from the rest.
+    fn plural(self, n: i64) -> &'static str {
+        match (self, n) {
+            (Locale::En | Locale::Es, 1) => "one",
+            _ => "other",
+        }
+    }
+}
+
+thread_local! {
+    // Tests run outside a browser, in English.
+    static CURRENT: Cell<Locale> = Cell::new(if cfg!(test) { Locale::En } else { Locale::from_browser() });
+}
+
+pub fn locale() -> Locale {
+    CURRENT.with(Cell::get)
+}
+
+/// Switches every string looked up from now on; views already rendered keep theirs until
+/// they render again.
+pub fn set_locale(locale: Locale) {
+    CURRENT.with(|current| current.set(locale));
+}
+
+fn lookup(locale: Locale, key: &str) -> Option<&'static str> {
+    locale.strings().iter().find(|(k, _)| *k == key).map(|(_, text)| *text)
+}
+
+/// The string for `key` with `args` filled in; what `t!` expands to. Plural strings are
+/// keyed `key.one`, `key.other` and so on, chosen by the `count` argument. A key missing
+/// from the locale falls back to English, and failing that shows as the key itself.
+pub fn translate(key: &str, args: &[(&str, String)]) -> String {
+    let locale = locale();
+    let count = args.iter().find(|(name, _)| *name == "count").and_then(|(_, n)| n.parse().ok());
+    let find = |locale: Locale| match count {
+        Some(n) => lookup(locale, &format!("{}.{}", key, locale.plural(n))).or_else(|| lookup(locale, key)),
+        None => lookup(locale, key),
+    };
+    let template = match find(locale).or_else(|| find(Locale::En)) {
+        Some(template) => template,
+        None => {
+            log::error!("missing UI string {:?}", key);
+            return key.to_string();
+        }
+    };
+    args.iter()
+        .fold(template.to_string(), |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+}
+
+/// A calendar date, as the current locale writes it.
+pub fn format_date(ms: f64) -> String {
+    js_sys::Date::new(&ms.into())
+        .to_locale_date_string(locale().tag(), &wasm_bindgen::JsValue::UNDEFINED)
+        .into()
+}
+
+/// The time of day as hours and minutes on a 24-hour clock, e.g. `14:32`.
+pub fn format_time(ms: f64) -> String {
+    let date = js_sys::Date::new(&ms.into());
+    format!("{:02}:{:02}", date.get_hours(), date.get_minutes())
+}
+
+/// A date and time of day, as the current locale writes it.
+pub fn format_datetime(ms: f64) -> String {
+    js_sys::Date::new(&ms.into())
+        .to_locale_string(locale().tag(), &wasm_bindgen::JsValue::UNDEFINED)
+        .into()
+}
+
+/// "just now", "5 minutes ago", "3 days ago"; a full date past a week.
+pub fn relative_time(ms: f64, now: f64) -> String {
+    let minutes = ((now - ms) / 60_000.0).floor().max(0.0) as i64;
+    match minutes {
+        0 => t!("time.just_now"),
+        1..=59 => t!("time.minutes_ago", count = minutes),
+        60..=1439 => t!("time.hours_ago", count = minutes / 60),
+        1440..=10079 => t!("time.days_ago", count = minutes / 1440),
+        _ => format_date(ms),
+    }
+}
+
+const EN: &[(&str, &str)] = &[
+    ("app_bar.menu", "Menu"),
+    ("app_bar.connected", "Connected"),
+    ("app_bar.disconnected", "Disconnected"),
+    ("app_bar.online", "{count} online"),
+    ("app_bar.more", "More"),
+    ("app_bar.settings", "Settings"),
+    ("app_bar.direct_messages", "Direct messages"),
+    ("app_bar.browse_channels", "Browse channels"),
+    ("app_bar.export", "Export chat"),
+    ("app_bar.stats", "Stats"),
+    ("app_bar.install", "Install app"),
+    ("app_bar.help", "Help"),
+    ("app_bar.log_out", "Log out"),
+    ("app_bar.tab.chats", "Chats"),
+    ("app_bar.tab.mentions", "Mentions"),
+    ("app_bar.tab.channels", "Channels"),
+    ("help.search_local", " search messages on this device"),
+    ("help.search_server", " search the room on the server"),
+    ("help.welcome", " set the room's greeting"),
+    ("help.slowmode", " limit how often people can post"),
+    ("help.ephemeral", " send a message that deletes itself"),
+    ("common.ok", "OK"),
+    ("common.cancel", "Cancel"),
+    ("common.close", "Close"),
+    ("common.save", "Save"),
+    ("common.retry", "Retry"),
+    ("error.reload", "Something went wrong — click here to reload"),
+    ("sidebar.users", "Users"),
+    ("sidebar.filter_users", "Filter users"),
+    ("sidebar.user_actions", "Actions for {name}"),
+    ("sidebar.archive", "Archive"),
+    ("sidebar.unarchive", "Unarchive"),
+    ("sidebar.archive_channel", "Archive {channel}"),
+    ("sidebar.unarchive_channel", "Unarchive {channel}"),
+    ("sidebar.browse", "+ Browse channels"),
+    ("sidebar.close_dm", "Close conversation with {name}"),
+    ("dialog.message_actions", "Message actions"),
+    ("dialog.server_offline", "Server offline"),
+    ("dialog.policy_notice", "Edited for policy reasons"),
+    ("dialog.image", "Image"),
+    ("dialog.pin_message", "Pin message"),
+    ("dialog.request_access", "Request access"),
+    ("dialog.session_stats", "Session stats"),
+    ("dialog.clear_data", "Clear local data"),
+    ("dialog.export", "Export {channel}"),
+    ("dialog.confirm_upload", "Send anyway?"),
+    ("pins.unpin", "Unpin"),
+    ("channel.unread.one", "{count} new message"),
+    ("channel.unread.other", "{count} new messages"),
+    ("messages.earlier", "— earlier messages —"),
+    ("messages.label", "Messages in {channel}"),
+    ("message.label", "{from}, {time}: {text}"),
+    ("message.label_untimed", "{from}: {text}"),
+    ("messages.viewing_day", "Viewing {day}"),
+    ("messages.back_to_latest", "Back to latest"),
+    ("messages.day_truncated", "Later messages from this day aren't loaded."),
+    ("messages.day_failed", "Couldn't load that day."),
+    ("composer.placeholder", "Type a message..."),
+    ("composer.label", "Message"),
+    ("composer.send", "Send"),
+    ("ephemeral.title", "Disappears for everyone when the countdown ends"),
+    ("ephemeral.remaining", "{seconds} s"),
+    ("composer.read_only", "Read-only mode"),
+    ("composer.offline", "You're offline — showing cached messages"),
+    ("composer.cooldown", "You can send again in {seconds} s"),
+    ("composer.locating", "Finding your location…"),
+    ("composer.share_location", "Share your location"),
+    ("composer.attach", "Attach a file"),
+    ("composer.record", "Record a clip"),
+    ("banner.read_only", "You are in read-only mode"),
+    ("banner.request_access", "Request access"),
+    ("typing.one", "{name} is typing…"),
+    ("typing.two", "{first} and {second} are typing…"),
+    ("typing.several", "{count} people are typing…"),
+    ("thread.open", "Reply in thread"),
+    ("thread.title", "Thread"),
+    ("thread.reply_count.one", "{count} reply"),
+    ("thread.reply_count.other", "{count} replies"),
+    ("thread.placeholder", "Reply in thread…"),
+    ("thread.send", "Send reply"),
+    ("profile.online", "Online"),
+    ("profile.offline", "Offline"),
+    ("profile.messages_loaded.one", "{count} message loaded"),
+    ("profile.messages_loaded.other", "{count} messages loaded"),
+    ("profile.note_placeholder", "Only you can see this note"),
+    ("profile.no_note", "No note"),
+    ("profile.edit_note", "Edit your private note"),
+    ("profile.message", "Message"),
+    ("settings.title", "Settings"),
+    ("settings.appearance", "Appearance"),
+    ("settings.notifications", "Notifications"),
+    ("settings.messages", "Messages"),
+    ("settings.data", "Data"),
+    ("settings.language", "Language"),
+    ("settings.theme", "Theme"),
+    ("settings.theme.light", "Light"),
+    ("settings.theme.dark", "Dark"),
+    ("settings.theme.system", "System"),
+    ("settings.density", "Density"),
+    ("settings.density.comfortable", "Comfortable"),
+    ("settings.density.compact", "Compact"),
+    ("settings.font_size", "Font size"),
+    ("settings.font_size.small", "Small"),
+    ("settings.font_size.default", "Default"),
+    ("settings.font_size.large", "Large"),
+    ("settings.skin_tone", "Emoji skin tone"),
+    ("settings.avatar_template", "Avatar URL template"),
+    ("settings.avatar_template_invalid", "{reason} Showing generated avatars instead."),
+    ("settings.avatar_default_invalid", "This build's avatar template doesn't work. {reason} Showing generated avatars instead."),
+    ("avatar.not_https", "The template must start with https://."),
+    ("avatar.no_placeholder", "The template must contain {seed}."),
+    ("skin_tone.default", "Default"),
+    ("skin_tone.light", "Light"),
+    ("skin_tone.medium_light", "Medium-light"),
+    ("skin_tone.medium", "Medium"),
+    ("skin_tone.medium_dark", "Medium-dark"),
+    ("skin_tone.dark", "Dark"),
+    (
+        "settings.notifications_blocked",
+        "Desktop notifications are blocked. To turn them back on, allow notifications for this site in your browser's site settings, then reload.",
+    ),
+    ("settings.sound", "Play a sound on new messages"),
+    ("settings.vibrate", "Vibrate on mentions (mobile)"),
+    ("settings.hide_sentiment", "Hide sentiment coloring"),
+    ("settings.history_api", "Put message links in the query string, not after #"),
+    ("settings.transcript", "Keep a daily transcript on this device"),
+    ("settings.download_transcript", "Download today's transcript"),
+    ("settings.clear_data", "Clear local data"),
+    ("settings.usage", "Usage"),
+    ("edit.banner", "Editing your message — Esc to cancel"),
+    ("edit.locked", "This message is locked."),
+    ("pwa.updated", "A new version of the chat is ready."),
+    ("pwa.refresh", "Refresh"),
+    ("status.uploading", "Uploading {name}…"),
+    ("status.video_clip", "video clip"),
+    ("shortcuts.title", "Keyboard shortcuts"),
+    ("shortcuts.search", "Search messages"),
+    ("shortcuts.previous_channel", "Previous channel"),
+    ("shortcuts.next_channel", "Next channel"),
+    ("shortcuts.escape", "Close, cancel the edit, or clear the composer"),
+    ("shortcuts.move_focus", "Move between messages"),
+    ("shortcuts.edit", "Edit the focused message"),
+    ("shortcuts.help", "Show this list"),
+    ("help.shortcuts", "Press ? for keyboard shortcuts"),
+    ("quota.storage", "File storage"),
+    ("quota.storage_detail", "{used} of {quota}"),
+    ("quota.messages", "Messages today"),
+    ("quota.messages_detail", "{used} of {limit}"),
+    ("quota.daily_limit", "Daily message limit reached"),
+    ("quota.upload_confirm", "You have used {used} of your {quota} file storage. Upload anyway?"),
+    ("upload.clip_too_big", "This clip is {size} MB, which is over the 5 MB limit."),
+    ("upload.send", "Send"),
+    ("time.just_now", "just now"),
+    ("time.minutes_ago.one", "{count} minute ago"),
+    ("time.minutes_ago.other", "{count} minutes ago"),
+    ("time.hours_ago.one", "{count} hour ago"),
+    ("time.hours_ago.other", "{count} hours ago"),
+    ("time.days_ago.one", "{count} day ago"),
+    ("time.days_ago.other", "{count} days ago"),
+    ("broadcast.placeholder", "Announcement"),
+    ("broadcast.rooms", "Rooms (none ticked sends to all)"),
+    ("broadcast.send_all", "Send to all rooms"),
+    ("broadcast.send_rooms.one", "Send to {count} room"),
+    ("broadcast.send_rooms.other", "Send to {count} rooms"),
+    ("video.play", "Play {name}"),
+    ("video.receiving", "Receiving {name}…"),
+    ("channel_browser.title", "Browse channels"),
+    ("channel_browser.loading", "Loading channels…"),
+    ("channel_browser.empty", "There are no public channels yet."),
+    ("channel_browser.no_match", "No channels match your search."),
+    ("channel_browser.members.one", "{count} member"),
+    ("channel_browser.members.other", "{count} members"),
+    ("channel_browser.joined", "Joined"),
+    ("channel_browser.join", "Join"),
+    ("channel_browser.search", "Search channels"),
+    ("channel_browser.create_room", "+ Create room"),
+    ("channel_browser.name_placeholder", "room-name"),
+    ("channel_browser.description", "Description (optional)"),
+    ("channel_browser.max_members", "Max members (optional)"),
+    ("channel_browser.max_invalid", "Use a whole number of 2 or more, or leave it empty."),
+    ("channel_browser.public", "Public"),
+    ("channel_browser.private", "Private"),
+    ("channel_browser.secret", "Secret"),
+    ("channel_browser.create", "Create"),
+    ("channel_browser.creating", "Creating…"),
+    ("channel_browser.create_timed_out", "No answer about #{name}. Try again."),
+    ("room_name.too_short", "Use at least {min} characters."),
+    ("room_name.too_long", "Use at most {max} characters."),
+    ("room_name.space", "Use - instead of spaces."),
+    ("room_name.invalid_char", "\"{char}\" isn't allowed. Use letters, digits and -."),
+    ("room_name.stray_hyphen", "Put - only between letters or digits."),
+    ("room_name.reserved", "That name is reserved."),
+    ("messages.history_failed", "Couldn't load messages from #{channel}."),
+    ("pins.pinned", "📌 Pinned"),
+    ("pins.highlight", "✨ Today's highlight"),
+    ("pins.not_loaded", "A message not loaded yet"),
+    ("pins.pin", "Pin"),
+    ("pin_dialog.permanent", "Permanent"),
+    ("pin_dialog.permanent_hint", "Stays at the top of the room until someone unpins it."),
+    ("pin_dialog.highlight", "Today's highlight"),
+    ("pin_dialog.highlight_hint", "Comes down by itself at midnight UTC."),
+    ("file.removed", "[File removed]"),
+    ("file.delete", "Delete file"),
+    ("file.receiving", "Receiving {name}…"),
+    ("like.like", "Like"),
+    ("like.unlike", "Unlike"),
+    ("reactions.cap_reached", "Reaction limit reached for this message."),
+    ("reactions.more", "More reactions"),
+    ("spam.badge", "⚠ Possible spam"),
+    ("spam.score", "score {score}"),
+    ("spam.mark_safe", "Mark safe"),
+    ("spam.show_anyway", "Show anyway"),
+    ("messages.unavailable", "That message is no longer available."),
+    ("messages.loading_earlier", "Loading earlier messages…"),
+    ("messages.day_empty", "No messages that day."),
+    ("sidebar.in_channel", "In this channel"),
+    ("sidebar.elsewhere", "Elsewhere online"),
+    ("sidebar.nudged", "{name} nudged you."),
+    ("sidebar.greeting", "Hi there!"),
+    ("sidebar.mentions", "@ Mentions"),
+    ("sidebar.channels", "Channels"),
+    ("sidebar.archived", "Archived"),
+    ("sidebar.hide_users", "Hide users"),
+    ("sidebar.show_users", "Show users"),
+    ("user_menu.nudge", "Nudge"),
+    ("user_menu.nudge_cooldown", "You can nudge once a minute"),
+    ("user_menu.mute", "Mute notifications"),
+    ("user_menu.unless_mentioned", "Unless they mention me"),
+    ("notify_prompt.text", "Enable desktop notifications so you don't miss replies"),
+    ("notify_prompt.enable", "Enable"),
+    ("notify_prompt.later", "Not now"),
+    ("notify_pref.all", "All messages"),
+    ("notify_pref.mentions_only", "Mentions only"),
+    ("notify_pref.muted", "Muted"),
+    ("notification_exceptions.title", "Notification exceptions"),
+    ("notification_exceptions.allow_mentions", "Still notify when they mention you"),
+    ("notification_exceptions.unmute", "Unmute"),
+    ("mentions.empty", "Nothing new mentions you."),
+    ("mentions.watch_word", "watch word"),
+    ("recorder.recording", "Recording… (30 s max)"),
+    ("recorder.stop", "Stop"),
+    ("recorder.screen", "Record screen"),
+    ("recorder.camera", "Record camera"),
+    ("recorder.start", "Start recording"),
+    ("recorder.failed", "Couldn't start recording."),
+    ("edited.own", "(edited)"),
+    ("edited.by", "edited by {name}"),
+    ("edited.by_moderator", "(edited by a moderator)"),
+    ("edited.policy_notice", "This message was edited for policy reasons."),
+    ("message_menu.copy_link", "Copy message link"),
+    ("message_menu.lock", "🔒 Lock"),
+    ("message_menu.unlock", "🔓 Unlock"),
+    ("message_menu.link_copied", "Message link copied."),
+    ("message_menu.copy_failed", "Couldn't copy the message link."),
+    ("admin.tab.stats", "Server stats"),
+    ("admin.tab.mod_log", "Mod Log"),
+    ("admin.tab.broadcast", "Broadcast"),
+    ("admin.stats_hint", "Send /admin stats to load them."),
+    ("admin.stats.rooms", "Rooms"),
+    ("admin.stats.users", "Users"),
+    ("admin.stats.messages_today", "Messages today"),
+    ("admin.stats.uptime", "Uptime"),
+    ("notification.title", "{from} in {channel}"),
+    ("channel_browser.create_failed", "Couldn't create #{name}."),
+    ("usage.slowmode", "Usage: /slowmode <seconds>"),
+    ("usage.ephemeral", "Usage: /ephemeral <seconds> <message>"),
+    ("request_access.intro", "Tell the admins who you are and why you'd like to join the conversation."),
+    ("request_access.placeholder", "Your message"),
+    ("request_access.sent", "Your request has been sent to the admins."),
+    ("upload.read_failed", "Couldn't read that file."),
+    ("composer.location_failed", "Couldn't get your location."),
+    ("app_bar.slow_mode", "🐢 Slow mode: {seconds} s"),
+    ("app_bar.sound_on", "Sound on"),
+    ("app_bar.sound_off", "Sound off"),
+    ("app_bar.dnd_on", "Do Not Disturb is on"),
+    ("app_bar.notify_all_channels", "Notifications for all channels"),
+    ("map.view", "View on map"),
+    ("map.alt", "Map"),
+    ("shutdown.countdown", "Server shutting down in {time}."),
+    ("shutdown.offline_text", "The server shut down as announced."),
+    ("shutdown.reconnect", "Try reconnecting"),
+    ("clear_data.intro", "This removes from this browser:"),
+    ("clear_data.messages", "Messages loaded in every channel"),
+    ("clear_data.files", "Files received in this session"),
+    ("clear_data.cache", "The recent-messages cache used on reload"),
+    ("clear_data.archive", "The message archive kept for search and export"),
+    ("clear_data.settings", "Your settings, notification preferences and watch words"),
+    ("clear_data.server_untouched", "Nothing is deleted from the server."),
+    ("clear_data.reset_settings", "Also reset my settings"),
+    ("clear_data.clear", "Clear"),
+    ("clear_data.done", "Local data cleared."),
+    ("dnd.title", "Do Not Disturb"),
+    ("dnd.turn_off", "Turn off"),
+    ("dnd.on", "On"),
+    ("dnd.hours.one", "{count} h"),
+    ("dnd.hours.other", "{count} h"),
+    ("dnd.every_day_from", "Every day from"),
+    ("dnd.to", "to"),
+    ("empty.loading", "Loading messages…"),
+    ("empty.no_messages", "No messages yet. Say hi! 👋"),
+    ("empty.no_results", "Nothing matches your search."),
+    ("empty.error", "Something went wrong."),
+    ("export.json_hint", "Every message with all its details, for tools and backups."),
+    ("export.text", "Plain text"),
+    ("export.text_hint", "Readable lines like \"[14:32] alice: hello\"."),
+    ("export.from_archive", "Includes everything archived on this device."),
+    ("export.from_loaded", "Includes the messages loaded so far."),
+    ("export.export", "Export"),
+    ("login.continue_as", "Continue as {name}"),
+    ("login.not_you", "Not you?"),
+    ("login.username", "Username"),
+    ("login.go", "Go Chatting!"),
+    ("username.too_short", "Use at least {min} characters."),
+    ("username.too_long", "Use at most {max} characters."),
+    ("username.space", "Spaces aren't allowed."),
+    ("username.invalid_char", "\"{char}\" isn't allowed. Use letters, digits, _ and -."),
+    ("username.no_letters_or_digits", "Include at least one letter or digit."),
+    ("mod_log.all", "All"),
+    ("mod_log.filter_user", "Filter by user"),
+    ("mod_log.empty", "No moderation actions yet."),
+    ("mod_log.no_match", "Nothing matches these filters."),
+    ("search.remove_filter", "Remove filter"),
+    ("search.no_match", "Nothing matches."),
+    ("search.no_match_query", "Nothing matches \"{query}\"."),
+    ("search.results_for", "Results for \"{query}\""),
+    ("search.unavailable", "Search is unavailable right now."),
+    ("search.no_mentions", "No recent mentions."),
+    ("search.placeholder", "Search, e.g. from:alice after:yesterday"),
+    ("search.scope.channel", "This channel"),
+    ("search.scope.all", "All channels"),
+    ("search.jump_to_date", "Jump to date"),
+    ("search.recent_mentions", "Recent mentions"),
+    ("search.source.archive", "Messages loaded and archived on this device"),
+    ("search.source.loaded", "Messages loaded so far"),
+    ("search.truncated.one", "Showing the newest match. Refine your search to see the rest."),
+    ("search.truncated.other", "Showing the newest {count} matches. Refine your search to see the rest."),
+    ("settings.export", "Export settings"),
+    ("settings.import", "Import settings"),
+    (
+        "settings.import_unsupported",
+        "That settings file is version {version}, which this version of YewChat can't read. Nothing was changed.",
+    ),
+    ("stats.empty", "No messages loaded yet. Stats will show up once the chat gets going."),
+    ("stats.average_length", "Average length"),
+    ("stats.chars", "chars"),
+    ("stats.busiest_hour", "Busiest hour"),
+    ("stats.per_person", "Messages per person"),
+    ("stats.top_reactions", "Top reactions"),
+    ("stats.no_reactions", "No reactions yet."),
+    ("stats.footnote", "Counted over the messages loaded in every channel."),
+    ("toasts.dismiss", "Dismiss"),
+    ("watch_words.title", "Watch words"),
+    ("watch_words.remove", "Remove"),
+    ("watch_words.full", "Limit reached"),
+    ("watch_words.placeholder", "Add a word and press Enter"),
+];
+
+const ES: &[(&str, &str)] = &[
+    ("app_bar.menu", "Menú"),
+    ("app_bar.connected", "Conectado"),
+    ("app_bar.disconnected", "Desconectado"),
+    ("app_bar.online", "{count} en línea"),
+    ("app_bar.more", "Más"),
+    ("app_bar.settings", "Ajustes"),
+    ("app_bar.direct_messages", "Mensajes directos"),
+    ("app_bar.browse_channels", "Explorar canales"),
+    ("app_bar.export", "Exportar chat"),
+    ("app_bar.stats", "Estadísticas"),
+    ("app_bar.install", "Instalar la aplicación"),
+    ("app_bar.help", "Ayuda"),
+    ("app_bar.log_out", "Cerrar sesión"),
+    ("app_bar.tab.chats", "Chats"),
+    ("app_bar.tab.mentions", "Menciones"),
+    ("app_bar.tab.channels", "Canales"),
+    ("help.search_local", " buscar mensajes en este dispositivo"),
+    ("help.search_server", " buscar en la sala en el servidor"),
+    ("help.welcome", " fijar el saludo de la sala"),
+    ("help.slowmode", " limitar cada cuánto se puede publicar"),
+    ("help.ephemeral", " enviar un mensaje que se borra solo"),
+    ("common.ok", "Aceptar"),
+    ("common.cancel", "Cancelar"),
+    ("common.close", "Cerrar"),
+    ("common.save", "Guardar"),
+    ("common.retry", "Reintentar"),
+    ("error.reload", "Algo salió mal — haz clic aquí para recargar"),
+    ("sidebar.users", "Usuarios"),
+    ("sidebar.filter_users", "Filtrar usuarios"),
+    ("sidebar.user_actions", "Acciones para {name}"),
+    ("sidebar.archive", "Archivar"),
+    ("sidebar.unarchive", "Desarchivar"),
+    ("sidebar.archive_channel", "Archivar {channel}"),
+    ("sidebar.unarchive_channel", "Desarchivar {channel}"),
+    ("sidebar.browse", "+ Explorar canales"),
+    ("sidebar.close_dm", "Cerrar la conversación con {name}"),
+    ("dialog.message_actions", "Acciones del mensaje"),
+    ("dialog.server_offline", "Servidor desconectado"),
+    ("dialog.policy_notice", "Editado por motivos de política"),
+    ("dialog.image", "Imagen"),
+    ("dialog.pin_message", "Fijar mensaje"),
+    ("dialog.request_access", "Solicitar acceso"),
+    ("dialog.session_stats", "Estadísticas de la sesión"),
+    ("dialog.clear_data", "Borrar datos locales"),
+    ("dialog.export", "Exportar {channel}"),
+    ("dialog.confirm_upload", "¿Enviar de todos modos?"),
+    ("pins.unpin", "Desfijar"),
+    ("channel.unread.one", "{count} mensaje nuevo"),
+    ("channel.unread.other", "{count} mensajes nuevos"),
+    ("messages.earlier", "— mensajes anteriores —"),
+    ("messages.label", "Mensajes en {channel}"),
+    ("message.label", "{from}, {time}: {text}"),
+    ("message.label_untimed", "{from}: {text}"),
+    ("messages.viewing_day", "Viendo {day}"),
+    ("messages.back_to_latest", "Volver a lo último"),
+    ("messages.day_truncated", "Los mensajes posteriores de ese día no están cargados."),
+    ("messages.day_failed", "No se pudo cargar ese día."),
+    ("composer.placeholder", "Escribe un mensaje..."),
+    ("composer.label", "Mensaje"),
+    ("composer.send", "Enviar"),
+    ("ephemeral.title", "Desaparece para todos cuando termina la cuenta atrás"),
+    ("ephemeral.remaining", "{seconds} s"),
+    ("composer.read_only", "Modo de solo lectura"),
+    ("composer.offline", "Sin conexión — mostrando mensajes guardados"),
+    ("composer.cooldown", "Podrás enviar de nuevo en {seconds} s"),
+    ("composer.locating", "Buscando tu ubicación…"),
+    ("composer.share_location", "Compartir tu ubicación"),
+    ("composer.attach", "Adjuntar un archivo"),
+    ("composer.record", "Grabar un clip"),
+    ("banner.read_only", "Estás en modo de solo lectura"),
+    ("banner.request_access", "Solicitar acceso"),
+    ("typing.one", "{name} está escribiendo…"),
+    ("typing.two", "{first} y {second} están escribiendo…"),
+    ("typing.several", "{count} personas están escribiendo…"),
+    ("thread.open", "Responder en un hilo"),
+    ("thread.title", "Hilo"),
+    ("thread.reply_count.one", "{count} respuesta"),
+    ("thread.reply_count.other", "{count} respuestas"),
+    ("thread.placeholder", "Responder en el hilo…"),
+    ("thread.send", "Enviar respuesta"),
+    ("profile.online", "En línea"),
+    ("profile.offline", "Desconectado"),
+    ("profile.messages_loaded.one", "{count} mensaje cargado"),
+    ("profile.messages_loaded.other", "{count} mensajes cargados"),
+    ("profile.note_placeholder", "Solo tú puedes ver esta nota"),
+    ("profile.no_note", "Sin nota"),
+    ("profile.edit_note", "Editar tu nota privada"),
+    ("profile.message", "Mensaje"),
+    ("settings.title", "Ajustes"),
+    ("settings.appearance", "Apariencia"),
+    ("settings.notifications", "Notificaciones"),
+    ("settings.messages", "Mensajes"),
+    ("settings.data", "Datos"),
+    ("settings.language", "Idioma"),
+    ("settings.theme", "Tema"),
+    ("settings.theme.light", "Claro"),
+    ("settings.theme.dark", "Oscuro"),
+    ("settings.theme.system", "Sistema"),
+    ("settings.density", "Densidad"),
+    ("settings.density.comfortable", "Cómoda"),
+    ("settings.density.compact", "Compacta"),
+    ("settings.font_size", "Tamaño de letra"),
+    ("settings.font_size.small", "Pequeño"),
+    ("settings.font_size.default", "Normal"),
+    ("settings.font_size.large", "Grande"),
+    ("settings.skin_tone", "Tono de piel de los emoji"),
+    ("settings.avatar_template", "Plantilla de URL de avatares"),
+    ("settings.avatar_template_invalid", "{reason} Se muestran avatares generados."),
+    ("settings.avatar_default_invalid", "La plantilla de avatares de esta versión no funciona. {reason} Se muestran avatares generados."),
+    ("avatar.not_https", "La plantilla debe empezar por https://."),
+    ("avatar.no_placeholder", "La plantilla debe contener {seed}."),
+    ("skin_tone.default", "Predeterminado"),
+    ("skin_tone.light", "Claro"),
+    ("skin_tone.medium_light", "Medio claro"),
+    ("skin_tone.medium", "Medio"),
+    ("skin_tone.medium_dark", "Medio oscuro"),
+    ("skin_tone.dark", "Oscuro"),
+    (
+        "settings.notifications_blocked",
+        "Las notificaciones de escritorio están bloqueadas. Para activarlas, permite las notificaciones de este sitio en los ajustes del navegador y recarga la página.",
+    ),
+    ("settings.sound", "Reproducir un sonido con cada mensaje nuevo"),
+    ("settings.vibrate", "Vibrar con las menciones (móvil)"),
+    ("settings.hide_sentiment", "Ocultar el color de sentimiento"),
+    ("settings.history_api", "Poner los enlaces a mensajes en la consulta, no tras #"),
+    ("settings.transcript", "Guardar una transcripción diaria en este dispositivo"),
+    ("settings.download_transcript", "Descargar la transcripción de hoy"),
+    ("settings.clear_data", "Borrar datos locales"),
+    ("settings.usage", "Uso"),
+    ("edit.banner", "Editando tu mensaje — Esc para cancelar"),
+    ("edit.locked", "Este mensaje está bloqueado."),
+    ("pwa.updated", "Hay una nueva versión del chat."),
+    ("pwa.refresh", "Actualizar"),
+    ("status.uploading", "Subiendo {name}…"),
+    ("status.video_clip", "videoclip"),
+    ("shortcuts.title", "Atajos de teclado"),
+    ("shortcuts.search", "Buscar mensajes"),
+    ("shortcuts.previous_channel", "Canal anterior"),
+    ("shortcuts.next_channel", "Canal siguiente"),
+    ("shortcuts.escape", "Cerrar, cancelar la edición o vaciar el cuadro de texto"),
+    ("shortcuts.move_focus", "Moverse entre mensajes"),
+    ("shortcuts.edit", "Editar el mensaje seleccionado"),
+    ("shortcuts.help", "Mostrar esta lista"),
+    ("help.shortcuts", "Pulsa ? para ver los atajos de teclado"),
+    ("quota.storage", "Almacenamiento de archivos"),
+    ("quota.storage_detail", "{used} de {quota}"),
+    ("quota.messages", "Mensajes de hoy"),
+    ("quota.messages_detail", "{used} de {limit}"),
+    ("quota.daily_limit", "Has alcanzado el límite diario de mensajes"),
+    ("quota.upload_confirm", "Has usado {used} de tus {quota} de almacenamiento. ¿Subir de todos modos?"),
+    ("upload.clip_too_big", "Este clip ocupa {size} MB, más del límite de 5 MB."),
+    ("upload.send", "Enviar"),
+    ("time.just_now", "ahora mismo"),
+    ("time.minutes_ago.one", "hace {count} minuto"),
+    ("time.minutes_ago.other", "hace {count} minutos"),
+    ("time.hours_ago.one", "hace {count} hora"),
+    ("time.hours_ago.other", "hace {count} horas"),
+    ("time.days_ago.one", "hace {count} día"),
+    ("time.days_ago.other", "hace {count} días"),
+    ("broadcast.placeholder", "Anuncio"),
+    ("broadcast.rooms", "Salas (sin marcar se envía a todas)"),
+    ("broadcast.send_all", "Enviar a todas las salas"),
+    ("broadcast.send_rooms.one", "Enviar a {count} sala"),
+    ("broadcast.send_rooms.other", "Enviar a {count} salas"),
+    ("video.play", "Reproducir {name}"),
+    ("video.receiving", "Recibiendo {name}…"),
+    ("channel_browser.title", "Explorar canales"),
+    ("channel_browser.loading", "Cargando canales…"),
+    ("channel_browser.empty", "Todavía no hay canales públicos."),
+    ("channel_browser.no_match", "Ningún canal coincide con tu búsqueda."),
+    ("channel_browser.members.one", "{count} miembro"),
+    ("channel_browser.members.other", "{count} miembros"),
+    ("channel_browser.joined", "Unido"),
+    ("channel_browser.join", "Unirse"),
+    ("channel_browser.search", "Buscar canales"),
+    ("channel_browser.create_room", "+ Crear sala"),
+    ("channel_browser.name_placeholder", "nombre-de-sala"),
+    ("channel_browser.description", "Descripción (opcional)"),
+    ("channel_browser.max_members", "Máximo de miembros (opcional)"),
+    ("channel_browser.max_invalid", "Usa un número entero de 2 o más, o déjalo vacío."),
+    ("channel_browser.public", "Pública"),
+    ("channel_browser.private", "Privada"),
+    ("channel_browser.secret", "Secreta"),
+    ("channel_browser.create", "Crear"),
+    ("channel_browser.creating", "Creando…"),
+    ("channel_browser.create_timed_out", "No hubo respuesta sobre #{name}. Inténtalo de nuevo."),
+    ("room_name.too_short", "Usa al menos {min} caracteres."),
+    ("room_name.too_long", "Usa como mucho {max} caracteres."),
+    ("room_name.space", "Usa - en lugar de espacios."),
+    ("room_name.invalid_char", "\"{char}\" no está permitido. Usa letras, dígitos y -."),
+    ("room_name.stray_hyphen", "Pon - solo entre letras o dígitos."),
+    ("room_name.reserved", "Ese nombre está reservado."),
+    ("messages.history_failed", "No se pudieron cargar los mensajes de #{channel}."),
+    ("pins.pinned", "📌 Fijado"),
+    ("pins.highlight", "✨ Destacado del día"),
+    ("pins.not_loaded", "Un mensaje que aún no se ha cargado"),
+    ("pins.pin", "Fijar"),
+    ("pin_dialog.permanent", "Permanente"),
+    ("pin_dialog.permanent_hint", "Se queda arriba en la sala hasta que alguien lo desfije."),
+    ("pin_dialog.highlight", "Destacado del día"),
+    ("pin_dialog.highlight_hint", "Se quita solo a medianoche UTC."),
+    ("file.removed", "[Archivo eliminado]"),
+    ("file.delete", "Eliminar archivo"),
+    ("file.receiving", "Recibiendo {name}…"),
+    ("like.like", "Me gusta"),
+    ("like.unlike", "Ya no me gusta"),
+    ("reactions.cap_reached", "Este mensaje ha llegado al límite de reacciones."),
+    ("reactions.more", "Más reacciones"),
+    ("spam.badge", "⚠ Posible spam"),
+    ("spam.score", "puntuación {score}"),
+    ("spam.mark_safe", "Marcar como seguro"),
+    ("spam.show_anyway", "Mostrar de todos modos"),
+    ("messages.unavailable", "Ese mensaje ya no está disponible."),
+    ("messages.loading_earlier", "Cargando mensajes anteriores…"),
+    ("messages.day_empty", "No hay mensajes ese día."),
+    ("sidebar.in_channel", "En este canal"),
+    ("sidebar.elsewhere", "En línea en otro sitio"),
+    ("sidebar.nudged", "{name} te ha dado un toque."),
+    ("sidebar.greeting", "¡Hola!"),
+    ("sidebar.mentions", "@ Menciones"),
+    ("sidebar.channels", "Canales"),
+    ("sidebar.archived", "Archivados"),
+    ("sidebar.hide_users", "Ocultar usuarios"),
+    ("sidebar.show_users", "Mostrar usuarios"),
+    ("user_menu.nudge", "Dar un toque"),
+    ("user_menu.nudge_cooldown", "Puedes dar un toque una vez por minuto"),
+    ("user_menu.mute", "Silenciar notificaciones"),
+    ("user_menu.unless_mentioned", "Salvo si me mencionan"),
+    ("notify_prompt.text", "Activa las notificaciones de escritorio para no perderte respuestas"),
+    ("notify_prompt.enable", "Activar"),
+    ("notify_prompt.later", "Ahora no"),
+    ("notify_pref.all", "Todos los mensajes"),
+    ("notify_pref.mentions_only", "Solo menciones"),
+    ("notify_pref.muted", "Silenciado"),
+    ("notification_exceptions.title", "Excepciones de notificaciones"),
+    ("notification_exceptions.allow_mentions", "Avisar igualmente si te mencionan"),
+    ("notification_exceptions.unmute", "Dejar de silenciar"),
+    ("mentions.empty", "No hay menciones nuevas."),
+    ("mentions.watch_word", "palabra vigilada"),
+    ("recorder.recording", "Grabando… (máx. 30 s)"),
+    ("recorder.stop", "Detener"),
+    ("recorder.screen", "Grabar pantalla"),
+    ("recorder.camera", "Grabar cámara"),
+    ("recorder.start", "Empezar a grabar"),
+    ("recorder.failed", "No se pudo empezar a grabar."),
+    ("edited.own", "(editado)"),
+    ("edited.by", "editado por {name}"),
+    ("edited.by_moderator", "(editado por un moderador)"),
+    ("edited.policy_notice", "Este mensaje se editó por motivos de política."),
+    ("message_menu.copy_link", "Copiar enlace del mensaje"),
+    ("message_menu.lock", "🔒 Bloquear"),
+    ("message_menu.unlock", "🔓 Desbloquear"),
+    ("message_menu.link_copied", "Enlace del mensaje copiado."),
+    ("message_menu.copy_failed", "No se pudo copiar el enlace del mensaje."),
+    ("admin.tab.stats", "Estadísticas del servidor"),
+    ("admin.tab.mod_log", "Registro de moderación"),
+    ("admin.tab.broadcast", "Anuncio"),
+    ("admin.stats_hint", "Envía /admin stats para cargarlas."),
+    ("admin.stats.rooms", "Salas"),
+    ("admin.stats.users", "Usuarios"),
+    ("admin.stats.messages_today", "Mensajes de hoy"),
+    ("admin.stats.uptime", "Tiempo activo"),
+    ("notification.title", "{from} en {channel}"),
+    ("channel_browser.create_failed", "No se pudo crear #{name}."),
+    ("usage.slowmode", "Uso: /slowmode <segundos>"),
+    ("usage.ephemeral", "Uso: /ephemeral <segundos> <mensaje>"),
+    ("request_access.intro", "Cuenta a los administradores quién eres y por qué quieres unirte a la conversación."),
+    ("request_access.placeholder", "Tu mensaje"),
+    ("request_access.sent", "Tu solicitud se ha enviado a los administradores."),
+    ("upload.read_failed", "No se pudo leer ese archivo."),
+    ("composer.location_failed", "No se pudo obtener tu ubicación."),
+    ("app_bar.slow_mode", "🐢 Modo lento: {seconds} s"),
+    ("app_bar.sound_on", "Sonido activado"),
+    ("app_bar.sound_off", "Sonido desactivado"),
+    ("app_bar.dnd_on", "No molestar está activado"),
+    ("app_bar.notify_all_channels", "Notificaciones de todos los canales"),
+    ("map.view", "Ver en el mapa"),
+    ("map.alt", "Mapa"),
+    ("shutdown.countdown", "El servidor se apagará en {time}."),
+    ("shutdown.offline_text", "El servidor se apagó según lo anunciado."),
+    ("shutdown.reconnect", "Intentar reconectar"),
+    ("clear_data.intro", "Esto borra de este navegador:"),
+    ("clear_data.messages", "Los mensajes cargados en todos los canales"),
+    ("clear_data.files", "Los archivos recibidos en esta sesión"),
+    ("clear_data.cache", "La caché de mensajes recientes que se usa al recargar"),
+    ("clear_data.archive", "El archivo de mensajes guardado para buscar y exportar"),
+    ("clear_data.settings", "Tus ajustes, preferencias de notificación y palabras vigiladas"),
+    ("clear_data.server_untouched", "No se borra nada del servidor."),
+    ("clear_data.reset_settings", "Restablecer también mis ajustes"),
+    ("clear_data.clear", "Borrar"),
+    ("clear_data.done", "Datos locales borrados."),
+    ("dnd.title", "No molestar"),
+    ("dnd.turn_off", "Desactivar"),
+    ("dnd.on", "Activado"),
+    ("dnd.hours.one", "{count} h"),
+    ("dnd.hours.other", "{count} h"),
+    ("dnd.every_day_from", "Todos los días de"),
+    ("dnd.to", "a"),
+    ("empty.loading", "Cargando mensajes…"),
+    ("empty.no_messages", "Aún no hay mensajes. ¡Saluda! 👋"),
+    ("empty.no_results", "Nada coincide con tu búsqueda."),
+    ("empty.error", "Algo salió mal."),
+    ("export.json_hint", "Cada mensaje con todos sus detalles, para herramientas y copias de seguridad."),
+    ("export.text", "Texto plano"),
+    ("export.text_hint", "Líneas legibles como \"[14:32] alice: hola\"."),
+    ("export.from_archive", "Incluye todo lo archivado en este dispositivo."),
+    ("export.from_loaded", "Incluye los mensajes cargados hasta ahora."),
+    ("export.export", "Exportar"),
+    ("login.continue_as", "Continuar como {name}"),
+    ("login.not_you", "¿No eres tú?"),
+    ("login.username", "Nombre de usuario"),
+    ("login.go", "¡A chatear!"),
+    ("username.too_short", "Usa al menos {min} caracteres."),
+    ("username.too_long", "Usa como mucho {max} caracteres."),
+    ("username.space", "No se permiten espacios."),
+    ("username.invalid_char", "\"{char}\" no está permitido. Usa letras, dígitos, _ y -."),
+    ("username.no_letters_or_digits", "Incluye al menos una letra o un dígito."),
+    ("mod_log.all", "Todo"),
+    ("mod_log.filter_user", "Filtrar por usuario"),
+    ("mod_log.empty", "Aún no hay acciones de moderación."),
+    ("mod_log.no_match", "Nada coincide con estos filtros."),
+    ("search.remove_filter", "Quitar filtro"),
+    ("search.no_match", "No hay coincidencias."),
+    ("search.no_match_query", "Nada coincide con \"{query}\"."),
+    ("search.results_for", "Resultados de \"{query}\""),
+    ("search.unavailable", "La búsqueda no está disponible ahora mismo."),
+    ("search.no_mentions", "No hay menciones recientes."),
+    ("search.placeholder", "Buscar, p. ej. from:alice after:yesterday"),
+    ("search.scope.channel", "Este canal"),
+    ("search.scope.all", "Todos los canales"),
+    ("search.jump_to_date", "Ir a una fecha"),
+    ("search.recent_mentions", "Menciones recientes"),
+    ("search.source.archive", "Mensajes cargados y archivados en este dispositivo"),
+    ("search.source.loaded", "Mensajes cargados hasta ahora"),
+    ("search.truncated.one", "Se muestra la coincidencia más reciente. Afina la búsqueda para ver el resto."),
+    (
+        "search.truncated.other",
+        "Se muestran las {count} coincidencias más recientes. Afina la búsqueda para ver el resto.",
+    ),
+    ("settings.export", "Exportar ajustes"),
+    ("settings.import", "Importar ajustes"),
+    (
+        "settings.import_unsupported",
+        "Ese archivo de ajustes es de la versión {version}, que esta versión de YewChat no sabe leer. No se ha cambiado nada.",
+    ),
+    ("stats.empty", "Aún no hay mensajes cargados. Las estadísticas aparecerán cuando empiece la conversación."),
+    ("stats.average_length", "Longitud media"),
+    ("stats.chars", "caracteres"),
+    ("stats.busiest_hour", "Hora con más actividad"),
+    ("stats.per_person", "Mensajes por persona"),
+    ("stats.top_reactions", "Reacciones más usadas"),
+    ("stats.no_reactions", "Aún no hay reacciones."),
+    ("stats.footnote", "Calculado sobre los mensajes cargados en todos los canales."),
+    ("toasts.dismiss", "Descartar"),
+    ("watch_words.title", "Palabras vigiladas"),
+    ("watch_words.remove", "Quitar"),
+    ("watch_words.full", "Límite alcanzado"),
+    ("watch_words.placeholder", "Añade una palabra y pulsa Intro"),
+];
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use super::*;
+
+    /// Plural forms any bundled locale picks between.
+    const PLURAL_FORMS: [&str; 2] = ["one", "other"];
+
+    fn sources(dir: &Path, found: &mut Vec<String>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                sources(&path, found);
+            } else if path.extension() == Some("rs".as_ref()) {
+                found.push(fs::read_to_string(&path).unwrap());
+            }
+        }
+    }
+
+    /// Every literal key passed to `t!` outside comments, and whether it is given a `count`.
+    fn keys_used() -> Vec<(String, bool)> {
+        let mut files = vec![];
+        sources(&Path::new(env!("CARGO_MANIFEST_DIR")).join("src"), &mut files);
+        let mut keys = vec![];
+        for file in &files {
+            for line in file.lines().filter(|line| !line.trim_start().starts_with("//")) {
+                for (at, call) in line.match_indices("t!(\"") {
+                    // Not the tail of `format!(` and the like.
+                    if line[..at].ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+                        continue;
+                    }
+                    if let Some((key, rest)) = line[at + call.len()..].split_once('"') {
+                        keys.push((key.to_string(), rest.starts_with(", count =")));
+                    }
+                }
+            }
+        }
+        keys
+    }
+
+    #[test]
+    fn finds_keys_in_the_source() {
+        let keys = keys_used();
+        assert!(keys.iter().any(|(key, _)| key == "settings.title"));
+        assert!(keys.iter().any(|(key, plural)| key == "time.minutes_ago" && *plural));
+    }
+
+    #[test]
+    fn every_key_used_in_code_is_in_every_locale() {
+        let mut missing = vec![];
+        for (key, plural) in keys_used() {
+            for locale in Locale::ALL {
+                let found = if plural {
+                    lookup(locale, &key).is_some()
+                        || PLURAL_FORMS.iter().all(|form| lookup(locale, &format!("{}.{}", key, form)).is_some())
+                } else {
+                    lookup(locale, &key).is_some()
+                };
+                if !found {
+                    missing.push(format!("{:?} in {:?}", key, locale));
+                }
+            }
+        }
+        missing.dedup();
+        assert!(missing.is_empty(), "missing UI strings: {}", missing.join(", "));
+    }
+
+    #[test]
+    fn bundles_have_the_same_keys() {
+        for locale in Locale::ALL {
+            for (key, _) in locale.strings() {
+                for other in Locale::ALL {
+                    assert!(lookup(other, key).is_some(), "{:?} has {:?} but {:?} doesn't", locale, key, other);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn no_key_is_listed_twice() {
+        for locale in Locale::ALL {
+            let strings = locale.strings();
+            for (i, (key, _)) in strings.iter().enumerate() {
+                assert!(!strings[..i].iter().any(|(k, _)| k == key), "{:?} lists {:?} twice", locale, key);
+            }
+        }
+    }
+}
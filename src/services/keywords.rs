@@ -0,0 +1,229 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+/// Most watch words a user can register.
+pub const MAX_WATCH_WORDS: usize = 20;
+/// Texts whose matches are remembered before the memo starts over.
+const MEMO_SIZE: usize = 2_000;
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Words of `text` with their byte ranges, lowercased. Anything that isn't a letter,
+/// digit or underscore separates words, in any script, except that trailing `+` and `#`
+/// stay on the word they end so "c++" and "c#" aren't just "c".
+fn words(text: &str) -> Vec<(String, Range<usize>)> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let at = |i: usize| chars.get(i).map_or(text.len(), |&(at, _)| at);
+    let mut words = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        if !is_word_char(chars[i].1) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && is_word_char(chars[i].1) {
+            i += 1;
+        }
+        let mut end = i;
+        while end < chars.len() && matches!(chars[end].1, '+' | '#') {
+            end += 1;
+        }
+        if chars.get(end).map_or(true, |&(_, c)| !is_word_char(c)) {
+            i = end;
+        }
+        let range = at(start)..at(i);
+        words.push((text[range.clone()].to_lowercase(), range));
+    }
+    words
+}
+
+/// All watch words compiled into one lookup, so a message is scanned once
+/// no matter how many words are watched.
+#[derive(Clone, Debug, Default)]
+pub struct KeywordMatcher {
+    /// Phrases, as word sequences, keyed by their first word.
+    by_first_word: HashMap<String, Vec<Vec<String>>>,
+    /// Matches by text, since every render asks again for every message on screen. Clones
+    /// watch the same words, so they share it.
+    memo: Rc<RefCell<HashMap<String, Vec<Range<usize>>>>>,
+}
+
+impl KeywordMatcher {
+    pub fn new(keywords: &[String]) -> Self {
+        let mut by_first_word: HashMap<String, Vec<Vec<String>>> = HashMap::new();
+        for keyword in keywords {
+            let phrase: Vec<String> = words(keyword).into_iter().map(|(w, _)| w).collect();
+            if let Some(first) = phrase.first() {
+                by_first_word.entry(first.clone()).or_default().push(phrase);
+            }
+        }
+        // Longest first, so "deploy failed" wins over "deploy".
+        for phrases in by_first_word.values_mut() {
+            phrases.sort_by_key(|p| std::cmp::Reverse(p.len()));
+        }
+        Self {
+            by_first_word,
+            memo: Rc::default(),
+        }
+    }
+
+    /// Byte ranges of every whole-word match in `text`, without overlaps.
+    pub fn find(&self, text: &str) -> Vec<Range<usize>> {
+        if self.by_first_word.is_empty() {
+            return vec![];
+        }
+        if let Some(hits) = self.memo.borrow().get(text) {
+            return hits.clone();
+        }
+        let hits = self.scan(text);
+        let mut memo = self.memo.borrow_mut();
+        if memo.len() >= MEMO_SIZE {
+            memo.clear();
+        }
+        memo.insert(text.to_string(), hits.clone());
+        hits
+    }
+
+    fn scan(&self, text: &str) -> Vec<Range<usize>> {
+        let words = words(text);
+        let mut hits = vec![];
+        let mut i = 0;
+        while i < words.len() {
+            let matched = self.by_first_word.get(&words[i].0).and_then(|phrases| {
+                phrases.iter().find(|phrase| {
+                    words.len() - i >= phrase.len()
+                        && phrase.iter().zip(&words[i..]).all(|(p, (w, _))| p == w)
+                })
+            });
+            match matched {
+                Some(phrase) => {
+                    let end = i + phrase.len() - 1;
+                    hits.push(words[i].1.start..words[end].1.end);
+                    i = end + 1;
+                }
+                None => i += 1,
+            }
+        }
+        hits
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        !self.find(text).is_empty()
+    }
+}
+
+/// One user's watch words. Persisted as part of their
+/// [`UserSettings`](crate::services::settings::UserSettings), as a plain list.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(from = "Vec<String>", into = "Vec<String>")]
+pub struct WatchWords {
+    words: Vec<String>,
+    matcher: KeywordMatcher,
+}
+
+impl From<Vec<String>> for WatchWords {
+    fn from(words: Vec<String>) -> Self {
+        let mut watch_words = Self::default();
+        watch_words.set(words);
+        watch_words
+    }
+}
+
+impl From<WatchWords> for Vec<String> {
+    fn from(watch_words: WatchWords) -> Self {
+        watch_words.words
+    }
+}
+
+impl PartialEq for WatchWords {
+    fn eq(&self, other: &Self) -> bool {
+        self.words == other.words
+    }
+}
+
+impl WatchWords {
+    /// The localStorage key watch words were kept under before they moved into the settings.
+    pub fn legacy_key(username: &str) -> String {
+        format!("yewchat.watch_words.{}", username)
+    }
+
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    pub fn matcher(&self) -> &KeywordMatcher {
+        &self.matcher
+    }
+
+    /// Replaces the list, dropping blanks and duplicates and anything past the cap.
+    pub fn set(&mut self, words: Vec<String>) {
+        let mut cleaned: Vec<String> = vec![];
+        for word in words {
+            let word = word.trim().to_string();
+            if !word.is_empty() && !cleaned.iter().any(|w| w.to_lowercase() == word.to_lowercase()) {
+                cleaned.push(word);
+            }
+        }
+        cleaned.truncate(MAX_WATCH_WORDS);
+        self.matcher = KeywordMatcher::new(&cleaned);
+        self.words = cleaned;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher(words: &[&str]) -> KeywordMatcher {
+        KeywordMatcher::new(&words.iter().map(|w| w.to_string()).collect::<Vec<_>>())
+    }
+
+    fn found<'a>(matcher: &KeywordMatcher, text: &'a str) -> Vec<&'a str> {
+        matcher.find(text).into_iter().map(|hit| &text[hit]).collect()
+    }
+
+    #[test]
+    fn whole_words_match_in_any_case() {
+        let m = matcher(&["deploy"]);
+        assert_eq!(found(&m, "Deploy done, deploy again"), ["Deploy", "deploy"]);
+        assert!(!m.is_match("redeployed"));
+        assert!(!matcher(&[]).is_match("deploy"));
+    }
+
+    #[test]
+    fn the_longest_phrase_wins() {
+        let m = matcher(&["deploy", "deploy failed"]);
+        assert_eq!(found(&m, "deploy  failed, then deploy"), ["deploy  failed", "deploy"]);
+    }
+
+    #[test]
+    fn symbols_ending_a_word_are_part_of_it() {
+        let m = matcher(&["c++", "c#"]);
+        assert_eq!(found(&m, "c, c++ and c#."), ["c++", "c#"]);
+        assert!(!matcher(&["c"]).is_match("c++ and c#"));
+        assert!(matcher(&["a"]).is_match("a+b"));
+    }
+
+    #[test]
+    fn repeated_lookups_agree() {
+        let m = matcher(&["ship"]);
+        assert_eq!(m.find("ship it"), m.find("ship it"));
+        assert_eq!(m.find("ship it"), vec![0..4]);
+    }
+
+    #[test]
+    fn the_list_is_trimmed_deduplicated_and_capped() {
+        let mut watch = WatchWords::default();
+        watch.set(vec![" Deploy ".into(), "deploy".into(), "".into(), "ship".into()]);
+        assert_eq!(watch.words(), ["Deploy", "ship"]);
+        watch.set((0..MAX_WATCH_WORDS + 5).map(|n| n.to_string()).collect());
+        assert_eq!(watch.words().len(), MAX_WATCH_WORDS);
+    }
+}
@@ -0,0 +1,44 @@
+use gloo_storage::{LocalStorage, Storage};
+
+/// The name this browser last joined with, for the login screen's "Continue as" button.
+/// Not per user like the rest: it is read before anyone has logged in.
+pub const KEY: &str = "yewchat.last_username";
+
+/// Whether joining as `name` has gone through and is worth remembering: the server
+/// answered this socket's registration with `confirmed`, the name it registered it under,
+/// and it isn't a guest visit. Being in the user list proves nothing, since a taken name
+/// is listed for whoever holds it; until the answer comes the one remembered before is kept.
+pub fn should_remember(confirmed: Option<&str>, name: &str, is_guest: bool) -> bool {
+    !is_guest && confirmed == Some(name)
+}
+
+pub fn remember(name: &str) {
+    if let Err(e) = LocalStorage::set(KEY, name) {
+        log::error!("failed to remember the username: {:?}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remembers_a_confirmed_registration() {
+        assert!(should_remember(Some("ann"), "ann", false));
+    }
+
+    #[test]
+    fn waits_for_the_server() {
+        assert!(!should_remember(None, "ann", false));
+    }
+
+    #[test]
+    fn ignores_a_registration_under_another_name() {
+        assert!(!should_remember(Some("ann_2"), "ann", false));
+    }
+
+    #[test]
+    fn never_remembers_guests() {
+        assert!(!should_remember(Some("ann"), "ann", true));
+    }
+}
@@ -0,0 +1,436 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use futures::channel::oneshot;
+use js_sys::{Array, JSON};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    IdbCursorDirection, IdbCursorWithValue, IdbDatabase, IdbKeyRange, IdbObjectStore, IdbObjectStoreParameters,
+    IdbOpenDbRequest, IdbRequest, IdbTransactionMode,
+};
+
+use crate::components::chat::MessageData;
+use crate::services::channel_store::DEFAULT_CHANNEL;
+use crate::services::message_cache::normalize_reactions;
+use crate::services::transcript::Transcript;
+
+/// 2 added the transcript store.
+const DB_VERSION: u32 = 2;
+const STORE: &str = "messages";
+/// One record per local day, keyed by `day`.
+const TRANSCRIPTS: &str = "transcripts";
+/// Index on the sender.
+const BY_SENDER: &str = "from";
+/// Index on the timestamp alone, across channels.
+const BY_TIME: &str = "ts";
+
+/// One archived message. Keyed by `(channel, ts, id)` so a channel's messages sit together
+/// in time order, and the id keeps messages sent in the same millisecond apart.
+#[derive(Serialize, Deserialize)]
+struct Record {
+    channel: String,
+    ts: f64,
+    id: String,
+    from: String,
+    data: MessageData,
+}
+
+impl Record {
+    /// The record `message` is archived as. `None` for messages with local ids, which are
+    /// reused after a reload and would overwrite each other, and for messages without a
+    /// timestamp, which have no place in the index and couldn't be found again to update.
+    fn of(message: &MessageData) -> Option<Self> {
+        if message.id.starts_with("local-") {
+            return None;
+        }
+        Some(Self {
+            channel: message.channel.clone().unwrap_or_else(|| DEFAULT_CHANNEL.to_string()),
+            ts: message.timestamp?,
+            id: message.id.clone(),
+            from: message.from.clone(),
+            data: message.clone(),
+        })
+    }
+
+    fn to_js(&self) -> Result<JsValue, JsValue> {
+        JSON::parse(&serde_json::to_string(self).unwrap())
+    }
+
+    fn from_js(value: &JsValue) -> Option<Self> {
+        let json: String = JSON::stringify(value).ok()?.into();
+        serde_json::from_str(&json).ok()
+    }
+}
+
+/// Resolves with the request's result once it succeeds.
+async fn request_done(request: &IdbRequest) -> Result<JsValue, JsValue> {
+    let (tx, rx) = oneshot::channel();
+    let tx = Rc::new(RefCell::new(Some(tx)));
+    let onsuccess = {
+        let tx = tx.clone();
+        let request = request.clone();
+        Closure::once(move || {
+            if let Some(tx) = tx.borrow_mut().take() {
+                let _ = tx.send(request.result());
+            }
+        })
+    };
+    let onerror = {
+        let request = request.clone();
+        Closure::once(move || {
+            if let Some(tx) = tx.borrow_mut().take() {
+                let error = request.error().ok().flatten().map(JsValue::from).unwrap_or(JsValue::NULL);
+                let _ = tx.send(Err(error));
+            }
+        })
+    };
+    request.set_onsuccess(Some(onsuccess.as_ref().unchecked_ref()));
+    request.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+    let result = rx.await.unwrap_or_else(|_| Err(JsValue::NULL));
+    request.set_onsuccess(None);
+    request.set_onerror(None);
+    result
+}
+
+/// Steps a cursor request through its records, handing each to `visit` until it returns
+/// false or the records run out.
+async fn walk(request: IdbRequest, mut visit: impl FnMut(&IdbCursorWithValue) -> bool + 'static) -> Result<(), JsValue> {
+    let (tx, rx) = oneshot::channel();
+    let tx = Rc::new(RefCell::new(Some(tx)));
+    let finish = {
+        let tx = tx.clone();
+        move |result: Result<(), JsValue>| {
+            if let Some(tx) = tx.borrow_mut().take() {
+                let _ = tx.send(result);
+            }
+        }
+    };
+    let onsuccess = {
+        let request = request.clone();
+        let finish = finish.clone();
+        Closure::wrap(Box::new(move || {
+            let cursor = request
+                .result()
+                .ok()
+                .and_then(|result| result.dyn_into::<IdbCursorWithValue>().ok());
+            match cursor {
+                Some(cursor) if visit(&cursor) => {
+                    if let Err(e) = cursor.continue_() {
+                        finish(Err(e));
+                    }
+                }
+                _ => finish(Ok(())),
+            }
+        }) as Box<dyn FnMut()>)
+    };
+    let onerror = {
+        let request = request.clone();
+        Closure::wrap(Box::new(move || {
+            let error = request.error().ok().flatten().map(JsValue::from).unwrap_or(JsValue::NULL);
+            finish(Err(error));
+        }) as Box<dyn FnMut()>)
+    };
+    request.set_onsuccess(Some(onsuccess.as_ref().unchecked_ref()));
+    request.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+    let result = rx.await.unwrap_or_else(|_| Err(JsValue::NULL));
+    request.set_onsuccess(None);
+    request.set_onerror(None);
+    result
+}
+
+/// Every message this browser has seen, in IndexedDB, for history beyond what fits in
+/// localStorage.
+///
+/// The localStorage cache still fills the screen on startup; this is slower to open but
+/// holds far more.
+#[derive(Clone)]
+pub struct MessageArchive {
+    db: IdbDatabase,
+}
+
+impl MessageArchive {
+    pub async fn open(username: &str) -> Result<Self, JsValue> {
+        let factory = web_sys::window()
+            .ok_or(JsValue::NULL)?
+            .indexed_db()?
+            .ok_or_else(|| JsValue::from_str("IndexedDB is unavailable"))?;
+        let request: IdbOpenDbRequest = factory.open_with_u32(&format!("yewchat.archive.{}", username), DB_VERSION)?;
+        let onupgradeneeded = {
+            let request = request.clone();
+            Closure::once(move || {
+                if let Err(e) = create_schema(&request) {
+                    log::error!("failed to set up the message archive: {:?}", e);
+                }
+            })
+        };
+        request.set_onupgradeneeded(Some(onupgradeneeded.as_ref().unchecked_ref()));
+        let db = request_done(&request).await?;
+        request.set_onupgradeneeded(None);
+        Ok(Self { db: db.dyn_into()? })
+    }
+
+    fn store(&self, mode: IdbTransactionMode) -> Result<IdbObjectStore, JsValue> {
+        self.db.transaction_with_str_and_mode(STORE, mode)?.object_store(STORE)
+    }
+
+    /// Stores `messages` in the background, skipping those [`Record::of`] leaves out.
+    pub fn archive(&self, messages: &[MessageData]) {
+        let store = match self.store(IdbTransactionMode::Readwrite) {
+            Ok(store) => store,
+            Err(e) => {
+                log::error!("failed to open the message archive: {:?}", e);
+                return;
+            }
+        };
+        for record in messages.iter().filter_map(Record::of) {
+            if let Err(e) = record.to_js().and_then(|value| store.put(&value)) {
+                log::error!("failed to archive message {}: {:?}", record.id, e);
+            }
+        }
+    }
+
+    /// Stores new versions of messages archived before. Records are keyed by channel,
+    /// timestamp and id, all of which an edit keeps, so this replaces them in place.
+    pub fn rewrite(&self, messages: &[MessageData]) {
+        self.archive(messages);
+    }
+
+    /// The newest `n` messages of `channel`, oldest first.
+    pub async fn recent(&self, channel: &str, n: usize) -> Result<Vec<MessageData>, JsValue> {
+        let range = channel_range(channel, f64::NEG_INFINITY, f64::INFINITY)?;
+        let request = self
+            .store(IdbTransactionMode::Readonly)?
+            .open_cursor_with_range_and_direction(&range, IdbCursorDirection::Prev)?;
+        let mut found = collect(request, n, |_| true).await?;
+        found.reverse();
+        Ok(found)
+    }
+
+    /// Messages of `channel` sent between `from` and `to` (ms since the epoch, inclusive), oldest first.
+    pub async fn range(&self, channel: &str, from: f64, to: f64) -> Result<Vec<MessageData>, JsValue> {
+        let range = channel_range(channel, from, to)?;
+        let request = self.store(IdbTransactionMode::Readonly)?.open_cursor_with_range(&range)?;
+        collect(request, usize::MAX, |_| true).await
+    }
+
+    /// Up to `limit` messages in any channel for which `keep(channel, message)` holds, newest first.
+    pub async fn search(
+        &self,
+        keep: impl Fn(&str, &MessageData) -> bool + 'static,
+        limit: usize,
+    ) -> Result<Vec<MessageData>, JsValue> {
+        let request = self
+            .store(IdbTransactionMode::Readonly)?
+            .index(BY_TIME)?
+            .open_cursor_with_range_and_direction(&JsValue::UNDEFINED, IdbCursorDirection::Prev)?;
+        collect(request, limit, move |record| keep(&record.channel, &record.data)).await
+    }
+
+    /// Deletes everything sent before `cutoff` (ms since the epoch) and returns how many went.
+    pub async fn prune(&self, cutoff: f64) -> Result<u32, JsValue> {
+        let range = IdbKeyRange::upper_bound_with_open(&cutoff.into(), true)?;
+        let request = self
+            .store(IdbTransactionMode::Readwrite)?
+            .index(BY_TIME)?
+            .open_cursor_with_range(&range)?;
+        let deleted = Rc::new(RefCell::new(0));
+        let counter = deleted.clone();
+        walk(request, move |cursor| {
+            if cursor.delete().is_ok() {
+                *counter.borrow_mut() += 1;
+            }
+            true
+        })
+        .await?;
+        let deleted = *deleted.borrow();
+        Ok(deleted)
+    }
+
+    /// Saves `transcript` in the background, over any earlier copy of the same day.
+    pub fn save_transcript(&self, transcript: &Transcript) {
+        let value = JSON::parse(&serde_json::to_string(transcript).unwrap());
+        let stored = self
+            .db
+            .transaction_with_str_and_mode(TRANSCRIPTS, IdbTransactionMode::Readwrite)
+            .and_then(|tx| tx.object_store(TRANSCRIPTS))
+            .and_then(|store| store.put(&value?));
+        if let Err(e) = stored {
+            log::error!("failed to save the transcript for {}: {:?}", transcript.day, e);
+        }
+    }
+
+    /// The transcript kept for `day` (`YYYY-MM-DD`), if any.
+    pub async fn transcript(&self, day: &str) -> Result<Option<Transcript>, JsValue> {
+        let request = self
+            .db
+            .transaction_with_str(TRANSCRIPTS)?
+            .object_store(TRANSCRIPTS)?
+            .get(&day.into())?;
+        let value = request_done(&request).await?;
+        if value.is_undefined() {
+            return Ok(None);
+        }
+        let json: String = JSON::stringify(&value)?.into();
+        Ok(serde_json::from_str(&json).ok())
+    }
+
+    /// Deletes every archived message and transcript.
+    pub async fn clear(&self) -> Result<(), JsValue> {
+        let request = self.store(IdbTransactionMode::Readwrite)?.clear()?;
+        request_done(&request).await?;
+        let request = self
+            .db
+            .transaction_with_str_and_mode(TRANSCRIPTS, IdbTransactionMode::Readwrite)?
+            .object_store(TRANSCRIPTS)?
+            .clear()?;
+        request_done(&request).await.map(|_| ())
+    }
+}
+
+/// Creates whichever stores the database doesn't have yet, so older versions upgrade in place.
+fn create_schema(request: &IdbOpenDbRequest) -> Result<(), JsValue> {
+    let db: IdbDatabase = request.result()?.dyn_into()?;
+    let existing = db.object_store_names();
+    if !existing.contains(STORE) {
+        let key_path = Array::of3(&"channel".into(), &"ts".into(), &"id".into());
+        let store = db.create_object_store_with_optional_parameters(
+            STORE,
+            IdbObjectStoreParameters::new().key_path(Some(&key_path)),
+        )?;
+        store.create_index_with_str(BY_SENDER, "from")?;
+        store.create_index_with_str(BY_TIME, "ts")?;
+    }
+    if !existing.contains(TRANSCRIPTS) {
+        db.create_object_store_with_optional_parameters(
+            TRANSCRIPTS,
+            IdbObjectStoreParameters::new().key_path(Some(&"day".into())),
+        )?;
+    }
+    Ok(())
+}
+
+/// Keys of `channel` between two timestamps, inclusive.
+fn channel_range(channel: &str, from: f64, to: f64) -> Result<IdbKeyRange, JsValue> {
+    // Arrays compare element by element and a shorter array sorts first, so `[channel, to, []]`
+    // is above every `[channel, to, id]`: arrays sort after strings.
+    let lower = Array::of2(&channel.into(), &from.into());
+    let upper = Array::of3(&channel.into(), &to.into(), &Array::new());
+    IdbKeyRange::bound(&lower, &upper)
+}
+
+/// Decodes the records a cursor walks over, keeping up to `limit` that pass `keep`.
+async fn collect(
+    request: IdbRequest,
+    limit: usize,
+    keep: impl Fn(&Record) -> bool + 'static,
+) -> Result<Vec<MessageData>, JsValue> {
+    if limit == 0 {
+        return Ok(vec![]);
+    }
+    let found = Rc::new(RefCell::new(Vec::new()));
+    let sink = found.clone();
+    walk(request, move |cursor| {
+        let record = cursor.value().ok().as_ref().and_then(Record::from_js);
+        gather(&mut sink.borrow_mut(), record, limit, &keep)
+    })
+    .await?;
+    let found = found.take();
+    Ok(found)
+}
+
+/// Adds the message of the record a cursor is on to `found` if it passes `keep`, and says
+/// whether to go on: until `limit` are found. Records that don't decode are stepped over.
+fn gather(found: &mut Vec<MessageData>, record: Option<Record>, limit: usize, keep: impl Fn(&Record) -> bool) -> bool {
+    if let Some(record) = record.filter(|record| keep(record)) {
+        let mut message = record.data;
+        normalize_reactions(&mut message);
+        found.push(message);
+    }
+    found.len() < limit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: &str, timestamp: Option<f64>, channel: Option<&str>) -> MessageData {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "from": "ann",
+            "message": "hi",
+            "reactions": null,
+            "timestamp": timestamp,
+            "channel": channel,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn records_are_keyed_by_channel_time_and_id() {
+        let record = Record::of(&message("a", Some(5.0), Some("random"))).unwrap();
+        assert_eq!((record.channel.as_str(), record.ts, record.id.as_str()), ("random", 5.0, "a"));
+        assert_eq!(record.from, "ann");
+        assert_eq!(Record::of(&message("a", Some(5.0), None)).unwrap().channel, DEFAULT_CHANNEL);
+    }
+
+    #[test]
+    fn unplaceable_messages_are_not_archived() {
+        assert!(Record::of(&message("local-1", Some(5.0), None)).is_none());
+        assert!(Record::of(&message("a", None, None)).is_none());
+    }
+
+    /// What a cursor over `records` in order would collect.
+    fn gather_all(records: Vec<Option<Record>>, limit: usize, keep: impl Fn(&Record) -> bool) -> Vec<String> {
+        let mut found = vec![];
+        for record in records {
+            if !gather(&mut found, record, limit, &keep) {
+                break;
+            }
+        }
+        found.into_iter().map(|m| m.id).collect()
+    }
+
+    fn records(ids: &[&str]) -> Vec<Option<Record>> {
+        ids.iter().enumerate().map(|(i, id)| Record::of(&message(id, Some(i as f64), None))).collect()
+    }
+
+    #[test]
+    fn a_query_stops_at_its_limit() {
+        assert_eq!(gather_all(records(&["a", "b", "c"]), 2, |_| true), ["a", "b"]);
+        assert_eq!(gather_all(records(&["a", "b"]), usize::MAX, |_| true), ["a", "b"]);
+    }
+
+    #[test]
+    fn only_matches_count_towards_the_limit() {
+        let found = gather_all(records(&["a", "b", "c", "d"]), 2, |record| record.id != "a" && record.id != "c");
+        assert_eq!(found, ["b", "d"]);
+    }
+
+    #[test]
+    fn undecodable_records_are_stepped_over() {
+        let mut walked = records(&["a", "b"]);
+        walked.insert(1, None);
+        assert_eq!(gather_all(walked, 5, |_| true), ["a", "b"]);
+    }
+
+    #[test]
+    fn archived_reactions_come_back_merged() {
+        let mut m = message("a", Some(1.0), None);
+        m.reactions = Some(vec![("👍".into(), vec!["ann".into()]), ("👍".into(), vec!["ann".into(), "bob".into()])]);
+        let mut found = vec![];
+        gather(&mut found, Record::of(&m), 5, |_| true);
+        assert_eq!(found[0].reactions, Some(vec![("👍".to_string(), vec!["ann".to_string(), "bob".to_string()])]));
+    }
+
+    #[test]
+    fn an_edit_keeps_the_record_key() {
+        let original = message("a", Some(5.0), Some("random"));
+        let mut edited = original.clone();
+        edited.message = "edited".into();
+        let (before, after) = (Record::of(&original).unwrap(), Record::of(&edited).unwrap());
+        assert_eq!((before.channel, before.ts, before.id), (after.channel, after.ts, after.id));
+        assert_eq!(after.data.message, "edited");
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::chat::MessageData;
+use crate::services::storage::{Local, Store};
+
+/// Bump whenever the stored shape changes; older caches are thrown away rather than migrated.
+const CACHE_VERSION: u32 = 1;
+
+/// Messages kept per channel, until the browser's quota says otherwise.
+pub const MAX_CACHED_MESSAGES: usize = 200;
+
+#[derive(Serialize)]
+struct StoredRef<'a> {
+    version: u32,
+    channels: HashMap<&'a str, Vec<&'a MessageData>>,
+}
+
+#[derive(Deserialize)]
+struct Stored {
+    version: u32,
+    channels: HashMap<String, Vec<MessageData>>,
+}
+
+/// Reads a stored cache, marking every message as cached. `None` if it doesn't parse or
+/// was written by another version.
+pub fn decode(json: &str) -> Option<HashMap<String, Vec<MessageData>>> {
+    unpack(serde_json::from_str(json).ok()?)
+}
+
+fn unpack(stored: Stored) -> Option<HashMap<String, Vec<MessageData>>> {
+    if stored.version != CACHE_VERSION {
+        return None;
+    }
+    let mut channels = stored.channels;
+    for message in channels.values_mut().flatten() {
+        message.cached = true;
+        normalize_reactions(message);
+    }
+    Some(channels)
+}
+
+/// Puts reactions read back from storage into the shape `apply_reaction` keeps them in:
+/// one entry per emoji, each user in it once, no empty entries. A hand-edited or
+/// half-written record would otherwise show doubled counts or let a reaction be taken
+/// back twice.
+pub fn normalize_reactions(message: &mut MessageData) {
+    let reactions = match message.reactions.take() {
+        Some(reactions) => reactions,
+        None => return,
+    };
+    let mut merged: Vec<(String, Vec<String>)> = vec![];
+    for (emoji, users) in reactions {
+        let index = match merged.iter().position(|(e, _)| *e == emoji) {
+            Some(index) => index,
+            None => {
+                merged.push((emoji, vec![]));
+                merged.len() - 1
+            }
+        };
+        let entry = &mut merged[index].1;
+        for user in users {
+            if !entry.contains(&user) {
+                entry.push(user);
+            }
+        }
+    }
+    merged.retain(|(_, users)| !users.is_empty());
+    message.reactions = (!merged.is_empty()).then_some(merged);
+}
+
+/// The newest `retained` messages of each channel, as stored. Messages with local ids are
+/// left out: nothing the server sends later could ever replace them. Ephemeral ones too,
+/// since they aren't meant to outlive their countdown anywhere.
+pub fn encode<'a>(channels: impl IntoIterator<Item = (&'a str, &'a [MessageData])>, retained: usize) -> String {
+    serde_json::to_string(&pack(channels, retained)).unwrap()
+}
+
+fn pack<'a>(channels: impl IntoIterator<Item = (&'a str, &'a [MessageData])>, retained: usize) -> StoredRef<'a> {
+    let channels = channels
+        .into_iter()
+        .map(|(name, messages)| {
+            let mut kept: Vec<&MessageData> = messages
+                .iter()
+                .rev()
+                .filter(|m| !m.id.starts_with("local-") && m.expires_at.is_none())
+                .take(retained)
+                .collect();
+            kept.reverse();
+            (name, kept)
+        })
+        .filter(|(_, messages)| !messages.is_empty())
+        .collect();
+    StoredRef {
+        version: CACHE_VERSION,
+        channels,
+    }
+}
+
+/// Squares a channel's cached messages with the first page of live history, before it is merged.
+///
+/// Cached copies of messages in `batch` are dropped so the server's version takes their place.
+/// Cached messages missing from `batch` are dropped too unless they are older than anything
+/// in it: those the server may still have, and paging back will tell. Without a timestamp
+/// there is no telling, so they go. When `has_more` is false the batch is everything and
+/// no cached message outlives it.
+pub fn reconcile(messages: &mut Vec<MessageData>, batch: &[MessageData], has_more: bool) {
+    let returned: HashSet<&str> = batch.iter().map(|m| m.id.as_str()).collect();
+    let covered_from = if has_more {
+        batch.iter().filter_map(|m| m.timestamp).fold(f64::INFINITY, f64::min)
+    } else {
+        f64::NEG_INFINITY
+    };
+    messages.retain(|m| {
+        !m.cached
+            || (!returned.contains(m.id.as_str()) && matches!(m.timestamp, Some(t) if t < covered_from))
+    });
+}
+
+/// Recent messages per channel in localStorage, so a reload has something to show before
+/// history arrives.
+pub struct MessageCache<S: Store = Local> {
+    store: S,
+    key: String,
+    /// Messages kept per channel; halved each time the browser runs out of room.
+    retained: usize,
+}
+
+impl MessageCache {
+    pub fn new(username: &str) -> Self {
+        Self::new_in(Local, username)
+    }
+}
+
+impl<S: Store> MessageCache<S> {
+    pub fn new_in(store: S, username: &str) -> Self {
+        Self {
+            store,
+            key: format!("yewchat.message_cache.{}", username),
+            retained: MAX_CACHED_MESSAGES,
+        }
+    }
+
+    pub fn load(&self) -> HashMap<String, Vec<MessageData>> {
+        self.store.get(&self.key).and_then(unpack).unwrap_or_default()
+    }
+
+    /// Stores the newest messages of each channel. A full quota halves how many are kept,
+    /// down to none at all, rather than failing.
+    pub fn save<'a>(&mut self, channels: impl IntoIterator<Item = (&'a str, &'a [MessageData])> + Clone) {
+        while self.retained > 0 {
+            match self.store.set(&self.key, &pack(channels.clone(), self.retained)) {
+                Ok(()) => return,
+                Err(e) => {
+                    log::error!("failed to cache {} messages per channel: {:?}", self.retained, e);
+                    self.retained /= 2;
+                }
+            }
+        }
+        self.store.delete(&self.key);
+    }
+
+    /// Caches under `username` from now on, after a rename. The old entry goes; save again
+    /// to write the new one.
+    pub fn rename(&mut self, username: &str) {
+        self.store.delete(&self.key);
+        self.key = format!("yewchat.message_cache.{}", username);
+    }
+
+    pub fn clear(&self) {
+        self.store.delete(&self.key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: &str, timestamp: Option<f64>) -> MessageData {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "from": "ann",
+            "message": "",
+            "reactions": null,
+            "timestamp": timestamp,
+        }))
+        .unwrap()
+    }
+
+    fn cached(id: &str, timestamp: Option<f64>) -> MessageData {
+        MessageData { cached: true, ..message(id, timestamp) }
+    }
+
+    fn ids(messages: &[MessageData]) -> Vec<&str> {
+        messages.iter().map(|m| m.id.as_str()).collect()
+    }
+
+    #[test]
+    fn round_trip_keeps_the_newest_server_messages() {
+        let mut ephemeral = message("e", Some(5.0));
+        ephemeral.expires_at = Some(10.0);
+        let general = vec![
+            message("a", Some(1.0)),
+            message("b", Some(2.0)),
+            message("local-1", None),
+            message("c", Some(3.0)),
+            ephemeral,
+        ];
+        let empty = vec![message("local-2", None)];
+        let json = encode([("general", general.as_slice()), ("random", empty.as_slice())], 2);
+
+        let decoded = decode(&json).unwrap();
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(ids(&decoded["general"]), ["b", "c"]);
+        assert!(decoded["general"].iter().all(|m| m.cached));
+    }
+
+    #[test]
+    fn other_versions_and_garbage_are_ignored() {
+        assert!(decode(r#"{"version":0,"channels":{}}"#).is_none());
+        assert!(decode("not json").is_none());
+        assert!(decode(&format!(r#"{{"version":{},"channels":{{}}}}"#, CACHE_VERSION)).is_some());
+    }
+
+    #[test]
+    fn reconcile_keeps_only_what_the_server_may_still_page_back_to() {
+        let mut messages = vec![
+            cached("old", Some(1.0)),
+            cached("undated", None),
+            cached("returned", Some(5.0)),
+            cached("gone", Some(6.0)),
+            message("live", Some(9.0)),
+        ];
+        let batch = vec![message("returned", Some(5.0)), message("new", Some(7.0))];
+        reconcile(&mut messages, &batch, true);
+        assert_eq!(ids(&messages), ["old", "live"]);
+
+        let mut messages = vec![cached("old", Some(1.0)), message("live", Some(9.0))];
+        reconcile(&mut messages, &batch, false);
+        assert_eq!(ids(&messages), ["live"]);
+    }
+
+    fn reactions(message: &MessageData) -> Vec<(&str, Vec<&str>)> {
+        message
+            .reactions
+            .iter()
+            .flatten()
+            .map(|(emoji, users)| (emoji.as_str(), users.iter().map(String::as_str).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn stored_reactions_are_merged_and_deduplicated() {
+        let mut m = message("a", Some(1.0));
+        m.reactions = Some(vec![
+            ("👍".into(), vec!["ann".into(), "bob".into()]),
+            ("🎉".into(), vec![]),
+            ("👍".into(), vec!["bob".into(), "cat".into()]),
+        ]);
+        normalize_reactions(&mut m);
+        assert_eq!(reactions(&m), [("👍", vec!["ann", "bob", "cat"])]);
+
+        m.reactions = Some(vec![("🎉".into(), vec![])]);
+        normalize_reactions(&mut m);
+        assert_eq!(m.reactions, None);
+    }
+
+    #[test]
+    fn reactions_and_edits_survive_a_reload() {
+        let mut m = message("a", Some(1.0));
+        m.reactions = Some(vec![("👍".into(), vec!["ann".into(), "ann".into()])]);
+        m.message = "edited".into();
+        m.edited = true;
+        let decoded = decode(&encode([("general", std::slice::from_ref(&m))], 10)).unwrap();
+        let restored = &decoded["general"][0];
+        assert_eq!(reactions(restored), [("👍", vec!["ann"])]);
+        assert_eq!(restored.message, "edited");
+        assert!(restored.edited);
+    }
+}
@@ -0,0 +1,57 @@
+/// A link straight to one message, e.g. `https://host/?room=general&message=42`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MessageLink {
+    pub room: String,
+    pub message: String,
+}
+
+fn encode(text: &str) -> String {
+    js_sys::encode_uri_component(text).into()
+}
+
+fn decode(text: &str) -> Option<String> {
+    js_sys::decode_uri_component(&text.replace('+', " ")).ok().map(String::from)
+}
+
+fn param(params: &str, key: &str) -> Option<String> {
+    params
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == key)
+        .and_then(|(_, value)| decode(value))
+        .filter(|value| !value.is_empty())
+}
+
+impl MessageLink {
+    /// The link under `origin`, with the parameters in the query string, or in the fragment
+    /// when `use_history_api` is off so the server never sees them.
+    pub fn to_url(&self, origin: &str, use_history_api: bool) -> String {
+        format!(
+            "{}/{}room={}&message={}",
+            origin,
+            if use_history_api { "?" } else { "#" },
+            encode(&self.room),
+            encode(&self.message)
+        )
+    }
+
+    /// The link in a page's query string (`?…`) or, failing that, its fragment (`#…`).
+    pub fn parse(search: &str, hash: &str) -> Option<Self> {
+        [search.trim_start_matches('?'), hash.trim_start_matches('#')]
+            .iter()
+            .find_map(|params| {
+                Some(Self {
+                    room: param(params, "room")?,
+                    message: param(params, "message")?,
+                })
+            })
+    }
+
+    /// The query opening the chat route on this message.
+    pub fn chat_query(&self) -> Vec<(String, String)> {
+        vec![
+            ("channel".to_string(), self.room.clone()),
+            ("message".to_string(), self.message.clone()),
+        ]
+    }
+}
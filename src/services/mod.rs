@@ -1,2 +1,49 @@
 pub mod websocket;
-pub mod event_bus;
\ No newline at end of file
+pub mod event_bus;
+pub mod channel_store;
+pub mod settings;
+pub mod download;
+pub mod recorder;
+pub mod file_chunks;
+pub mod notifications;
+pub mod typing;
+pub mod geolocation;
+pub mod sound;
+pub mod title;
+pub mod favicon;
+pub mod dnd;
+pub mod toasts;
+pub mod browser_notifications;
+pub mod welcome;
+pub mod keywords;
+pub mod feature_flags;
+pub mod scroll_sentinel;
+pub mod presence_service;
+pub mod message_cache;
+pub mod message_archive;
+pub mod chat_export;
+pub mod thumbnail;
+pub mod search;
+pub mod connection;
+pub mod session_stats;
+pub mod transcript;
+pub mod scroll_memory;
+pub mod drafts;
+pub mod username;
+pub mod last_username;
+pub mod clipboard;
+pub mod message_link;
+pub mod audio_service;
+pub mod theme;
+pub mod display;
+pub mod i18n;
+pub mod shortcuts;
+pub mod room_name;
+pub mod viewport;
+pub mod pwa;
+pub mod a11y;
+pub mod playback;
+pub mod skin_tone;
+pub mod avatar;
+pub mod uri_component;
+pub mod storage;
@@ -0,0 +1,398 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::t;
+
+/// How loudly a channel, or the app as a whole, may get the user's attention.
+///
+/// Ordered from most to least permissive, so the stricter of two is their `max`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RoomPreference {
+    #[default]
+    All,
+    MentionsOnly,
+    Muted,
+}
+
+impl RoomPreference {
+    pub fn next(self) -> Self {
+        match self {
+            RoomPreference::All => RoomPreference::MentionsOnly,
+            RoomPreference::MentionsOnly => RoomPreference::Muted,
+            RoomPreference::Muted => RoomPreference::All,
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            RoomPreference::All => "🔔",
+            RoomPreference::MentionsOnly => "@",
+            RoomPreference::Muted => "🔕",
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            RoomPreference::All => t!("notify_pref.all"),
+            RoomPreference::MentionsOnly => t!("notify_pref.mentions_only"),
+            RoomPreference::Muted => t!("notify_pref.muted"),
+        }
+    }
+}
+
+/// Where the user's attention is relative to the incoming message.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Visibility {
+    /// The tab is visible and the message's channel is open.
+    Viewing,
+    /// The tab is visible but another channel is open.
+    Background,
+    /// The tab is hidden.
+    Hidden,
+}
+
+/// What the notification pipeline is allowed to do with a message.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum NotifyLevel {
+    /// Nothing beyond a subtle activity dot.
+    Silent,
+    /// Count it as unread, but don't interrupt.
+    Badge,
+    /// Count it and let every enabled output (notification, sound, title) fire.
+    Alert,
+}
+
+/// Per-sender notification mute. Their messages still show inline.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub struct UserMute {
+    /// Still notify when the muted user mentions me.
+    pub allow_mentions: bool,
+}
+
+/// What [`should_notify`] decided for a message.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Decision {
+    pub level: NotifyLevel,
+    /// Buzz the phone, still subject to [`should_cue`] like the sound.
+    pub vibrate: bool,
+}
+
+impl Decision {
+    pub const SILENT: Decision = Decision { level: NotifyLevel::Silent, vibrate: false };
+}
+
+/// Archived channels stay silent except for mentions, which are judged by `pref` as usual.
+/// `global` and `pref` combine so that the stricter of the two wins. A muted `sender`
+/// silences the message outright unless it is a mention they are allowed through with.
+///
+/// A buzz is kept for alerting mentions and DMs while the tab is hidden, with the phone
+/// likely in a pocket, and only with `vibrate_on_mention` turned on.
+pub fn should_notify(
+    global: RoomPreference,
+    pref: RoomPreference,
+    sender: Option<UserMute>,
+    archived: bool,
+    is_mention: bool,
+    visibility: Visibility,
+    vibrate_on_mention: bool,
+) -> Decision {
+    if visibility == Visibility::Viewing || (archived && !is_mention) {
+        return Decision::SILENT;
+    }
+    if sender.map_or(false, |mute| !(mute.allow_mentions && is_mention)) {
+        return Decision::SILENT;
+    }
+    let level = match global.max(pref) {
+        RoomPreference::All => NotifyLevel::Alert,
+        RoomPreference::MentionsOnly if is_mention => NotifyLevel::Alert,
+        RoomPreference::MentionsOnly => NotifyLevel::Badge,
+        RoomPreference::Muted => NotifyLevel::Silent,
+    };
+    Decision {
+        level,
+        vibrate: vibrate_on_mention && is_mention && visibility == Visibility::Hidden && level == NotifyLevel::Alert,
+    }
+}
+
+/// How long after the composer was last touched incoming messages stay quiet.
+pub const COMPOSING_QUIET_MS: f64 = 2_000.0;
+
+/// Whether an incoming message deserves a cue that is `enabled`: the pop sound, or the buzz
+/// [`should_notify`] asked for.
+///
+/// `background_level` is what `should_notify` decides for the message as if its channel
+/// weren't open: the open channel still cues, unless the user is busy typing into it.
+pub fn should_cue(enabled: bool, own: bool, since_composing_ms: f64, background_level: NotifyLevel) -> bool {
+    enabled && !own && since_composing_ms >= COMPOSING_QUIET_MS && background_level == NotifyLevel::Alert
+}
+
+/// Vibrates with `pattern` (ms on, off, on, …) where the Vibration API exists; a no-op elsewhere.
+pub fn vibrate(pattern: &[u32]) {
+    let navigator = match web_sys::window() {
+        Some(window) => window.navigator(),
+        None => return,
+    };
+    if !js_sys::Reflect::has(&navigator, &"vibrate".into()).unwrap_or(false) {
+        return;
+    }
+    let pattern: js_sys::Array = pattern.iter().map(|&ms| wasm_bindgen::JsValue::from(ms)).collect();
+    navigator.vibrate_with_pattern(&pattern);
+}
+
+/// Whether a message pulls an archived channel back into the main rail until it is viewed.
+pub fn surfaces_archived(archived: bool, is_mention: bool, level: NotifyLevel) -> bool {
+    archived && is_mention && level != NotifyLevel::Silent
+}
+
+/// Whether `text` mentions `@username` as a whole word.
+pub fn is_mention(text: &str, username: &str) -> bool {
+    let needle = format!("@{}", username.to_lowercase());
+    let text = text.to_lowercase();
+    text.match_indices(&needle).any(|(at, _)| {
+        text[at + needle.len()..]
+            .chars()
+            .next()
+            .map_or(true, |c| !(c.is_alphanumeric() || c == '_' || c == '-'))
+    })
+}
+
+/// Byte ranges of the `@name` tokens in `text`, `@` included. An `@` in the middle of a
+/// word, as in an email address, doesn't start one.
+pub fn mention_spans(text: &str) -> Vec<Range<usize>> {
+    let is_name_char = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+    let mut spans = vec![];
+    let mut prev: Option<char> = None;
+    for (at, c) in text.char_indices() {
+        let starts_word = !matches!(prev, Some(p) if is_name_char(p));
+        prev = Some(c);
+        if c != '@' || !starts_word {
+            continue;
+        }
+        let name_len: usize = text[at + 1..].chars().take_while(|&c| is_name_char(c)).map(char::len_utf8).sum();
+        if name_len > 0 {
+            spans.push(at..at + 1 + name_len);
+        }
+    }
+    spans
+}
+
+/// Per-channel preferences of one user. Persisted as part of their
+/// [`UserSettings`](crate::services::settings::UserSettings).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct RoomPreferences {
+    muted_users: HashMap<String, UserMute>,
+    #[serde(rename = "mode")]
+    global: RoomPreference,
+    #[serde(rename = "channels")]
+    prefs: HashMap<String, RoomPreference>,
+    archived: HashSet<String>,
+}
+
+impl RoomPreferences {
+    /// Reads the localStorage keys these preferences were kept under before they moved
+    /// into the settings, through `read`. Missing or unreadable keys fall back to defaults.
+    pub fn from_legacy(username: &str, read: impl Fn(&str) -> Option<serde_json::Value>) -> Self {
+        let keys = Self::legacy_keys(username);
+        let mut legacy = serde_json::Map::new();
+        for (field, key) in ["muted_users", "mode", "channels", "archived"].iter().zip(&keys) {
+            if let Some(value) = read(key) {
+                legacy.insert(field.to_string(), value);
+            }
+        }
+        serde_json::from_value(legacy.into()).unwrap_or_default()
+    }
+
+    /// The keys [`RoomPreferences::from_legacy`] reads, which can go once migrated.
+    pub fn legacy_keys(username: &str) -> [String; 4] {
+        ["muted_users", "notify_mode", "room_prefs", "archived"].map(|name| format!("yewchat.{}.{}", name, username))
+    }
+
+    pub fn user_mute(&self, user: &str) -> Option<UserMute> {
+        self.muted_users.get(user).copied()
+    }
+
+    /// Muted users, sorted by name.
+    pub fn muted_users(&self) -> Vec<(String, UserMute)> {
+        let mut users: Vec<_> = self.muted_users.iter().map(|(u, m)| (u.clone(), *m)).collect();
+        users.sort_by(|a, b| a.0.cmp(&b.0));
+        users
+    }
+
+    /// `None` unmutes.
+    pub fn set_user_mute(&mut self, user: &str, mute: Option<UserMute>) {
+        match mute {
+            Some(mute) => self.muted_users.insert(user.to_string(), mute),
+            None => self.muted_users.remove(user),
+        };
+    }
+
+    /// The app-wide mode, applied on top of every channel's own preference.
+    pub fn global(&self) -> RoomPreference {
+        self.global
+    }
+
+    pub fn set_global(&mut self, mode: RoomPreference) {
+        self.global = mode;
+    }
+
+    pub fn is_archived(&self, channel: &str) -> bool {
+        self.archived.contains(channel)
+    }
+
+    pub fn set_archived(&mut self, channel: &str, archived: bool) {
+        if archived {
+            self.archived.insert(channel.to_string());
+        } else {
+            self.archived.remove(channel);
+        }
+    }
+
+    pub fn get(&self, channel: &str) -> RoomPreference {
+        self.prefs.get(channel).copied().unwrap_or_default()
+    }
+
+    pub fn set(&mut self, channel: &str, pref: RoomPreference) {
+        self.prefs.insert(channel.to_string(), pref);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use RoomPreference::*;
+
+    fn level(global: RoomPreference, pref: RoomPreference, visibility: Visibility) -> NotifyLevel {
+        should_notify(global, pref, None, false, false, visibility, false).level
+    }
+
+    #[test]
+    fn the_open_channel_never_notifies() {
+        assert_eq!(level(All, All, Visibility::Viewing), NotifyLevel::Silent);
+        assert_eq!(level(All, All, Visibility::Background), NotifyLevel::Alert);
+        assert_eq!(level(All, All, Visibility::Hidden), NotifyLevel::Alert);
+    }
+
+    #[test]
+    fn the_stricter_of_global_and_channel_wins() {
+        assert_eq!(level(All, Muted, Visibility::Hidden), NotifyLevel::Silent);
+        assert_eq!(level(Muted, All, Visibility::Hidden), NotifyLevel::Silent);
+        assert_eq!(level(MentionsOnly, All, Visibility::Hidden), NotifyLevel::Badge);
+        assert_eq!(level(All, MentionsOnly, Visibility::Hidden), NotifyLevel::Badge);
+    }
+
+    #[test]
+    fn cues_are_held_back_while_typing_and_for_own_messages() {
+        let quiet = COMPOSING_QUIET_MS;
+        assert!(should_cue(true, false, quiet, NotifyLevel::Alert));
+        assert!(!should_cue(false, false, quiet, NotifyLevel::Alert));
+        assert!(!should_cue(true, true, quiet, NotifyLevel::Alert));
+        assert!(!should_cue(true, false, quiet - 1.0, NotifyLevel::Alert));
+        assert!(!should_cue(true, false, quiet, NotifyLevel::Badge));
+    }
+
+    #[test]
+    fn only_alerting_mentions_in_a_hidden_tab_vibrate() {
+        let cases = [
+            // (enabled, pref, mute, archived, mention, visibility, vibrates)
+            (true, All, None, false, true, Visibility::Hidden, true),
+            (false, All, None, false, true, Visibility::Hidden, false),
+            (true, All, None, false, false, Visibility::Hidden, false),
+            (true, All, None, false, true, Visibility::Background, false),
+            (true, All, None, false, true, Visibility::Viewing, false),
+            (true, MentionsOnly, None, false, true, Visibility::Hidden, true),
+            (true, Muted, None, false, true, Visibility::Hidden, false),
+            (true, All, None, true, true, Visibility::Hidden, true),
+            (true, All, Some(UserMute { allow_mentions: false }), false, true, Visibility::Hidden, false),
+            (true, All, Some(UserMute { allow_mentions: true }), false, true, Visibility::Hidden, true),
+        ];
+        for (enabled, pref, mute, archived, mention, visibility, vibrates) in cases {
+            let decision = should_notify(All, pref, mute, archived, mention, visibility, enabled);
+            assert_eq!(decision.vibrate, vibrates, "{:?}", (enabled, pref, mute, archived, mention, visibility));
+        }
+    }
+
+    #[test]
+    fn archived_channels_only_speak_up_for_mentions() {
+        let archived = |is_mention| should_notify(All, All, None, true, is_mention, Visibility::Hidden, false).level;
+        assert_eq!(archived(false), NotifyLevel::Silent);
+        assert_eq!(archived(true), NotifyLevel::Alert);
+        assert!(surfaces_archived(true, true, NotifyLevel::Badge));
+        assert!(!surfaces_archived(true, true, NotifyLevel::Silent));
+        assert!(!surfaces_archived(true, false, NotifyLevel::Alert));
+        assert!(!surfaces_archived(false, true, NotifyLevel::Alert));
+    }
+
+    #[test]
+    fn archiving_is_per_channel() {
+        let mut prefs = RoomPreferences::default();
+        prefs.set_archived("random", true);
+        assert!(prefs.is_archived("random"));
+        assert!(!prefs.is_archived("general"));
+        prefs.set_archived("random", false);
+        assert!(!prefs.is_archived("random"));
+    }
+
+    #[test]
+    fn mentions_match_whole_names_only() {
+        assert!(is_mention("hey @Ann!", "ann"));
+        assert!(is_mention("@ann", "ann"));
+        assert!(!is_mention("hey @anna", "ann"));
+        assert!(!is_mention("hey @ann_b", "ann"));
+        assert!(!is_mention("hey ann", "ann"));
+    }
+
+    #[test]
+    fn mentions_only_still_alerts_for_mentions() {
+        let mention = |global, pref| {
+            should_notify(global, pref, None, false, true, Visibility::Background, false).level
+        };
+        assert_eq!(mention(MentionsOnly, All), NotifyLevel::Alert);
+        assert_eq!(mention(All, MentionsOnly), NotifyLevel::Alert);
+        assert_eq!(mention(Muted, MentionsOnly), NotifyLevel::Silent);
+    }
+
+    #[test]
+    fn the_global_mode_cycles_and_is_kept_apart_from_channels() {
+        assert_eq!(All.next().next().next(), All);
+        let mut prefs = RoomPreferences::default();
+        prefs.set_global(Muted);
+        prefs.set("random", MentionsOnly);
+        assert_eq!(prefs.global(), Muted);
+        assert_eq!(prefs.get("random"), MentionsOnly);
+        assert_eq!(prefs.get("general"), All);
+    }
+
+    #[test]
+    fn muted_senders_are_silent_unless_their_mentions_are_let_through() {
+        let from = |mute, is_mention| {
+            should_notify(All, All, Some(mute), false, is_mention, Visibility::Hidden, false).level
+        };
+        let strict = UserMute { allow_mentions: false };
+        let lenient = UserMute { allow_mentions: true };
+        assert_eq!(from(strict, false), NotifyLevel::Silent);
+        assert_eq!(from(strict, true), NotifyLevel::Silent);
+        assert_eq!(from(lenient, false), NotifyLevel::Silent);
+        assert_eq!(from(lenient, true), NotifyLevel::Alert);
+    }
+
+    #[test]
+    fn muted_users_are_listed_by_name() {
+        let mut prefs = RoomPreferences::default();
+        prefs.set_user_mute("zed", Some(UserMute::default()));
+        prefs.set_user_mute("amy", Some(UserMute { allow_mentions: true }));
+        let names: Vec<String> = prefs.muted_users().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, ["amy", "zed"]);
+        prefs.set_user_mute("zed", None);
+        assert_eq!(prefs.user_mute("zed"), None);
+    }
+
+    #[test]
+    fn preferences_default_to_all() {
+        let prefs = RoomPreferences::default();
+        assert_eq!(prefs.global(), All);
+        assert_eq!(prefs.get("general"), All);
+    }
+}
@@ -0,0 +1,29 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use web_sys::HtmlMediaElement;
+
+/// Shared through a context so only one player on the page makes sound at a time: a player
+/// that starts tells it, and whatever was playing before is paused.
+#[derive(Clone, Default)]
+pub struct PlaybackContext {
+    current: Rc<RefCell<Option<HtmlMediaElement>>>,
+}
+
+impl PartialEq for PlaybackContext {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.current, &other.current)
+    }
+}
+
+impl PlaybackContext {
+    /// `element` just started playing.
+    pub fn started(&self, element: HtmlMediaElement) {
+        let previous = self.current.replace(Some(element.clone()));
+        if let Some(previous) = previous.filter(|previous| *previous != element) {
+            if let Err(e) = previous.pause() {
+                log::error!("failed to pause the other player: {:?}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use futures::channel::mpsc::Sender;
+use gloo_timers::callback::Interval;
+use serde::Serialize;
+
+use crate::components::chat::{MsgTypes, WebSocketMessage};
+use crate::services::typing::{TypingContext, TypingEvent};
+
+/// How often the server hears from us while the service runs.
+const HEARTBEAT_MS: u32 = 30_000;
+
+/// Without any activity for this long, the next heartbeat reports the user as idle.
+pub const IDLE_AFTER_MS: f64 = 5.0 * 60_000.0;
+
+/// Minimum time between two outgoing typing frames.
+const TYPING_THROTTLE_MS: f64 = 2_000.0;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PresenceStatus {
+    Online,
+    Idle,
+    Offline,
+}
+
+/// Payload of a `presence` frame, sent on every heartbeat and whenever the status changes.
+#[derive(Serialize)]
+pub struct PresenceUpdate {
+    pub status: PresenceStatus,
+}
+
+struct State {
+    tx: Option<Sender<String>>,
+    status: PresenceStatus,
+    last_activity: f64,
+}
+
+impl State {
+    fn send(&mut self, message_type: MsgTypes, data: String) {
+        let message = WebSocketMessage {
+            message_type,
+            data: Some(data),
+            data_array: None,
+            channel: None,
+        };
+        if let Some(tx) = &mut self.tx {
+            let _ = tx.try_send(serde_json::to_string(&message).unwrap());
+        }
+    }
+
+    fn send_status(&mut self) {
+        let update = PresenceUpdate { status: self.status };
+        self.send(MsgTypes::Presence, serde_json::to_string(&update).unwrap());
+    }
+
+    fn set_status(&mut self, status: PresenceStatus) {
+        if self.status != status {
+            self.status = status;
+            self.send_status();
+        }
+    }
+
+    fn heartbeat(&mut self, now: f64) {
+        self.status = if now - self.last_activity >= IDLE_AFTER_MS {
+            PresenceStatus::Idle
+        } else {
+            PresenceStatus::Online
+        };
+        self.send_status();
+    }
+}
+
+/// Everything that tells the server whether we're around: heartbeats, idle detection
+/// and typing indicators.
+pub struct PresenceService {
+    state: Rc<RefCell<State>>,
+    last_typing_sent: f64,
+    /// When the composer last saw input, for keeping the pop sound quiet while typing.
+    last_composed_at: f64,
+    heartbeat: Option<Interval>,
+}
+
+impl Default for PresenceService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PresenceService {
+    pub fn new() -> Self {
+        Self {
+            state: Rc::new(RefCell::new(State {
+                tx: None,
+                status: PresenceStatus::Offline,
+                last_activity: js_sys::Date::now(),
+            })),
+            last_typing_sent: 0.0,
+            last_composed_at: 0.0,
+            heartbeat: None,
+        }
+    }
+
+    /// Goes online over `tx` and starts the heartbeat.
+    pub fn start(&mut self, tx: Sender<String>) {
+        {
+            let mut state = self.state.borrow_mut();
+            state.tx = Some(tx);
+            state.last_activity = js_sys::Date::now();
+            state.set_status(PresenceStatus::Online);
+        }
+        let state = self.state.clone();
+        self.heartbeat = Some(Interval::new(HEARTBEAT_MS, move || {
+            state.borrow_mut().heartbeat(js_sys::Date::now());
+        }));
+    }
+
+    /// Says goodbye and stops the heartbeat. Nothing is sent after this until `start`.
+    pub fn stop(&mut self) {
+        self.heartbeat = None;
+        let mut state = self.state.borrow_mut();
+        state.set_status(PresenceStatus::Offline);
+        state.tx = None;
+    }
+
+    /// The user did something; an idle user is back online straight away.
+    pub fn notify_activity(&mut self) {
+        let mut state = self.state.borrow_mut();
+        state.last_activity = js_sys::Date::now();
+        if state.status == PresenceStatus::Idle {
+            state.set_status(PresenceStatus::Online);
+        }
+    }
+
+    /// The user typed in `room`'s composer, or in the composer of its thread `thread_id`.
+    pub fn typing(&mut self, room: &str, thread_id: Option<&str>) {
+        let now = js_sys::Date::now();
+        self.last_composed_at = now;
+        if now - self.last_typing_sent < TYPING_THROTTLE_MS {
+            return;
+        }
+        self.last_typing_sent = now;
+        let event = TypingEvent {
+            from: String::new(),
+            context: TypingContext {
+                room: room.to_string(),
+                thread_id: thread_id.map(str::to_string),
+            },
+        };
+        self.state
+            .borrow_mut()
+            .send(MsgTypes::Typing, serde_json::to_string(&event).unwrap());
+    }
+
+    /// Milliseconds since the composer last saw input.
+    pub fn since_composing(&self, now: f64) -> f64 {
+        now - self.last_composed_at
+    }
+}
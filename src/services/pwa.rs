@@ -0,0 +1,105 @@
+use std::cell::RefCell;
+
+use gloo_events::EventListener;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Event, ServiceWorkerContainer};
+use yew::Callback;
+use yew_agent::{Bridge, Bridged, Dispatched};
+
+use crate::services::toasts::{Toast, ToastBus, ToastLevel, ToastMsg};
+use crate::t;
+
+/// Id of the "Refresh" button on the toast announcing a new version.
+const REFRESH_ACTION: &str = "pwa.refresh";
+
+thread_local! {
+    /// The browser's `beforeinstallprompt` event, held back until the user asks to install.
+    static INSTALL_PROMPT: RefCell<Option<Event>> = const { RefCell::new(None) };
+}
+
+/// `navigator.serviceWorker`, which only exists in secure contexts.
+fn service_worker() -> Option<ServiceWorkerContainer> {
+    let navigator = web_sys::window()?.navigator();
+    let container = js_sys::Reflect::get(&navigator, &JsValue::from_str("serviceWorker")).ok()?;
+    container.dyn_into().ok()
+}
+
+/// Registers `sw.js`, which precaches the app shell for offline starts. The URL stays the
+/// same across builds: a new build changes the script itself, and the browser's own update
+/// check picks that up even on a page still running the old build.
+pub fn register_service_worker() {
+    let container = match service_worker() {
+        Some(container) => container,
+        None => return,
+    };
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Err(e) = JsFuture::from(container.register("/sw.js")).await {
+            log::error!("failed to register the service worker: {:?}", e);
+        }
+    });
+}
+
+/// Holds on to `beforeinstallprompt` from page load on, since it can fire long before
+/// anyone opens the menu that offers to install.
+pub fn capture_install_prompt() {
+    let window = match web_sys::window() {
+        Some(window) => window,
+        None => return,
+    };
+    EventListener::new(&window, "beforeinstallprompt", |e| {
+        e.prevent_default();
+        INSTALL_PROMPT.with(|prompt| *prompt.borrow_mut() = Some(e.clone()));
+    })
+    .forget();
+    EventListener::new(&window, "appinstalled", |_| {
+        INSTALL_PROMPT.with(|prompt| *prompt.borrow_mut() = None);
+    })
+    .forget();
+}
+
+/// The browser is ready to install the app if asked.
+pub fn install_available() -> bool {
+    INSTALL_PROMPT.with(|prompt| prompt.borrow().is_some())
+}
+
+/// Shows the browser's install dialog. The event can only prompt once, so it's used up.
+pub fn prompt_install() {
+    let event = match INSTALL_PROMPT.with(|prompt| prompt.borrow_mut().take()) {
+        Some(event) => event,
+        None => return,
+    };
+    let prompt = js_sys::Reflect::get(&event, &JsValue::from_str("prompt"))
+        .ok()
+        .and_then(|prompt| prompt.dyn_into::<js_sys::Function>().ok());
+    if let Some(Err(e)) = prompt.map(|prompt| prompt.call0(&event)) {
+        log::error!("failed to show the install prompt: {:?}", e);
+    }
+}
+
+/// Offers a refresh when a new service worker takes over a page the old one was serving,
+/// and reloads once the user takes it up.
+pub struct UpdateWatch {
+    _controller_change: Option<EventListener>,
+    _toasts: Box<dyn Bridge<ToastBus>>,
+}
+
+impl UpdateWatch {
+    pub fn start() -> Self {
+        let _toasts = ToastBus::bridge(Callback::from(|msg| {
+            if matches!(msg, ToastMsg::Action(id) if id == REFRESH_ACTION) {
+                if let Err(e) = web_sys::window().unwrap().location().reload() {
+                    log::error!("failed to reload: {:?}", e);
+                }
+            }
+        }));
+        // With no worker in control yet this is the first install, not an update.
+        let _controller_change = service_worker().filter(|c| c.controller().is_some()).map(|container| {
+            EventListener::new(&container, "controllerchange", |_| {
+                let toast = Toast::new(ToastLevel::Info, t!("pwa.updated")).with_action(t!("pwa.refresh"), REFRESH_ACTION);
+                ToastBus::dispatcher().send(ToastMsg::Show(toast));
+            })
+        });
+        Self { _controller_change, _toasts }
+    }
+}
@@ -0,0 +1,72 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, BlobEvent, BlobPropertyBag, MediaRecorder, MediaStream, MediaStreamConstraints, MediaStreamTrack};
+use yew::Callback;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ClipSource {
+    Screen,
+    Camera,
+}
+
+/// A running MediaRecorder session. The finished clip is handed to `on_done` once
+/// `stop` has been called and the recorder has flushed its last chunk.
+pub struct ClipRecorder {
+    recorder: MediaRecorder,
+    _ondataavailable: Closure<dyn FnMut(BlobEvent)>,
+    _onstop: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+impl ClipRecorder {
+    pub async fn start(source: ClipSource, on_done: Callback<Blob>) -> Result<Self, JsValue> {
+        let devices = web_sys::window().unwrap().navigator().media_devices()?;
+        let promise = match source {
+            ClipSource::Screen => devices.get_display_media()?,
+            ClipSource::Camera => devices.get_user_media_with_constraints(
+                MediaStreamConstraints::new().video(&true.into()).audio(&true.into()),
+            )?,
+        };
+        let stream: MediaStream = JsFuture::from(promise).await?.dyn_into()?;
+        let recorder = MediaRecorder::new_with_media_stream(&stream)?;
+
+        let chunks = Rc::new(RefCell::new(js_sys::Array::new()));
+        let ondataavailable = {
+            let chunks = chunks.clone();
+            Closure::wrap(Box::new(move |e: BlobEvent| {
+                if let Some(data) = e.data() {
+                    chunks.borrow().push(&data);
+                }
+            }) as Box<dyn FnMut(BlobEvent)>)
+        };
+        let onstop = Closure::wrap(Box::new(move |_: web_sys::Event| {
+            stream
+                .get_tracks()
+                .iter()
+                .filter_map(|track| track.dyn_into::<MediaStreamTrack>().ok())
+                .for_each(|track| track.stop());
+            match Blob::new_with_blob_sequence_and_options(&chunks.borrow(), BlobPropertyBag::new().type_("video/webm")) {
+                Ok(blob) => on_done.emit(blob),
+                Err(e) => log::error!("failed to assemble clip: {:?}", e),
+            }
+        }) as Box<dyn FnMut(web_sys::Event)>);
+
+        recorder.set_ondataavailable(Some(ondataavailable.as_ref().unchecked_ref()));
+        recorder.set_onstop(Some(onstop.as_ref().unchecked_ref()));
+        recorder.start()?;
+
+        Ok(Self {
+            recorder,
+            _ondataavailable: ondataavailable,
+            _onstop: onstop,
+        })
+    }
+
+    pub fn stop(&self) {
+        if let Err(e) = self.recorder.stop() {
+            log::error!("failed to stop recorder: {:?}", e);
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::fmt;
+
+use crate::t;
+
+pub const MIN_ROOM_NAME_CHARS: usize = 2;
+pub const MAX_ROOM_NAME_CHARS: usize = 32;
+/// Names that would pass for the server or a moderator talking.
+const RESERVED_ROOM_NAMES: [&str; 4] = ["admin", "moderators", "server", "system"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RoomNameError {
+    TooShort,
+    TooLong,
+    /// The first character that isn't a lowercase letter, digit or `-`.
+    InvalidChar(char),
+    /// Starts or ends with `-`, or has two in a row.
+    StrayHyphen,
+    Reserved,
+}
+
+impl fmt::Display for RoomNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RoomNameError::TooShort => f.write_str(&t!("room_name.too_short", min = MIN_ROOM_NAME_CHARS)),
+            RoomNameError::TooLong => f.write_str(&t!("room_name.too_long", max = MAX_ROOM_NAME_CHARS)),
+            RoomNameError::InvalidChar(' ') => f.write_str(&t!("room_name.space")),
+            RoomNameError::InvalidChar(c) => f.write_str(&t!("room_name.invalid_char", char = c)),
+            RoomNameError::StrayHyphen => f.write_str(&t!("room_name.stray_hyphen")),
+            RoomNameError::Reserved => f.write_str(&t!("room_name.reserved")),
+        }
+    }
+}
+
+/// The room name `input` stands for: trimmed and lowercased, 2–32 ASCII letters, digits
+/// and single hyphens between them, e.g. `book-club`, and none of the reserved names.
+pub fn validate_room_name(input: &str) -> Result<String, RoomNameError> {
+    let name = input.trim().to_lowercase();
+    if let Some(c) = name.chars().find(|&c| !(c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')) {
+        return Err(RoomNameError::InvalidChar(c));
+    }
+    let len = name.chars().count();
+    if len < MIN_ROOM_NAME_CHARS {
+        return Err(RoomNameError::TooShort);
+    }
+    if len > MAX_ROOM_NAME_CHARS {
+        return Err(RoomNameError::TooLong);
+    }
+    if name.starts_with('-') || name.ends_with('-') || name.contains("--") {
+        return Err(RoomNameError::StrayHyphen);
+    }
+    if RESERVED_ROOM_NAMES.contains(&name.as_str()) {
+        return Err(RoomNameError::Reserved);
+    }
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_normalised_and_validated() {
+        let cases: [(&str, Result<&str, RoomNameError>); 17] = [
+            ("book-club", Ok("book-club")),
+            ("r2", Ok("r2")),
+            ("  Book-Club ", Ok("book-club")),
+            ("RUST", Ok("rust")),
+            ("a-b-c-1-2-3", Ok("a-b-c-1-2-3")),
+            ("book club", Err(RoomNameError::InvalidChar(' '))),
+            ("book_club", Err(RoomNameError::InvalidChar('_'))),
+            ("café", Err(RoomNameError::InvalidChar('é'))),
+            ("#general", Err(RoomNameError::InvalidChar('#'))),
+            ("", Err(RoomNameError::TooShort)),
+            ("  a  ", Err(RoomNameError::TooShort)),
+            ("-book", Err(RoomNameError::StrayHyphen)),
+            ("book-", Err(RoomNameError::StrayHyphen)),
+            ("book--club", Err(RoomNameError::StrayHyphen)),
+            ("admin", Err(RoomNameError::Reserved)),
+            (" System ", Err(RoomNameError::Reserved)),
+            ("admins", Ok("admins")),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(validate_room_name(input), expected.map(String::from), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn length_is_counted_after_trimming() {
+        let longest = "a".repeat(MAX_ROOM_NAME_CHARS);
+        assert_eq!(validate_room_name(&format!("  {}  ", longest)), Ok(longest.clone()));
+        assert_eq!(validate_room_name(&format!("{}a", longest)), Err(RoomNameError::TooLong));
+        assert_eq!(validate_room_name(&"a".repeat(MIN_ROOM_NAME_CHARS)).map(|n| n.len()), Ok(MIN_ROOM_NAME_CHARS));
+    }
+}
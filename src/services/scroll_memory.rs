@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+
+use crate::services::storage::{Local, Store};
+
+/// Channels whose position is kept; past this the one read longest ago is forgotten.
+pub const MAX_REMEMBERED_CHANNELS: usize = 50;
+
+/// Where someone was reading in a channel: the topmost visible message, and how far its
+/// top edge sat below the top of the list.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReadingPosition {
+    pub message_id: String,
+    pub offset: i32,
+}
+
+/// The reading position per channel, kept across reloads. Channels scrolled to the bottom
+/// have none, so they open at the bottom again.
+pub struct ScrollMemory<S: Store = Local> {
+    store: S,
+    key: String,
+    /// Read longest ago first.
+    positions: Vec<(String, ReadingPosition)>,
+}
+
+impl ScrollMemory {
+    pub fn load(username: &str) -> Self {
+        Self::load_from(Local, username)
+    }
+}
+
+impl<S: Store> ScrollMemory<S> {
+    pub fn load_from(store: S, username: &str) -> Self {
+        let key = format!("yewchat.scroll.{}", username);
+        Self {
+            positions: store.get(&key).unwrap_or_default(),
+            store,
+            key,
+        }
+    }
+
+    pub fn get(&self, channel: &str) -> Option<&ReadingPosition> {
+        self.positions.iter().find(|(name, _)| name == channel).map(|(_, position)| position)
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = (&String, &ReadingPosition)> {
+        self.positions.iter().map(|(name, position)| (name, position))
+    }
+
+    /// Remembers `position` for `channel`; `None` means it is at the bottom.
+    pub fn set(&mut self, channel: &str, position: Option<ReadingPosition>) {
+        let old = self.positions.iter().position(|(name, _)| name == channel).map(|i| self.positions.remove(i));
+        let changed = old.as_ref().map(|(_, position)| position) != position.as_ref();
+        if let Some(position) = position {
+            self.positions.push((channel.to_string(), position));
+            if self.positions.len() > MAX_REMEMBERED_CHANNELS {
+                self.positions.remove(0);
+            }
+        }
+        if !changed {
+            return;
+        }
+        if let Err(e) = self.store.set(&self.key, &self.positions) {
+            log::error!("failed to save scroll positions: {:?}", e);
+        }
+    }
+
+    /// Files the positions under `username` from now on, after a rename.
+    pub fn rename(&mut self, username: &str) {
+        let old = std::mem::replace(&mut self.key, format!("yewchat.scroll.{}", username));
+        match self.store.set(&self.key, &self.positions) {
+            Ok(()) => self.store.delete(&old),
+            Err(e) => log::error!("failed to save scroll positions: {:?}", e),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.positions.clear();
+        self.store.delete(&self.key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::storage::MemoryStore;
+
+    fn at(message_id: &str) -> Option<ReadingPosition> {
+        Some(ReadingPosition { message_id: message_id.into(), offset: -12 })
+    }
+
+    #[test]
+    fn positions_are_restored_per_channel() {
+        let store = MemoryStore::default();
+        let mut memory = ScrollMemory::load_from(store.clone(), "ann");
+        memory.set("general", at("7"));
+        memory.set("random", at("3"));
+        let reloaded = ScrollMemory::load_from(store, "ann");
+        assert_eq!(reloaded.get("general"), at("7").as_ref());
+        assert_eq!(reloaded.get("random"), at("3").as_ref());
+        assert_eq!(reloaded.get("help"), None);
+    }
+
+    #[test]
+    fn reaching_the_bottom_forgets_the_position() {
+        let store = MemoryStore::default();
+        let mut memory = ScrollMemory::load_from(store.clone(), "ann");
+        memory.set("general", at("7"));
+        memory.set("general", None);
+        assert_eq!(ScrollMemory::load_from(store, "ann").get("general"), None);
+    }
+
+    #[test]
+    fn the_channel_read_longest_ago_is_evicted() {
+        let mut memory = ScrollMemory::load_from(MemoryStore::default(), "ann");
+        for i in 0..MAX_REMEMBERED_CHANNELS {
+            memory.set(&format!("room{}", i), at("1"));
+        }
+        // Reading room0 again makes room1 the oldest.
+        memory.set("room0", at("2"));
+        memory.set("one-more", at("1"));
+        assert_eq!(memory.positions().count(), MAX_REMEMBERED_CHANNELS);
+        assert_eq!(memory.get("room0"), at("2").as_ref());
+        assert_eq!(memory.get("room1"), None);
+        assert_eq!(memory.get("one-more"), at("1").as_ref());
+    }
+
+    #[test]
+    fn a_rename_moves_the_positions() {
+        let store = MemoryStore::default();
+        let mut memory = ScrollMemory::load_from(store.clone(), "ann");
+        memory.set("general", at("7"));
+        memory.rename("anna");
+        assert_eq!(store.raw("yewchat.scroll.ann"), None);
+        assert_eq!(ScrollMemory::load_from(store, "anna").get("general"), at("7").as_ref());
+    }
+
+    #[test]
+    fn an_unchanged_position_is_not_written_again() {
+        let store = MemoryStore::default();
+        let mut memory = ScrollMemory::load_from(store.clone(), "ann");
+        memory.set("general", at("7"));
+        store.delete("yewchat.scroll.ann");
+        memory.set("general", at("7"));
+        assert_eq!(store.raw("yewchat.scroll.ann"), None);
+    }
+}
@@ -0,0 +1,74 @@
+use js_sys::Array;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, IntersectionObserver, IntersectionObserverEntry};
+use yew::Callback;
+
+/// Where `scrollTop` has to go after content was added above the viewport, so what the
+/// user was looking at stays put: everything moved down by however much the list grew.
+pub fn preserved_scroll_top(old_height: i32, old_top: i32, new_height: i32) -> i32 {
+    (old_top + new_height - old_height).max(0)
+}
+
+/// Fires a callback whenever an element scrolls into view.
+///
+/// Meant for an empty marker at the edge of a scrolling list: when it shows up, the
+/// user has reached that edge.
+pub struct ScrollSentinel {
+    observer: IntersectionObserver,
+    _callback: Closure<dyn FnMut(Array)>,
+    target: Option<Element>,
+}
+
+impl ScrollSentinel {
+    /// `None` where the browser has no `IntersectionObserver`.
+    pub fn new(on_visible: Callback<()>) -> Option<Self> {
+        let callback = Closure::wrap(Box::new(move |entries: Array| {
+            let visible = entries
+                .iter()
+                .filter_map(|entry| entry.dyn_into::<IntersectionObserverEntry>().ok())
+                .any(|entry| entry.is_intersecting());
+            if visible {
+                on_visible.emit(());
+            }
+        }) as Box<dyn FnMut(Array)>);
+        let observer = IntersectionObserver::new(callback.as_ref().unchecked_ref()).ok()?;
+        Some(Self {
+            observer,
+            _callback: callback,
+            target: None,
+        })
+    }
+
+    /// Watches `target` instead of whatever was watched before. Cheap to call on every
+    /// render: nothing happens unless the element actually changed.
+    pub fn watch(&mut self, target: Option<Element>) {
+        if self.target == target {
+            return;
+        }
+        self.observer.disconnect();
+        if let Some(target) = &target {
+            self.observer.observe(target);
+        }
+        self.target = target;
+    }
+}
+
+impl Drop for ScrollSentinel {
+    fn drop(&mut self) {
+        self.observer.disconnect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prepended_content_keeps_the_view_in_place() {
+        assert_eq!(preserved_scroll_top(1_000, 0, 1_600), 600);
+        assert_eq!(preserved_scroll_top(1_000, 250, 1_600), 850);
+        assert_eq!(preserved_scroll_top(1_000, 250, 1_000), 250);
+        assert_eq!(preserved_scroll_top(1_000, 100, 800), 0);
+    }
+}
@@ -0,0 +1,578 @@
+use std::collections::HashSet;
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::chat::MessageData;
+
+/// Results beyond this many aren't rendered; the panel asks for a narrower query instead.
+pub const MAX_RESULTS: usize = 100;
+/// Characters of context kept on either side of the first match.
+const SNIPPET_CONTEXT: usize = 40;
+pub const DAY_MS: f64 = 86_400_000.0;
+
+/// The `key:` of a structured search token.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterKey {
+    From,
+    Before,
+    After,
+    In,
+}
+
+impl FilterKey {
+    fn parse(key: &str) -> Option<Self> {
+        match key.to_lowercase().as_str() {
+            "from" => Some(FilterKey::From),
+            "before" => Some(FilterKey::Before),
+            "after" => Some(FilterKey::After),
+            "in" => Some(FilterKey::In),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FilterKey::From => "from",
+            FilterKey::Before => "before",
+            FilterKey::After => "after",
+            FilterKey::In => "in",
+        }
+    }
+}
+
+/// Which channels the search panel looks through. An `in:` filter overrides it.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchScope {
+    /// The channel open when searching.
+    #[default]
+    Channel,
+    All,
+}
+
+/// A parsed search box: free text plus `from:alice`, `before:2025-03-01`, `after:yesterday`
+/// and `in:#general` filters, all of which a message must satisfy.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SearchQuery {
+    /// Words and quoted phrases, each of which must appear in the message.
+    pub terms: Vec<String>,
+    pub from: Option<String>,
+    /// Channel name, without the `#`.
+    pub channel: Option<String>,
+    /// Only messages sent before this, in ms since the epoch.
+    pub before: Option<f64>,
+    /// Only messages sent at or after this, in ms since the epoch.
+    pub after: Option<f64>,
+    /// The filters as typed, for showing them back.
+    pub filters: Vec<(FilterKey, String)>,
+}
+
+/// Splits `input` on whitespace outside double quotes. Tokens come back as typed, quotes
+/// and all; an unterminated quote runs to the end.
+fn tokens(input: &str) -> Vec<&str> {
+    let mut tokens = vec![];
+    let mut start = None;
+    let mut quoted = false;
+    for (i, c) in input.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        match (start, c.is_whitespace() && !quoted) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                tokens.push(&input[s..i]);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        tokens.push(&input[s..]);
+    }
+    tokens
+}
+
+fn unquote(token: &str) -> String {
+    token.replace('"', "")
+}
+
+/// Splits `key:value` where the key is one of the filters and the value isn't blank.
+fn filter(token: &str) -> Option<(FilterKey, String)> {
+    let (key, value) = token.split_once(':')?;
+    let key = FilterKey::parse(key)?;
+    let value = unquote(value);
+    if value.trim().is_empty() {
+        return None;
+    }
+    Some((key, value))
+}
+
+/// Days from 1970-01-01 to `year-month-day` in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// `(year, month, day)` of the day `days` after 1970-01-01; the inverse of [`days_from_civil`].
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// `YYYY-MM-DD` of the local day `ts` (ms since the epoch) falls on.
+pub fn day_key(ts: f64, utc_offset_minutes: i32) -> String {
+    let days = ((ts + utc_offset_minutes as f64 * 60_000.0) / DAY_MS).floor() as i64;
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Local midnight starting the day `value` names, in ms since the epoch: `today`,
+/// `yesterday` or `YYYY-MM-DD`. Local time is `utc_offset_minutes` ahead of UTC.
+pub fn parse_day(value: &str, now: f64, utc_offset_minutes: i32) -> Option<f64> {
+    let offset_ms = utc_offset_minutes as f64 * 60_000.0;
+    let today = ((now + offset_ms) / DAY_MS).floor();
+    let day = match value.to_lowercase().as_str() {
+        "today" => today,
+        "yesterday" => today - 1.0,
+        date => {
+            let mut parts = date.splitn(3, '-');
+            let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+            if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+                return None;
+            }
+            let (year, month, day): (i64, i64, i64) = (year.parse().ok()?, month.parse().ok()?, day.parse().ok()?);
+            if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+                return None;
+            }
+            days_from_civil(year, month, day) as f64
+        }
+    };
+    Some(day * DAY_MS - offset_ms)
+}
+
+impl SearchQuery {
+    /// Parses the search box. Tokens that look like filters but aren't (`foo:bar`, `from:`)
+    /// count as text. `before:` keeps messages from earlier days, `after:` those from later
+    /// ones; either day is `today`, `yesterday` or `YYYY-MM-DD` in local time. The error is
+    /// meant for showing under the box.
+    pub fn parse(input: &str, now: f64, utc_offset_minutes: i32) -> Result<Self, String> {
+        let mut query = Self::default();
+        for token in tokens(input) {
+            let (key, value) = match filter(token) {
+                Some(filter) => filter,
+                None => {
+                    let term = unquote(token);
+                    if !term.trim().is_empty() {
+                        query.terms.push(term.trim().to_string());
+                    }
+                    continue;
+                }
+            };
+            let day = || {
+                parse_day(value.trim(), now, utc_offset_minutes).ok_or_else(|| {
+                    format!("\"{}\" isn't a date. Use YYYY-MM-DD, today or yesterday.", value.trim())
+                })
+            };
+            match key {
+                FilterKey::From => query.from = Some(value.trim().trim_start_matches('@').to_string()),
+                FilterKey::In => query.channel = Some(value.trim().trim_start_matches('#').to_string()),
+                FilterKey::Before => query.before = Some(day()?),
+                FilterKey::After => query.after = Some(day()? + DAY_MS),
+            }
+            query.filters.retain(|(k, _)| *k != key);
+            query.filters.push((key, value));
+        }
+        Ok(query)
+    }
+
+    /// Neither text nor filters.
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty() && self.filters.is_empty()
+    }
+
+    /// Whether `message`, found in `channel`, satisfies every part of the query. Date filters
+    /// rule out messages without a timestamp.
+    pub fn matches(&self, channel: &str, message: &MessageData) -> bool {
+        if let Some(from) = &self.from {
+            if !from.eq_ignore_ascii_case(&message.from) {
+                return false;
+            }
+        }
+        if let Some(wanted) = &self.channel {
+            if !wanted.eq_ignore_ascii_case(channel) {
+                return false;
+            }
+        }
+        if let Some(before) = self.before {
+            if !matches!(message.timestamp, Some(ts) if ts < before) {
+                return false;
+            }
+        }
+        if let Some(after) = self.after {
+            if !matches!(message.timestamp, Some(ts) if ts >= after) {
+                return false;
+            }
+        }
+        self.terms.iter().all(|term| !match_spans(&message.message, term).is_empty())
+    }
+
+    /// [`matches`](Self::matches), limited to `scope` with `active` as the open channel
+    /// unless the query names a channel itself.
+    pub fn matches_in(&self, scope: SearchScope, active: &str, channel: &str, message: &MessageData) -> bool {
+        let in_scope = self.channel.is_some() || scope == SearchScope::All || channel == active;
+        in_scope && self.matches(channel, message)
+    }
+
+    /// Where the query's terms appear in `text`, in order and without overlaps.
+    pub fn spans(&self, text: &str) -> Vec<Range<usize>> {
+        let mut spans: Vec<Range<usize>> = self.terms.iter().flat_map(|term| match_spans(text, term)).collect();
+        spans.sort_by_key(|s| (s.start, std::cmp::Reverse(s.end)));
+        let mut merged: Vec<Range<usize>> = vec![];
+        for span in spans {
+            match merged.last_mut() {
+                Some(last) if span.start < last.end => last.end = last.end.max(span.end),
+                _ => merged.push(span),
+            }
+        }
+        merged
+    }
+}
+
+/// `input` with every token for `key` taken out, for removing a filter chip.
+pub fn without_filter(input: &str, key: FilterKey) -> String {
+    tokens(input)
+        .into_iter()
+        .filter(|token| !matches!(filter(token), Some((k, _)) if k == key))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Byte ranges of every case-insensitive occurrence of `query` in `text`, left to right and
+/// without overlaps. An empty (or all-blank) query matches nothing.
+///
+/// Matching works on lowercased characters, but the ranges always cover whole characters of
+/// `text`, so they are safe to slice with even around emoji or letters whose lowercase form
+/// is longer than they are.
+pub fn match_spans(text: &str, query: &str) -> Vec<Range<usize>> {
+    let needle: Vec<char> = query.trim().chars().flat_map(char::to_lowercase).collect();
+    if needle.is_empty() {
+        return vec![];
+    }
+    // Each lowercased character next to the bytes of the character it came from.
+    let folded: Vec<(char, Range<usize>)> = text
+        .char_indices()
+        .flat_map(|(i, c)| c.to_lowercase().map(move |lower| (lower, i..i + c.len_utf8())))
+        .collect();
+
+    let mut spans = vec![];
+    let mut k = 0;
+    while k + needle.len() <= folded.len() {
+        let window = &folded[k..k + needle.len()];
+        if window.iter().map(|(c, _)| *c).eq(needle.iter().copied()) {
+            let span = window[0].1.start..window[needle.len() - 1].1.end;
+            k += needle.len();
+            // Don't start the next match inside a character this one already covers.
+            while k < folded.len() && folded[k].1.start < span.end {
+                k += 1;
+            }
+            spans.push(span);
+        } else {
+            k += 1;
+        }
+    }
+    spans
+}
+
+/// Cuts `text` down to the area around the first of `spans`, split into `(text, is_match)`
+/// parts with every span inside that area marked.
+pub fn highlight(text: &str, spans: &[Range<usize>]) -> Vec<(String, bool)> {
+    let first = match spans.first() {
+        Some(first) => first.clone(),
+        None => return vec![(text.to_string(), false)],
+    };
+    let start = text[..first.start]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CONTEXT - 1)
+        .map_or(0, |(i, _)| i);
+    let end = text[first.end..]
+        .char_indices()
+        .nth(SNIPPET_CONTEXT)
+        .map_or(text.len(), |(i, _)| first.end + i);
+
+    let mut parts = vec![];
+    if start > 0 {
+        parts.push(("…".to_string(), false));
+    }
+    let mut at = start;
+    for span in spans.iter().filter(|s| s.end <= end) {
+        if span.start > at {
+            parts.push((text[at..span.start].to_string(), false));
+        }
+        parts.push((text[span.clone()].to_string(), true));
+        at = span.end;
+    }
+    if end > at {
+        parts.push((text[at..end].to_string(), false));
+    }
+    if end < text.len() {
+        parts.push(("…".to_string(), false));
+    }
+    parts
+}
+
+/// Messages in `scope` satisfying `query`, newest first, each tagged with the channel it was
+/// found in. `active` is the open channel. Stops one past [`MAX_RESULTS`] so callers can tell
+/// there were more.
+pub fn search<'a>(
+    messages: impl Iterator<Item = (&'a str, &'a MessageData)>,
+    query: &SearchQuery,
+    scope: SearchScope,
+    active: &str,
+) -> Vec<MessageData> {
+    let mut hits: Vec<MessageData> = messages
+        .filter(|(channel, m)| query.matches_in(scope, active, channel, m))
+        .map(|(channel, m)| {
+            let mut hit = m.clone();
+            hit.channel.get_or_insert_with(|| channel.to_string());
+            hit
+        })
+        .collect();
+    newest_first(&mut hits);
+    hits.truncate(MAX_RESULTS + 1);
+    hits
+}
+
+/// Adds archive hits to the in-memory ones, one copy per id, newest first and capped the
+/// same way as [`search`].
+pub fn merge_results(mut hits: Vec<MessageData>, archived: Vec<MessageData>) -> Vec<MessageData> {
+    let mut seen: HashSet<String> = hits.iter().map(|m| m.id.clone()).collect();
+    hits.extend(archived.into_iter().filter(|m| seen.insert(m.id.clone())));
+    newest_first(&mut hits);
+    hits.truncate(MAX_RESULTS + 1);
+    hits
+}
+
+/// Results under their channel's heading. Channels come in the order of their first result
+/// and keep their results' order, so with newest-first results the channel with the newest
+/// hit leads.
+pub fn group_by_channel(results: &[MessageData]) -> Vec<(String, Vec<MessageData>)> {
+    let mut groups: Vec<(String, Vec<MessageData>)> = vec![];
+    for message in results {
+        let channel = message.channel.clone().unwrap_or_default();
+        match groups.iter_mut().find(|(name, _)| *name == channel) {
+            Some((_, messages)) => messages.push(message.clone()),
+            None => groups.push((channel, vec![message.clone()])),
+        }
+    }
+    groups
+}
+
+fn newest_first(messages: &mut [MessageData]) {
+    messages.sort_by(|a, b| b.timestamp.unwrap_or(0.0).total_cmp(&a.timestamp.unwrap_or(0.0)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: &str, channel: Option<&str>, timestamp: f64, text: &str) -> MessageData {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "from": "ann",
+            "message": text,
+            "reactions": null,
+            "timestamp": timestamp,
+            "channel": channel,
+        }))
+        .unwrap()
+    }
+
+    fn ids(messages: &[MessageData]) -> Vec<&str> {
+        messages.iter().map(|m| m.id.as_str()).collect()
+    }
+
+    #[test]
+    fn groups_keep_first_seen_order() {
+        let results = [
+            message("c", Some("random"), 3.0, ""),
+            message("b", Some("general"), 2.0, ""),
+            message("a", Some("random"), 1.0, ""),
+        ];
+        let groups = group_by_channel(&results);
+        let names: Vec<&str> = groups.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["random", "general"]);
+        assert_eq!(ids(&groups[0].1), ["c", "a"]);
+        assert_eq!(ids(&groups[1].1), ["b"]);
+    }
+
+    #[test]
+    fn scope_limits_search_unless_the_query_names_a_channel() {
+        let general = message("a", None, 1.0, "hello");
+        let random = message("b", None, 2.0, "hello there");
+        let messages = || [("general", &general), ("random", &random)].into_iter();
+
+        let query = SearchQuery::parse("hello", 0.0, 0).unwrap();
+        assert_eq!(ids(&search(messages(), &query, SearchScope::Channel, "general")), ["a"]);
+        let all = search(messages(), &query, SearchScope::All, "general");
+        assert_eq!(ids(&all), ["b", "a"]);
+        assert_eq!(all[0].channel.as_deref(), Some("random"));
+
+        let query = SearchQuery::parse("hello in:#random", 0.0, 0).unwrap();
+        assert_eq!(ids(&search(messages(), &query, SearchScope::Channel, "general")), ["b"]);
+    }
+
+    #[test]
+    fn spans_ignore_case_and_cover_whole_characters() {
+        assert_eq!(match_spans("Hello hello", "HELLO"), vec![0..5, 6..11]);
+        assert_eq!(match_spans("aaaa", "aa"), vec![0..2, 2..4]);
+        assert_eq!(match_spans("hello", "  "), vec![]);
+        // "İ" lowercases to two characters; the span still covers all of it.
+        let text = "İstanbul";
+        let spans = match_spans(text, "i̇s");
+        assert_eq!(spans, vec![0..3]);
+        assert_eq!(&text[spans[0].clone()], "İs");
+    }
+
+    #[test]
+    fn highlight_trims_around_the_first_match() {
+        assert_eq!(highlight("no match", &[]), [("no match".to_string(), false)]);
+        assert_eq!(
+            highlight("say hi to me", &match_spans("say hi to me", "hi")),
+            [("say ".to_string(), false), ("hi".to_string(), true), (" to me".to_string(), false)]
+        );
+
+        let long = format!("{}needle{}", "x".repeat(100), "y".repeat(100));
+        let parts = highlight(&long, &match_spans(&long, "needle"));
+        assert_eq!(parts.first().unwrap().0, "…");
+        assert_eq!(parts.last().unwrap().0, "…");
+        assert_eq!(parts[1].0.chars().count(), SNIPPET_CONTEXT);
+        assert_eq!(parts[2], ("needle".to_string(), true));
+    }
+
+    #[test]
+    fn results_are_newest_first_and_stop_one_past_the_cap() {
+        let many: Vec<MessageData> = (0..MAX_RESULTS + 10)
+            .map(|n| message(&n.to_string(), None, n as f64, "hit"))
+            .collect();
+        let query = SearchQuery::parse("hit", 0.0, 0).unwrap();
+        let hits = search(many.iter().map(|m| ("general", m)), &query, SearchScope::All, "general");
+        assert_eq!(hits.len(), MAX_RESULTS + 1);
+        assert_eq!(hits[0].id, (MAX_RESULTS + 9).to_string());
+        assert_eq!(hits[0].channel.as_deref(), Some("general"));
+    }
+
+    #[test]
+    fn archive_hits_add_to_memory_without_duplicates() {
+        let memory = vec![message("b", None, 2.0, "live")];
+        let archived = vec![
+            message("a", None, 1.0, ""),
+            message("b", None, 2.0, "archived"),
+            message("c", None, 3.0, ""),
+        ];
+        let merged = merge_results(memory, archived);
+        assert_eq!(ids(&merged), ["c", "b", "a"]);
+        assert_eq!(merged[1].message, "live");
+    }
+
+    #[test]
+    fn filters_are_pulled_out_of_the_text() {
+        let query = SearchQuery::parse(r#"From:@Ann "deploy failed" in:#ops foo:bar"#, 0.0, 0).unwrap();
+        assert_eq!(query.terms, ["deploy failed", "foo:bar"]);
+        assert_eq!(query.from.as_deref(), Some("Ann"));
+        assert_eq!(query.channel.as_deref(), Some("ops"));
+        assert_eq!(query.filters.len(), 2);
+
+        let blank = SearchQuery::parse("from: hi", 0.0, 0).unwrap();
+        assert_eq!(blank.terms, ["from:", "hi"]);
+        assert!(blank.from.is_none());
+    }
+
+    #[test]
+    fn the_last_of_a_repeated_filter_wins() {
+        let query = SearchQuery::parse("from:ann from:bob", 0.0, 0).unwrap();
+        assert_eq!(query.from.as_deref(), Some("bob"));
+        assert_eq!(query.filters, [(FilterKey::From, "bob".to_string())]);
+    }
+
+    #[test]
+    fn dates_bound_whole_local_days() {
+        let query = SearchQuery::parse("after:1970-01-02 before:1970-01-04", 0.0, 0).unwrap();
+        let at = |ts: f64| message("m", None, ts, "");
+        // Only 1970-01-03 is after the 2nd and before the 4th.
+        assert!(!query.matches("general", &at(2.0 * DAY_MS - 1.0)));
+        assert!(query.matches("general", &at(2.0 * DAY_MS)));
+        assert!(query.matches("general", &at(3.0 * DAY_MS - 1.0)));
+        assert!(!query.matches("general", &at(3.0 * DAY_MS)));
+        let mut undated = at(0.0);
+        undated.timestamp = None;
+        assert!(!query.matches("general", &undated));
+    }
+
+    #[test]
+    fn bad_dates_are_reported() {
+        let error = SearchQuery::parse("before:tomorrow", 0.0, 0).unwrap_err();
+        assert!(error.contains("tomorrow"));
+    }
+
+    #[test]
+    fn removing_a_filter_leaves_the_rest() {
+        assert_eq!(without_filter(r#"hi from:ann "a b" in:ops"#, FilterKey::From), r#"hi "a b" in:ops"#);
+    }
+
+    #[test]
+    fn day_keys_follow_the_local_calendar() {
+        assert_eq!(day_key(0.0, 0), "1970-01-01");
+        assert_eq!(day_key(0.0, -60), "1969-12-31");
+        assert_eq!(day_key(DAY_MS - 1.0, 60), "1970-01-02");
+        assert_eq!(day_key(951_782_400_000.0, 0), "2000-02-29");
+    }
+
+    #[test]
+    fn days_parse_to_local_midnight() {
+        assert_eq!(parse_day("1970-01-02", 0.0, 0), Some(DAY_MS));
+        assert_eq!(parse_day("1970-01-02", 0.0, 60), Some(DAY_MS - 3_600_000.0));
+        assert_eq!(parse_day("2000-02-29", 0.0, 0), Some(951_782_400_000.0));
+        assert_eq!(parse_day("Today", DAY_MS + 5.0, 0), Some(DAY_MS));
+        assert_eq!(parse_day("yesterday", DAY_MS + 5.0, 0), Some(0.0));
+    }
+
+    #[test]
+    fn impossible_days_are_rejected() {
+        for bad in ["2001-02-29", "2024-13-01", "2024-04-31", "2024-1-01", "24-01-01", "2024-01-00", "soon"] {
+            assert_eq!(parse_day(bad, 0.0, 0), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn day_keys_and_parsing_agree() {
+        for ts in [0.0, 1e12, 1.7e12, -1e11] {
+            let key = day_key(ts, 120);
+            let midnight = parse_day(&key, 0.0, 120).unwrap();
+            assert_eq!(day_key(midnight, 120), key);
+            assert!(midnight <= ts && ts < midnight + DAY_MS);
+        }
+    }
+}
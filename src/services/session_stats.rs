@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use crate::components::chat::MessageData;
+
+const HOUR_MS: f64 = 3_600_000.0;
+
+/// Reactions listed in the stats modal.
+pub const TOP_REACTIONS: usize = 5;
+
+/// Aggregates over the messages loaded this session, worked out once when the modal opens.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SessionStats {
+    pub total: usize,
+    /// Busiest first.
+    pub per_user: Vec<(String, usize)>,
+    /// Local hour of day (0–23) with the most messages, and how many.
+    pub busiest_hour: Option<(u32, usize)>,
+    /// Most used first.
+    pub top_reactions: Vec<(String, usize)>,
+    /// In characters.
+    pub average_length: Option<f64>,
+}
+
+impl SessionStats {
+    /// `utc_offset_minutes` is how far local time is ahead of UTC.
+    pub fn compute(messages: &[MessageData], utc_offset_minutes: i32) -> Self {
+        Self {
+            total: messages.len(),
+            per_user: messages_per_user(messages),
+            busiest_hour: busiest_hour(messages, utc_offset_minutes),
+            top_reactions: top_reactions(messages, TOP_REACTIONS),
+            average_length: average_length(messages),
+        }
+    }
+}
+
+/// Sorts counts busiest first, ties by name so the order is stable.
+fn ranked(counts: HashMap<String, usize>) -> Vec<(String, usize)> {
+    let mut ranked: Vec<(String, usize)> = counts.into_iter().collect();
+    ranked.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.cmp(b)));
+    ranked
+}
+
+/// How many messages each author sent, busiest first.
+pub fn messages_per_user(messages: &[MessageData]) -> Vec<(String, usize)> {
+    let mut counts = HashMap::new();
+    for message in messages {
+        *counts.entry(message.from.clone()).or_insert(0) += 1;
+    }
+    ranked(counts)
+}
+
+/// The local hour of day most messages were sent in. Earlier hours win ties; messages
+/// without a timestamp don't count.
+pub fn busiest_hour(messages: &[MessageData], utc_offset_minutes: i32) -> Option<(u32, usize)> {
+    let mut hours = [0usize; 24];
+    for ts in messages.iter().filter_map(|m| m.timestamp) {
+        let local = ts + utc_offset_minutes as f64 * 60_000.0;
+        let hour = (local / HOUR_MS).floor().rem_euclid(24.0) as usize;
+        hours[hour] += 1;
+    }
+    let max = *hours.iter().max()?;
+    if max == 0 {
+        return None;
+    }
+    hours.iter().position(|&count| count == max).map(|hour| (hour as u32, max))
+}
+
+/// The `n` emoji reacted with most, counting each person's reaction once.
+pub fn top_reactions(messages: &[MessageData], n: usize) -> Vec<(String, usize)> {
+    let mut counts = HashMap::new();
+    for (emoji, users) in messages.iter().filter_map(|m| m.reactions.as_ref()).flatten() {
+        *counts.entry(emoji.clone()).or_insert(0) += users.len();
+    }
+    let mut ranked = ranked(counts);
+    ranked.retain(|(_, count)| *count > 0);
+    ranked.truncate(n);
+    ranked
+}
+
+/// Mean length of the messages' text in characters. `None` with no messages.
+pub fn average_length(messages: &[MessageData]) -> Option<f64> {
+    if messages.is_empty() {
+        return None;
+    }
+    let chars: usize = messages.iter().map(|m| m.message.chars().count()).sum();
+    Some(chars as f64 / messages.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(from: &str, text: &str, timestamp: Option<f64>) -> MessageData {
+        serde_json::from_value(serde_json::json!({
+            "from": from,
+            "message": text,
+            "reactions": null,
+            "timestamp": timestamp,
+        }))
+        .unwrap()
+    }
+
+    fn owned(ranked: &[(&str, usize)]) -> Vec<(String, usize)> {
+        ranked.iter().map(|(name, n)| (name.to_string(), *n)).collect()
+    }
+
+    #[test]
+    fn users_rank_busiest_first_then_by_name() {
+        let messages = ["cat", "bob", "cat", "amy"].map(|from| message(from, "", None));
+        assert_eq!(messages_per_user(&messages), owned(&[("cat", 2), ("amy", 1), ("bob", 1)]));
+    }
+
+    #[test]
+    fn busiest_hour_is_local_and_earliest_on_ties() {
+        let at = |hour: f64| message("ann", "", Some(hour * HOUR_MS));
+        let messages = [at(1.0), at(3.0), at(3.5), at(1.5), message("ann", "", None)];
+        assert_eq!(busiest_hour(&messages, 0), Some((1, 2)));
+        assert_eq!(busiest_hour(&messages, 120), Some((3, 2)));
+        assert_eq!(busiest_hour(&[at(0.5)], -60), Some((23, 1)));
+        assert_eq!(busiest_hour(&[message("ann", "", None)], 0), None);
+    }
+
+    #[test]
+    fn reactions_count_each_person_once_per_message() {
+        let mut a = message("ann", "", None);
+        a.reactions = Some(vec![("👍".into(), vec!["bob".into(), "cat".into()]), ("🎉".into(), vec![])]);
+        let mut b = message("ann", "", None);
+        b.reactions = Some(vec![("🎉".into(), vec!["bob".into()]), ("👍".into(), vec!["bob".into()])]);
+        assert_eq!(top_reactions(&[a.clone(), b.clone()], 5), owned(&[("👍", 3), ("🎉", 1)]));
+        assert_eq!(top_reactions(&[a, b], 1), owned(&[("👍", 3)]));
+    }
+
+    #[test]
+    fn average_length_counts_characters() {
+        assert_eq!(average_length(&[]), None);
+        assert_eq!(average_length(&[message("ann", "héllo", None), message("ann", "hi", None)]), Some(3.5));
+    }
+}
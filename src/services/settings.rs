@@ -0,0 +1,309 @@
+use gloo_storage::errors::StorageError;
+use gloo_storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::services::channel_store::DEFAULT_CHANNEL;
+use crate::services::display::{Density, FontSize};
+use crate::services::dnd::DndSettings;
+use crate::services::i18n::Locale;
+use crate::services::keywords::WatchWords;
+use crate::services::notifications::RoomPreferences;
+use crate::services::search::SearchScope;
+use crate::services::skin_tone::SkinTone;
+use crate::services::theme::Theme;
+
+/// Version 1 kept only the fields up to `dnd`, under one key shared by every user of the
+/// browser; notification preferences and watch words sat in keys of their own.
+pub const SETTINGS_VERSION: u64 = 2;
+
+/// Where version 1 settings were kept.
+const LEGACY_SETTINGS_KEY: &str = "yewchat.settings";
+
+/// Fields that are never written to an export and never taken from an import.
+const REDACTED_FIELDS: [&str; 1] = ["session_token"];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct UserSettings {
+    /// Files without one predate versioning and are version 1.
+    #[serde(default = "legacy_version")]
+    pub version: u64,
+    /// Channel opened right after logging in.
+    pub default_channel: String,
+    /// Token of the remembered session, if any.
+    pub session_token: Option<String>,
+    /// Pop on incoming messages. Off until the user opts in.
+    pub sound_enabled: bool,
+    /// Buzz on mentions and DMs while the tab is hidden, where the browser supports it.
+    pub vibrate_on_mention: bool,
+    /// No coloured border on messages the server scored for sentiment.
+    pub hide_sentiment: bool,
+    /// Keep a per-day transcript of every message on this device. Off until the user opts in.
+    pub save_transcript: bool,
+    /// Message links carry the room and message in the query string rather than the fragment.
+    pub use_history_api: bool,
+    /// Light, dark, or whichever the system prefers.
+    pub theme: Theme,
+    pub density: Density,
+    pub font_size: FontSize,
+    /// Language of the interface; unset follows the browser's.
+    pub locale: Option<Locale>,
+    /// Where avatars are fetched from, with `{seed}` for the username; unset uses the
+    /// build's default.
+    pub avatar_template: Option<String>,
+    /// Applied to emoji that take one, in the reaction bar and its picker.
+    pub skin_tone: SkinTone,
+    /// Scope the search panel opens with: the last one used.
+    pub search_scope: SearchScope,
+    pub dnd: DndSettings,
+    pub notifications: RoomPreferences,
+    pub watch_words: WatchWords,
+}
+
+fn legacy_version() -> u64 {
+    1
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            default_channel: DEFAULT_CHANNEL.to_string(),
+            session_token: None,
+            sound_enabled: false,
+            vibrate_on_mention: false,
+            hide_sentiment: false,
+            save_transcript: false,
+            use_history_api: true,
+            theme: Theme::default(),
+            density: Density::default(),
+            font_size: FontSize::default(),
+            locale: None,
+            avatar_template: None,
+            skin_tone: SkinTone::default(),
+            search_scope: SearchScope::default(),
+            dnd: DndSettings::default(),
+            notifications: RoomPreferences::default(),
+            watch_words: WatchWords::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImportError {
+    /// The file says it is a version this build doesn't know. Holds the version as written.
+    UnsupportedVersion(String),
+    Invalid(Vec<FieldError>),
+}
+
+impl ImportError {
+    fn file(message: impl ToString) -> Self {
+        ImportError::Invalid(vec![FieldError {
+            field: "(file)".into(),
+            message: message.to_string(),
+        }])
+    }
+}
+
+fn settings_key(username: &str) -> String {
+    format!("{}.{}", LEGACY_SETTINGS_KEY, username)
+}
+
+/// Builds current settings out of the version 1 record and the loose per-user keys,
+/// looked up through `read`. Anything missing keeps its default. The version 1 record was
+/// shared by everyone on the browser, so its redacted fields (the session) are left behind.
+pub fn migrate(username: &str, read: impl Fn(&str) -> Option<Value>) -> UserSettings {
+    let mut settings: UserSettings = read(LEGACY_SETTINGS_KEY)
+        .map(|mut v| {
+            if let Value::Object(map) = &mut v {
+                for field in REDACTED_FIELDS {
+                    map.remove(field);
+                }
+            }
+            v
+        })
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+    settings.version = SETTINGS_VERSION;
+    settings.notifications = RoomPreferences::from_legacy(username, &read);
+    settings.watch_words = read(&WatchWords::legacy_key(username))
+        .and_then(|v| serde_json::from_value::<Vec<String>>(v).ok())
+        .unwrap_or_default()
+        .into();
+    settings
+}
+
+/// Settings out of a stored record. One that doesn't parse as a whole, say with a field
+/// from a newer build, keeps every field that does rather than starting over.
+fn from_record(record: Value) -> UserSettings {
+    if let Ok(settings) = serde_json::from_value(record.clone()) {
+        return settings;
+    }
+    let fields = match record {
+        Value::Object(fields) => fields,
+        _ => return UserSettings::default(),
+    };
+    // Left out of `overlay` for imports, but this is the user's own record.
+    let session_token = fields.get("session_token").and_then(Value::as_str).map(String::from);
+    let (merged, errors) = UserSettings::default().overlay(fields);
+    for error in errors {
+        log::error!("ignoring stored setting {}: {}", error.field, error.message);
+    }
+    let mut settings: UserSettings = serde_json::from_value(Value::Object(merged)).unwrap_or_default();
+    settings.version = SETTINGS_VERSION;
+    settings.session_token = session_token;
+    settings
+}
+
+impl UserSettings {
+    /// `username`'s settings, migrated from the version 1 keys the first time round.
+    pub fn load(username: &str) -> Self {
+        match LocalStorage::get::<Value>(settings_key(username)) {
+            Ok(record) => return from_record(record),
+            Err(StorageError::KeyNotFound(_)) => {}
+            Err(e) => {
+                log::error!("failed to read settings: {:?}", e);
+                return Self::default();
+            }
+        }
+        let settings = migrate(username, |key| LocalStorage::get(key).ok());
+        settings.save(username);
+        // The shared version 1 record stays for any other user of this browser.
+        for key in RoomPreferences::legacy_keys(username) {
+            LocalStorage::delete(key);
+        }
+        LocalStorage::delete(WatchWords::legacy_key(username));
+        settings
+    }
+
+    pub fn save(&self, username: &str) {
+        if let Err(e) = LocalStorage::set(settings_key(username), self) {
+            log::error!("failed to save settings: {:?}", e);
+        }
+    }
+
+    /// Moves these settings from `from` to `to`, after a rename.
+    pub fn rename(&self, from: &str, to: &str) {
+        self.save(to);
+        LocalStorage::delete(settings_key(from));
+    }
+
+    /// Puts `username`'s settings back to the defaults and returns them. The defaults are
+    /// written rather than the key deleted, so `load` can't migrate a version 1 record back.
+    pub fn clear(username: &str) -> Self {
+        let settings = Self::default();
+        settings.save(username);
+        settings
+    }
+
+    /// Pretty-printed JSON of these settings with sensitive fields removed.
+    pub fn to_export_json(&self) -> String {
+        let mut value = serde_json::to_value(self).unwrap();
+        if let Value::Object(map) = &mut value {
+            for field in REDACTED_FIELDS {
+                map.remove(field);
+            }
+        }
+        serde_json::to_string_pretty(&value).unwrap()
+    }
+
+    /// Overlays the fields of an exported settings file on top of `self`.
+    ///
+    /// Files from a version this build doesn't know are refused whole. Otherwise every
+    /// imported field is checked on its own so that all invalid values are reported at
+    /// once instead of just the first one serde trips over.
+    pub fn merge_import(&self, json: &str) -> Result<UserSettings, ImportError> {
+        let mut imported: Map<String, Value> = serde_json::from_str(json).map_err(ImportError::file)?;
+        match imported.remove("version") {
+            None => {}
+            Some(Value::Number(n)) if matches!(n.as_u64(), Some(v) if (1..=SETTINGS_VERSION).contains(&v)) => {}
+            Some(other) => return Err(ImportError::UnsupportedVersion(other.to_string())),
+        }
+        let (merged, errors) = self.overlay(imported);
+        if !errors.is_empty() {
+            return Err(ImportError::Invalid(errors));
+        }
+
+        serde_json::from_str::<UserSettings>(&Value::Object(merged).to_string()).map_err(ImportError::file)
+    }
+
+    /// `self` as JSON with each of `fields` laid over it that is valid on its own, and what
+    /// was wrong with the rest. Redacted fields are skipped.
+    fn overlay(&self, fields: Map<String, Value>) -> (Map<String, Value>, Vec<FieldError>) {
+        let current = match serde_json::to_value(self).unwrap() {
+            Value::Object(map) => map,
+            _ => unreachable!("settings serialize to an object"),
+        };
+
+        let mut merged = current.clone();
+        let mut errors = vec![];
+        for (field, value) in fields {
+            if REDACTED_FIELDS.contains(&field.as_str()) {
+                continue;
+            }
+            let mut single = current.clone();
+            single.insert(field.clone(), value.clone());
+            match serde_json::from_value::<UserSettings>(Value::Object(single)) {
+                Ok(_) => {
+                    merged.insert(field, value);
+                }
+                Err(e) => errors.push(FieldError {
+                    field,
+                    message: e.to_string(),
+                }),
+            }
+        }
+        (merged, errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn migration_leaves_the_shared_session_behind() {
+        let settings = migrate("ann", |key| {
+            (key == LEGACY_SETTINGS_KEY).then(|| json!({ "session_token": "bob's", "sound_enabled": true }))
+        });
+        assert_eq!(settings.session_token, None);
+        assert!(settings.sound_enabled);
+        assert_eq!(settings.version, SETTINGS_VERSION);
+    }
+
+    #[test]
+    fn record_from_a_newer_build_keeps_what_parses() {
+        let settings = from_record(json!({
+            "version": SETTINGS_VERSION + 1,
+            "session_token": "mine",
+            "sound_enabled": true,
+            "default_channel": "random",
+            "something_new": 1,
+        }));
+        assert!(settings.sound_enabled);
+        assert_eq!(settings.default_channel, "random");
+        assert_eq!(settings.session_token.as_deref(), Some("mine"));
+        assert_eq!(settings.version, SETTINGS_VERSION);
+    }
+
+    #[test]
+    fn record_with_a_bad_value_keeps_the_rest() {
+        let settings = from_record(json!({ "sound_enabled": "loud", "hide_sentiment": true }));
+        assert!(!settings.sound_enabled);
+        assert!(settings.hide_sentiment);
+    }
+
+    #[test]
+    fn valid_record_loads_as_is() {
+        let stored = UserSettings { save_transcript: true, ..UserSettings::default() };
+        assert_eq!(from_record(serde_json::to_value(&stored).unwrap()), stored);
+    }
+}
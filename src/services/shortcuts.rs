@@ -0,0 +1,206 @@
+use gloo_events::{EventListener, EventListenerOptions};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, KeyboardEvent};
+use yew::{Callback, NodeRef};
+
+/// What a key press asks the chat to do.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shortcut {
+    Search,
+    PreviousChannel,
+    NextChannel,
+    /// Closes the top dialog, else cancels an edit, else clears the composer.
+    Escape,
+    /// Edit the message with this id.
+    EditMessage(String),
+    /// Move focus from the message with this id to the one above it.
+    PreviousMessage(String),
+    /// Move focus from the message with this id to the one below it.
+    NextMessage(String),
+    ShowHelp,
+}
+
+/// Where keyboard focus is when a key goes down.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Focus {
+    Page,
+    Composer { empty: bool },
+    /// Some other text field; only shortcuts with modifiers, and Escape, get through.
+    TextField,
+    /// A message bubble, by id.
+    Message(String),
+}
+
+/// The key, with its modifiers, as far as shortcuts care.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeyPress {
+    pub key: String,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub meta: bool,
+}
+
+impl From<&KeyboardEvent> for KeyPress {
+    fn from(e: &KeyboardEvent) -> Self {
+        Self { key: e.key(), ctrl: e.ctrl_key(), alt: e.alt_key(), meta: e.meta_key() }
+    }
+}
+
+/// Every shortcut, as the cheat sheet lists them.
+pub const CHEAT_SHEET: [(&str, &str); 7] = [
+    ("Ctrl+K", "shortcuts.search"),
+    ("Alt+↑", "shortcuts.previous_channel"),
+    ("Alt+↓", "shortcuts.next_channel"),
+    ("Esc", "shortcuts.escape"),
+    ("↑ ↓", "shortcuts.move_focus"),
+    ("e", "shortcuts.edit"),
+    ("?", "shortcuts.help"),
+];
+
+/// The shortcut `key` triggers with focus at `focus`, if any. Keys that type something
+/// only count where nothing is being typed.
+pub fn shortcut_for(key: &KeyPress, focus: &Focus) -> Option<Shortcut> {
+    let command = key.ctrl || key.meta;
+    match key.key.as_str() {
+        "k" | "K" if command && !key.alt => Some(Shortcut::Search),
+        "ArrowUp" if key.alt && !command => Some(Shortcut::PreviousChannel),
+        "ArrowDown" if key.alt && !command => Some(Shortcut::NextChannel),
+        "Escape" => Some(Shortcut::Escape),
+        _ if command || key.alt => None,
+        "e" => match focus {
+            Focus::Message(id) => Some(Shortcut::EditMessage(id.clone())),
+            _ => None,
+        },
+        "ArrowUp" => match focus {
+            Focus::Message(id) => Some(Shortcut::PreviousMessage(id.clone())),
+            _ => None,
+        },
+        "ArrowDown" => match focus {
+            Focus::Message(id) => Some(Shortcut::NextMessage(id.clone())),
+            _ => None,
+        },
+        "?" => match focus {
+            Focus::Page | Focus::Message(_) | Focus::Composer { empty: true } => Some(Shortcut::ShowHelp),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Where focus is right now, telling the composer apart from other fields.
+fn current_focus(composer: &NodeRef) -> Focus {
+    let active = match web_sys::window().and_then(|w| w.document()).and_then(|d| d.active_element()) {
+        Some(active) => active,
+        None => return Focus::Page,
+    };
+    if let Some(input) = composer.cast::<web_sys::HtmlInputElement>() {
+        if active == *input.unchecked_ref::<Element>() {
+            return Focus::Composer { empty: input.value().is_empty() };
+        }
+    }
+    let editable = active.dyn_ref::<web_sys::HtmlElement>().map_or(false, |e| e.is_content_editable());
+    if editable || matches!(active.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT") {
+        return Focus::TextField;
+    }
+    match active.get_attribute("data-message-id") {
+        Some(id) => Focus::Message(id),
+        None => Focus::Page,
+    }
+}
+
+/// The one keydown listener behind every keyboard shortcut. Keys it acts on go no further.
+pub struct Shortcuts {
+    _listener: EventListener,
+}
+
+impl Shortcuts {
+    pub fn attach(composer: NodeRef, on_shortcut: Callback<Shortcut>) -> Self {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let options = EventListenerOptions::enable_prevent_default();
+        let _listener = EventListener::new_with_options(&document, "keydown", options, move |e| {
+            let e = match e.dyn_ref::<KeyboardEvent>() {
+                Some(e) => e,
+                None => return,
+            };
+            if let Some(shortcut) = shortcut_for(&e.into(), &current_focus(&composer)) {
+                e.prevent_default();
+                on_shortcut.emit(shortcut);
+            }
+        });
+        Self { _listener }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: &str) -> KeyPress {
+        KeyPress { key: key.to_string(), ..KeyPress::default() }
+    }
+
+    fn ctrl(key: &str) -> KeyPress {
+        KeyPress { ctrl: true, ..self::key(key) }
+    }
+
+    fn alt(key: &str) -> KeyPress {
+        KeyPress { alt: true, ..self::key(key) }
+    }
+
+    const EVERYWHERE: [Focus; 4] = [
+        Focus::Page,
+        Focus::Composer { empty: true },
+        Focus::Composer { empty: false },
+        Focus::TextField,
+    ];
+
+    #[test]
+    fn modified_shortcuts_work_everywhere() {
+        for focus in EVERYWHERE.iter().chain([Focus::Message("1".into())].iter()) {
+            assert_eq!(shortcut_for(&ctrl("k"), focus), Some(Shortcut::Search));
+            assert_eq!(shortcut_for(&ctrl("K"), focus), Some(Shortcut::Search));
+            assert_eq!(shortcut_for(&KeyPress { meta: true, ..key("k") }, focus), Some(Shortcut::Search));
+            assert_eq!(shortcut_for(&alt("ArrowUp"), focus), Some(Shortcut::PreviousChannel));
+            assert_eq!(shortcut_for(&alt("ArrowDown"), focus), Some(Shortcut::NextChannel));
+            assert_eq!(shortcut_for(&key("Escape"), focus), Some(Shortcut::Escape));
+        }
+    }
+
+    #[test]
+    fn wrong_modifiers_do_nothing() {
+        assert_eq!(shortcut_for(&key("k"), &Focus::Page), None);
+        assert_eq!(shortcut_for(&KeyPress { alt: true, ..ctrl("k") }, &Focus::Page), None);
+        assert_eq!(shortcut_for(&key("ArrowUp"), &Focus::Page), None);
+        assert_eq!(shortcut_for(&KeyPress { ctrl: true, ..alt("ArrowDown") }, &Focus::Page), None);
+        assert_eq!(shortcut_for(&ctrl("e"), &Focus::Message("1".into())), None);
+        assert_eq!(shortcut_for(&alt("?"), &Focus::Page), None);
+    }
+
+    #[test]
+    fn e_edits_only_a_focused_message() {
+        assert_eq!(shortcut_for(&key("e"), &Focus::Message("42".into())), Some(Shortcut::EditMessage("42".into())));
+        for focus in &EVERYWHERE {
+            assert_eq!(shortcut_for(&key("e"), focus), None);
+        }
+    }
+
+    #[test]
+    fn arrows_move_between_messages_only_from_a_message() {
+        let focus = Focus::Message("7".into());
+        assert_eq!(shortcut_for(&key("ArrowUp"), &focus), Some(Shortcut::PreviousMessage("7".into())));
+        assert_eq!(shortcut_for(&key("ArrowDown"), &focus), Some(Shortcut::NextMessage("7".into())));
+        for focus in &EVERYWHERE {
+            assert_eq!(shortcut_for(&key("ArrowUp"), focus), None);
+            assert_eq!(shortcut_for(&key("ArrowDown"), focus), None);
+        }
+    }
+
+    #[test]
+    fn question_mark_never_interrupts_typing() {
+        assert_eq!(shortcut_for(&key("?"), &Focus::Page), Some(Shortcut::ShowHelp));
+        assert_eq!(shortcut_for(&key("?"), &Focus::Message("1".into())), Some(Shortcut::ShowHelp));
+        assert_eq!(shortcut_for(&key("?"), &Focus::Composer { empty: true }), Some(Shortcut::ShowHelp));
+        assert_eq!(shortcut_for(&key("?"), &Focus::Composer { empty: false }), None);
+        assert_eq!(shortcut_for(&key("?"), &Focus::TextField), None);
+    }
+}
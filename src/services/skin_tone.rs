@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+
+use crate::t;
+
+/// Zero-width joiner: what glues family, profession and similar sequences together.
+const ZWJ: char = '\u{200D}';
+/// Variation selector 16, asking for the emoji rather than text style of the character
+/// before it. A skin-tone modifier already implies emoji style, so it's dropped.
+const VS16: char = '\u{FE0F}';
+
+/// Emoji that take a Fitzpatrick modifier, by base code point. Not every one Unicode lists,
+/// but everything the pickers offer and the people and hands that come up most.
+const MODIFIABLE: [char; 49] = [
+    '☝', '⛹', '✊', '✋', '✌', '✍', '🎅', '🏃', '🏄', '🏊', '👂', '👃', '👆', '👇', '👈', '👉',
+    '👊', '👋', '👌', '👍', '👎', '👏', '👐', '👦', '👧', '👨', '👩', '👮', '👴', '👵', '👶', '💁',
+    '💅', '💪', '🙅', '🙆', '🙋', '🙌', '🙏', '🤌', '🤏', '🤘', '🤙', '🤚', '🤛', '🤜', '🤞', '🤟',
+    '🤦',
+];
+
+/// The emoji skin tone the user picked, applied to whatever they react or insert with.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SkinTone {
+    /// No modifier: the yellow emoji.
+    #[default]
+    Default,
+    Light,
+    MediumLight,
+    Medium,
+    MediumDark,
+    Dark,
+}
+
+impl SkinTone {
+    pub const ALL: [SkinTone; 6] = [
+        SkinTone::Default,
+        SkinTone::Light,
+        SkinTone::MediumLight,
+        SkinTone::Medium,
+        SkinTone::MediumDark,
+        SkinTone::Dark,
+    ];
+
+    /// U+1F3FB to U+1F3FF, lightest to darkest.
+    pub fn modifier(self) -> Option<char> {
+        match self {
+            SkinTone::Default => None,
+            SkinTone::Light => Some('\u{1F3FB}'),
+            SkinTone::MediumLight => Some('\u{1F3FC}'),
+            SkinTone::Medium => Some('\u{1F3FD}'),
+            SkinTone::MediumDark => Some('\u{1F3FE}'),
+            SkinTone::Dark => Some('\u{1F3FF}'),
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            SkinTone::Default => t!("skin_tone.default"),
+            SkinTone::Light => t!("skin_tone.light"),
+            SkinTone::MediumLight => t!("skin_tone.medium_light"),
+            SkinTone::Medium => t!("skin_tone.medium"),
+            SkinTone::MediumDark => t!("skin_tone.medium_dark"),
+            SkinTone::Dark => t!("skin_tone.dark"),
+        }
+    }
+
+    /// A waving hand in this tone, to pick it by.
+    pub fn swatch(self) -> String {
+        apply("👋", self)
+    }
+}
+
+fn is_modifier(c: char) -> bool {
+    ('\u{1F3FB}'..='\u{1F3FF}').contains(&c)
+}
+
+/// `emoji` in `tone`. Emoji that don't take a modifier, already carry one, or are ZWJ
+/// sequences (where each part would need its own) come back as they were.
+pub fn apply(emoji: &str, tone: SkinTone) -> String {
+    let modifier = match tone.modifier() {
+        Some(modifier) => modifier,
+        None => return emoji.to_string(),
+    };
+    let mut chars = emoji.chars();
+    let base = match chars.next() {
+        Some(base) if MODIFIABLE.contains(&base) => base,
+        _ => return emoji.to_string(),
+    };
+    if emoji.chars().any(|c| c == ZWJ || is_modifier(c)) {
+        return emoji.to_string();
+    }
+    let rest = chars.as_str();
+    format!("{}{}{}", base, modifier, rest.strip_prefix(VS16).unwrap_or(rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_tone_changes_nothing() {
+        for emoji in ["👍", "👋🏽", "😂", "👨\u{200D}💻"] {
+            assert_eq!(apply(emoji, SkinTone::Default), emoji);
+        }
+    }
+
+    #[test]
+    fn modifiable_emoji_take_the_modifier() {
+        assert_eq!(apply("👍", SkinTone::Light), "👍\u{1F3FB}");
+        assert_eq!(apply("👏", SkinTone::MediumDark), "👏\u{1F3FE}");
+        assert_eq!(apply("🙏", SkinTone::Dark), "🙏\u{1F3FF}");
+    }
+
+    #[test]
+    fn variation_selector_gives_way_to_the_modifier() {
+        assert_eq!(apply("✌\u{FE0F}", SkinTone::Medium), "✌\u{1F3FD}");
+        assert_eq!(apply("☝\u{FE0F}", SkinTone::MediumLight), "☝\u{1F3FC}");
+    }
+
+    #[test]
+    fn other_emoji_are_left_alone() {
+        for emoji in ["😂", "❤\u{FE0F}", "🔥", "", "a"] {
+            assert_eq!(apply(emoji, SkinTone::Dark), emoji);
+        }
+    }
+
+    #[test]
+    fn already_modified_input_is_left_alone() {
+        assert_eq!(apply("👍\u{1F3FB}", SkinTone::Dark), "👍\u{1F3FB}");
+        assert_eq!(apply(&apply("👋", SkinTone::Medium), SkinTone::Dark), "👋\u{1F3FD}");
+    }
+
+    #[test]
+    fn zwj_sequences_are_left_alone() {
+        for emoji in ["👨\u{200D}💻", "👩\u{200D}👩\u{200D}👧", "🏃\u{200D}♀\u{FE0F}"] {
+            assert_eq!(apply(emoji, SkinTone::MediumDark), emoji);
+        }
+    }
+
+    #[test]
+    fn every_tone_has_its_own_modifier() {
+        let modifiers: Vec<char> = SkinTone::ALL.iter().filter_map(|t| t.modifier()).collect();
+        assert_eq!(modifiers, ['\u{1F3FB}', '\u{1F3FC}', '\u{1F3FD}', '\u{1F3FE}', '\u{1F3FF}']);
+    }
+}
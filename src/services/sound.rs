@@ -0,0 +1,71 @@
+use gloo_events::EventListener;
+use web_sys::{AudioBuffer, AudioContext, AudioContextState};
+
+/// Length of the pop, in seconds.
+const POP_SECONDS: f32 = 0.08;
+
+/// A short pop rendered once into an `AudioBuffer` and replayed from memory.
+pub struct PopSound {
+    context: AudioContext,
+    buffer: AudioBuffer,
+    /// Resumes the context on the first user gesture, as autoplay policies require.
+    _unlock: Vec<EventListener>,
+}
+
+impl PopSound {
+    pub fn new() -> Option<Self> {
+        let context = AudioContext::new()
+            .map_err(|e| log::error!("failed to create audio context: {:?}", e))
+            .ok()?;
+        let buffer = render_pop(&context)
+            .map_err(|e| log::error!("failed to render pop sound: {:?}", e))
+            .ok()?;
+        let document = web_sys::window().unwrap().document().unwrap();
+        let _unlock = ["pointerdown", "keydown"]
+            .iter()
+            .map(|event| {
+                let context = context.clone();
+                EventListener::new(&document, *event, move |_| {
+                    if context.state() == AudioContextState::Suspended {
+                        let _ = context.resume();
+                    }
+                })
+            })
+            .collect();
+        Some(Self {
+            context,
+            buffer,
+            _unlock,
+        })
+    }
+
+    /// Plays the pop `rate` times as fast as rendered, which also raises its pitch by as much.
+    pub fn play_at(&self, rate: f32) {
+        let result = self.context.create_buffer_source().and_then(|source| {
+            source.set_buffer(Some(&self.buffer));
+            source.playback_rate().set_value(rate);
+            source.connect_with_audio_node(&self.context.destination())?;
+            source.start()
+        });
+        if let Err(e) = result {
+            log::error!("failed to play pop sound: {:?}", e);
+        }
+    }
+}
+
+/// A falling sine chirp with an exponential decay.
+fn render_pop(context: &AudioContext) -> Result<AudioBuffer, wasm_bindgen::JsValue> {
+    let rate = context.sample_rate();
+    let len = (rate * POP_SECONDS) as usize;
+    let mut phase = 0.0f32;
+    let samples: Vec<f32> = (0..len)
+        .map(|i| {
+            let t = i as f32 / len as f32;
+            phase += std::f32::consts::TAU * (880.0 - 440.0 * t) / rate;
+            phase.sin() * (-6.0 * t).exp() * 0.4
+        })
+        .collect();
+    let buffer = context.create_buffer(1, len as u32, rate)?;
+    buffer.copy_to_channel(&samples, 0)?;
+    Ok(buffer)
+}
@@ -0,0 +1,90 @@
+#[cfg(test)]
+use std::cell::RefCell;
+#[cfg(test)]
+use std::collections::HashMap;
+#[cfg(test)]
+use std::rc::Rc;
+
+use gloo_storage::errors::StorageError;
+use gloo_storage::{LocalStorage, Storage};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Where state kept across reloads goes: the browser's localStorage in the app, a
+/// [`MemoryStore`] in tests.
+pub trait Store {
+    /// The value under `key`, if there is one that reads as a `T`.
+    fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T>;
+    fn set<T: Serialize>(&self, key: &str, value: &T) -> Result<(), StorageError>;
+    fn delete(&self, key: &str);
+}
+
+/// The browser's localStorage.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Local;
+
+impl Store for Local {
+    fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        LocalStorage::get(key).ok()
+    }
+
+    fn set<T: Serialize>(&self, key: &str, value: &T) -> Result<(), StorageError> {
+        LocalStorage::set(key, value)
+    }
+
+    fn delete(&self, key: &str) {
+        LocalStorage::delete(key);
+    }
+}
+
+/// JSON by key in memory. Clones share their contents, so one can be handed to the code
+/// under test and another kept to look inside or load from again.
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct MemoryStore(Rc<RefCell<HashMap<String, String>>>);
+
+#[cfg(test)]
+impl MemoryStore {
+    /// The JSON stored under `key`.
+    pub fn raw(&self, key: &str) -> Option<String> {
+        self.0.borrow().get(key).cloned()
+    }
+}
+
+#[cfg(test)]
+impl Store for MemoryStore {
+    fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        serde_json::from_str(self.0.borrow().get(key)?).ok()
+    }
+
+    fn set<T: Serialize>(&self, key: &str, value: &T) -> Result<(), StorageError> {
+        let json = serde_json::to_string(value)?;
+        self.0.borrow_mut().insert(key.to_string(), json);
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) {
+        self.0.borrow_mut().remove(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_share_what_was_stored() {
+        let store = MemoryStore::default();
+        store.clone().set("k", &vec![1, 2]).unwrap();
+        assert_eq!(store.get::<Vec<u32>>("k"), Some(vec![1, 2]));
+        assert_eq!(store.raw("k").as_deref(), Some("[1,2]"));
+        store.delete("k");
+        assert_eq!(store.get::<Vec<u32>>("k"), None);
+    }
+
+    #[test]
+    fn values_of_another_shape_read_as_missing() {
+        let store = MemoryStore::default();
+        store.set("k", &"text").unwrap();
+        assert_eq!(store.get::<u32>("k"), None);
+    }
+}
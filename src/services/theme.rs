@@ -0,0 +1,95 @@
+use gloo_events::EventListener;
+use gloo_storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
+use web_sys::MediaQueryList;
+
+use crate::t;
+
+/// Not per user like the settings it mirrors: it is read before anyone has logged in, so
+/// the page comes up in the right colours.
+const KEY: &str = "yewchat.theme";
+
+const DARK_QUERY: &str = "(prefers-color-scheme: dark)";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    Light,
+    Dark,
+    /// Whatever the operating system is set to, following it as it changes.
+    #[default]
+    System,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Light, Theme::Dark, Theme::System];
+
+    pub fn label(self) -> String {
+        match self {
+            Theme::Light => t!("settings.theme.light"),
+            Theme::Dark => t!("settings.theme.dark"),
+            Theme::System => t!("settings.theme.system"),
+        }
+    }
+}
+
+/// Whether the page should be dark, given the setting and what the system prefers.
+pub fn resolve(theme: Theme, system_dark: bool) -> bool {
+    match theme {
+        Theme::Light => false,
+        Theme::Dark => true,
+        Theme::System => system_dark,
+    }
+}
+
+fn dark_query() -> Option<MediaQueryList> {
+    web_sys::window()?.match_media(DARK_QUERY).ok()?
+}
+
+fn set_dark(dark: bool) {
+    let root = web_sys::window().and_then(|w| w.document()).and_then(|d| d.document_element());
+    if let Some(root) = root {
+        if let Err(e) = root.class_list().toggle_with_force("dark", dark) {
+            log::error!("failed to switch theme: {:?}", e);
+        }
+    }
+}
+
+/// Puts `theme` into effect and remembers it for the next page load. For `System`, the
+/// returned listener keeps following the system setting until it is dropped.
+pub fn apply(theme: Theme) -> Option<EventListener> {
+    if let Err(e) = LocalStorage::set(KEY, theme) {
+        log::error!("failed to remember the theme: {:?}", e);
+    }
+    let query = dark_query();
+    set_dark(resolve(theme, query.as_ref().map_or(false, MediaQueryList::matches)));
+    if theme != Theme::System {
+        return None;
+    }
+    let query = query?;
+    Some(EventListener::new(&query, "change", |e| {
+        if let Some(query) = e.target().and_then(|t| t.dyn_into::<MediaQueryList>().ok()) {
+            set_dark(resolve(Theme::System, query.matches()));
+        }
+    }))
+}
+
+/// The theme last applied on this browser, for the very first paint.
+pub fn load() -> Theme {
+    LocalStorage::get(KEY).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_only_follows_the_system_for_system() {
+        for system_dark in [false, true] {
+            assert!(!resolve(Theme::Light, system_dark));
+            assert!(resolve(Theme::Dark, system_dark));
+            assert_eq!(resolve(Theme::System, system_dark), system_dark);
+        }
+    }
+}
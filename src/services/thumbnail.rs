@@ -0,0 +1,41 @@
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, HtmlImageElement};
+
+/// Thumbnails are this many pixels square: enough for a blurred preview, small enough to
+/// ride along inside the attachment frame.
+const THUMBNAIL_SIZE: u32 = 10;
+
+pub struct Thumbnail {
+    pub data_uri: String,
+    /// Of the full image, so receivers can reserve its space before it arrives.
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Shrinks the image at `url` into a tiny PNG data URI. `None` if it doesn't decode.
+pub async fn thumbnail(url: &str) -> Option<Thumbnail> {
+    let image = HtmlImageElement::new().ok()?;
+    let loaded = js_sys::Promise::new(&mut |resolve, reject| {
+        image.set_onload(Some(&resolve));
+        image.set_onerror(Some(&reject));
+    });
+    image.set_src(url);
+    JsFuture::from(loaded).await.ok()?;
+    image.set_onload(None);
+    image.set_onerror(None);
+
+    let document = web_sys::window()?.document()?;
+    let canvas: HtmlCanvasElement = document.create_element("canvas").ok()?.dyn_into().ok()?;
+    canvas.set_width(THUMBNAIL_SIZE);
+    canvas.set_height(THUMBNAIL_SIZE);
+    let ctx: CanvasRenderingContext2d = canvas.get_context("2d").ok()??.dyn_into().ok()?;
+    let size = THUMBNAIL_SIZE as f64;
+    ctx.draw_image_with_html_image_element_and_dw_and_dh(&image, 0.0, 0.0, size, size)
+        .ok()?;
+    Some(Thumbnail {
+        data_uri: canvas.to_data_url().ok()?,
+        width: image.natural_width(),
+        height: image.natural_height(),
+    })
+}
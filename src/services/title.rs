@@ -0,0 +1,80 @@
+/// Owns `document.title` so an unread count can be prefixed and later removed.
+pub struct TitleBadge {
+    original: String,
+    unread: usize,
+}
+
+impl TitleBadge {
+    pub fn new() -> Self {
+        let current = document().map(|d| d.title()).unwrap_or_default();
+        Self {
+            original: strip_unread_prefix(&current).to_string(),
+            unread: 0,
+        }
+    }
+
+    pub fn unread(&self) -> usize {
+        self.unread
+    }
+
+    pub fn set_unread(&mut self, n: usize) {
+        if n == self.unread {
+            return;
+        }
+        self.unread = n;
+        if let Some(document) = document() {
+            document.set_title(&format_title(&self.original, n));
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.set_unread(0);
+    }
+}
+
+impl Default for TitleBadge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn document() -> Option<web_sys::Document> {
+    web_sys::window()?.document()
+}
+
+/// `"(3) Yewchat!"`, or the bare title when there is nothing unread.
+pub fn format_title(original: &str, unread: usize) -> String {
+    let base = strip_unread_prefix(original);
+    match unread {
+        0 => base.to_string(),
+        n => format!("({}) {}", n, base),
+    }
+}
+
+/// Drops a leading `"(N) "` so a count is never stacked on top of another.
+fn strip_unread_prefix(title: &str) -> &str {
+    title
+        .strip_prefix('(')
+        .and_then(|rest| rest.split_once(") "))
+        .filter(|(count, _)| !count.is_empty() && count.chars().all(|c| c.is_ascii_digit()))
+        .map_or(title, |(_, base)| base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_prefixes_the_bare_title() {
+        assert_eq!(format_title("Yewchat!", 3), "(3) Yewchat!");
+        assert_eq!(format_title("(3) Yewchat!", 12), "(12) Yewchat!");
+        assert_eq!(format_title("(3) Yewchat!", 0), "Yewchat!");
+    }
+
+    #[test]
+    fn only_a_numeric_prefix_is_stripped() {
+        assert_eq!(strip_unread_prefix("(beta) Yewchat!"), "(beta) Yewchat!");
+        assert_eq!(strip_unread_prefix("() Yewchat!"), "() Yewchat!");
+        assert_eq!(strip_unread_prefix("(42)Yewchat!"), "(42)Yewchat!");
+    }
+}
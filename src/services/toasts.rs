@@ -0,0 +1,201 @@
+use std::collections::{HashSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+use yew_agent::{Agent, AgentLink, Context, HandlerId};
+
+/// How long a toast stays up while the pointer is away from it.
+pub const TOAST_TTL_MS: f64 = 6_000.0;
+/// Toasts beyond this many wait their turn.
+pub const MAX_VISIBLE: usize = 4;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ToastLevel {
+    Info,
+    Success,
+    Warn,
+    Error,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Toast {
+    pub level: ToastLevel,
+    pub text: String,
+    /// Button label and the action id broadcast back over the bus when it is clicked.
+    pub action: Option<(String, String)>,
+}
+
+impl Toast {
+    pub fn new(level: ToastLevel, text: impl Into<String>) -> Self {
+        Self {
+            level,
+            text: text.into(),
+            action: None,
+        }
+    }
+
+    pub fn with_action(mut self, label: impl Into<String>, action_id: impl Into<String>) -> Self {
+        self.action = Some((label.into(), action_id.into()));
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShownToast {
+    pub id: u64,
+    pub toast: Toast,
+    /// `None` while hovered.
+    expires_at: Option<f64>,
+    /// Time left on the clock when hovering paused it.
+    remaining: f64,
+}
+
+/// Which toasts are on screen, which are waiting, and when each one goes.
+#[derive(Default)]
+pub struct ToastQueue {
+    visible: Vec<ShownToast>,
+    queued: VecDeque<Toast>,
+    next_id: u64,
+}
+
+impl ToastQueue {
+    pub fn visible(&self) -> &[ShownToast] {
+        &self.visible
+    }
+
+    pub fn push(&mut self, toast: Toast, now: f64) {
+        self.queued.push_back(toast);
+        self.promote(now);
+    }
+
+    pub fn dismiss(&mut self, id: u64, now: f64) {
+        self.visible.retain(|t| t.id != id);
+        self.promote(now);
+    }
+
+    /// Pauses the toast's timer while hovered and restarts it with the time it had left.
+    pub fn set_hovered(&mut self, id: u64, hovered: bool, now: f64) {
+        if let Some(shown) = self.visible.iter_mut().find(|t| t.id == id) {
+            match (hovered, shown.expires_at) {
+                (true, Some(at)) => {
+                    shown.remaining = (at - now).max(0.0);
+                    shown.expires_at = None;
+                }
+                (false, None) => shown.expires_at = Some(now + shown.remaining),
+                _ => {}
+            }
+        }
+    }
+
+    /// Drops expired toasts and moves waiting ones up. Returns whether anything changed.
+    pub fn tick(&mut self, now: f64) -> bool {
+        let before = self.visible.len();
+        self.visible
+            .retain(|t| t.expires_at.map_or(true, |at| at > now));
+        let expired = self.visible.len() != before;
+        self.promote(now) || expired
+    }
+
+    fn promote(&mut self, now: f64) -> bool {
+        let mut changed = false;
+        while self.visible.len() < MAX_VISIBLE {
+            let toast = match self.queued.pop_front() {
+                Some(toast) => toast,
+                None => break,
+            };
+            self.next_id += 1;
+            self.visible.push(ShownToast {
+                id: self.next_id,
+                toast,
+                expires_at: Some(now + TOAST_TTL_MS),
+                remaining: TOAST_TTL_MS,
+            });
+            changed = true;
+        }
+        changed
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum ToastMsg {
+    Show(Toast),
+    /// A toast's action button was clicked.
+    Action(String),
+}
+
+/// Relays toasts from anywhere in the app to the toast stack, and action clicks back out.
+pub struct ToastBus {
+    link: AgentLink<ToastBus>,
+    subscribers: HashSet<HandlerId>,
+}
+
+impl Agent for ToastBus {
+    type Reach = Context<Self>;
+    type Message = ();
+    type Input = ToastMsg;
+    type Output = ToastMsg;
+
+    fn create(link: AgentLink<Self>) -> Self {
+        Self {
+            link,
+            subscribers: HashSet::new(),
+        }
+    }
+
+    fn update(&mut self, _msg: Self::Message) {}
+
+    fn handle_input(&mut self, msg: Self::Input, _id: HandlerId) {
+        for sub in self.subscribers.iter() {
+            self.link.respond(*sub, msg.clone())
+        }
+    }
+
+    fn connected(&mut self, id: HandlerId) {
+        self.subscribers.insert(id);
+    }
+
+    fn disconnected(&mut self, id: HandlerId) {
+        self.subscribers.remove(&id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(queue: &ToastQueue) -> Vec<&str> {
+        queue.visible().iter().map(|t| t.toast.text.as_str()).collect()
+    }
+
+    #[test]
+    fn extra_toasts_wait_for_a_free_slot() {
+        let mut queue = ToastQueue::default();
+        for n in 0..MAX_VISIBLE + 1 {
+            queue.push(Toast::new(ToastLevel::Info, n.to_string()), 0.0);
+        }
+        assert_eq!(texts(&queue), ["0", "1", "2", "3"]);
+        let first = queue.visible()[0].id;
+        queue.dismiss(first, 0.0);
+        assert_eq!(texts(&queue), ["1", "2", "3", "4"]);
+    }
+
+    #[test]
+    fn toasts_expire_after_their_ttl() {
+        let mut queue = ToastQueue::default();
+        queue.push(Toast::new(ToastLevel::Info, "hi"), 0.0);
+        assert!(!queue.tick(TOAST_TTL_MS - 1.0));
+        assert!(queue.tick(TOAST_TTL_MS));
+        assert!(queue.visible().is_empty());
+    }
+
+    #[test]
+    fn hovering_pauses_the_clock() {
+        let mut queue = ToastQueue::default();
+        queue.push(Toast::new(ToastLevel::Info, "hi"), 0.0);
+        let id = queue.visible()[0].id;
+        queue.set_hovered(id, true, 1_000.0);
+        assert!(!queue.tick(60_000.0));
+        queue.set_hovered(id, false, 60_000.0);
+        assert!(!queue.tick(60_000.0 + TOAST_TTL_MS - 1_001.0));
+        assert!(queue.tick(60_000.0 + TOAST_TTL_MS - 1_000.0));
+    }
+}
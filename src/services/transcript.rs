@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+
+use crate::components::chat::MessageData;
+use crate::services::search::day_key;
+
+/// One local day's record of every finished message, for users who keep a transcript.
+///
+/// Each message is in here once, in the order it first arrived. Later edits replace it in
+/// place, a deleted file stays listed with the attachment marked removed, and a deleted
+/// message is taken out.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct Transcript {
+    /// `YYYY-MM-DD` in local time.
+    pub day: String,
+    pub messages: Vec<MessageData>,
+}
+
+impl Transcript {
+    /// An empty transcript for the local day `now` (ms since the epoch) falls on. Local time
+    /// is `utc_offset_minutes` ahead of UTC.
+    pub fn starting(now: f64, utc_offset_minutes: i32) -> Self {
+        Self {
+            day: day_key(now, utc_offset_minutes),
+            messages: vec![],
+        }
+    }
+
+    /// Whether the server has confirmed `message`. Messages still waiting on it have no id
+    /// or a local one, and aren't recorded yet.
+    pub fn is_final(message: &MessageData) -> bool {
+        !message.id.is_empty() && !message.id.starts_with("local-")
+    }
+
+    /// Moves on to a fresh day once `now` is past this one's local midnight, handing back
+    /// the finished day to be saved.
+    pub fn roll_over(&mut self, now: f64, utc_offset_minutes: i32) -> Option<Transcript> {
+        let today = Self::starting(now, utc_offset_minutes);
+        if today.day == self.day {
+            return None;
+        }
+        Some(std::mem::replace(self, today))
+    }
+
+    /// Records `message` as of `now`, replacing the copy already here if there is one.
+    /// Returns the previous day if it had to be rolled over first. Unconfirmed messages
+    /// are left out.
+    pub fn append(&mut self, message: &MessageData, now: f64, utc_offset_minutes: i32) -> Option<Transcript> {
+        let finished = self.roll_over(now, utc_offset_minutes);
+        if Self::is_final(message) {
+            match self.messages.iter_mut().find(|m| m.id == message.id) {
+                Some(existing) => *existing = message.clone(),
+                None => self.messages.push(message.clone()),
+            }
+        }
+        finished
+    }
+
+    /// Brings messages already recorded up to date after edits, reactions or deleted files.
+    /// Messages that aren't here belong to an earlier day, or never made it in, and are
+    /// skipped. Returns whether anything changed.
+    pub fn amend(&mut self, messages: &[MessageData]) -> bool {
+        let mut changed = false;
+        for message in messages {
+            if let Some(existing) = self.messages.iter_mut().find(|m| m.id == message.id) {
+                if existing != message {
+                    *existing = message.clone();
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Takes out a message that was deleted. Returns whether it was here.
+    pub fn remove(&mut self, message_id: &str) -> bool {
+        let before = self.messages.len();
+        self.messages.retain(|m| m.id != message_id);
+        self.messages.len() != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: f64 = 86_400_000.0;
+    const HOUR: f64 = 3_600_000.0;
+
+    fn message(id: &str, text: &str) -> MessageData {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "from": "ann",
+            "message": text,
+            "reactions": null,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn day_follows_local_time() {
+        assert_eq!(Transcript::starting(0.0, 0).day, "1970-01-01");
+        assert_eq!(Transcript::starting(DAY - HOUR, 120).day, "1970-01-02");
+        assert_eq!(Transcript::starting(HOUR, -120).day, "1969-12-31");
+    }
+
+    #[test]
+    fn rolls_over_at_local_midnight() {
+        let mut transcript = Transcript::starting(0.0, 60);
+        assert!(transcript.append(&message("a", ""), DAY - 2.0 * HOUR, 60).is_none());
+        let finished = transcript.append(&message("b", ""), DAY - HOUR / 2.0, 60).unwrap();
+        assert_eq!(finished.day, "1970-01-01");
+        assert_eq!(finished.messages.len(), 1);
+        assert_eq!(transcript.day, "1970-01-02");
+        assert_eq!(transcript.messages[0].id, "b");
+        assert!(transcript.roll_over(DAY + HOUR, 60).is_none());
+    }
+
+    #[test]
+    fn append_replaces_and_skips_unconfirmed() {
+        let mut transcript = Transcript::starting(0.0, 0);
+        transcript.append(&message("a", "first"), 0.0, 0);
+        transcript.append(&message("a", "edited"), 0.0, 0);
+        transcript.append(&message("local-1", ""), 0.0, 0);
+        transcript.append(&message("", ""), 0.0, 0);
+        assert_eq!(transcript.messages.len(), 1);
+        assert_eq!(transcript.messages[0].message, "edited");
+    }
+
+    #[test]
+    fn amend_only_touches_recorded_messages() {
+        let mut transcript = Transcript::starting(0.0, 0);
+        transcript.append(&message("a", "first"), 0.0, 0);
+        assert!(!transcript.amend(&[message("a", "first"), message("z", "")]));
+        assert!(transcript.amend(&[message("a", "edited")]));
+        assert_eq!(transcript.messages.len(), 1);
+    }
+
+    #[test]
+    fn deleted_messages_are_taken_out() {
+        let mut transcript = Transcript::starting(0.0, 0);
+        transcript.append(&message("a", ""), 0.0, 0);
+        transcript.append(&message("b", ""), 0.0, 0);
+        assert!(transcript.remove("a"));
+        assert!(!transcript.remove("a"));
+        assert_eq!(transcript.messages[0].id, "b");
+    }
+}
@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+
+use crate::t;
+
+/// Typing indicators disappear this long after the last keystroke event.
+pub const TYPING_TIMEOUT_MS: f64 = 3_000.0;
+
+/// Where someone is typing: a room's main composer, or a thread inside it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TypingContext {
+    pub room: String,
+    pub thread_id: Option<String>,
+}
+
+/// Payload of a `typing` frame. `from` is filled in by the server.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TypingEvent {
+    #[serde(default)]
+    pub from: String,
+    pub context: TypingContext,
+}
+
+/// Payload of a `usertypinglist` frame: everyone the server saw typing in `context` during
+/// its last one-second window. Replaces per-user `typing` frames on servers that batch them.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TypingList {
+    pub context: TypingContext,
+    pub users: Vec<String>,
+}
+
+struct TypingEntry {
+    user: String,
+    context: TypingContext,
+    expires_at: f64,
+}
+
+#[derive(Default)]
+pub struct TypingTracker {
+    entries: Vec<TypingEntry>,
+}
+
+impl TypingTracker {
+    pub fn record(&mut self, event: TypingEvent, now: f64) {
+        self.entries
+            .retain(|e| !(e.user == event.from && e.context == event.context));
+        self.entries.push(TypingEntry {
+            user: event.from,
+            context: event.context,
+            expires_at: now + TYPING_TIMEOUT_MS,
+        });
+    }
+
+    /// Makes `list` the set of users typing in its context, leaving out `own`. Each keeps
+    /// the usual timeout, so the indicators still clear if the lists stop coming.
+    /// Returns whether the set of users changed.
+    pub fn replace(&mut self, list: TypingList, own: &str, now: f64) -> bool {
+        let mut before: Vec<String> = self.in_context(&list.context).into_iter().map(String::from).collect();
+        self.entries.retain(|e| e.context != list.context);
+        for user in list.users {
+            if user != own && !self.entries.iter().any(|e| e.user == user && e.context == list.context) {
+                self.entries.push(TypingEntry {
+                    user,
+                    context: list.context.clone(),
+                    expires_at: now + TYPING_TIMEOUT_MS,
+                });
+            }
+        }
+        let mut after = self.in_context(&list.context);
+        before.sort_unstable();
+        after.sort_unstable();
+        before != after
+    }
+
+    fn in_context(&self, context: &TypingContext) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|e| &e.context == context)
+            .map(|e| e.user.as_str())
+            .collect()
+    }
+
+    /// Drops a user's indicators, e.g. once their message has arrived.
+    pub fn clear_user(&mut self, user: &str) {
+        self.entries.retain(|e| e.user != user);
+    }
+
+    /// Drops expired indicators and returns whether anything changed.
+    pub fn prune(&mut self, now: f64) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.expires_at > now);
+        self.entries.len() != before
+    }
+
+    /// Users typing in the main composer of `room`.
+    pub fn in_room(&self, room: &str) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|e| e.context.room == room && e.context.thread_id.is_none())
+            .map(|e| e.user.as_str())
+            .collect()
+    }
+
+    /// Users typing in the thread `thread_id`.
+    pub fn in_thread(&self, thread_id: &str) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|e| e.context.thread_id.as_deref() == Some(thread_id))
+            .map(|e| e.user.as_str())
+            .collect()
+    }
+}
+
+/// "Alice is typing…", "Alice and Bob are typing…", "3 people are typing…".
+pub fn typing_label(users: &[&str]) -> Option<String> {
+    match users {
+        [] => None,
+        [one] => Some(t!("typing.one", name = one)),
+        [first, second] => Some(t!("typing.two", first = first, second = second)),
+        _ => Some(t!("typing.several", count = users.len())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(thread_id: Option<&str>) -> TypingContext {
+        TypingContext {
+            room: "general".into(),
+            thread_id: thread_id.map(String::from),
+        }
+    }
+
+    fn event(from: &str, thread_id: Option<&str>) -> TypingEvent {
+        TypingEvent {
+            from: from.into(),
+            context: context(thread_id),
+        }
+    }
+
+    #[test]
+    fn room_and_thread_typing_are_kept_apart() {
+        let mut tracker = TypingTracker::default();
+        tracker.record(event("ann", None), 0.0);
+        tracker.record(event("bo", Some("m1")), 0.0);
+        tracker.record(event("cy", Some("m2")), 0.0);
+        assert_eq!(tracker.in_room("general"), ["ann"]);
+        assert_eq!(tracker.in_thread("m1"), ["bo"]);
+        assert_eq!(tracker.in_thread("m2"), ["cy"]);
+        assert!(tracker.in_room("random").is_empty());
+    }
+
+    #[test]
+    fn indicators_expire_after_the_timeout() {
+        let mut tracker = TypingTracker::default();
+        tracker.record(event("ann", None), 0.0);
+        tracker.record(event("bo", Some("m1")), 1_000.0);
+        assert!(!tracker.prune(TYPING_TIMEOUT_MS - 1.0));
+        assert!(tracker.prune(TYPING_TIMEOUT_MS));
+        assert!(tracker.in_room("general").is_empty());
+        assert_eq!(tracker.in_thread("m1"), ["bo"]);
+        assert!(tracker.prune(1_000.0 + TYPING_TIMEOUT_MS));
+        assert!(tracker.in_thread("m1").is_empty());
+    }
+
+    #[test]
+    fn typing_again_pushes_the_expiry_back() {
+        let mut tracker = TypingTracker::default();
+        tracker.record(event("ann", Some("m1")), 0.0);
+        tracker.record(event("ann", Some("m1")), 2_000.0);
+        assert!(!tracker.prune(TYPING_TIMEOUT_MS));
+        assert_eq!(tracker.in_thread("m1"), ["ann"]);
+    }
+
+    #[test]
+    fn a_message_clears_the_sender_everywhere() {
+        let mut tracker = TypingTracker::default();
+        tracker.record(event("ann", None), 0.0);
+        tracker.record(event("ann", Some("m1")), 0.0);
+        tracker.clear_user("ann");
+        assert!(tracker.in_room("general").is_empty());
+        assert!(tracker.in_thread("m1").is_empty());
+    }
+
+    #[test]
+    fn a_typing_list_replaces_its_context_only() {
+        let mut tracker = TypingTracker::default();
+        tracker.record(event("ann", None), 0.0);
+        tracker.record(event("bo", Some("m1")), 0.0);
+        let list = TypingList {
+            context: context(Some("m1")),
+            users: vec!["cy".into(), "me".into()],
+        };
+        assert!(tracker.replace(list.clone(), "me", 0.0));
+        assert!(!tracker.replace(list, "me", 0.0));
+        assert_eq!(tracker.in_thread("m1"), ["cy"]);
+        assert_eq!(tracker.in_room("general"), ["ann"]);
+    }
+}
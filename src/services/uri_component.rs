@@ -0,0 +1,67 @@
+/// Characters `encodeURIComponent` leaves as they are.
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"-_.!~*'()".contains(&byte)
+}
+
+/// `text` percent-encoded the way `encodeURIComponent` does it, without needing a browser.
+/// Everything but unreserved characters is escaped, byte by byte, as UTF-8.
+pub fn encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for &byte in text.as_bytes() {
+        if is_unreserved(byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// `text` with its `%XX` escapes decoded; `None` for a broken escape or escapes that
+/// aren't UTF-8.
+pub fn decode(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = bytes.get(i + 1..i + 3).filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))?;
+            decoded.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_like_the_browser() {
+        assert_eq!(encode("general"), "general");
+        assert_eq!(encode("a b&c=d/e?"), "a%20b%26c%3Dd%2Fe%3F");
+        assert_eq!(encode("it's (fine)!*~"), "it's%20(fine)!*~");
+        assert_eq!(encode("café 👍"), "caf%C3%A9%20%F0%9F%91%8D");
+    }
+
+    #[test]
+    fn decodes_what_it_encodes() {
+        for text in ["", "plain", "a b&c=d", "100%", "café 👍", "/chat?channel=x"] {
+            assert_eq!(decode(&encode(text)).as_deref(), Some(text));
+        }
+        assert_eq!(decode("%e2%82%ac").as_deref(), Some("€"));
+    }
+
+    #[test]
+    fn rejects_broken_escapes() {
+        assert_eq!(decode("%"), None);
+        assert_eq!(decode("%4"), None);
+        assert_eq!(decode("%zz"), None);
+        assert_eq!(decode("%C3"), None);
+        assert_eq!(decode("%+1"), None);
+    }
+}
@@ -0,0 +1,91 @@
+use std::fmt;
+
+use crate::t;
+
+pub const MIN_USERNAME_CHARS: usize = 2;
+pub const MAX_USERNAME_CHARS: usize = 24;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum UsernameError {
+    TooShort,
+    TooLong,
+    /// The first character that isn't a letter, digit, `_` or `-`.
+    InvalidChar(char),
+    /// Nothing but `_` and `-`.
+    NoLettersOrDigits,
+}
+
+impl fmt::Display for UsernameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UsernameError::TooShort => f.write_str(&t!("username.too_short", min = MIN_USERNAME_CHARS)),
+            UsernameError::TooLong => f.write_str(&t!("username.too_long", max = MAX_USERNAME_CHARS)),
+            UsernameError::InvalidChar(' ') => f.write_str(&t!("username.space")),
+            UsernameError::InvalidChar(c) => f.write_str(&t!("username.invalid_char", char = c)),
+            UsernameError::NoLettersOrDigits => f.write_str(&t!("username.no_letters_or_digits")),
+        }
+    }
+}
+
+/// Canonical composition, so the same name typed on two keyboards is one user.
+fn nfc(text: &str) -> String {
+    js_sys::JsString::from(text).normalize("NFC").into()
+}
+
+/// The username `input` stands for: trimmed and NFC-normalized, 2–24 letters, digits, `_`
+/// or `-`, with at least one letter or digit among them.
+pub fn validate_username(input: &str) -> Result<String, UsernameError> {
+    check_username(nfc(input.trim()))
+}
+
+/// The rules of [`validate_username`], for a name already trimmed and normalized.
+fn check_username(name: String) -> Result<String, UsernameError> {
+    if let Some(c) = name.chars().find(|&c| !(c.is_alphanumeric() || c == '_' || c == '-')) {
+        return Err(UsernameError::InvalidChar(c));
+    }
+    let len = name.chars().count();
+    if len < MIN_USERNAME_CHARS {
+        return Err(UsernameError::TooShort);
+    }
+    if len > MAX_USERNAME_CHARS {
+        return Err(UsernameError::TooLong);
+    }
+    if !name.chars().any(char::is_alphanumeric) {
+        return Err(UsernameError::NoLettersOrDigits);
+    }
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_every_rule() {
+        let cases: &[(&str, Result<&str, UsernameError>)] = &[
+            ("ann", Ok("ann")),
+            ("al", Ok("al")),
+            ("a_b-c", Ok("a_b-c")),
+            ("_1", Ok("_1")),
+            ("José", Ok("José")),
+            ("東京", Ok("東京")),
+            ("abcdefghijklmnopqrstuvwx", Ok("abcdefghijklmnopqrstuvwx")),
+            ("", Err(UsernameError::TooShort)),
+            ("a", Err(UsernameError::TooShort)),
+            ("abcdefghijklmnopqrstuvwxy", Err(UsernameError::TooLong)),
+            ("ann lee", Err(UsernameError::InvalidChar(' '))),
+            ("ann!", Err(UsernameError::InvalidChar('!'))),
+            ("@ann", Err(UsernameError::InvalidChar('@'))),
+            ("guest:ann", Err(UsernameError::InvalidChar(':'))),
+            ("a😀", Err(UsernameError::InvalidChar('😀'))),
+            // A bad character is reported before the length.
+            ("!", Err(UsernameError::InvalidChar('!'))),
+            ("__", Err(UsernameError::NoLettersOrDigits)),
+            ("-_-", Err(UsernameError::NoLettersOrDigits)),
+        ];
+        for (input, expected) in cases {
+            let expected = expected.clone().map(String::from);
+            assert_eq!(check_username(input.to_string()), expected, "{:?}", input);
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use gloo_events::EventListener;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::EventTarget;
+use yew::Callback;
+
+/// How long a finger has to stay down on a message before its menu opens.
+pub const LONG_PRESS_MS: u32 = 500;
+
+/// How far, in CSS pixels, a finger may drift during a long press before it counts as a scroll.
+pub const LONG_PRESS_SLOP: i32 = 10;
+
+/// Whether a finger that went down at `start` and is now at `at` is still pressing, not scrolling.
+pub fn within_slop(start: (i32, i32), at: (i32, i32)) -> bool {
+    let (dx, dy) = (at.0 - start.0, at.1 - start.1);
+    dx * dx + dy * dy <= LONG_PRESS_SLOP * LONG_PRESS_SLOP
+}
+
+/// Classes that grow a control to a 40px touch target below Tailwind's `md` breakpoint.
+pub const TOUCH_TARGET: &str = "min-w-[40px] min-h-[40px] md:min-w-0 md:min-h-0";
+
+/// How much of the bottom of the layout viewport the visual viewport no longer covers,
+/// i.e. the height of the on-screen keyboard on browsers that don't resize the page for it.
+pub fn keyboard_inset(inner_height: f64, visual_height: f64, visual_offset_top: f64) -> f64 {
+    (inner_height - visual_height - visual_offset_top).max(0.0).round()
+}
+
+fn number(target: &JsValue, key: &str) -> Option<f64> {
+    js_sys::Reflect::get(target, &JsValue::from_str(key)).ok()?.as_f64()
+}
+
+/// `window.visualViewport`, where the browser has one.
+fn visual_viewport() -> Option<JsValue> {
+    let window = web_sys::window()?;
+    js_sys::Reflect::get(&window, &JsValue::from_str("visualViewport"))
+        .ok()
+        .filter(|viewport| viewport.is_object())
+}
+
+fn current_inset(viewport: &JsValue) -> f64 {
+    let inner = web_sys::window().and_then(|w| w.inner_height().ok()).and_then(|h| h.as_f64());
+    match (inner, number(viewport, "height"), number(viewport, "offsetTop")) {
+        (Some(inner), Some(height), Some(top)) => keyboard_inset(inner, height, top),
+        _ => 0.0,
+    }
+}
+
+/// Reports the keyboard inset whenever the visual viewport moves or resizes. iOS Safari
+/// slides the keyboard over the page instead of shrinking it, so without this the composer
+/// ends up behind the keyboard.
+pub struct KeyboardWatch {
+    _resize: EventListener,
+    _scroll: EventListener,
+}
+
+impl KeyboardWatch {
+    /// `None` on browsers without `visualViewport`, which all resize the page themselves.
+    pub fn attach(on_inset: Callback<f64>) -> Option<Self> {
+        let viewport = visual_viewport()?;
+        let target: &EventTarget = viewport.unchecked_ref();
+        let listen = |event: &'static str| {
+            let viewport = viewport.clone();
+            let on_inset = on_inset.clone();
+            EventListener::new(target, event, move |_| on_inset.emit(current_inset(&viewport)))
+        };
+        Some(Self { _resize: listen("resize"), _scroll: listen("scroll") })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jitter_keeps_a_long_press() {
+        assert!(within_slop((100, 100), (100, 100)));
+        assert!(within_slop((100, 100), (106, 108)));
+        assert!(within_slop((100, 100), (90, 100)));
+    }
+
+    #[test]
+    fn a_drag_cancels_a_long_press() {
+        assert!(!within_slop((100, 100), (100, 111)));
+        assert!(!within_slop((100, 100), (108, 108)));
+    }
+
+    #[test]
+    fn inset_is_the_covered_strip() {
+        assert_eq!(keyboard_inset(800.0, 500.0, 0.0), 300.0);
+        assert_eq!(keyboard_inset(800.0, 500.0, 100.0), 200.0);
+        assert_eq!(keyboard_inset(800.0, 800.0, 0.0), 0.0);
+        // Pinch zoom can make the sum overshoot; that isn't a keyboard.
+        assert_eq!(keyboard_inset(800.0, 600.0, 250.0), 0.0);
+    }
+}
@@ -1,5 +1,7 @@
-use futures::{channel::mpsc::Sender, SinkExt, StreamExt};
+use futures::future::{self, Either};
+use futures::{channel::mpsc::Sender, channel::oneshot, SinkExt, StreamExt};
 use reqwasm::websocket::{futures::WebSocket, Message};
+use yew::Callback;
 use yew_agent::Dispatched;
 use crate::services::event_bus::{EventBus, Request};
 
@@ -10,23 +12,41 @@ pub struct WebsocketService {
 }
 
 impl WebsocketService {
-    pub fn new() -> Self {
+    /// `on_open` fires once the first message has gone out, i.e. the socket is up;
+    /// `on_close` once the server connection has gone away or never came up.
+    pub fn new(on_open: Callback<()>, on_close: Callback<()>) -> Self {
         let ws = WebSocket::open("ws://127.0.0.1:8080").unwrap();
 
         let (mut write, mut read) = ws.split();
 
         let (in_tx, mut in_rx) = futures::channel::mpsc::channel::<String>(1000);
+        let (hangup_tx, mut hangup_rx) = oneshot::channel();
         let mut event_bus = EventBus::dispatcher();
 
         spawn_local(async move {
+            let mut opened = false;
             while let Some(s) = in_rx.next().await {
                 log::debug!("got event from channel! {}", s);
-                write.send(Message::Text(s)).await.unwrap();
+                if let Err(e) = write.send(Message::Text(s)).await {
+                    log::error!("ws send: {:?}", e);
+                    return;
+                }
+                if !opened {
+                    opened = true;
+                    on_open.emit(());
+                }
             }
+            // Closing a split sink does nothing, so the reader puts the socket back together.
+            let _ = hangup_tx.send(write);
         });
 
         spawn_local(async move {
-            while let Some(msg) = read.next().await {
+            let write = loop {
+                let msg = match future::select(read.next(), &mut hangup_rx).await {
+                    Either::Left((Some(msg), _)) => msg,
+                    Either::Left((None, _)) => break None,
+                    Either::Right((write, _)) => break write.ok(),
+                };
                 match msg {
                     Ok(Message::Text(data)) => {
                         log::debug!("from websocket: {}", data);
@@ -43,10 +63,26 @@ impl WebsocketService {
                         log::error!("ws: {:?}", e)
                     }
                 }
+            };
+            if let Some(write) = write {
+                match write.reunite(read) {
+                    Ok(ws) => {
+                        if let Err(e) = ws.close(None, None) {
+                            log::error!("ws close: {:?}", e);
+                        }
+                    }
+                    Err(e) => log::error!("ws close: {:?}", e),
+                }
             }
             log::debug!("WebSocket Closed");
+            on_close.emit(());
         });
 
         Self { tx: in_tx }
     }
+
+    /// Hangs up once anything already queued has gone out; `on_close` fires after.
+    pub fn close(&mut self) {
+        self.tx.close_channel();
+    }
 }
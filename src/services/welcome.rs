@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::services::storage::{Local, Store};
+
+/// Sent privately when joining a room.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WelcomeMessage {
+    pub text: String,
+}
+
+/// Admins set a room's greeting with `/welcome <text>`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SetWelcome {
+    pub room: String,
+    pub text: String,
+}
+
+/// The last welcome text shown per room, so returning users only see it again once it changes.
+pub struct SeenWelcomes<S: Store = Local> {
+    store: S,
+    key: String,
+    seen: HashMap<String, String>,
+}
+
+impl SeenWelcomes {
+    pub fn load(username: &str) -> Self {
+        Self::load_from(Local, username)
+    }
+}
+
+impl<S: Store> SeenWelcomes<S> {
+    pub fn load_from(store: S, username: &str) -> Self {
+        let key = format!("yewchat.welcome_seen.{}", username);
+        Self {
+            seen: store.get(&key).unwrap_or_default(),
+            store,
+            key,
+        }
+    }
+
+    /// Whether `text` is new for `room`; remembers it as seen either way.
+    pub fn claim(&mut self, room: &str, text: &str) -> bool {
+        if self.seen.get(room).map(String::as_str) == Some(text) {
+            return false;
+        }
+        self.seen.insert(room.to_string(), text.to_string());
+        if let Err(e) = self.store.set(&self.key, &self.seen) {
+            log::error!("failed to save seen welcomes: {:?}", e);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::storage::MemoryStore;
+
+    #[test]
+    fn a_welcome_is_new_once() {
+        let mut seen = SeenWelcomes::load_from(MemoryStore::default(), "ann");
+        assert!(seen.claim("general", "Hi!"));
+        assert!(!seen.claim("general", "Hi!"));
+    }
+
+    #[test]
+    fn a_changed_welcome_is_new_again() {
+        let mut seen = SeenWelcomes::load_from(MemoryStore::default(), "ann");
+        assert!(seen.claim("general", "Hi!"));
+        assert!(seen.claim("general", "Hello!"));
+        assert!(seen.claim("general", "Hi!"));
+    }
+
+    #[test]
+    fn rooms_are_tracked_apart() {
+        let mut seen = SeenWelcomes::load_from(MemoryStore::default(), "ann");
+        assert!(seen.claim("general", "Hi!"));
+        assert!(seen.claim("random", "Hi!"));
+    }
+
+    #[test]
+    fn seen_welcomes_survive_a_reload() {
+        let store = MemoryStore::default();
+        assert!(SeenWelcomes::load_from(store.clone(), "ann").claim("general", "Hi!"));
+        let mut reloaded = SeenWelcomes::load_from(store.clone(), "ann");
+        assert!(!reloaded.claim("general", "Hi!"));
+        // Someone else on the same browser hasn't seen it.
+        assert!(SeenWelcomes::load_from(store, "bo").claim("general", "Hi!"));
+    }
+}
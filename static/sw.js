@@ -0,0 +1,65 @@
+// The app shell, precached per build so the chat opens (in offline mode) without a network.
+// webpack stamps the build id below, so every build's worker differs from the last byte for
+// byte and the browser installs it on its next update check, whichever build the page runs.
+// Each worker has a cache of its own; the old one goes once the new worker takes over.
+const VERSION = '__BUILD_ID__';
+const CACHE = `yewchat-${VERSION}`;
+const TAILWIND = 'https://cdn.tailwindcss.com';
+const SHELL = ['/', '/index.html', '/yewchat.js', '/yewchat_bg.wasm', '/manifest.webmanifest', '/icon.svg'];
+
+self.addEventListener('install', (event) => {
+    event.waitUntil(
+        caches.open(CACHE)
+            .then((cache) => Promise.all([
+                cache.addAll(SHELL),
+                // Cross-origin and without CORS, so it can only be stored as an opaque response.
+                fetch(new Request(TAILWIND, { mode: 'no-cors' })).then((response) => cache.put(TAILWIND, response)),
+            ]))
+            .then(() => self.skipWaiting())
+    );
+});
+
+self.addEventListener('activate', (event) => {
+    event.waitUntil(
+        caches.keys()
+            .then((keys) => Promise.all(keys.filter((key) => key.startsWith('yewchat-') && key !== CACHE).map((key) => caches.delete(key))))
+            .then(() => self.clients.claim())
+    );
+});
+
+self.addEventListener('fetch', (event) => {
+    const request = event.request;
+    const url = new URL(request.url);
+    // WebSocket traffic never reaches a fetch handler, but anything that isn't a plain GET
+    // over http(s) is left alone all the same.
+    if (request.method !== 'GET' || !url.protocol.startsWith('http')) {
+        return;
+    }
+    if (request.mode === 'navigate') {
+        // Always the live page when online; the cached shell lets the router take it from there.
+        event.respondWith(fetch(request).catch(() => caches.match('/index.html', { cacheName: CACHE })));
+        return;
+    }
+    const isShell = url.href === TAILWIND || (url.origin === self.location.origin && /\.(js|wasm|svg|webmanifest)$/.test(url.pathname));
+    if (!isShell) {
+        return;
+    }
+    // Stale-while-revalidate: the cached copy answers at once and the network refreshes it
+    // behind the scenes. The file names carry no hash, so pure cache-first would keep
+    // serving whatever was fetched first.
+    event.respondWith(
+        caches.open(CACHE).then((cache) => cache.match(request).then((hit) => {
+            const refresh = fetch(request).then((response) => {
+                if (response.ok || response.type === 'opaque') {
+                    cache.put(request, response.clone());
+                }
+                return response;
+            });
+            if (!hit) {
+                return refresh;
+            }
+            event.waitUntil(refresh.catch(() => undefined));
+            return hit;
+        }))
+    );
+});
@@ -3,6 +3,8 @@ const CopyWebpackPlugin = require('copy-webpack-plugin');
 const WasmPackPlugin = require('@wasm-tool/wasm-pack-plugin');
 
 const distPath = path.resolve(__dirname, 'dist');
+// Stamped into sw.js, so each build ships a worker the browser sees as new.
+const buildId = Date.now().toString(36);
 
 module.exports = {
     mode: 'production',
@@ -17,7 +19,13 @@ module.exports = {
     },
     plugins: [
         new CopyWebpackPlugin({
-            patterns: [{ from: './static', to: distPath }],
+            patterns: [{
+                from: './static',
+                to: distPath,
+                transform: (content, from) => path.basename(from) === 'sw.js'
+                    ? content.toString().replace('__BUILD_ID__', buildId)
+                    : content,
+            }],
         }),
         new WasmPackPlugin({
             crateDirectory: '.',